@@ -0,0 +1,77 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ratatui_counter_app::maze::{Generator, UnionFind, DEFAULT_COVERAGE};
+
+/// Maze sizes in cells per side.
+const SIZES: [usize; 3] = [16, 64, 256];
+
+fn generators(c: &mut Criterion) {
+    let generators = [
+        Generator::Kruskal,
+        Generator::Drunkard {
+            coverage: DEFAULT_COVERAGE,
+        },
+    ];
+    for generator in generators {
+        let mut group = c.benchmark_group(generator.to_string());
+        // the largest mazes take seconds each
+        group.sample_size(10);
+        for size in SIZES {
+            group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+                let mut rng = StdRng::seed_from_u64(0);
+                b.iter(|| generator.generate(size, size, &mut rng));
+            });
+        }
+        group.finish();
+    }
+}
+
+/// Kruskal at the largest menu size, where it generates in parallel.
+fn large(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kruskal-large");
+    group.sample_size(10);
+    group.bench_function("1000", |b| {
+        let mut rng = StdRng::seed_from_u64(0);
+        b.iter(|| Generator::Kruskal.generate(1000, 1000, &mut rng));
+    });
+    group.finish();
+}
+
+fn union_find(c: &mut Criterion) {
+    let mut group = c.benchmark_group("union-find");
+    group.sample_size(10);
+    for size in SIZES {
+        let n = size * size;
+        // the same random pairs for every run
+        let mut rng = StdRng::seed_from_u64(0);
+        let pairs: Vec<(usize, usize)> = (0..n)
+            .map(|_| (rng.random_range(0..n), rng.random_range(0..n)))
+            .collect();
+        group.bench_with_input(BenchmarkId::new("join", n), &pairs, |b, pairs| {
+            b.iter(|| {
+                let mut sets = UnionFind::new(n);
+                for &(a, b) in pairs {
+                    if !sets.in_same_set(a, b) {
+                        sets.join(a, b);
+                    }
+                }
+                sets
+            });
+        });
+        let mut joined = UnionFind::new(n);
+        for &(a, b) in &pairs {
+            if !joined.in_same_set(a, b) {
+                joined.join(a, b);
+            }
+        }
+        group.bench_with_input(BenchmarkId::new("rep", n), &joined, |b, sets| {
+            b.iter(|| (0..n).map(|a| sets.rep(black_box(a))).sum::<usize>());
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, generators, large, union_find);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+//! A generator plugin carving binary tree mazes, where every cell opens to
+//! the north or to the east.
+//!
+//! Build it with `cargo build --example plugin`, copy the library from
+//! `target/debug/examples` into a directory of plugins and start the app
+//! with `--plugins` pointing at it, to pick it with `algo binary-tree`.
+
+use std::ffi::c_char;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use ratatui_counter_app::plugin::{PluginInfo, ABI_VERSION};
+
+static INFO: PluginInfo = PluginInfo {
+    abi_version: ABI_VERSION,
+    name: c"binary-tree".as_ptr() as *const c_char,
+    generate,
+};
+
+#[no_mangle]
+pub extern "C" fn maze_generator_plugin() -> *const PluginInfo {
+    &INFO
+}
+
+extern "C" fn generate(nx: usize, ny: usize, seed: u64, tiles: *mut u8) -> i32 {
+    let (w, h) = (2 * nx + 1, 2 * ny + 1);
+    // SAFETY: the app hands over exactly this many tiles
+    let tiles = unsafe { std::slice::from_raw_parts_mut(tiles, w * h) };
+    let mut rng = StdRng::seed_from_u64(seed);
+    for cy in 0..ny {
+        for cx in 0..nx {
+            let (x, y) = (2 * cx + 1, 2 * cy + 1);
+            let north = cy > 0 && (cx == nx - 1 || rng.random_bool(0.5));
+            if north {
+                tiles[(y - 1) * w + x] = 0;
+            } else if cx < nx - 1 {
+                tiles[y * w + x + 1] = 0;
+            }
+        }
+    }
+    0
+}
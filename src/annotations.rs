@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// What the annotation layer labels the tiles with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Source {
+    /// Labels left with the `note` command.
+    #[default]
+    Notes,
+    /// The cost of the cheapest path to the goal.
+    Distances,
+    /// How often the robot entered each tile.
+    Visits,
+}
+
+impl Source {
+    pub fn next(self) -> Self {
+        match self {
+            Source::Notes => Source::Distances,
+            Source::Distances => Source::Visits,
+            Source::Visits => Source::Notes,
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Source::Notes => "notes",
+            Source::Distances => "distances",
+            Source::Visits => "visits",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Characters a label can have, as many as a tile is wide.
+pub const LABEL_WIDTH: usize = 2;
+
+/// `n` as a label, or `9+` if it has too many digits.
+pub fn number(n: u64) -> String {
+    match n {
+        0..=99 => n.to_string(),
+        _ => "9+".to_string(),
+    }
+}
+
+/// Short labels on tiles, drawn as text over their colors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Annotations {
+    labels: HashMap<(u16, u16), String>,
+}
+
+impl Annotations {
+    /// Label the tile at `pos`, cutting the label to fit the tile. Returns
+    /// the label it had.
+    pub fn set(&mut self, pos: (u16, u16), label: &str) -> Option<String> {
+        self.labels
+            .insert(pos, label.chars().take(LABEL_WIDTH).collect())
+    }
+
+    /// Label the tile at `pos` with `n`, see `number`. Returns the label it
+    /// had.
+    pub fn set_number(&mut self, pos: (u16, u16), n: u64) -> Option<String> {
+        self.labels.insert(pos, number(n))
+    }
+
+    /// Put `label` back on the tile at `pos`, as `set` returned it.
+    pub fn restore(&mut self, pos: (u16, u16), label: Option<String>) {
+        match label {
+            Some(label) => self.labels.insert(pos, label),
+            None => self.labels.remove(&pos),
+        };
+    }
+
+    /// Take the label off the tile at `pos`, returning whether it had one.
+    pub fn remove(&mut self, pos: (u16, u16)) -> bool {
+        self.labels.remove(&pos).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((u16, u16), &str)> {
+        self.labels
+            .iter()
+            .map(|(&pos, label)| (pos, label.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels() {
+        let mut annotations = Annotations::default();
+        annotations.set((1, 1), "exit");
+        annotations.set_number((3, 1), 7);
+        annotations.set_number((5, 1), 120);
+        let mut labels: Vec<_> = annotations.iter().collect();
+        labels.sort();
+        assert_eq!(vec![((1, 1), "ex"), ((3, 1), "7"), ((5, 1), "9+")], labels);
+        assert!(annotations.remove((1, 1)));
+        assert!(!annotations.remove((1, 1)));
+        assert_eq!(2, annotations.iter().count());
+
+        let old = annotations.set((3, 1), "up");
+        annotations.restore((3, 1), old);
+        let old = annotations.set((1, 1), "in");
+        annotations.restore((1, 1), old);
+        let mut labels: Vec<_> = annotations.iter().collect();
+        labels.sort();
+        assert_eq!(vec![((3, 1), "7"), ((5, 1), "9+")], labels);
+    }
+}
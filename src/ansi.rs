@@ -0,0 +1,130 @@
+use std::fmt::Write;
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    style::{Color, Modifier},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// The escape sequence that resets all attributes and then sets the colors
+/// and modifiers of `cell`.
+pub fn sgr(cell: &Cell) -> String {
+    let mut codes = vec!["0".to_string()];
+    for (modifier, code) in [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ] {
+        if cell.modifier.contains(modifier) {
+            codes.push(code.to_string());
+        }
+    }
+    codes.push(color_code(cell.fg, false));
+    codes.push(color_code(cell.bg, true));
+    format!("\x1b[{}m", codes.join(";"))
+}
+
+fn color_code(color: Color, background: bool) -> String {
+    let base = if background { 10 } else { 0 };
+    let named = |code: u8| (code + base).to_string();
+    match color {
+        Color::Reset => named(39),
+        Color::Black => named(30),
+        Color::Red => named(31),
+        Color::Green => named(32),
+        Color::Yellow => named(33),
+        Color::Blue => named(34),
+        Color::Magenta => named(35),
+        Color::Cyan => named(36),
+        Color::Gray => named(37),
+        Color::DarkGray => named(90),
+        Color::LightRed => named(91),
+        Color::LightGreen => named(92),
+        Color::LightYellow => named(93),
+        Color::LightBlue => named(94),
+        Color::LightMagenta => named(95),
+        Color::LightCyan => named(96),
+        Color::White => named(97),
+        Color::Indexed(i) => format!("{};5;{}", 38 + base, i),
+        Color::Rgb(r, g, b) => format!("{};2;{};{};{}", 38 + base, r, g, b),
+    }
+}
+
+/// Escape sequences that draw `updates`, as returned by `Buffer::diff`,
+/// moving the cursor only where the cells are not next to each other.
+pub fn updates(updates: &[(u16, u16, &Cell)]) -> String {
+    let mut out = String::new();
+    let mut cursor = None;
+    let mut style = String::new();
+    for &(x, y, cell) in updates {
+        if cursor != Some((x, y)) {
+            write!(out, "\x1b[{};{}H", y + 1, x + 1).unwrap();
+        }
+        let cell_style = sgr(cell);
+        if cell_style != style {
+            out.push_str(&cell_style);
+            style = cell_style;
+        }
+        out.push_str(cell.symbol());
+        cursor = Some((x + cell.symbol().width().max(1) as u16, y));
+    }
+    if !updates.is_empty() {
+        out.push_str("\x1b[0m");
+    }
+    out
+}
+
+/// All of `buffer` as lines of text with escape sequences for the styles.
+pub fn lines(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in buffer.area.top()..buffer.area.bottom() {
+        let mut style = String::new();
+        let mut skip = 0;
+        for x in buffer.area.left()..buffer.area.right() {
+            if skip > 0 {
+                // covered by the wide character before
+                skip -= 1;
+                continue;
+            }
+            let cell = &buffer[(x, y)];
+            let cell_style = sgr(cell);
+            if cell_style != style {
+                out.push_str(&cell_style);
+                style = cell_style;
+            }
+            out.push_str(cell.symbol());
+            skip = cell.symbol().width().saturating_sub(1);
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_updates() {
+        let mut a = Cell::new("a");
+        a.set_fg(Color::Red).set_bg(Color::Rgb(1, 2, 3));
+        let b = Cell::new("b");
+        assert_eq!(
+            "\x1b[2;1H\x1b[0;31;48;2;1;2;3ma\x1b[0;39;49mb\x1b[2;5Hb\x1b[0m",
+            updates(&[(0, 1, &a), (1, 1, &b), (4, 1, &b)])
+        );
+    }
+
+    #[test]
+    fn test_lines() {
+        let mut buffer = Buffer::empty(ratatui::layout::Rect::new(0, 0, 3, 2));
+        buffer.set_string(0, 0, "⚑界", ratatui::style::Style::new().fg(Color::Red));
+        assert_eq!(
+            "\x1b[0;31;49m⚑界\x1b[0m\n\x1b[0;39;49m   \x1b[0m\n",
+            lines(&buffer)
+        );
+    }
+}
@@ -0,0 +1,194 @@
+use rand::Rng;
+use ratatui_counter_app::pathfinding::{Point, Step};
+
+/// Ants sent out per iteration.
+pub const ANTS: usize = 20;
+/// Iterations before the colony stops.
+pub const ITERATIONS: u32 = 100;
+/// Share of the pheromone that evaporates per iteration.
+const EVAPORATION: f64 = 0.1;
+/// Pheromone an ant spreads over its path, so shorter paths get more per tile.
+const DEPOSIT: f64 = 10.0;
+/// Attraction of a tile without any pheromone, so ants keep exploring.
+const BASE_ATTRACTION: f64 = 0.1;
+/// How strongly ants prefer tiles with more pheromone.
+const GREED: i32 = 2;
+
+/// Pheromone on the tiles of a map, and the best path the ants found.
+#[derive(Debug, Clone)]
+pub struct Colony {
+    width: u16,
+    height: u16,
+    /// Pheromone per tile, to be multiplied by `scale`. Evaporating scales
+    /// all of it down at once.
+    raw: Vec<f64>,
+    scale: f64,
+    pub iteration: u32,
+    /// The moves of the shortest path found so far.
+    pub best: Option<Vec<Step>>,
+    /// Ants that reached the goal in the last iteration.
+    pub reached: usize,
+}
+
+/// The state of a colony before an iteration, to undo it.
+#[derive(Debug, Clone)]
+pub struct Undo {
+    /// Raw pheromone of the tiles the iteration changed, oldest first.
+    raw: Vec<(usize, f64)>,
+    scale: f64,
+    best: Option<Vec<Step>>,
+    reached: usize,
+}
+
+impl Colony {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            raw: vec![0.0; width as usize * height as usize],
+            scale: 1.0,
+            iteration: 0,
+            best: None,
+            reached: 0,
+        }
+    }
+
+    fn tile(&self, pos: Point) -> usize {
+        assert!(pos.0 < self.width && pos.1 < self.height);
+        pos.1 as usize * self.width as usize + pos.0 as usize
+    }
+
+    pub fn pheromone(&self, pos: Point) -> f64 {
+        self.raw[self.tile(pos)] * self.scale
+    }
+
+    /// The most pheromone on any tile.
+    pub fn max(&self) -> f64 {
+        self.raw.iter().copied().fold(0.0, f64::max) * self.scale
+    }
+
+    /// Send out `ANTS` ants from `start` to `goal`, moving as `steps`
+    /// allows, then let the pheromone evaporate and the ants that arrived
+    /// mark their paths.
+    pub fn iterate(
+        &mut self,
+        start: Point,
+        goal: Point,
+        steps: impl Fn(Point) -> Vec<Step>,
+        rng: &mut impl Rng,
+    ) -> Undo {
+        let mut undo = Undo {
+            raw: Vec::new(),
+            scale: self.scale,
+            best: self.best.clone(),
+            reached: self.reached,
+        };
+        let paths: Vec<Vec<Step>> = (0..ANTS)
+            .filter_map(|_| self.walk(start, goal, &steps, rng))
+            .collect();
+        self.iteration += 1;
+        self.reached = paths.len();
+        self.scale *= 1.0 - EVAPORATION;
+        for path in paths {
+            let amount = DEPOSIT / path.len().max(1) as f64 / self.scale;
+            for step in &path {
+                let i = self.tile(step.to);
+                undo.raw.push((i, self.raw[i]));
+                self.raw[i] += amount;
+            }
+            if self
+                .best
+                .as_ref()
+                .is_none_or(|best| path.len() < best.len())
+            {
+                self.best = Some(path);
+            }
+        }
+        undo
+    }
+
+    pub fn undo(&mut self, undo: Undo) {
+        for &(i, raw) in undo.raw.iter().rev() {
+            self.raw[i] = raw;
+        }
+        self.scale = undo.scale;
+        self.best = undo.best;
+        self.reached = undo.reached;
+        self.iteration -= 1;
+    }
+
+    /// One ant searching depth first for `goal`, picking the next tile by
+    /// its pheromone and backing up at dead ends. Returns the moves from
+    /// `start` to `goal`, or `None` if it can't get there.
+    fn walk(
+        &self,
+        start: Point,
+        goal: Point,
+        steps: impl Fn(Point) -> Vec<Step>,
+        rng: &mut impl Rng,
+    ) -> Option<Vec<Step>> {
+        let mut seen = vec![false; self.raw.len()];
+        seen[self.tile(start)] = true;
+        let mut path: Vec<Step> = Vec::new();
+        loop {
+            let pos = path.last().map_or(start, |step| step.to);
+            if pos == goal {
+                return Some(path);
+            }
+            let options: Vec<Step> = steps(pos)
+                .into_iter()
+                .filter(|step| !seen[self.tile(step.to)])
+                .collect();
+            if options.is_empty() {
+                // a dead end, or nowhere left to go at the start
+                path.pop()?;
+                continue;
+            }
+            let attraction: Vec<f64> = options
+                .iter()
+                .map(|step| (BASE_ATTRACTION + self.pheromone(step.to)).powi(GREED))
+                .collect();
+            let mut r = rng.random::<f64>() * attraction.iter().sum::<f64>();
+            let mut choice = options.len() - 1;
+            for (i, a) in attraction.iter().enumerate() {
+                if r < *a {
+                    choice = i;
+                    break;
+                }
+                r -= a;
+            }
+            let step = options[choice];
+            seen[self.tile(step.to)] = true;
+            path.push(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use ratatui_counter_app::pathfinding::grid_steps;
+    use ratatui_counter_app::tile::TileMap;
+
+    #[test]
+    fn test_short_way_wins() {
+        // a ring of 3 x 3 tiles around a wall in the middle: from (0, 0) to
+        // (2, 0) it is two steps along the top and six around the bottom
+        let mut walls: TileMap<bool> = TileMap::with_default(3, 3);
+        walls[(1, 1)] = true;
+        let steps = grid_steps(&walls, |wall| !wall);
+        let mut colony = Colony::new(3, 3);
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..30 {
+            colony.iterate((0, 0), (2, 0), &steps, &mut rng);
+        }
+        assert_eq!(2, colony.best.as_ref().unwrap().len());
+        assert!(colony.pheromone((1, 0)) > 5.0 * colony.pheromone((1, 2)));
+        let before = colony.pheromone((1, 0));
+        let undo = colony.iterate((0, 0), (2, 0), &steps, &mut rng);
+        colony.undo(undo);
+        assert_eq!(30, colony.iteration);
+        assert!((before - colony.pheromone((1, 0))).abs() < 1e-9);
+    }
+}
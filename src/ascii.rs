@@ -0,0 +1,119 @@
+use std::env;
+
+use ratatui::{buffer::Buffer, style::Color};
+
+use crate::kitty;
+
+/// The 16 basic colors every color terminal has.
+const BASIC_COLORS: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Gray,
+    Color::DarkGray,
+    Color::LightRed,
+    Color::LightGreen,
+    Color::LightYellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::White,
+];
+
+/// Whether the terminal looks like it can't show Unicode characters, e.g. the
+/// Linux console or a session without a UTF-8 locale.
+pub fn detect() -> bool {
+    let term = env::var("TERM").unwrap_or_default();
+    if term == "linux" || term == "dumb" || term.starts_with("vt") {
+        return true;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()));
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        }
+        None => false,
+    }
+}
+
+/// Replace every character in `buf` with an ASCII one and every color with
+/// one of the 16 basic colors.
+pub fn downgrade(buf: &mut Buffer) {
+    let area = buf.area;
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = &mut buf[(x, y)];
+            if !cell.symbol().is_ascii() {
+                let c = cell.symbol().chars().next().map_or(' ', ascii_char);
+                cell.set_char(c);
+            }
+            cell.fg = basic_color(cell.fg);
+            cell.bg = basic_color(cell.bg);
+        }
+    }
+}
+
+/// The ASCII character that looks most like `c`.
+fn ascii_char(c: char) -> char {
+    match c {
+        '─' | '━' | '═' | '╴' | '╶' => '-',
+        '│' | '┃' | '║' | '╵' | '╷' => '|',
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╭' | '╮' | '╰' | '╯' => {
+            '+'
+        }
+        '→' | '⮕' | '▶' => '>',
+        '←' | '◀' => '<',
+        '↑' | '▲' => '^',
+        '↓' | '⬇' | '▼' => 'v',
+        '·' | '•' => '.',
+        '█' | '■' => '#',
+        '░' | '▒' | '▓' => ':',
+        '⚑' => 'F',
+        '◊' => 'o',
+        '…' => '.',
+        _ => '?',
+    }
+}
+
+/// The basic color closest to `color`. Named colors are kept as they are.
+fn basic_color(color: Color) -> Color {
+    if !matches!(color, Color::Rgb(..) | Color::Indexed(_)) {
+        return color;
+    }
+    let rgb = kitty::rgb(color);
+    let distance = |basic: &Color| {
+        let other = kitty::rgb(*basic);
+        (0..3)
+            .map(|i| (rgb[i] as i32 - other[i] as i32).pow(2))
+            .sum::<i32>()
+    };
+    *BASIC_COLORS.iter().min_by_key(|c| distance(c)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn test_downgrade() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        buf.set_string(0, 0, "┌⮕a", ratatui::style::Style::new());
+        buf[(2, 0)].set_bg(Color::Rgb(250, 250, 240));
+        buf[(3, 0)].set_fg(Color::Rgb(20, 40, 120));
+        downgrade(&mut buf);
+        assert_eq!(
+            "+>a ",
+            buf.content.iter().map(|c| c.symbol()).collect::<String>()
+        );
+        assert_eq!(Color::White, buf[(2, 0)].bg);
+        assert_eq!(Color::Blue, buf[(3, 0)].fg);
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::mpsc::Receiver;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Padding, Paragraph, Sparkline, Widget},
+};
+
+/// Seeds a batch runs the solver on.
+pub const BATCH_RUNS: u64 = 200;
+
+/// How one run of a batch ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchRun {
+    pub seed: u64,
+    pub steps: u64,
+    /// Tiles visited, the one started on included.
+    pub visited: usize,
+    pub caught: bool,
+}
+
+/// Distribution of the steps the runs of a batch took.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Summary {
+    pub min: u64,
+    pub median: u64,
+    pub max: u64,
+    pub mean: f64,
+}
+
+impl Summary {
+    pub fn of(steps: &[u64]) -> Option<Self> {
+        let mut sorted = steps.to_vec();
+        sorted.sort_unstable();
+        Some(Self {
+            min: *sorted.first()?,
+            median: sorted[sorted.len() / 2],
+            max: *sorted.last()?,
+            mean: sorted.iter().sum::<u64>() as f64 / sorted.len() as f64,
+        })
+    }
+}
+
+/// Number of `steps` in each of `buckets` equally wide ranges from the
+/// smallest to the largest.
+pub fn histogram(steps: &[u64], buckets: usize) -> Vec<u64> {
+    let mut counts = vec![0; buckets];
+    let (Some(&min), Some(&max)) = (steps.iter().min(), steps.iter().max()) else {
+        return counts;
+    };
+    let width = (max - min) as f64 / buckets as f64;
+    for &s in steps {
+        let i = if width > 0.0 {
+            ((s - min) as f64 / width) as usize
+        } else {
+            0
+        };
+        counts[i.min(buckets - 1)] += 1;
+    }
+    counts
+}
+
+/// The runs a batch running elsewhere finished so far.
+#[derive(Debug)]
+pub struct BatchView {
+    receiver: Receiver<BatchRun>,
+    runs: Vec<BatchRun>,
+    /// What ran, e.g. the solver and maze size.
+    label: String,
+}
+
+impl BatchView {
+    pub fn new(receiver: Receiver<BatchRun>, label: String) -> Self {
+        Self {
+            receiver,
+            runs: Vec::new(),
+            label,
+        }
+    }
+
+    /// Take the runs that finished since the last call.
+    pub fn poll(&mut self) {
+        self.runs.extend(self.receiver.try_iter());
+    }
+}
+
+impl Widget for &BatchView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let steps: Vec<u64> = self
+            .runs
+            .iter()
+            .filter(|run| !run.caught)
+            .map(|run| run.steps)
+            .collect();
+        let caught = self.runs.len() - steps.len();
+        let block = Block::bordered()
+            .title(format!(" batch: {} ", self.label))
+            .padding(Padding::horizontal(1));
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [info_area, histogram_area, hint_area] = Layout::vertical([
+            Constraint::Length(7),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(inner);
+        let mut lines = vec![Line::from(format!(
+            "runs:    {}/{}",
+            self.runs.len(),
+            BATCH_RUNS
+        ))];
+        if caught > 0 {
+            lines.push(Line::from(format!("caught:  {}", caught)));
+        }
+        match Summary::of(&steps) {
+            Some(summary) => lines.extend([
+                Line::from(format!("min:     {} steps", summary.min)),
+                Line::from(format!("median:  {} steps", summary.median)),
+                Line::from(format!("max:     {} steps", summary.max)),
+                Line::from(format!("mean:    {:.1} steps", summary.mean)),
+            ]),
+            None => lines.push(Line::from("waiting for the first run...")),
+        }
+        let (visited, all_steps) = self.runs.iter().fold((0, 0), |(visited, steps), run| {
+            (visited + run.visited.saturating_sub(1), steps + run.steps)
+        });
+        if all_steps > 0 {
+            lines.push(Line::from(format!(
+                "found:   {:.2} tiles per step",
+                visited as f64 / all_steps as f64
+            )));
+        }
+        Paragraph::new(lines).render(info_area, buf);
+        let counts = histogram(&steps, histogram_area.width.max(1) as usize);
+        Sparkline::default()
+            .block(Block::new().title("steps, fewest to most"))
+            .data(&counts)
+            .cyan()
+            .render(histogram_area, buf);
+        Line::from("Esc back  q quit")
+            .style(Style::new().dim())
+            .render(hint_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summary() {
+        let steps = [40, 10, 30, 20, 50];
+        assert_eq!(
+            Some(Summary {
+                min: 10,
+                median: 30,
+                max: 50,
+                mean: 30.0
+            }),
+            Summary::of(&steps)
+        );
+        assert_eq!(None, Summary::of(&[]));
+        assert_eq!(vec![2, 1, 2], histogram(&steps, 3));
+        assert_eq!(vec![2, 0], histogram(&[7, 7], 2));
+    }
+}
@@ -0,0 +1,248 @@
+use ratatui_counter_app::pathfinding::{Point, Step};
+
+/// Which end a search starts from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Start,
+    Goal,
+}
+
+/// How a search reached a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reached {
+    side: Side,
+    /// The tile it came from, `None` for the ends.
+    parent: Option<Point>,
+    /// The tile stepped into to get here, which differs from it behind a
+    /// teleporter.
+    tile: Point,
+    /// Moves from its end.
+    depth: u32,
+}
+
+/// Two breadth first searches, one from the start and one from the goal,
+/// growing a layer at a time until they touch. Counting moves, not their
+/// costs, they find a path with the fewest moves.
+///
+/// The search from the goal steps backwards, so it only takes moves that
+/// lead back to where they came from. Teleporters and one-way passages are
+/// left to the search from the start.
+#[derive(Debug, Clone)]
+pub struct Bidirectional {
+    width: u16,
+    reached: Vec<Option<Reached>>,
+    /// The tiles each search reached last, indexed by `Side`.
+    frontiers: [Vec<Point>; 2],
+    /// Layers grown so far, by both searches together.
+    pub layers: u32,
+    /// Where the searches touched.
+    pub meeting: Option<Point>,
+    /// The tiles to step into from the start to the goal, once the searches
+    /// touched.
+    pub path: Option<Vec<Point>>,
+}
+
+/// The state of a search before it grew a layer, to undo it.
+#[derive(Debug, Clone)]
+pub struct Undo {
+    side: Side,
+    frontier: Vec<Point>,
+    /// Tiles the layer reached.
+    reached: Vec<Point>,
+}
+
+impl Undo {
+    /// The tiles the layer reached.
+    pub fn reached(&self) -> &[Point] {
+        &self.reached
+    }
+}
+
+impl Bidirectional {
+    pub fn new(width: u16, height: u16, start: Point, goal: Point) -> Self {
+        let mut search = Self {
+            width,
+            reached: vec![None; width as usize * height as usize],
+            frontiers: [vec![start], vec![goal]],
+            layers: 0,
+            meeting: None,
+            path: None,
+        };
+        for (side, pos) in [(Side::Start, start), (Side::Goal, goal)] {
+            let i = search.tile(pos);
+            search.reached[i] = Some(Reached {
+                side,
+                parent: None,
+                tile: pos,
+                depth: 0,
+            });
+        }
+        if start == goal {
+            search.meeting = Some(goal);
+            search.path = Some(Vec::new());
+        }
+        search
+    }
+
+    fn tile(&self, pos: Point) -> usize {
+        pos.1 as usize * self.width as usize + pos.0 as usize
+    }
+
+    /// Whether the searches touched, or one of them ran out of tiles
+    /// without it.
+    pub fn is_done(&self) -> bool {
+        self.meeting.is_some() || self.frontiers.iter().any(Vec::is_empty)
+    }
+
+    /// The side that reached `pos` and the tile it came from, if any did.
+    pub fn reached(&self, pos: Point) -> Option<(Side, Option<Point>)> {
+        self.reached[self.tile(pos)].map(|reached| (reached.side, reached.parent))
+    }
+
+    /// Tiles reached from each end, indexed by `Side`.
+    pub fn counts(&self) -> [usize; 2] {
+        let count = |side| {
+            self.reached
+                .iter()
+                .flatten()
+                .filter(|reached| reached.side == side)
+                .count()
+        };
+        [count(Side::Start), count(Side::Goal)]
+    }
+
+    /// Grow the smaller frontier by a layer, moving as `steps` allows. The
+    /// first layer to touch the other search ends it, with the shortest
+    /// path through any of the tiles it touched.
+    pub fn grow(&mut self, steps: impl Fn(Point) -> Vec<Step>) -> Undo {
+        let side = match self.frontiers[0].len() <= self.frontiers[1].len() {
+            true => Side::Start,
+            false => Side::Goal,
+        };
+        let frontier = std::mem::take(&mut self.frontiers[side as usize]);
+        let mut undo = Undo {
+            side,
+            frontier: frontier.clone(),
+            reached: Vec::new(),
+        };
+        // the shortest path through a tile of each side found so far
+        let mut best: Option<(u32, Point, Step)> = None;
+        for &from in &frontier {
+            let depth = self.reached[self.tile(from)].map_or(0, |reached| reached.depth) + 1;
+            for step in steps(from) {
+                let reversible = || {
+                    steps(step.to)
+                        .iter()
+                        .any(|back| back.tile == from && back.to == from)
+                };
+                if side == Side::Goal && (step.tile != step.to || !reversible()) {
+                    continue;
+                }
+                let i = self.tile(step.to);
+                match self.reached[i] {
+                    None => {
+                        self.reached[i] = Some(Reached {
+                            side,
+                            parent: Some(from),
+                            tile: step.tile,
+                            depth,
+                        });
+                        self.frontiers[side as usize].push(step.to);
+                        undo.reached.push(step.to);
+                    }
+                    Some(other) if other.side != side => {
+                        let length = depth + other.depth;
+                        if best.is_none_or(|(shortest, _, _)| length < shortest) {
+                            best = Some((length, from, step));
+                        }
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        self.layers += 1;
+        if let Some((_, from, step)) = best {
+            self.meeting = Some(step.to);
+            self.path = Some(match side {
+                Side::Start => self.join(from, step.tile, step.to),
+                // the move back from `from` leads straight to it
+                Side::Goal => self.join(step.to, from, from),
+            });
+        }
+        undo
+    }
+
+    /// The tiles to step into from the start over `last` of its search, by
+    /// stepping into `tile`, to `first` of the goal's, and on to the goal.
+    fn join(&self, last: Point, tile: Point, first: Point) -> Vec<Point> {
+        let mut path = Vec::new();
+        let mut pos = last;
+        while let Some(reached) = self.reached[self.tile(pos)] {
+            let Some(parent) = reached.parent else {
+                break;
+            };
+            path.push(reached.tile);
+            pos = parent;
+        }
+        path.reverse();
+        path.push(tile);
+        let mut pos = first;
+        while let Some(parent) = self.reached[self.tile(pos)].and_then(|reached| reached.parent) {
+            path.push(parent);
+            pos = parent;
+        }
+        path
+    }
+
+    pub fn undo(&mut self, undo: Undo) {
+        for pos in undo.reached {
+            let i = self.tile(pos);
+            self.reached[i] = None;
+        }
+        self.frontiers[undo.side as usize] = undo.frontier;
+        self.layers -= 1;
+        self.meeting = None;
+        self.path = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui_counter_app::pathfinding::grid_steps;
+    use ratatui_counter_app::tile::TileMap;
+
+    #[test]
+    fn test_searches_meet() {
+        // a ring of 3 x 3 tiles around a wall in the middle: from (0, 0) to
+        // (2, 2) it is four steps either way around
+        let mut walls: TileMap<bool> = TileMap::with_default(3, 3);
+        walls[(1, 1)] = true;
+        let steps = grid_steps(&walls, |wall| !wall);
+        let mut search = Bidirectional::new(3, 3, (0, 0), (2, 2));
+        let mut undos = Vec::new();
+        while !search.is_done() {
+            undos.push(search.grow(&steps));
+        }
+        let path = search.path.clone().unwrap();
+        assert_eq!(4, path.len());
+        assert_eq!(Some(&(2, 2)), path.last());
+        assert!(path
+            .windows(2)
+            .all(|w| w[0].0.abs_diff(w[1].0) + w[0].1.abs_diff(w[1].1) == 1));
+        assert_eq!(
+            Some(Side::Start),
+            search.reached((1, 0)).map(|(side, _)| side)
+        );
+        assert_eq!(
+            Some(Side::Goal),
+            search.reached((2, 1)).map(|(side, _)| side)
+        );
+
+        for undo in undos.into_iter().rev() {
+            search.undo(undo);
+        }
+        assert_eq!([1, 1], search.counts());
+        assert!(!search.is_done());
+    }
+}
@@ -0,0 +1,85 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use ratatui::buffer::Buffer;
+use serde::Serialize;
+
+use crate::ansi;
+
+#[derive(Serialize)]
+struct Header {
+    version: u8,
+    width: u16,
+    height: u16,
+    timestamp: u64,
+}
+
+/// Writes rendered frames as an asciinema v2 cast, which replays them at
+/// the pace they were drawn.
+#[derive(Debug)]
+pub struct Cast {
+    out: BufWriter<File>,
+    /// When the first frame was drawn.
+    start: Option<Instant>,
+    /// The frame written last, to only write the cells that changed.
+    last: Buffer,
+}
+
+impl Cast {
+    /// Write to the file at `path`. The header is written with the first
+    /// frame, once the terminal size is known.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+            start: None,
+            last: Buffer::default(),
+        })
+    }
+
+    pub fn frame(&mut self, buffer: &Buffer) -> io::Result<()> {
+        let area = buffer.area;
+        let start = match self.start {
+            Some(start) => start,
+            None => {
+                let header = Header {
+                    version: 2,
+                    width: area.width,
+                    height: area.height,
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                };
+                serde_json::to_writer(&mut self.out, &header)?;
+                writeln!(self.out)?;
+                *self.start.insert(Instant::now())
+            }
+        };
+        let time = start.elapsed().as_secs_f64();
+        let mut data = String::new();
+        if area != self.last.area {
+            if !self.last.area.is_empty() {
+                let size = format!("{}x{}", area.width, area.height);
+                self.event(time, "r", &size)?;
+            }
+            self.last = Buffer::empty(area);
+            data.push_str("\x1b[2J");
+        }
+        data.push_str(&ansi::updates(&self.last.diff(buffer)));
+        if !data.is_empty() {
+            self.event(time, "o", &data)?;
+        }
+        self.last = buffer.clone();
+        Ok(())
+    }
+
+    fn event(&mut self, time: f64, kind: &str, data: &str) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &(time, kind, data))?;
+        writeln!(self.out)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,133 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::maze::{Maze, Tile};
+
+/// An unbounded maze made of Kruskal chunks that are generated on demand.
+///
+/// Every chunk is derived only from the world seed and its chunk coordinates,
+/// so chunks can be generated in any order and always look the same.
+/// Neighbouring chunks share their boundary wall, and each shared boundary
+/// gets one doorway that both sides agree on, which keeps the whole world
+/// connected.
+#[derive(Debug)]
+pub struct ChunkedMaze {
+    seed: u64,
+    chunk_cells: usize,
+    cols: usize,
+    rows: usize,
+}
+
+// salts for the different things derived from a chunk coordinate
+const SALT_MAZE: u64 = 0;
+const SALT_DOOR_EAST: u64 = 1;
+const SALT_DOOR_SOUTH: u64 = 2;
+
+fn mix(seed: u64, cx: usize, cy: usize, salt: u64) -> u64 {
+    // splitmix64 over the combined inputs
+    let mut z = seed
+        ^ (cx as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (cy as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F)
+        ^ salt.wrapping_mul(0x1656_67B1_9E37_79F9);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl ChunkedMaze {
+    pub fn new(seed: u64, chunk_cells: usize) -> Self {
+        Self {
+            seed,
+            chunk_cells,
+            cols: 0,
+            rows: 0,
+        }
+    }
+
+    /// Number of tiles between two chunk origins.
+    pub fn chunk_tiles(&self) -> usize {
+        2 * self.chunk_cells
+    }
+
+    pub fn width(&self) -> usize {
+        self.cols * self.chunk_tiles() + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows * self.chunk_tiles() + 1
+    }
+
+    /// Cell offset of the doorway on the east side of chunk (cx, cy).
+    fn door_east(&self, cx: usize, cy: usize) -> usize {
+        (mix(self.seed, cx, cy, SALT_DOOR_EAST) % self.chunk_cells as u64) as usize
+    }
+
+    /// Cell offset of the doorway on the south side of chunk (cx, cy).
+    fn door_south(&self, cx: usize, cy: usize) -> usize {
+        (mix(self.seed, cx, cy, SALT_DOOR_SOUTH) % self.chunk_cells as u64) as usize
+    }
+
+    /// Generate chunk (cx, cy) including the doorways on all four sides.
+    ///
+    /// The returned maze is `2 * chunk_cells + 1` tiles wide and high, and its
+    /// top left tile sits at `(cx * chunk_tiles, cy * chunk_tiles)`.
+    pub fn chunk(&self, cx: usize, cy: usize) -> Maze {
+        let mut rng = StdRng::seed_from_u64(mix(self.seed, cx, cy, SALT_MAZE));
+        let mut maze = Maze::kruskal(self.chunk_cells, self.chunk_cells, &mut rng);
+        let last = self.chunk_tiles();
+        if cx > 0 {
+            maze.tiles[2 * self.door_east(cx - 1, cy) + 1][0] = Tile::Free;
+        }
+        maze.tiles[2 * self.door_east(cx, cy) + 1][last] = Tile::Free;
+        if cy > 0 {
+            maze.tiles[0][2 * self.door_south(cx, cy - 1) + 1] = Tile::Free;
+        }
+        maze.tiles[last][2 * self.door_south(cx, cy) + 1] = Tile::Free;
+        maze
+    }
+
+    /// Grow the loaded region to at least `cols` x `rows` chunks.
+    ///
+    /// Returns the coordinates of all newly loaded chunks.
+    pub fn load(&mut self, cols: usize, rows: usize) -> Vec<(usize, usize)> {
+        let (new_cols, new_rows) = (self.cols.max(cols), self.rows.max(rows));
+        let mut loaded = Vec::new();
+        for cy in 0..new_rows {
+            for cx in 0..new_cols {
+                if cx >= self.cols || cy >= self.rows {
+                    loaded.push((cx, cy));
+                }
+            }
+        }
+        self.cols = new_cols;
+        self.rows = new_rows;
+        loaded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_boundaries_match() {
+        let world = ChunkedMaze::new(42, 4);
+        let last = world.chunk_tiles();
+        for cy in 0..3 {
+            for cx in 0..3 {
+                let chunk = world.chunk(cx, cy);
+                let east = world.chunk(cx + 1, cy);
+                let south = world.chunk(cx, cy + 1);
+                for i in 0..=last {
+                    assert_eq!(
+                        matches!(chunk.tiles[i][last], Tile::Free),
+                        matches!(east.tiles[i][0], Tile::Free)
+                    );
+                    assert_eq!(
+                        matches!(chunk.tiles[last][i], Tile::Free),
+                        matches!(south.tiles[0][i], Tile::Free)
+                    );
+                }
+            }
+        }
+    }
+}
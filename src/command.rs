@@ -0,0 +1,221 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::events::{Breakpoint, EVENT_NAMES};
+use crate::maze::{Generator, GENERATOR_NAMES};
+use crate::plugin;
+use crate::watch::{Watch, WATCH_NAMES};
+
+/// Something to do, typed into the command line after a `:`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Seed(u64),
+    Algo(Generator),
+    /// Maze width and height in cells.
+    Size(usize, usize),
+    /// Write the maze as text to a file, or into the gallery if no path is
+    /// given.
+    Export(Option<PathBuf>),
+    /// Add the breakpoint, or remove it if it is set already.
+    Break(Breakpoint),
+    ClearBreakpoints,
+    /// Add the watch, or remove it if it is set already.
+    Watch(Watch),
+    ClearWatches,
+    /// Label the tile, or take its label off if no label is given.
+    Note(u16, u16, Option<String>),
+    ClearNotes,
+    /// Pause the run and go on by this many ticks.
+    Steps(u64),
+    Quit,
+}
+
+const COMMANDS: [&str; 9] = [
+    "algo", "break", "export", "note", "quit", "seed", "size", "steps", "watch",
+];
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("no command given".to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        let number = |arg: &str| {
+            arg.parse::<u64>()
+                .map_err(|_| format!("'{}' is not a number", arg))
+        };
+        let coordinate = |arg: &str| {
+            u16::try_from(number(arg)?)
+                .map_err(|_| format!("'{}' is too large for a coordinate", arg))
+        };
+        match (name, &args[..]) {
+            ("seed", [seed]) => Ok(Command::Seed(number(seed)?)),
+            ("algo", [name]) => Ok(Command::Algo(name.parse()?)),
+            ("size", [width, height]) => Ok(Command::Size(
+                number(width)? as usize,
+                number(height)? as usize,
+            )),
+            ("export", []) => Ok(Command::Export(None)),
+            ("export", [path]) => Ok(Command::Export(Some(PathBuf::from(path)))),
+            ("break", [x, y]) => Ok(Command::Break(Breakpoint::Tile(
+                coordinate(x)?,
+                coordinate(y)?,
+            ))),
+            ("break", ["clear"]) => Ok(Command::ClearBreakpoints),
+            // the robot backtracks by popping the stack
+            ("break", ["backtrack"]) => Ok(Command::Break(Breakpoint::Event("pop"))),
+            ("break", [name]) => match EVENT_NAMES.iter().find(|n| *n == name) {
+                Some(name) => Ok(Command::Break(Breakpoint::Event(name))),
+                None => Err(format!(
+                    "unknown event '{}', expected one of: {}, backtrack",
+                    name,
+                    EVENT_NAMES.join(", ")
+                )),
+            },
+            ("watch", ["stack", depth]) => Ok(Command::Watch(Watch::StackDepth(
+                number(depth)? as usize,
+            ))),
+            ("watch", ["revisit"]) => Ok(Command::Watch(Watch::Revisit)),
+            ("watch", ["coverage", percent]) => match percent.parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => {
+                    Ok(Command::Watch(Watch::Coverage(percent)))
+                }
+                _ => Err(format!("'{}' is not a percentage", percent)),
+            },
+            ("watch", ["clear"]) => Ok(Command::ClearWatches),
+            ("note", ["clear"]) => Ok(Command::ClearNotes),
+            ("note", [x, y, label @ ..]) => Ok(Command::Note(
+                coordinate(x)?,
+                coordinate(y)?,
+                (!label.is_empty()).then(|| label.join(" ")),
+            )),
+            ("steps", [ticks]) => Ok(Command::Steps(number(ticks)?)),
+            ("quit" | "q", []) => Ok(Command::Quit),
+            ("seed", _) => Err("usage: seed <number>".to_string()),
+            ("algo", _) => Err(format!(
+                "usage: algo <{}>",
+                GENERATOR_NAMES
+                    .into_iter()
+                    .chain(plugin::names())
+                    .collect::<Vec<_>>()
+                    .join("|")
+            )),
+            ("size", _) => Err("usage: size <width> <height>".to_string()),
+            ("export", _) => Err("usage: export [path]".to_string()),
+            ("break", _) => Err("usage: break <x> <y> | break <event> | break clear".to_string()),
+            ("watch", _) => Err(
+                "usage: watch stack <depth> | watch revisit | watch coverage <percent> | watch clear"
+                    .to_string(),
+            ),
+            ("note", _) => Err("usage: note <x> <y> [label] | note clear".to_string()),
+            ("steps", _) => Err("usage: steps <ticks>".to_string()),
+            ("quit" | "q", _) => Err("usage: quit".to_string()),
+            _ => Err(format!("unknown command '{}'", name)),
+        }
+    }
+}
+
+/// `input` with its last word completed as far as it is unambiguous, either
+/// to a command or to an argument of `algo`, `break` or `watch`.
+pub fn complete(input: &str) -> String {
+    let (head, word) = match input.rfind(' ') {
+        Some(i) => input.split_at(i + 1),
+        None => ("", input),
+    };
+    let candidates: Vec<&str> = match head.split_whitespace().collect::<Vec<_>>()[..] {
+        [] => COMMANDS.to_vec(),
+        ["algo"] => GENERATOR_NAMES.into_iter().chain(plugin::names()).collect(),
+        ["break"] => ["clear", "backtrack"]
+            .into_iter()
+            .chain(EVENT_NAMES)
+            .collect(),
+        ["watch"] => WATCH_NAMES.to_vec(),
+        _ => Vec::new(),
+    };
+    let matches: Vec<&str> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+    let completed = match matches[..] {
+        [] => word.to_string(),
+        [only] => format!("{} ", only),
+        [first, ..] => {
+            // the longest prefix all matches share
+            let len = (word.len()..first.len())
+                .take_while(|&i| {
+                    matches
+                        .iter()
+                        .all(|m| m.as_bytes().get(i) == first.as_bytes().get(i))
+                })
+                .count();
+            first[..word.len() + len].to_string()
+        }
+    };
+    format!("{}{}", head, completed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ok(Command::Seed(42)), "seed 42".parse());
+        assert_eq!(Ok(Command::Size(32, 16)), " size  32 16".parse());
+        assert_eq!(
+            Ok(Command::Algo(Generator::Kruskal)),
+            "algo kruskal".parse()
+        );
+        assert!("algo prim".parse::<Command>().is_err());
+        assert!("size 32".parse::<Command>().is_err());
+        assert!("seed x".parse::<Command>().is_err());
+        assert_eq!(
+            Ok(Command::Break(Breakpoint::Tile(3, 5))),
+            "break 3 5".parse()
+        );
+        assert_eq!(
+            Ok(Command::Break(Breakpoint::Event("pop"))),
+            "break pop".parse()
+        );
+        assert_eq!(
+            Ok(Command::Break(Breakpoint::Event("pop"))),
+            "break backtrack".parse()
+        );
+        assert_eq!(
+            Err("'70000' is too large for a coordinate".to_string()),
+            "break 70000 5".parse::<Command>()
+        );
+        assert!("note 3 70000".parse::<Command>().is_err());
+        assert_eq!(
+            Ok(Command::Watch(Watch::StackDepth(20))),
+            "watch stack 20".parse()
+        );
+        assert_eq!(
+            Ok(Command::Watch(Watch::Coverage(50.0))),
+            "watch coverage 50".parse()
+        );
+        assert!("watch coverage 150".parse::<Command>().is_err());
+        assert_eq!(
+            Ok(Command::Note(3, 5, Some("dead end".to_string()))),
+            "note 3 5 dead end".parse()
+        );
+        assert_eq!(Ok(Command::Note(3, 5, None)), "note 3 5".parse());
+        assert!("note 3".parse::<Command>().is_err());
+        assert_eq!(Ok(Command::Steps(50)), "steps 50".parse());
+        assert!("steps".parse::<Command>().is_err());
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!("algo ", complete("a"));
+        assert_eq!("s", complete("s"));
+        assert_eq!("seed ", complete("se"));
+        assert_eq!("algo drunkard ", complete("algo d"));
+        assert_eq!("watch revisit ", complete("watch r"));
+        assert_eq!("break backtrack ", complete("break ba"));
+        assert_eq!("size 3", complete("size 3"));
+    }
+}
@@ -0,0 +1,247 @@
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::thread;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Where control requests come from, picked with `--control`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ControlMode {
+    /// JSON-RPC requests on stdin, one per line, and responses on stdout.
+    Stdio,
+}
+
+/// The terminal drawn to while stdin and stdout carry requests.
+pub const TTY_PATH: &str = "/dev/tty";
+
+// error codes from the JSON-RPC 2.0 spec
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+/// The request was fine, but the app can't do it right now.
+pub const FAILED: i32 = -32000;
+
+/// Something the app is asked to do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Method {
+    Pause,
+    Resume,
+    /// Run this many ticks while paused.
+    Step {
+        ticks: u64,
+    },
+    SetSpeed {
+        ticks_per_second: u32,
+    },
+    /// Move the goal to a free tile.
+    PlaceGoal {
+        x: usize,
+        y: usize,
+    },
+    QueryState,
+}
+
+#[derive(Debug, Deserialize)]
+struct StepParams {
+    #[serde(default = "one")]
+    ticks: u64,
+}
+
+fn one() -> u64 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct SpeedParams {
+    ticks_per_second: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoalParams {
+    x: usize,
+    y: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawRequest {
+    jsonrpc: String,
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+/// A request to answer, unless it has no id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub id: Option<Value>,
+    pub method: Method,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Response {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+impl Response {
+    pub fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+
+    fn error(id: Value, code: i32, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self::new(id, Err(RpcError { code, message }))
+    }
+}
+
+fn params<T: for<'de> Deserialize<'de>>(params: Value) -> Result<T, String> {
+    // no params at all are the same as empty ones
+    let params = if params.is_null() {
+        Value::Object(Default::default())
+    } else {
+        params
+    };
+    serde_json::from_value(params).map_err(|e| e.to_string())
+}
+
+/// Parse a line of JSON-RPC, or return the error response to send back.
+pub fn parse(line: &str) -> Result<Request, Response> {
+    let raw: RawRequest = match serde_json::from_str::<Value>(line) {
+        Err(e) => return Err(Response::error(Value::Null, PARSE_ERROR, e.to_string())),
+        Ok(value) => serde_json::from_value(value)
+            .map_err(|e| Response::error(Value::Null, INVALID_REQUEST, e.to_string()))?,
+    };
+    let id = raw.id.clone().unwrap_or(Value::Null);
+    if raw.jsonrpc != "2.0" {
+        return Err(Response::error(
+            id,
+            INVALID_REQUEST,
+            "jsonrpc must be \"2.0\"",
+        ));
+    }
+    let method = match raw.method.as_str() {
+        "pause" => Ok(Method::Pause),
+        "resume" => Ok(Method::Resume),
+        "step" => params(raw.params).map(|p: StepParams| Method::Step { ticks: p.ticks }),
+        "set_speed" => params(raw.params).map(|p: SpeedParams| Method::SetSpeed {
+            ticks_per_second: p.ticks_per_second,
+        }),
+        "place_goal" => {
+            params(raw.params).map(|p: GoalParams| Method::PlaceGoal { x: p.x, y: p.y })
+        }
+        "query_state" => Ok(Method::QueryState),
+        name => {
+            let message = format!("unknown method '{}'", name);
+            return Err(Response::error(id, METHOD_NOT_FOUND, message));
+        }
+    };
+    match method {
+        Ok(method) => Ok(Request { id: raw.id, method }),
+        Err(e) => Err(Response::error(id, INVALID_PARAMS, e)),
+    }
+}
+
+/// Requests read on a thread of their own, so the app can go on while
+/// waiting for them.
+#[derive(Debug)]
+pub struct Control {
+    lines: mpsc::Receiver<String>,
+}
+
+impl Control {
+    pub fn stdio() -> Self {
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                if !line.trim().is_empty() && sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self { lines }
+    }
+
+    /// The requests that came in since the last call.
+    pub fn poll(&self) -> Vec<Result<Request, Response>> {
+        self.lines.try_iter().map(|line| parse(&line)).collect()
+    }
+
+    pub fn respond(&self, response: &Response) -> io::Result<()> {
+        let mut out = io::stdout().lock();
+        serde_json::to_writer(&mut out, response)?;
+        writeln!(out)?;
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(line: &str) -> i32 {
+        parse(line).unwrap_err().error.unwrap().code
+    }
+
+    #[test]
+    fn test_parse() {
+        let request = parse(r#"{"jsonrpc":"2.0","id":1,"method":"step","params":{"ticks":5}}"#);
+        assert_eq!(
+            Ok(Request {
+                id: Some(Value::from(1)),
+                method: Method::Step { ticks: 5 }
+            }),
+            request
+        );
+        let request = parse(r#"{"jsonrpc":"2.0","id":"a","method":"step"}"#).unwrap();
+        assert_eq!(Method::Step { ticks: 1 }, request.method);
+        let request = parse(r#"{"jsonrpc":"2.0","method":"pause"}"#).unwrap();
+        assert_eq!(None, request.id);
+
+        assert_eq!(PARSE_ERROR, code("{"));
+        assert_eq!(INVALID_REQUEST, code(r#"{"id":1,"method":"pause"}"#));
+        assert_eq!(
+            INVALID_REQUEST,
+            code(r#"{"jsonrpc":"1.0","id":1,"method":"pause"}"#)
+        );
+        assert_eq!(
+            METHOD_NOT_FOUND,
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"fly"}"#)
+        );
+        assert_eq!(
+            INVALID_PARAMS,
+            code(r#"{"jsonrpc":"2.0","id":1,"method":"place_goal","params":{"x":1}}"#)
+        );
+
+        let response = Response::new(Value::from(7), Ok(Value::from("ok")));
+        assert_eq!(
+            r#"{"jsonrpc":"2.0","id":7,"result":"ok"}"#,
+            serde_json::to_string(&response).unwrap()
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+/// When the robot passed a checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Split {
+    pub tick: u64,
+    pub time: Duration,
+}
+
+/// Checkpoints the robot has to pass in order, timed as it does, which
+/// turns the maze into a time trial. Passing one out of order doesn't count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Course {
+    checkpoints: Vec<(u16, u16)>,
+    /// Of the checkpoints passed so far.
+    splits: Vec<Split>,
+}
+
+impl Course {
+    pub fn new(checkpoints: Vec<(u16, u16)>) -> Self {
+        Self {
+            checkpoints,
+            splits: Vec::new(),
+        }
+    }
+
+    pub fn checkpoints(&self) -> &[(u16, u16)] {
+        &self.checkpoints
+    }
+
+    pub fn splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    /// The checkpoint to pass next.
+    pub fn next(&self) -> Option<(u16, u16)> {
+        self.checkpoints.get(self.splits.len()).copied()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.splits.len() == self.checkpoints.len()
+    }
+
+    /// The robot got to `pos`. If that is the next checkpoint, it is
+    /// passed, and its number from 1 is returned.
+    pub fn pass(&mut self, pos: (u16, u16), tick: u64, time: Duration) -> Option<usize> {
+        if self.next() != Some(pos) {
+            return None;
+        }
+        self.splits.push(Split { tick, time });
+        Some(self.splits.len())
+    }
+
+    /// The time from the checkpoint before the `i`th one, or the start, to
+    /// the `i`th one, once it is passed.
+    pub fn lap(&self, i: usize) -> Option<Duration> {
+        let split = self.splits.get(i)?;
+        let before = i
+            .checked_sub(1)
+            .map_or(Duration::ZERO, |i| self.splits[i].time);
+        Some(split.time - before)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_order() {
+        let mut course = Course::new(vec![(1, 1), (3, 1)]);
+        let secs = Duration::from_secs;
+        assert_eq!(None, course.pass((3, 1), 2, secs(1)));
+        assert_eq!(Some(1), course.pass((1, 1), 5, secs(2)));
+        assert_eq!(None, course.pass((1, 1), 6, secs(3)));
+        assert!(!course.is_done());
+        assert_eq!(Some(2), course.pass((3, 1), 9, secs(7)));
+        assert!(course.is_done());
+        assert_eq!(None, course.next());
+        assert_eq!(Some(secs(2)), course.lap(0));
+        assert_eq!(Some(secs(5)), course.lap(1));
+        assert_eq!(None, course.lap(2));
+    }
+}
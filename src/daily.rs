@@ -0,0 +1,135 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::maze::Generator;
+use crate::menu::{Chassis, Minotaur, Openings, RunConfig, Solver};
+
+pub const DAILY_PATH: &str = "daily.tsv";
+
+/// Days since the Unix epoch, in UTC so the day changes at the same moment
+/// for everyone.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86_400)
+}
+
+/// The puzzle of `day`. Everything but the solver is fixed, so all runs of
+/// a day are on the same maze.
+pub fn config(day: u64, solver: Solver) -> RunConfig {
+    RunConfig {
+        generator: Generator::Kruskal,
+        infinite: false,
+        fit: false,
+        width: 24,
+        height: 16,
+        seed: seed(day),
+        items: 10,
+        doors: 1,
+        teleporters: 1,
+        one_ways: 0,
+        terrain: 2,
+        minotaur: Minotaur::Off,
+        solver,
+        goals: 4,
+        robots: 3,
+        comm_radius: 0,
+        shift_interval: 0,
+        chassis: Chassis::Legs,
+        helper_chassis: Chassis::Legs,
+        coverage_goal: 0,
+        checkpoints: 0,
+        openings: Openings::Closed,
+    }
+}
+
+/// A seed that looks random but is the same for everyone on `day`.
+fn seed(day: u64) -> u64 {
+    // the finalizer of splitmix64
+    let mut z = day.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    (z ^ (z >> 31)) % 1_000_000
+}
+
+/// The days a daily puzzle was solved on. Stored as one line per solve with
+/// the day and the steps it took.
+#[derive(Debug)]
+pub struct DailyHistory {
+    path: PathBuf,
+    days: Vec<u64>,
+}
+
+impl DailyHistory {
+    /// Load the history from `path`. A missing file is an empty history,
+    /// lines that can't be parsed are skipped.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let days = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| line.split('\t').next()?.parse().ok())
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self { path, days })
+    }
+
+    /// An empty history that will be written to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            days: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, day: u64, steps: u64) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}\t{}", day, steps)?;
+        self.days.push(day);
+        Ok(())
+    }
+
+    /// Days in a row the puzzle was solved, up to `today`. A streak that
+    /// ended yesterday still counts, as today's puzzle may not be solved yet.
+    pub fn streak(&self, today: u64) -> u32 {
+        let solved = |day: u64| self.days.contains(&day);
+        let mut day = if solved(today) {
+            today
+        } else {
+            today.saturating_sub(1)
+        };
+        let mut streak = 0;
+        while solved(day) {
+            streak += 1;
+            let Some(before) = day.checked_sub(1) else {
+                break;
+            };
+            day = before;
+        }
+        streak
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_streak() {
+        let mut history = DailyHistory::new("unused");
+        history.days = vec![10, 12, 13, 13, 14];
+        assert_eq!(3, history.streak(14));
+        assert_eq!(3, history.streak(15));
+        assert_eq!(0, history.streak(16));
+        assert_eq!(1, history.streak(11));
+        assert_eq!(seed(20_000), seed(20_000));
+        assert_ne!(seed(20_000), seed(20_001));
+    }
+}
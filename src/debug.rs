@@ -1,23 +1,234 @@
-use lazy_static::lazy_static;
-use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 
-lazy_static! {
-    static ref FILE: Mutex<File> = Mutex::new(
-        OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open("debug.txt")
-            .unwrap()
-    );
+/// Where log messages go, if anywhere.
+static LOG: Mutex<Option<Log>> = Mutex::new(None);
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Lines of the log kept in memory for the log pane.
+const RECENT_LINES: usize = 500;
+
+/// The latest lines logged, whether or not they go to a file, once
+/// `keep_recent` was called.
+static RECENT: Mutex<Recent> = Mutex::new(Recent::new());
+
+static KEEP_RECENT: AtomicBool = AtomicBool::new(false);
+
+/// The latest lines of the log, up to a fixed number. Older ones are
+/// dropped.
+#[derive(Debug)]
+pub struct Recent {
+    /// The newest last.
+    lines: VecDeque<String>,
+}
+
+impl Recent {
+    const fn new() -> Self {
+        Self {
+            lines: VecDeque::new(),
+        }
+    }
+
+    /// Add the lines of `text`.
+    fn push(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.lines.len() == RECENT_LINES {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line.to_string());
+        }
+    }
+
+    pub fn lines(&self) -> &VecDeque<String> {
+        &self.lines
+    }
+}
+
+/// A log file that is rotated once it reaches a size limit: `debug.txt`
+/// becomes `debug.txt.1`, `debug.txt.1` becomes `debug.txt.2` and so on,
+/// dropping the oldest.
+#[derive(Debug)]
+struct Log {
+    file: File,
+    path: PathBuf,
+    /// Bytes in the current file.
+    size: u64,
+    max_size: u64,
+    /// Number of rotated files to keep.
+    keep: usize,
+}
+
+impl Log {
+    fn rotated(&self, i: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", i));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep > 0 {
+            for i in (1..self.keep).rev() {
+                let from = self.rotated(i);
+                if from.exists() {
+                    fs::rename(from, self.rotated(i + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+        }
+        // a new file also writes from the start when nothing is kept,
+        // rather than at the end of what was cut off
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+
+    /// Write a message, rotating first if the file is full. Messages are
+    /// never split across files.
+    fn message(&mut self, args: fmt::Arguments) -> io::Result<()> {
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+        self.write_fmt(args)
+    }
+}
+
+impl Write for Log {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
 }
 
-pub fn debug_print(s: String) {
-    write!(FILE.lock().unwrap(), "{}", s).unwrap();
+/// Write log messages to the file at `path`, appending to it or replacing
+/// it. Once the file reaches `max_size` bytes it is rotated, keeping
+/// `keep` older files. Until this succeeds, messages are dropped.
+pub fn open(path: impl AsRef<Path>, append: bool, max_size: u64, keep: usize) -> io::Result<()> {
+    *LOG.lock().unwrap() = Some(open_log(path.as_ref(), append, max_size, keep)?);
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
 }
 
-pub fn debug_println(s: String) {
-    writeln!(FILE.lock().unwrap(), "{}", s).unwrap();
+fn open_log(path: &Path, append: bool, max_size: u64, keep: usize) -> io::Result<Log> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(path)?;
+    Ok(Log {
+        size: file.metadata()?.len(),
+        file,
+        path: path.to_path_buf(),
+        max_size,
+        keep,
+    })
+}
+
+/// Keep the latest lines of the log in memory from now on, for the log
+/// pane, even without a log file.
+pub fn keep_recent() {
+    KEEP_RECENT.store(true, Ordering::Relaxed);
+}
+
+/// Look at the latest lines of the log.
+pub fn with_recent<R>(f: impl FnOnce(&Recent) -> R) -> R {
+    f(&RECENT.lock().unwrap())
+}
+
+/// Whether messages are written to the log file or kept in memory. The
+/// macros check this before formatting anything.
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed) || KEEP_RECENT.load(Ordering::Relaxed)
+}
+
+/// Write to the log. Writing to the file stops if this fails, e.g. once
+/// the disk is full.
+pub fn write(args: fmt::Arguments) {
+    let text = fmt::format(args);
+    if KEEP_RECENT.load(Ordering::Relaxed) {
+        RECENT.lock().unwrap().push(&text);
+    }
+    let mut log = LOG.lock().unwrap();
+    if let Some(Err(_)) = log
+        .as_mut()
+        .map(|log| log.message(format_args!("{}", text)))
+    {
+        *log = None;
+        ENABLED.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Log a message, formatting it like `print!` only if logging is enabled.
+#[allow(unused_macros)]
+macro_rules! debug_print {
+    ($($arg:tt)*) => {
+        if $crate::debug::enabled() {
+            $crate::debug::write(format_args!($($arg)*));
+        }
+    };
+}
+
+/// Log a line, formatting it like `println!` only if logging is enabled.
+macro_rules! debug_println {
+    ($($arg:tt)*) => {
+        if $crate::debug::enabled() {
+            $crate::debug::write(format_args!("{}\n", format_args!($($arg)*)));
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use debug_print;
+pub(crate) use debug_println;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate() {
+        let dir = std::env::temp_dir().join(format!("debug-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("debug.txt");
+        let mut log = open_log(&path, false, 9, 2).unwrap();
+        for line in ["aaaaaaaa", "bbbbbbbb", "cccccccc", "dddddddd"] {
+            log.message(format_args!("{}\n", line)).unwrap();
+        }
+        assert_eq!("dddddddd\n", fs::read_to_string(&path).unwrap());
+        assert_eq!("cccccccc\n", fs::read_to_string(log.rotated(1)).unwrap());
+        assert_eq!("bbbbbbbb\n", fs::read_to_string(log.rotated(2)).unwrap());
+        assert!(!log.rotated(3).exists());
+
+        let path = dir.join("single.txt");
+        let mut log = open_log(&path, false, 9, 0).unwrap();
+        for line in ["aaaaaaaa", "bbbbbbbb", "cccccccc"] {
+            log.message(format_args!("{}\n", line)).unwrap();
+        }
+        assert_eq!("cccccccc\n", fs::read_to_string(&path).unwrap());
+        assert!(!log.rotated(1).exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_recent() {
+        let mut recent = Recent::new();
+        recent.push("first\nsecond\n");
+        assert_eq!(vec!["first", "second"], Vec::from(recent.lines().clone()));
+        for i in 0..RECENT_LINES {
+            recent.push(&format!("{}\n", i));
+        }
+        assert_eq!(RECENT_LINES, recent.lines().len());
+        assert_eq!(Some(&"0".to_string()), recent.lines().front());
+    }
 }
@@ -0,0 +1,269 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Clear, Padding, Paragraph, Widget},
+};
+
+use crate::menu::centered;
+
+/// What a dialog asks for, below its message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Body {
+    /// Nothing to answer, just OK.
+    Message,
+    /// Just yes or no.
+    Confirm,
+    Input(String),
+    Select {
+        options: Vec<String>,
+        selected: usize,
+    },
+}
+
+/// Which part of the dialog takes the keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    /// The text field or the list.
+    Body,
+    Ok,
+    Cancel,
+}
+
+/// What the dialog was answered with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    Text(String),
+    /// The index of the selected option.
+    Choice(usize),
+}
+
+/// Where a dialog is at after a key.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Open,
+    Cancelled,
+    Answered(Answer),
+}
+
+/// A modal dialog drawn over everything else, which takes all keys until it
+/// is answered or cancelled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dialog {
+    title: String,
+    message: String,
+    body: Body,
+    focus: Focus,
+}
+
+impl Dialog {
+    fn new(title: &str, message: &str, body: Body, focus: Focus) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+            body,
+            focus,
+        }
+    }
+
+    /// Tell about something, like an error, that only needs to be seen.
+    pub fn message(title: &str, message: &str) -> Self {
+        Self::new(title, message, Body::Message, Focus::Ok)
+    }
+
+    /// Ask a yes or no question. `y` and `n` answer it right away.
+    pub fn confirm(title: &str, message: &str) -> Self {
+        Self::new(title, message, Body::Confirm, Focus::Ok)
+    }
+
+    /// Ask for a line of text, starting out as `text`.
+    pub fn input(title: &str, message: &str, text: &str) -> Self {
+        Self::new(title, message, Body::Input(text.to_string()), Focus::Body)
+    }
+
+    /// Ask to pick one of `options`.
+    pub fn select(title: &str, message: &str, options: Vec<String>) -> Self {
+        let body = Body::Select {
+            options,
+            selected: 0,
+        };
+        Self::new(title, message, body, Focus::Body)
+    }
+
+    /// Tab and Shift+Tab move the focus, Enter answers unless Cancel has
+    /// it, and Esc cancels.
+    pub fn handle_key(&mut self, code: KeyCode) -> Outcome {
+        if self.body == Body::Message {
+            return match code {
+                KeyCode::Enter | KeyCode::Esc => Outcome::Answered(Answer::Yes),
+                _ => Outcome::Open,
+            };
+        }
+        let has_body = self.body != Body::Confirm;
+        match (code, self.focus, &mut self.body) {
+            (KeyCode::Esc, _, _) => return Outcome::Cancelled,
+            (KeyCode::Enter, Focus::Cancel, _) => return Outcome::Cancelled,
+            (KeyCode::Enter, _, Body::Confirm) => return Outcome::Answered(Answer::Yes),
+            (KeyCode::Enter, _, Body::Input(text)) => {
+                return Outcome::Answered(Answer::Text(text.clone()))
+            }
+            (KeyCode::Enter, _, Body::Select { selected, .. }) => {
+                return Outcome::Answered(Answer::Choice(*selected))
+            }
+            (KeyCode::Char('y'), _, Body::Confirm) => return Outcome::Answered(Answer::Yes),
+            (KeyCode::Char('n'), _, Body::Confirm) => return Outcome::Cancelled,
+            (KeyCode::Tab, focus, _) => {
+                self.focus = match focus {
+                    Focus::Body => Focus::Ok,
+                    Focus::Ok => Focus::Cancel,
+                    Focus::Cancel if has_body => Focus::Body,
+                    Focus::Cancel => Focus::Ok,
+                }
+            }
+            (KeyCode::BackTab, focus, _) => {
+                self.focus = match focus {
+                    Focus::Body => Focus::Cancel,
+                    Focus::Ok if has_body => Focus::Body,
+                    Focus::Ok => Focus::Cancel,
+                    Focus::Cancel => Focus::Ok,
+                }
+            }
+            (KeyCode::Left | KeyCode::Right, Focus::Ok, _) => self.focus = Focus::Cancel,
+            (KeyCode::Left | KeyCode::Right, Focus::Cancel, _) => self.focus = Focus::Ok,
+            (KeyCode::Char(c), Focus::Body, Body::Input(text)) => text.push(c),
+            (KeyCode::Backspace, Focus::Body, Body::Input(text)) => {
+                text.pop();
+            }
+            (KeyCode::Up, Focus::Body, Body::Select { options, selected }) => {
+                *selected = (*selected + options.len() - 1) % options.len().max(1);
+            }
+            (KeyCode::Down, Focus::Body, Body::Select { options, selected }) => {
+                *selected = (*selected + 1) % options.len().max(1);
+            }
+            _ => {}
+        }
+        Outcome::Open
+    }
+}
+
+impl Widget for &Dialog {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let focused = |focus: Focus, span: Span<'static>| {
+            if self.focus == focus {
+                span.reversed()
+            } else {
+                span
+            }
+        };
+        let mut lines = vec![Line::from(self.message.clone()), Line::default()];
+        match &self.body {
+            Body::Message | Body::Confirm => {}
+            Body::Input(text) => {
+                let mut line = Line::from(format!("> {}", text));
+                if self.focus == Focus::Body {
+                    line.push_span(Span::raw(" ").reversed());
+                }
+                lines.push(line);
+                lines.push(Line::default());
+            }
+            Body::Select { options, selected } => {
+                for (i, option) in options.iter().enumerate() {
+                    let line = Line::from(format!("  {}", option));
+                    lines.push(match (i == *selected, self.focus == Focus::Body) {
+                        (true, true) => line.reversed(),
+                        (true, false) => line.bold(),
+                        (false, _) => line,
+                    });
+                }
+                lines.push(Line::default());
+            }
+        }
+        let (ok, cancel) = match self.body {
+            Body::Confirm => ("  Yes  ", "  No  "),
+            _ => ("  OK  ", "  Cancel  "),
+        };
+        let (buttons, keys) = match self.body {
+            Body::Message => (vec![focused(Focus::Ok, Span::raw(ok))], " Enter ok "),
+            _ => (
+                vec![
+                    focused(Focus::Ok, Span::raw(ok)),
+                    Span::raw("  "),
+                    focused(Focus::Cancel, Span::raw(cancel)),
+                ],
+                " Tab focus  Enter ok  Esc cancel ",
+            ),
+        };
+        lines.push(Line::from(buttons));
+
+        let width = lines.iter().map(|l| l.width()).max().unwrap_or(0).max(36) as u16 + 4;
+        let area = centered(area, width, lines.len() as u16 + 2);
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(format!(" {} ", self.title))
+                    .title_bottom(Line::from(keys).dim())
+                    .padding(Padding::horizontal(1)),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(dialog: &mut Dialog, codes: &[KeyCode]) -> Outcome {
+        codes
+            .iter()
+            .map(|&code| dialog.handle_key(code))
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut confirm = Dialog::confirm("quit", "really?");
+        assert_eq!(
+            Outcome::Answered(Answer::Yes),
+            keys(&mut confirm, &[KeyCode::Enter])
+        );
+        assert_eq!(
+            Outcome::Cancelled,
+            keys(&mut confirm, &[KeyCode::Tab, KeyCode::Enter])
+        );
+
+        let mut input = Dialog::input("export", "path", "ab");
+        let outcome = keys(
+            &mut input,
+            &[
+                KeyCode::Backspace,
+                KeyCode::Char('x'),
+                KeyCode::Tab,
+                KeyCode::Char('y'),
+                KeyCode::Enter,
+            ],
+        );
+        assert_eq!(Outcome::Answered(Answer::Text("ax".to_string())), outcome);
+
+        let options = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let mut select = Dialog::select("pick", "one", options);
+        let outcome = keys(&mut select, &[KeyCode::Up, KeyCode::Up, KeyCode::Enter]);
+        assert_eq!(Outcome::Answered(Answer::Choice(1)), outcome);
+        assert_eq!(
+            Outcome::Cancelled,
+            keys(&mut select, &[KeyCode::BackTab, KeyCode::Enter])
+        );
+        assert_eq!(Outcome::Cancelled, select.handle_key(KeyCode::Esc));
+
+        let mut message = Dialog::message("error", "broken");
+        assert_eq!(Outcome::Open, keys(&mut message, &[KeyCode::Tab]));
+        assert_eq!(
+            Outcome::Answered(Answer::Yes),
+            message.handle_key(KeyCode::Esc)
+        );
+    }
+}
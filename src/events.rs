@@ -0,0 +1,382 @@
+use std::cell::{Ref, RefCell, RefMut};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::rc::Rc;
+
+use serde::Serialize;
+
+use crate::Direction;
+
+/// Something that happened in the simulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    /// A new run started.
+    Start {
+        seed: u64,
+        width: u16,
+        height: u16,
+    },
+    Tick,
+    /// The robot stepped onto a tile.
+    Move {
+        x: u16,
+        y: u16,
+    },
+    /// The robot was moved to the twin of the teleporter it stepped into.
+    Teleport {
+        x: u16,
+        y: u16,
+    },
+    /// The robot turned and now faces `dir`.
+    Turn {
+        dir: Direction,
+    },
+    Push {
+        x: u16,
+        y: u16,
+    },
+    /// The robot backtracks from the tile.
+    Pop {
+        x: u16,
+        y: u16,
+    },
+    /// The robot reached a tile for the first time.
+    Visit {
+        x: u16,
+        y: u16,
+    },
+    /// The robot walked into the tile, which it can't pass, and stayed
+    /// where it was.
+    Bump {
+        x: u16,
+        y: u16,
+    },
+    /// The run ended, either fully explored or with the robot caught.
+    Finish {
+        caught: bool,
+    },
+}
+
+/// Names of all events, as in the `event` field of their JSON.
+pub const EVENT_NAMES: [&str; 10] = [
+    "start", "tick", "move", "teleport", "turn", "push", "pop", "visit", "bump", "finish",
+];
+
+impl Event {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::Start { .. } => "start",
+            Event::Tick => "tick",
+            Event::Move { .. } => "move",
+            Event::Teleport { .. } => "teleport",
+            Event::Turn { .. } => "turn",
+            Event::Push { .. } => "push",
+            Event::Pop { .. } => "pop",
+            Event::Visit { .. } => "visit",
+            Event::Bump { .. } => "bump",
+            Event::Finish { .. } => "finish",
+        }
+    }
+}
+
+/// Something that pauses the simulation when it happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// The robot steps or teleports onto the tile.
+    Tile(u16, u16),
+    /// An event with the name, one of `EVENT_NAMES`, happens.
+    Event(&'static str),
+}
+
+impl Breakpoint {
+    pub fn hit(&self, event: &Event) -> bool {
+        match (self, event) {
+            (Breakpoint::Tile(x, y), Event::Move { x: ex, y: ey })
+            | (Breakpoint::Tile(x, y), Event::Teleport { x: ex, y: ey }) => (x, y) == (ex, ey),
+            (Breakpoint::Tile(..), _) => false,
+            (Breakpoint::Event(name), event) => *name == event.name(),
+        }
+    }
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Tile(x, y) => write!(f, "tile ({}, {})", x, y),
+            Breakpoint::Event(name) => write!(f, "{} event", name),
+        }
+    }
+}
+
+/// Something told about every event of a run, e.g. to count or record it.
+pub trait Subscriber {
+    fn notify(&mut self, tick: u64, event: &Event) -> Result<(), String>;
+
+    /// Called once the events of a tick are all published.
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Any closure taking the tick and the event is a subscriber.
+impl<F> Subscriber for F
+where
+    F: FnMut(u64, &Event) -> Result<(), String>,
+{
+    fn notify(&mut self, tick: u64, event: &Event) -> Result<(), String> {
+        self(tick, event)
+    }
+}
+
+/// A subscriber on the bus that the app keeps a handle on, to read what it
+/// collected and to set it up for the next run.
+#[derive(Debug, Default)]
+pub struct Shared<S>(Rc<RefCell<S>>);
+
+impl<S> Shared<S> {
+    pub fn new(subscriber: S) -> Self {
+        Self(Rc::new(RefCell::new(subscriber)))
+    }
+
+    pub fn borrow(&self) -> Ref<'_, S> {
+        self.0.borrow()
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, S> {
+        self.0.borrow_mut()
+    }
+
+    /// Put `subscriber` in place of the shared one, for all handles.
+    pub fn replace(&self, subscriber: S) {
+        *self.0.borrow_mut() = subscriber;
+    }
+}
+
+impl<S> Clone for Shared<S> {
+    fn clone(&self) -> Self {
+        Self(Rc::clone(&self.0))
+    }
+}
+
+impl<S: Subscriber> Subscriber for Shared<S> {
+    fn notify(&mut self, tick: u64, event: &Event) -> Result<(), String> {
+        self.borrow_mut().notify(tick, event)
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        self.borrow_mut().flush()
+    }
+}
+
+/// Where the events of a run are published, for all subscribers to see in
+/// the order they subscribed.
+#[derive(Default)]
+pub struct EventBus {
+    subscribers: Vec<(&'static str, Box<dyn Subscriber>)>,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, name: &'static str, subscriber: impl Subscriber + 'static) {
+        self.subscribers.push((name, Box::new(subscriber)));
+    }
+
+    pub fn is_subscribed(&self, name: &str) -> bool {
+        self.subscribers.iter().any(|(n, _)| *n == name)
+    }
+
+    /// Tell all subscribers about `event`. Subscribers that fail are
+    /// dropped, and returned with why.
+    pub fn publish(&mut self, tick: u64, event: &Event) -> Vec<(&'static str, String)> {
+        self.each(|subscriber| subscriber.notify(tick, event))
+    }
+
+    /// Flush all subscribers, dropping the ones that fail like `publish`.
+    pub fn flush(&mut self) -> Vec<(&'static str, String)> {
+        self.each(|subscriber| subscriber.flush())
+    }
+
+    fn each(
+        &mut self,
+        mut f: impl FnMut(&mut dyn Subscriber) -> Result<(), String>,
+    ) -> Vec<(&'static str, String)> {
+        let mut failed = Vec::new();
+        self.subscribers
+            .retain_mut(|(name, subscriber)| match f(subscriber.as_mut()) {
+                Ok(()) => true,
+                Err(e) => {
+                    failed.push((*name, e));
+                    false
+                }
+            });
+        failed
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self.subscribers.iter().map(|(name, _)| name).collect();
+        write!(f, "EventBus({:?})", names)
+    }
+}
+
+/// What happened during the current tick, as far as the watches and the
+/// toasts care.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TickTracker {
+    /// A tile the robot found during the tick and hasn't stepped on yet.
+    fresh_visit: Option<(u16, u16)>,
+    /// Whether the robot stepped onto a visited tile during the tick.
+    pub revisited: bool,
+    /// Tiles backtracked in a row, over all ticks.
+    pub backtrack_run: usize,
+}
+
+impl Subscriber for TickTracker {
+    fn notify(&mut self, _: u64, event: &Event) -> Result<(), String> {
+        match *event {
+            Event::Tick => {
+                self.fresh_visit = None;
+                self.revisited = false;
+            }
+            Event::Visit { x, y } => self.fresh_visit = Some((x, y)),
+            Event::Move { x, y } => self.revisited |= self.fresh_visit.take() != Some((x, y)),
+            Event::Push { .. } => self.backtrack_run = 0,
+            Event::Pop { .. } => self.backtrack_run += 1,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct Line {
+    tick: u64,
+    #[serde(flatten)]
+    event: Event,
+}
+
+/// Writes events as newline-delimited JSON, one object per event.
+#[derive(Debug)]
+pub struct EventStream {
+    out: BufWriter<File>,
+}
+
+impl EventStream {
+    /// Write to the file or named pipe at `path`, replacing an existing file.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            out: BufWriter::new(File::create(path)?),
+        })
+    }
+
+    pub fn emit(&mut self, tick: u64, event: Event) -> io::Result<()> {
+        serde_json::to_writer(&mut self.out, &Line { tick, event })?;
+        writeln!(self.out)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl Subscriber for EventStream {
+    fn notify(&mut self, tick: u64, event: &Event) -> Result<(), String> {
+        self.emit(tick, *event).map_err(|e| e.to_string())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        EventStream::flush(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() {
+        let line = |event| serde_json::to_string(&Line { tick: 3, event }).unwrap();
+        assert_eq!(
+            r#"{"tick":3,"event":"move","x":1,"y":2}"#,
+            line(Event::Move { x: 1, y: 2 })
+        );
+        assert_eq!(r#"{"tick":3,"event":"tick"}"#, line(Event::Tick));
+        assert_eq!(
+            r#"{"tick":3,"event":"turn","dir":"N"}"#,
+            line(Event::Turn { dir: Direction::N })
+        );
+    }
+
+    #[test]
+    fn test_event_names() {
+        let events = [
+            Event::Start {
+                seed: 0,
+                width: 1,
+                height: 1,
+            },
+            Event::Tick,
+            Event::Move { x: 1, y: 2 },
+            Event::Teleport { x: 1, y: 2 },
+            Event::Turn { dir: Direction::N },
+            Event::Push { x: 1, y: 2 },
+            Event::Pop { x: 1, y: 2 },
+            Event::Visit { x: 1, y: 2 },
+            Event::Bump { x: 1, y: 2 },
+            Event::Finish { caught: false },
+        ];
+        for (event, name) in events.iter().zip(EVENT_NAMES) {
+            let json = serde_json::to_value(event).unwrap();
+            assert_eq!(name, json["event"]);
+            assert_eq!(name, event.name());
+        }
+        assert!(Breakpoint::Tile(1, 2).hit(&Event::Teleport { x: 1, y: 2 }));
+        assert!(!Breakpoint::Tile(1, 2).hit(&Event::Visit { x: 1, y: 2 }));
+        assert!(Breakpoint::Event("pop").hit(&Event::Pop { x: 0, y: 0 }));
+    }
+
+    #[test]
+    fn test_bus() {
+        let mut bus = EventBus::default();
+        let shared = Shared::new(TickTracker::default());
+        bus.subscribe("tracker", shared.clone());
+        let mut moves = 0;
+        bus.subscribe("moves", move |_: u64, event: &Event| {
+            moves += matches!(event, Event::Move { .. }) as u32;
+            match moves {
+                2 => Err("enough".to_string()),
+                _ => Ok(()),
+            }
+        });
+        assert!(bus.publish(1, &Event::Move { x: 1, y: 2 }).is_empty());
+        assert!(bus.publish(1, &Event::Tick).is_empty());
+        assert_eq!(
+            vec![("moves", "enough".to_string())],
+            bus.publish(2, &Event::Move { x: 1, y: 3 })
+        );
+        assert!(bus.is_subscribed("tracker"));
+        // seen through the handle kept
+        assert!(shared.borrow().revisited);
+        assert!(!bus.is_subscribed("moves"));
+        assert!(bus.flush().is_empty());
+
+        let mut tracker = TickTracker::default();
+        for event in [
+            Event::Visit { x: 1, y: 2 },
+            Event::Move { x: 1, y: 2 },
+            Event::Pop { x: 1, y: 2 },
+            Event::Pop { x: 1, y: 1 },
+        ] {
+            tracker.notify(0, &event).unwrap();
+        }
+        assert!(!tracker.revisited);
+        assert_eq!(2, tracker.backtrack_run);
+        tracker.notify(0, &Event::Move { x: 1, y: 3 }).unwrap();
+        assert!(tracker.revisited);
+        tracker.notify(1, &Event::Tick).unwrap();
+        assert!(!tracker.revisited);
+    }
+}
@@ -0,0 +1,243 @@
+use std::sync::mpsc::Receiver;
+
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    symbols::Marker,
+    text::Line,
+    widgets::{Axis, Block, Chart, Dataset, GraphType, Padding, Paragraph, Widget},
+};
+
+use crate::weights::{Field, Weights};
+
+/// Individuals per generation.
+pub const POPULATION: usize = 12;
+/// Generations bred before the evolution stops.
+pub const GENERATIONS: u32 = 30;
+/// The best individuals carried over into the next generation unchanged.
+const ELITES: usize = 2;
+/// Individuals compared to pick a parent.
+const TOURNAMENT: usize = 3;
+/// Chance of each weight changing in a child.
+const MUTATION: f64 = 0.3;
+
+/// How one generation did, lower fitness being better.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Generation {
+    pub number: u32,
+    pub best: f64,
+    pub mean: f64,
+    pub best_weights: Weights,
+}
+
+/// A population of robot weights bred towards a lower fitness.
+#[derive(Debug)]
+pub struct Evolution {
+    rng: StdRng,
+    population: Vec<Weights>,
+    generation: u32,
+}
+
+impl Evolution {
+    /// A random first generation.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let population = (0..POPULATION).map(|_| random_weights(&mut rng)).collect();
+        Self {
+            rng,
+            population,
+            generation: 0,
+        }
+    }
+
+    pub fn done(&self) -> bool {
+        self.generation >= GENERATIONS
+    }
+
+    /// Rate the current generation with `fitness` and breed the next one
+    /// from it.
+    pub fn step(&mut self, fitness: impl Fn(&Weights) -> f64) -> Generation {
+        let mut rated: Vec<(Weights, f64)> = self
+            .population
+            .iter()
+            .map(|weights| (*weights, fitness(weights)))
+            .collect();
+        rated.sort_by(|a, b| a.1.total_cmp(&b.1));
+        self.generation += 1;
+        let generation = Generation {
+            number: self.generation,
+            best: rated[0].1,
+            mean: rated.iter().map(|(_, f)| f).sum::<f64>() / rated.len() as f64,
+            best_weights: rated[0].0,
+        };
+        let mut next: Vec<Weights> = rated.iter().take(ELITES).map(|(w, _)| *w).collect();
+        while next.len() < POPULATION {
+            let a = self.tournament(&rated);
+            let b = self.tournament(&rated);
+            next.push(self.child(a, b));
+        }
+        self.population = next;
+        generation
+    }
+
+    /// The fittest of a few random individuals.
+    fn tournament(&mut self, rated: &[(Weights, f64)]) -> Weights {
+        (0..TOURNAMENT)
+            .map(|_| rated.choose(&mut self.rng).unwrap())
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap()
+            .0
+    }
+
+    /// Each weight from either parent, some of them mutated.
+    fn child(&mut self, a: Weights, b: Weights) -> Weights {
+        let mut child = Weights {
+            heuristic: if self.rng.random() {
+                a.heuristic
+            } else {
+                b.heuristic
+            },
+            revisit: if self.rng.random() {
+                a.revisit
+            } else {
+                b.revisit
+            },
+            tie_break: if self.rng.random() {
+                a.tie_break
+            } else {
+                b.tie_break
+            },
+        };
+        for field in Field::ALL {
+            if self.rng.random_bool(MUTATION) {
+                child.adjust(field, self.rng.random());
+            }
+        }
+        child
+    }
+}
+
+/// The generations an evolution running elsewhere sent so far.
+#[derive(Debug)]
+pub struct EvolutionView {
+    receiver: Receiver<Generation>,
+    generations: Vec<Generation>,
+}
+
+impl EvolutionView {
+    pub fn new(receiver: Receiver<Generation>) -> Self {
+        Self {
+            receiver,
+            generations: Vec::new(),
+        }
+    }
+
+    /// Take the generations that arrived since the last call.
+    pub fn poll(&mut self) {
+        self.generations.extend(self.receiver.try_iter());
+    }
+
+    /// The best weights found so far.
+    pub fn best(&self) -> Option<Weights> {
+        self.generations
+            .iter()
+            .min_by(|a, b| a.best.total_cmp(&b.best))
+            .map(|generation| generation.best_weights)
+    }
+}
+
+impl Widget for &EvolutionView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // generations that never reached the goal are left off the chart
+        let points = |fitness: fn(&Generation) -> f64| -> Vec<(f64, f64)> {
+            self.generations
+                .iter()
+                .map(|generation| (generation.number as f64, fitness(generation)))
+                .filter(|(_, y)| y.is_finite())
+                .collect()
+        };
+        let best = points(|generation| generation.best);
+        let mean = points(|generation| generation.mean);
+        let top = mean.iter().map(|(_, y)| *y).fold(1.0, f64::max).ceil();
+        let chart = Chart::new(vec![
+            Dataset::default()
+                .name("best")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .green()
+                .data(&best),
+            Dataset::default()
+                .name("mean")
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .yellow()
+                .data(&mean),
+        ])
+        .block(Block::bordered().title(" evolution: ticks to goal per generation "))
+        .x_axis(
+            Axis::default()
+                .bounds([1.0, GENERATIONS as f64])
+                .labels(["1".to_string(), GENERATIONS.to_string()]),
+        )
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, top])
+                .labels(["0".to_string(), format!("{}", top)]),
+        );
+        let [chart_area, info_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(4)]).areas(area);
+        chart.render(chart_area, buf);
+        let status = match self.generations.last() {
+            Some(last) if last.number >= GENERATIONS => "done".to_string(),
+            Some(last) => format!("generation {}/{}", last.number + 1, GENERATIONS),
+            None => format!("generation 1/{}", GENERATIONS),
+        };
+        let best = match self.best() {
+            Some(weights) => format!(
+                "best: heuristic {:.1}, revisit {:.1}, ties {}",
+                weights.heuristic, weights.revisit, weights.tie_break
+            ),
+            None => "best: -".to_string(),
+        };
+        let lines = vec![
+            Line::from(status),
+            Line::from(best),
+            Line::from("Enter run with the best weights  Esc back").style(Style::new().dim()),
+        ];
+        Paragraph::new(lines)
+            .block(Block::new().padding(Padding::horizontal(1)))
+            .render(info_area, buf);
+    }
+}
+
+fn random_weights(rng: &mut impl Rng) -> Weights {
+    let mut weights = Weights::default();
+    for field in Field::ALL {
+        for _ in 0..rng.random_range(0..8) {
+            weights.adjust(field, true);
+        }
+    }
+    weights
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evolve() {
+        // the best weights are as close as possible to a heuristic of 3
+        let fitness = |weights: &Weights| (weights.heuristic - 3.0).abs() + weights.revisit;
+        let mut evolution = Evolution::new(7);
+        let mut last = None;
+        while !evolution.done() {
+            let generation = evolution.step(fitness);
+            // elitism never loses the best individual
+            assert!(last.is_none_or(|best| generation.best <= best));
+            last = Some(generation.best);
+        }
+        assert_eq!(Some(0.0), last);
+    }
+}
@@ -0,0 +1,282 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+use ratatui_counter_app::maze::{Maze, Tile};
+
+use crate::image;
+use crate::menu;
+use crate::minimap::Minimap;
+use crate::palette;
+use crate::replay::Replay;
+
+/// Where `:export` puts mazes without a path, and where the gallery looks.
+pub const GALLERY_DIR: &str = "mazes";
+
+/// What a file in the gallery holds.
+#[derive(Debug, Clone, PartialEq)]
+enum Saved {
+    Maze(Maze),
+    Replay(Replay),
+}
+
+/// A maze or replay file in the gallery, which may not hold one after all.
+#[derive(Debug)]
+struct Entry {
+    path: PathBuf,
+    saved: Result<Saved, String>,
+}
+
+impl Entry {
+    fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned())
+    }
+}
+
+/// What the app should do after the gallery handled a key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GalleryAction {
+    None,
+    /// Start a run on the maze.
+    Load(Maze),
+    /// Play the replay from its start.
+    Play(Replay),
+    Back,
+    Quit,
+}
+
+/// The mazes and replays saved in a directory each, to pick a maze to run
+/// on or a replay to play. PNG and BMP images are shown as the mazes they
+/// make at one pixel per tile.
+#[derive(Debug)]
+pub struct Gallery {
+    dir: PathBuf,
+    replay_dir: PathBuf,
+    entries: Vec<Entry>,
+    selected: usize,
+}
+
+impl Gallery {
+    /// All files in `dir` sorted by name, then the replays in `replay_dir`.
+    /// A missing directory has nothing in it.
+    pub fn load(dir: impl Into<PathBuf>, replay_dir: impl Into<PathBuf>) -> io::Result<Self> {
+        let (dir, replay_dir) = (dir.into(), replay_dir.into());
+        let mazes = files(&dir)?.into_iter().map(|path| Entry {
+            saved: read_maze(&path).map(Saved::Maze),
+            path,
+        });
+        let replays = files(&replay_dir)?
+            .into_iter()
+            .filter(|path| path.to_string_lossy().ends_with(".json.gz"))
+            .map(|path| Entry {
+                saved: Replay::load(&path).map(Saved::Replay),
+                path,
+            });
+        Ok(Self {
+            dir,
+            replay_dir,
+            entries: mazes.chain(replays).collect(),
+            selected: 0,
+        })
+    }
+
+    /// Handle `code`, or return an error to show if it failed.
+    pub fn handle_key(&mut self, code: KeyCode) -> Result<GalleryAction, String> {
+        let len = self.entries.len();
+        match code {
+            KeyCode::Char('q') => return Ok(GalleryAction::Quit),
+            KeyCode::Esc => return Ok(GalleryAction::Back),
+            KeyCode::Up if len > 0 => self.selected = (self.selected + len - 1) % len,
+            KeyCode::Down if len > 0 => self.selected = (self.selected + 1) % len,
+            KeyCode::Enter => {
+                let Some(entry) = self.entries.get(self.selected) else {
+                    return Err("no maze selected".to_string());
+                };
+                return match entry.saved.clone()? {
+                    Saved::Maze(maze) => Ok(GalleryAction::Load(maze)),
+                    Saved::Replay(replay) => Ok(GalleryAction::Play(replay)),
+                };
+            }
+            KeyCode::Char('d') => {
+                let Some(entry) = self.entries.get(self.selected) else {
+                    return Err("no maze selected".to_string());
+                };
+                fs::remove_file(&entry.path)
+                    .map_err(|e| format!("could not delete {}: {}", entry.name(), e))?;
+                self.entries.remove(self.selected);
+                self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+            }
+            _ => {}
+        }
+        Ok(GalleryAction::None)
+    }
+}
+
+/// The files in `dir` sorted by name, none if it is missing.
+fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(read) => read
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    paths.sort();
+    Ok(paths)
+}
+
+fn read_maze(path: &Path) -> Result<Maze, String> {
+    if image::is_image(path) {
+        return image::load(path, 1).map(|(maze, _)| maze);
+    }
+    let maze = fs::read_to_string(path)
+        .map_err(|e| e.to_string())?
+        .parse()?;
+    menu::check_maze_size(&maze)?;
+    Ok(maze)
+}
+
+impl Widget for &Gallery {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(format!(
+                " gallery: {}, {} ",
+                self.dir.display(),
+                self.replay_dir.display()
+            ))
+            .title_bottom(Line::from(" ↑/↓ select  Enter run or play  d delete  Esc back ").dim());
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Fill(1)]).areas(inner);
+        if self.entries.is_empty() {
+            Paragraph::new(vec![
+                Line::from("no saved mazes or replays yet"),
+                Line::from("save one with :export or :replay during a run").dim(),
+            ])
+            .render(list_area, buf);
+            return;
+        }
+        // keep the selected entry in view
+        let first = self
+            .selected
+            .saturating_sub(list_area.height.saturating_sub(1) as usize);
+        let lines: Vec<Line> = self
+            .entries
+            .iter()
+            .enumerate()
+            .skip(first)
+            .map(|(i, entry)| {
+                let line = Line::from(entry.name());
+                match (i == self.selected, &entry.saved) {
+                    (true, _) => line.reversed(),
+                    (false, Err(_)) => line.style(Style::new().dim()),
+                    (false, Ok(_)) => line,
+                }
+            })
+            .collect();
+        Paragraph::new(lines).render(list_area, buf);
+        match &self.entries[self.selected].saved {
+            Ok(Saved::Maze(maze)) => render_maze(maze, "", preview_area, buf),
+            Ok(Saved::Replay(replay)) => {
+                let config = &replay.config;
+                let about = format!(
+                    "{} ticks of {} on {}",
+                    replay.ticks, config.solver, config.generator
+                );
+                match replay.maze.as_deref().map(str::parse::<Maze>) {
+                    Some(Ok(maze)) => render_maze(&maze, &about, preview_area, buf),
+                    _ => Paragraph::new(vec![
+                        Line::from(about),
+                        Line::from(format!(
+                            "{}x{}, seed {}",
+                            config.width, config.height, config.seed
+                        )),
+                    ])
+                    .block(Block::bordered().title("replay"))
+                    .render(preview_area, buf),
+                }
+            }
+            Err(e) => Paragraph::new(format!("not a maze or replay: {}", e))
+                .block(Block::bordered())
+                .render(preview_area, buf),
+        }
+    }
+}
+
+/// A preview of `maze` titled with its size and `about`.
+fn render_maze(maze: &Maze, about: &str, area: Rect, buf: &mut Buffer) {
+    let (w, h) = (maze.tiles[0].len() as u16, maze.tiles.len() as u16);
+    let palette = palette::current();
+    let title = match about {
+        "" => format!("{}x{}", w / 2, h / 2),
+        _ => format!("{}x{}, {}", w / 2, h / 2, about),
+    };
+    Minimap::new(w, h, |x, y| match maze.tiles[y as usize][x as usize] {
+        Tile::Wall => palette.wall,
+        Tile::Free => palette.free,
+    })
+    .block(Block::bordered().title(title))
+    .render(area, buf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete() {
+        let dir = std::env::temp_dir().join(format!("gallery-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.txt"), "OOO\nO O\nOOO\n").unwrap();
+        fs::write(dir.join("b.txt"), "not a maze").unwrap();
+        let mut gallery = Gallery::load(&dir, dir.join("replays")).unwrap();
+        assert_eq!(2, gallery.entries.len());
+        assert!(matches!(
+            gallery.handle_key(KeyCode::Enter),
+            Ok(GalleryAction::Load(_))
+        ));
+        gallery.handle_key(KeyCode::Down).unwrap();
+        assert!(gallery.handle_key(KeyCode::Enter).is_err());
+        gallery.handle_key(KeyCode::Char('d')).unwrap();
+        assert_eq!(1, gallery.entries.len());
+        assert_eq!(0, gallery.selected);
+        assert!(!dir.join("b.txt").exists());
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(0, Gallery::load(&dir, &dir).unwrap().entries.len());
+    }
+
+    #[test]
+    fn test_replays() {
+        let dir = std::env::temp_dir().join(format!("gallery-replays-{}", std::process::id()));
+        let replays = dir.join("replays");
+        fs::create_dir_all(&replays).unwrap();
+        let replay = Replay {
+            config: Default::default(),
+            weights: Default::default(),
+            maze: None,
+            ticks: 12,
+            maze_hash: None,
+        };
+        replay.save(&replays.join("7-12.json.gz")).unwrap();
+        fs::write(replays.join("notes.txt"), "not a replay").unwrap();
+        let mut gallery = Gallery::load(dir.join("mazes"), &replays).unwrap();
+        assert_eq!(1, gallery.entries.len());
+        assert_eq!(
+            Ok(GalleryAction::Play(replay)),
+            gallery.handle_key(KeyCode::Enter)
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
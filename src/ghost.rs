@@ -0,0 +1,60 @@
+/// Where the robot was at every tick of a run, to show as a ghost racing
+/// the robot when the same maze is run again.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    /// `replay::maze_hash` of the maze the run was on.
+    pub maze_hash: u64,
+    /// The solver of the run, to tell the ghost by.
+    pub solver: String,
+    /// Indexed by tick, from the start of the run.
+    positions: Vec<(u16, u16)>,
+}
+
+impl Track {
+    pub fn new(maze_hash: u64, solver: String, start: (u16, u16)) -> Self {
+        Self {
+            maze_hash,
+            solver,
+            positions: vec![start],
+        }
+    }
+
+    /// Note where the robot is at `tick`, forgetting where it was after,
+    /// if the run was rewound.
+    pub fn record(&mut self, tick: u64, pos: (u16, u16)) {
+        self.positions.truncate(tick as usize);
+        self.positions.push(pos);
+    }
+
+    /// Where the robot was at `tick`, or where it ended up if the run was
+    /// shorter.
+    pub fn at(&self, tick: u64) -> (u16, u16) {
+        let last = self.positions.len() - 1;
+        self.positions[(tick as usize).min(last)]
+    }
+
+    /// Ticks of the run recorded.
+    pub fn ticks(&self) -> u64 {
+        self.positions.len() as u64 - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let mut track = Track::new(7, "robot-dfs".to_string(), (1, 1));
+        for tick in 1..=3 {
+            track.record(tick, (1 + tick as u16, 1));
+        }
+        assert_eq!(3, track.ticks());
+        assert_eq!((3, 1), track.at(2));
+        assert_eq!((4, 1), track.at(10));
+        // rewound by two ticks, then gone another way
+        track.record(2, (2, 2));
+        assert_eq!(2, track.ticks());
+        assert_eq!((2, 2), track.at(3));
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// The only compression method gzip has.
+const DEFLATE: u8 = 8;
+const HEADER_LEN: usize = 10;
+// header flags that add fields before the data
+const FLAG_EXTRA: u8 = 4;
+const FLAG_NAME: u8 = 8;
+const FLAG_COMMENT: u8 = 16;
+const FLAG_HEADER_CRC: u8 = 2;
+
+/// The CRC-32 gzip and PNG files use.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// `data` as a gzip file, which `gunzip` and `zcat` read too.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    // no flags, no time, no extra flags, unknown OS
+    bytes.extend(MAGIC);
+    bytes.extend([DEFLATE, 0, 0, 0, 0, 0, 0, 255]);
+    bytes.extend(miniz_oxide::deflate::compress_to_vec(data, 6));
+    bytes.extend(crc32(data).to_le_bytes());
+    bytes.extend((data.len() as u32).to_le_bytes());
+    bytes
+}
+
+/// Unpack a gzip file, checking that nothing got lost or changed.
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let truncated = || "the file is cut short".to_string();
+    if bytes.len() < HEADER_LEN + 8 || bytes[..2] != MAGIC {
+        return Err("not a gzip file".to_string());
+    }
+    if bytes[2] != DEFLATE {
+        return Err(format!("unknown compression method {}", bytes[2]));
+    }
+    let flags = bytes[3];
+    let mut at = HEADER_LEN;
+    if flags & FLAG_EXTRA != 0 {
+        let len = bytes.get(at..at + 2).ok_or_else(truncated)?;
+        at += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FLAG_NAME, FLAG_COMMENT] {
+        if flags & flag != 0 {
+            let rest = bytes.get(at..).ok_or_else(truncated)?;
+            at += rest.iter().position(|&b| b == 0).ok_or_else(truncated)? + 1;
+        }
+    }
+    if flags & FLAG_HEADER_CRC != 0 {
+        at += 2;
+    }
+    let body = bytes.get(at..bytes.len() - 8).ok_or_else(truncated)?;
+    let data = miniz_oxide::inflate::decompress_to_vec(body)
+        .map_err(|e| format!("the compressed data is broken: {:?}", e.status))?;
+    let trailer = &bytes[bytes.len() - 8..];
+    let crc = u32::from_le_bytes(trailer[..4].try_into().unwrap());
+    let len = u32::from_le_bytes(trailer[4..].try_into().unwrap());
+    if crc != crc32(&data) || len != data.len() as u32 {
+        return Err("the checksum doesn't match, the file is corrupted".to_string());
+    }
+    Ok(data)
+}
+
+/// Write `text` gzipped to `path`.
+pub fn write(path: &Path, text: &str) -> io::Result<()> {
+    fs::write(path, compress(text.as_bytes()))
+}
+
+/// Read the text at `path`, unpacking it if it is gzipped, so files saved
+/// before they were compressed still load.
+pub fn read_to_string(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let bytes = if bytes.starts_with(&MAGIC) {
+        decompress(&bytes)?
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes).map_err(|_| "the file is not text".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(0xcbf4_3926, crc32(b"123456789"));
+        let text = "a maze, a maze, a maze".repeat(20);
+        let mut bytes = compress(text.as_bytes());
+        assert!(bytes.len() < text.len());
+        assert_eq!(Ok(text.into_bytes()), decompress(&bytes));
+
+        let last = bytes.len() - 9;
+        bytes[last] ^= 1;
+        assert!(decompress(&bytes).is_err());
+        assert!(decompress(&bytes[..12]).is_err());
+        assert!(decompress(b"{\"json\": true}").is_err());
+    }
+}
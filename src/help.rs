@@ -0,0 +1,64 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, Padding, Paragraph, Widget},
+};
+
+use crate::menu::centered;
+
+/// A centered modal listing keybindings and the current settings.
+pub struct Help<'a> {
+    bindings: &'a [(&'a str, &'a str)],
+    settings: Vec<(&'a str, String)>,
+}
+
+impl<'a> Help<'a> {
+    pub fn new(bindings: &'a [(&'a str, &'a str)], settings: Vec<(&'a str, String)>) -> Self {
+        Self { bindings, settings }
+    }
+}
+
+impl Widget for Help<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let key_width = self
+            .bindings
+            .iter()
+            .map(|(k, _)| k.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut lines = vec![Line::from("keys").bold()];
+        for (key, action) in self.bindings {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{:<key_width$}", key), Style::new().yellow()),
+                Span::raw("  "),
+                Span::raw(*action),
+            ]));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from("settings").bold());
+        let name_width = self
+            .settings
+            .iter()
+            .map(|(n, _)| n.len())
+            .max()
+            .unwrap_or(0);
+        for (name, value) in &self.settings {
+            lines.push(Line::from(format!("{:<name_width$}  {}", name, value)));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from("Esc close").dim());
+
+        let width = lines.iter().map(|l| l.width()).max().unwrap_or(0) as u16 + 4;
+        let area = centered(area, width, lines.len() as u16 + 2);
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(" help ")
+                    .padding(Padding::horizontal(1)),
+            )
+            .render(area, buf);
+    }
+}
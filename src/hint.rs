@@ -0,0 +1,102 @@
+use std::rc::Rc;
+
+use ratatui::style::Color;
+use ratatui_counter_app::tile::TileMap;
+
+use crate::events::{Event, Subscriber};
+
+/// Whether the last move of the robot took it closer to the goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Heat {
+    Warmer,
+    Colder,
+    /// As far from the goal as before, or it can't get there from here.
+    Same,
+}
+
+impl From<Heat> for Color {
+    fn from(value: Heat) -> Self {
+        match value {
+            Heat::Warmer => Color::Red,
+            Heat::Colder => Color::Blue,
+            Heat::Same => Color::DarkGray,
+        }
+    }
+}
+
+/// Tells warmer or colder from the moves of the robot, by the cost of the
+/// cheapest path from each tile to the goal.
+#[derive(Debug, Clone)]
+pub struct HotCold {
+    /// Shared with the copies kept to rewind the run.
+    cost: Rc<TileMap<Option<u32>>>,
+    /// The cost from where the robot was last.
+    last: Option<u32>,
+    pub heat: Option<Heat>,
+}
+
+impl Default for HotCold {
+    fn default() -> Self {
+        Self::new(TileMap::with_default(1, 1))
+    }
+}
+
+impl HotCold {
+    pub fn new(cost: TileMap<Option<u32>>) -> Self {
+        Self {
+            cost: Rc::new(cost),
+            last: None,
+            heat: None,
+        }
+    }
+
+    /// The cost of the cheapest path from `pos` to the goal, if there is one.
+    pub fn cost(&self, (x, y): (u16, u16)) -> Option<u32> {
+        // the world of an infinite run is larger than any map of it
+        let inside = x < self.cost.width() && y < self.cost.height();
+        inside.then(|| self.cost[(x, y)]).flatten()
+    }
+}
+
+impl Subscriber for HotCold {
+    fn notify(&mut self, _: u64, event: &Event) -> Result<(), String> {
+        match *event {
+            Event::Start { .. } => {
+                self.last = None;
+                self.heat = None;
+            }
+            Event::Move { x, y } | Event::Teleport { x, y } => {
+                let cost = self.cost((x, y));
+                self.heat = self.last.map(|last| match cost {
+                    Some(cost) if cost < last => Heat::Warmer,
+                    Some(cost) if cost > last => Heat::Colder,
+                    _ => Heat::Same,
+                });
+                self.last = cost;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heat() {
+        let mut cost = TileMap::with_default(3, 1);
+        cost[(0, 0)] = Some(2);
+        cost[(1, 0)] = Some(1);
+        let mut hot_cold = HotCold::new(cost);
+        let mut step = |x| {
+            hot_cold.notify(0, &Event::Move { x, y: 0 }).unwrap();
+            hot_cold.heat
+        };
+        assert_eq!(None, step(0));
+        assert_eq!(Some(Heat::Warmer), step(1));
+        assert_eq!(Some(Heat::Colder), step(0));
+        assert_eq!(Some(Heat::Same), step(2));
+    }
+}
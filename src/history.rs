@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+use std::ops::IndexMut;
+
+/// The most recent entries, up to a fixed number. Older ones are dropped.
+#[derive(Debug)]
+pub struct History<T> {
+    entries: VecDeque<T>,
+    capacity: usize,
+}
+
+impl<T> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, entry: T) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Remove and return the most recent entry.
+    pub fn pop(&mut self) -> Option<T> {
+        self.entries.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+/// The tiles of a layer that changed, with the values they had before.
+#[derive(Debug, Clone)]
+pub struct LayerDelta<V>(Vec<((u16, u16), V)>);
+
+impl<V> LayerDelta<V>
+where
+    V: Clone + PartialEq,
+{
+    /// The tiles of the `width` x `height` layer `current` that differ from
+    /// `shadow`, its copy from before. `shadow` is brought up to date.
+    pub fn diff<M>(shadow: &mut M, current: &M, width: u16, height: u16) -> Self
+    where
+        M: IndexMut<(u16, u16), Output = V>,
+    {
+        let mut changed = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                if shadow[(x, y)] != current[(x, y)] {
+                    let old = std::mem::replace(&mut shadow[(x, y)], current[(x, y)].clone());
+                    changed.push(((x, y), old));
+                }
+            }
+        }
+        Self(changed)
+    }
+
+    /// Put the earlier values back into `layer` and its `shadow`.
+    pub fn undo<M>(self, layer: &mut M, shadow: &mut M)
+    where
+        M: IndexMut<(u16, u16), Output = V>,
+    {
+        for (pos, old) in self.0 {
+            shadow[pos] = old.clone();
+            layer[pos] = old;
+        }
+    }
+}
+
+/// The end of a stack a tick changed: where it starts, and the entries it
+/// had from there before. Ticks push and pop a few entries at a time, so
+/// this stays small however deep the stack is.
+#[derive(Debug, Clone)]
+pub struct StackDelta<V> {
+    kept: usize,
+    old: Vec<V>,
+}
+
+impl<V> StackDelta<V>
+where
+    V: Clone + PartialEq,
+{
+    /// How `current` differs from `shadow`, its copy from before. `shadow`
+    /// is brought up to date.
+    pub fn diff(shadow: &mut Vec<V>, current: &[V]) -> Self {
+        let kept = shadow
+            .iter()
+            .zip(current)
+            .take_while(|(old, new)| old == new)
+            .count();
+        let old = shadow.split_off(kept);
+        shadow.extend_from_slice(&current[kept..]);
+        Self { kept, old }
+    }
+
+    /// Put the earlier entries back into `stack` and its `shadow`.
+    pub fn undo(self, stack: &mut Vec<V>, shadow: &mut Vec<V>) {
+        stack.truncate(self.kept);
+        stack.extend_from_slice(&self.old);
+        shadow.truncate(self.kept);
+        shadow.extend(self.old);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui_counter_app::tile::AlphaTileMap;
+
+    #[test]
+    fn test_diff_undo() {
+        let mut layer = AlphaTileMap::empty(3, 2);
+        let mut shadow = layer.clone();
+        layer[(1, 1)] = Some(7);
+        layer[(2, 0)] = Some(3);
+        let delta = LayerDelta::diff(&mut shadow, &layer, 3, 2);
+        assert_eq!(2, delta.0.len());
+        assert_eq!(Some(7), shadow[(1, 1)]);
+        delta.undo(&mut layer, &mut shadow);
+        assert_eq!(None, layer[(1, 1)]);
+        assert_eq!(None, shadow[(2, 0)]);
+
+        let mut history = History::new(2);
+        for i in 0..3 {
+            history.push(i);
+        }
+        assert_eq!(Some(2), history.pop());
+        assert_eq!(Some(1), history.pop());
+        assert_eq!(None, history.pop());
+    }
+
+    #[test]
+    fn test_stack_diff_undo() {
+        let mut stack = vec![1, 2, 3];
+        let mut shadow = stack.clone();
+        stack.pop();
+        stack.pop();
+        stack.push(5);
+        let delta = StackDelta::diff(&mut shadow, &stack);
+        assert_eq!((1, vec![2, 3]), (delta.kept, delta.old.clone()));
+        assert_eq!(stack, shadow);
+        delta.undo(&mut stack, &mut shadow);
+        assert_eq!(vec![1, 2, 3], stack);
+        assert_eq!(stack, shadow);
+    }
+}
@@ -0,0 +1,130 @@
+//! Callbacks around every tick of a run, which can look at the run and
+//! wall up tiles.
+//!
+//! Anything implementing [`Hook`], closures included, can be added to the
+//! [`Hooks`] of a run; the app's `--script` adds a Lua script as one.
+
+use std::fmt;
+
+/// When during a tick a hook is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Before the robot moves.
+    Pre,
+    /// After the robot and the minotaur moved.
+    Post,
+}
+
+/// What a hook sees of the run, and what it asks to change.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TickState {
+    pub tick: u64,
+    pub robot: (usize, usize),
+    pub goal: Option<(usize, usize)>,
+    /// Map size in tiles.
+    pub width: usize,
+    pub height: usize,
+    /// Tiles to wall up once the hook returns. Tiles that aren't free, and
+    /// the ones the robot, the goal or the minotaur are on, are left alone.
+    pub walls: Vec<(usize, usize)>,
+    /// Named values to write to the debug log.
+    pub metrics: Vec<(String, f64)>,
+    /// Labels to put on tiles once the hook returns, as with the `note`
+    /// command. Tiles outside the map are left alone.
+    pub labels: Vec<((usize, usize), String)>,
+}
+
+/// Something called around every tick, which can look at the run and wall
+/// up tiles.
+pub trait Hook {
+    fn call(&mut self, phase: Phase, state: &mut TickState) -> Result<(), String>;
+}
+
+/// Any closure taking the phase and the state is a hook.
+impl<F> Hook for F
+where
+    F: FnMut(Phase, &mut TickState) -> Result<(), String>,
+{
+    fn call(&mut self, phase: Phase, state: &mut TickState) -> Result<(), String> {
+        self(phase, state)
+    }
+}
+
+/// The hooks of a run, called in the order they were added.
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<Box<dyn Hook>>,
+}
+
+impl Hooks {
+    pub fn add(&mut self, hook: impl Hook + 'static) {
+        self.hooks.push(Box::new(hook));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Call all hooks with `state`. A hook that fails is dropped, so it
+    /// doesn't fail again on every tick.
+    pub fn call(&mut self, phase: Phase, state: &mut TickState) -> Result<(), String> {
+        let mut result = Ok(());
+        self.hooks.retain_mut(|hook| match hook.call(phase, state) {
+            Ok(()) => true,
+            Err(e) => {
+                if result.is_ok() {
+                    result = Err(e);
+                }
+                false
+            }
+        });
+        result
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Hooks({})", self.hooks.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks() {
+        let mut hooks = Hooks::default();
+        assert!(hooks.is_empty());
+        let mut calls = 0;
+        hooks.add(move |_: Phase, state: &mut TickState| {
+            calls += 1;
+            state.metrics.push(("calls".to_string(), calls as f64));
+            Ok(())
+        });
+        hooks.add(|phase: Phase, state: &mut TickState| match phase {
+            Phase::Pre => {
+                state.walls.push((state.robot.0 + 1, state.robot.1));
+                Ok(())
+            }
+            Phase::Post => Err("no post".to_string()),
+        });
+
+        let mut state = TickState {
+            tick: 4,
+            robot: (3, 1),
+            ..TickState::default()
+        };
+        hooks.call(Phase::Pre, &mut state).unwrap();
+        assert_eq!(vec![(4, 1)], state.walls);
+        assert_eq!(vec![("calls".to_string(), 1.0)], state.metrics);
+
+        // the failing hook is dropped, the other one goes on
+        assert_eq!(
+            Err("no post".to_string()),
+            hooks.call(Phase::Post, &mut state)
+        );
+        assert!(hooks.call(Phase::Post, &mut state).is_ok());
+        assert_eq!(("calls".to_string(), 3.0), state.metrics[2]);
+    }
+}
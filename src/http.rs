@@ -0,0 +1,131 @@
+use std::fmt;
+use std::io;
+
+use tiny_http::{Header, Method, Response, Server};
+
+use crate::gzip::crc32;
+use crate::kitty::Image;
+
+/// Pixels per side of a tile in `/maze.png`.
+pub const PNG_BLOCK: u32 = 4;
+/// Longest side of `/maze.png` in pixels, which big mazes are shrunk to
+/// fit.
+const PNG_MAX_SIDE: u32 = 1024;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+/// Answers dashboards polling a run: `/state` with the same JSON a
+/// `query_state` control request gets, and `/maze.png` with a picture of
+/// the maze.
+pub struct StatusServer {
+    server: Server,
+}
+
+impl StatusServer {
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let server = Server::http(addr).map_err(io::Error::other)?;
+        Ok(Self { server })
+    }
+
+    /// Answer the requests that came in, without waiting for more.
+    pub fn serve(&self, state: impl Fn() -> serde_json::Value, maze: impl Fn() -> Image) {
+        while let Ok(Some(request)) = self.server.try_recv() {
+            let path = request.url().split('?').next().unwrap_or_default();
+            let response = match (request.method(), path) {
+                (Method::Get, "/state") => {
+                    data(serde_json::to_vec(&state()).unwrap(), "application/json")
+                }
+                (Method::Get, "/maze.png") => data(encode_png(&maze()), "image/png"),
+                _ => Response::from_data(b"not found".to_vec()).with_status_code(404),
+            };
+            // a client that went away doesn't matter to the run
+            let _ = request.respond(response);
+        }
+    }
+}
+
+impl fmt::Debug for StatusServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StatusServer({:?})", self.server.server_addr().to_ip())
+    }
+}
+
+/// Pixels per side of a tile in `/maze.png` of a map with the size, and
+/// every how many tiles one is drawn. Only big maps skip tiles.
+pub fn png_scale(width: u16, height: u16) -> (u32, u32) {
+    let side = width.max(height).max(1) as u32;
+    let step = side.div_ceil(PNG_MAX_SIDE);
+    let block = (PNG_MAX_SIDE / side).clamp(1, PNG_BLOCK);
+    (block, step)
+}
+
+fn data(bytes: Vec<u8>, content_type: &str) -> Response<io::Cursor<Vec<u8>>> {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    Response::from_data(bytes)
+        .with_header(header)
+        .with_header(Header::from_bytes("Cache-Control", "no-store").unwrap())
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend((data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend(kind);
+    png.extend(data);
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+/// Encode `image` as an 8 bit RGB PNG, unfiltered.
+fn encode_png(image: &Image) -> Vec<u8> {
+    let stride = image.width as usize * 3;
+    let mut raw = Vec::with_capacity((stride + 1) * image.height as usize);
+    for row in image.rgb.chunks(stride.max(1)) {
+        raw.push(0);
+        raw.extend(row);
+    }
+    let mut header = Vec::new();
+    header.extend(image.width.to_be_bytes());
+    header.extend(image.height.to_be_bytes());
+    // 8 bits per channel, RGB, default compression, filters and no
+    // interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(
+        &mut png,
+        b"IDAT",
+        &miniz_oxide::deflate::compress_to_vec_zlib(&raw, 6),
+    );
+    chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_png() {
+        let mut image = Image::new(2, 1);
+        image.fill_block(1, 0, 1, [255, 0, 0]);
+        let png = encode_png(&image);
+        assert!(png.starts_with(&PNG_SIGNATURE));
+        // the well known checksum of an empty IEND chunk
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+        let idat = png.windows(4).position(|w| w == b"IDAT").unwrap();
+        let len = u32::from_be_bytes(png[idat - 4..idat].try_into().unwrap()) as usize;
+        let raw = miniz_oxide::inflate::decompress_to_vec_zlib(&png[idat + 4..idat + 4 + len]);
+        assert_eq!(Ok(vec![0, 0, 0, 0, 255, 0, 0]), raw.map_err(|_| ()));
+    }
+
+    #[test]
+    fn test_png_scale() {
+        assert_eq!((PNG_BLOCK, 1), png_scale(65, 33));
+        assert_eq!((2, 1), png_scale(300, 500));
+        // the biggest maze has 2001 tiles a side
+        let (block, step) = png_scale(2001, 2001);
+        assert_eq!((1, 2), (block, step));
+        assert!(2001u32.div_ceil(step) * block <= PNG_MAX_SIDE);
+    }
+}
@@ -0,0 +1,331 @@
+use ratatui_counter_app::pathfinding::{Point, Step};
+use std::collections::HashMap;
+
+/// Moves the search makes per tick, into a tile or back out of one.
+pub const MOVES_PER_TICK: usize = 32;
+
+/// How a sweep reached a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reached {
+    /// Moves from the start.
+    pub depth: u32,
+    /// The tile it came from, `None` for the start.
+    pub parent: Option<Point>,
+}
+
+/// A tile on the way down, and its moves not tried yet.
+#[derive(Debug, Clone)]
+struct Frame {
+    pos: Point,
+    /// The tile stepped into to get here, which differs from it behind a
+    /// teleporter.
+    tile: Point,
+    steps: Vec<Step>,
+    next: usize,
+}
+
+/// A change of the search, to undo it.
+#[derive(Debug, Clone)]
+enum Change {
+    /// The sweep reached the tile, which it had reached before as given.
+    Set(Point, Option<Reached>),
+    /// A sweep started, forgetting the tiles the last one reached.
+    Cleared(HashMap<Point, Reached>),
+    /// A frame for the tile went on the stack.
+    Pushed(Point),
+    Popped(Frame),
+    /// The top frame went on to its next step.
+    Tried,
+}
+
+/// Iterative deepening depth first search: sweeps depth first from the
+/// start, none going deeper than the depth limit, which grows by one after
+/// each sweep that missed the goal. Like breadth first search it finds a
+/// path with the fewest moves, keeping only the way down in memory, at the
+/// cost of sweeping the shallow tiles over and over.
+///
+/// A sweep enters a tile again only if it gets there in fewer moves than
+/// before, so it doesn't wander around loops.
+#[derive(Debug, Clone)]
+pub struct Deepening {
+    start: Point,
+    goal: Point,
+    stack: Vec<Frame>,
+    /// The tiles the current sweep reached.
+    reached: HashMap<Point, Reached>,
+    /// Whether the current sweep left tiles out for the depth limit.
+    cut_off: bool,
+    pub limit: u32,
+    /// Sweeps started, the current one included.
+    pub sweeps: u32,
+    /// Tiles entered over all sweeps.
+    pub expanded: usize,
+    /// The tiles to step into from the start to the goal, once found.
+    pub path: Option<Vec<Point>>,
+    /// Whether the goal was found, or a sweep reached all it could without
+    /// running into the depth limit.
+    pub done: bool,
+}
+
+/// The changes of a search as it moved, to undo them.
+#[derive(Debug, Clone)]
+pub struct Undo {
+    changes: Vec<Change>,
+    cut_off: bool,
+    limit: u32,
+    sweeps: u32,
+    expanded: usize,
+    /// Whether the moves found the path.
+    found: bool,
+    done: bool,
+}
+
+impl Undo {
+    /// Whether a sweep started, leaving none of the tiles reached before.
+    pub fn swept(&self) -> bool {
+        self.changes
+            .iter()
+            .any(|change| matches!(change, Change::Cleared(_)))
+    }
+
+    /// The tiles reached, entered or left on the way down, and the tiles
+    /// they were reached from before.
+    pub fn touched(&self) -> impl Iterator<Item = Point> + '_ {
+        self.changes
+            .iter()
+            .flat_map(|change| match change {
+                Change::Set(pos, before) => [Some(*pos), before.and_then(|reached| reached.parent)],
+                Change::Pushed(pos) => [Some(*pos), None],
+                Change::Popped(frame) => [Some(frame.pos), None],
+                Change::Cleared(_) | Change::Tried => [None, None],
+            })
+            .flatten()
+    }
+}
+
+impl Deepening {
+    pub fn new(start: Point, goal: Point) -> Self {
+        let mut search = Self {
+            start,
+            goal,
+            stack: Vec::new(),
+            reached: HashMap::new(),
+            cut_off: false,
+            limit: 1,
+            sweeps: 0,
+            expanded: 0,
+            path: None,
+            done: false,
+        };
+        if start == goal {
+            search.path = Some(Vec::new());
+            search.done = true;
+        }
+        search
+    }
+
+    /// The tiles the current sweep reached.
+    pub fn reached(&self) -> &HashMap<Point, Reached> {
+        &self.reached
+    }
+
+    /// The tiles the sweep is on the way down through, from the start.
+    pub fn way_down(&self) -> impl Iterator<Item = Point> + '_ {
+        self.stack.iter().map(|frame| frame.pos)
+    }
+
+    /// Make up to `moves` moves, as `steps` allows, starting a deeper sweep
+    /// whenever one ends without finding the goal.
+    pub fn advance(&mut self, moves: usize, steps: impl Fn(Point) -> Vec<Step>) -> Undo {
+        let mut undo = Undo {
+            changes: Vec::new(),
+            cut_off: self.cut_off,
+            limit: self.limit,
+            sweeps: self.sweeps,
+            expanded: self.expanded,
+            found: false,
+            done: self.done,
+        };
+        for _ in 0..moves {
+            if self.done {
+                break;
+            }
+            let Some(frame) = self.stack.last_mut() else {
+                if self.sweeps > 0 && !self.cut_off {
+                    // the last sweep reached everything it could
+                    self.done = true;
+                    break;
+                }
+                if self.sweeps > 0 {
+                    self.limit += 1;
+                }
+                self.sweeps += 1;
+                self.cut_off = false;
+                undo.changes
+                    .push(Change::Cleared(std::mem::take(&mut self.reached)));
+                let root = Reached {
+                    depth: 0,
+                    parent: None,
+                };
+                self.reached.insert(self.start, root);
+                undo.changes.push(Change::Pushed(self.start));
+                self.stack.push(Frame {
+                    pos: self.start,
+                    tile: self.start,
+                    steps: steps(self.start),
+                    next: 0,
+                });
+                continue;
+            };
+            let Some(&step) = frame.steps.get(frame.next) else {
+                undo.changes.extend(self.stack.pop().map(Change::Popped));
+                continue;
+            };
+            frame.next += 1;
+            undo.changes.push(Change::Tried);
+            let from = frame.pos;
+            let depth = self.stack.len() as u32;
+            if self
+                .reached
+                .get(&step.to)
+                .is_some_and(|reached| reached.depth <= depth)
+            {
+                continue;
+            }
+            let reached = Reached {
+                depth,
+                parent: Some(from),
+            };
+            undo.changes
+                .push(Change::Set(step.to, self.reached.insert(step.to, reached)));
+            self.expanded += 1;
+            if step.to == self.goal {
+                let mut path: Vec<Point> = self.stack[1..].iter().map(|frame| frame.tile).collect();
+                path.push(step.tile);
+                self.path = Some(path);
+                undo.found = true;
+                self.done = true;
+                break;
+            }
+            if depth == self.limit {
+                self.cut_off = true;
+                continue;
+            }
+            undo.changes.push(Change::Pushed(step.to));
+            self.stack.push(Frame {
+                pos: step.to,
+                tile: step.tile,
+                steps: steps(step.to),
+                next: 0,
+            });
+        }
+        undo
+    }
+
+    pub fn undo(&mut self, undo: Undo) {
+        for change in undo.changes.into_iter().rev() {
+            match change {
+                Change::Set(pos, Some(reached)) => {
+                    self.reached.insert(pos, reached);
+                }
+                Change::Set(pos, None) => {
+                    self.reached.remove(&pos);
+                }
+                Change::Cleared(reached) => self.reached = reached,
+                Change::Pushed(_) => {
+                    self.stack.pop();
+                }
+                Change::Popped(frame) => self.stack.push(frame),
+                Change::Tried => {
+                    if let Some(frame) = self.stack.last_mut() {
+                        frame.next -= 1;
+                    }
+                }
+            }
+        }
+        self.cut_off = undo.cut_off;
+        self.limit = undo.limit;
+        self.sweeps = undo.sweeps;
+        self.expanded = undo.expanded;
+        if undo.found {
+            self.path = None;
+        }
+        self.done = undo.done;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui_counter_app::pathfinding::grid_steps;
+    use ratatui_counter_app::tile::TileMap;
+
+    /// A 3 x 3 map, walled at `walls`.
+    fn map(walls: &[Point]) -> TileMap<bool> {
+        let mut map = TileMap::with_default(3, 3);
+        for &wall in walls {
+            map[wall] = true;
+        }
+        map
+    }
+
+    #[test]
+    fn test_deepens_until_found() {
+        // a ring around a wall in the middle: four moves either way around
+        let map = map(&[(1, 1)]);
+        let steps = grid_steps(&map, |wall| !wall);
+        let mut search = Deepening::new((0, 0), (2, 2));
+        let mut undos = Vec::new();
+        while !search.done {
+            undos.push(search.advance(1, &steps));
+        }
+        assert_eq!(4, search.path.as_ref().unwrap().len());
+        assert_eq!(4, search.limit);
+        assert_eq!(4, search.sweeps);
+        // the shallow tiles were entered in every sweep
+        assert!(search.expanded > 8);
+
+        for undo in undos.into_iter().rev() {
+            search.undo(undo);
+        }
+        assert_eq!(
+            (1, 0, None),
+            (search.limit, search.sweeps, search.path.clone())
+        );
+        assert!(search.reached().is_empty());
+        assert_eq!(0, search.way_down().count());
+    }
+
+    #[test]
+    fn test_undo_midway() {
+        let map = map(&[(1, 1)]);
+        let steps = grid_steps(&map, |wall| !wall);
+        let mut search = Deepening::new((0, 0), (2, 2));
+        for _ in 0..3 {
+            search.advance(2, &steps);
+        }
+        let before = search.clone();
+        let undo = search.advance(5, &steps);
+        assert!(undo.touched().count() > 0);
+        search.undo(undo);
+        assert_eq!(before.reached(), search.reached());
+        assert!(before.way_down().eq(search.way_down()));
+        // the same moves again
+        let undo = search.advance(5, &steps);
+        search.undo(undo);
+        search.advance(MOVES_PER_TICK * 4, &steps);
+        assert_eq!(Some(4), search.path.map(|path| path.len()));
+    }
+
+    #[test]
+    fn test_unreachable_goal() {
+        let map = map(&[(1, 0), (1, 1), (1, 2)]);
+        let steps = grid_steps(&map, |wall| !wall);
+        let mut search = Deepening::new((0, 0), (2, 2));
+        while !search.done {
+            search.advance(MOVES_PER_TICK, &steps);
+        }
+        assert_eq!(None, search.path);
+        assert_eq!(3, search.reached().len());
+    }
+}
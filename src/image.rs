@@ -0,0 +1,448 @@
+use std::fs;
+use std::path::Path;
+
+use ratatui_counter_app::maze::{Maze, Tile};
+
+use crate::menu::MAX_SIZE;
+
+const PNG_SIGNATURE: [u8; 8] = [137, 80, 78, 71, 13, 10, 26, 10];
+/// Blocks of pixels darker than this on average become walls.
+const THRESHOLD: u32 = 128;
+/// Images with more pixels are refused whatever the scale, before anything
+/// is allocated for them.
+const MAX_PIXELS: usize = 1 << 26;
+
+/// An image as the brightness of each pixel, transparent parts counting as
+/// white.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gray {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// Whether `path` looks like an image `load` can read, going by its extension.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png") || ext.eq_ignore_ascii_case("bmp"))
+}
+
+/// A maze from the PNG or BMP file at `path`, each `scale` x `scale` block
+/// of pixels becoming one tile: a wall if it is dark, free if it is light.
+/// Returns the maze and the number of free tiles that were walled up because
+/// the robot couldn't reach them from the top left corner.
+pub fn load(path: &Path, scale: usize) -> Result<(Maze, usize), String> {
+    let scale = scale.max(1);
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    let gray = decode(&bytes, scale)?;
+    to_maze(&gray, scale)
+}
+
+/// Whether a `width` x `height` image is small enough to decode, and to
+/// make a maze of at `scale` pixels per tile.
+fn check_size(width: usize, height: usize, scale: usize) -> Result<(), String> {
+    let (w, h) = (width.div_ceil(scale), height.div_ceil(scale));
+    if w + 2 > 2 * MAX_SIZE + 1
+        || h + 2 > 2 * MAX_SIZE + 1
+        || width
+            .checked_mul(height)
+            .is_none_or(|pixels| pixels > MAX_PIXELS)
+    {
+        return Err(format!(
+            "a {}x{} image makes too big a maze at scale {}",
+            width, height, scale
+        ));
+    }
+    Ok(())
+}
+
+/// The image in `bytes`, refused if it is too big for a maze at `scale`.
+fn decode(bytes: &[u8], scale: usize) -> Result<Gray, String> {
+    if bytes.starts_with(&PNG_SIGNATURE) {
+        decode_png(bytes, scale)
+    } else if bytes.starts_with(b"BM") {
+        decode_bmp(bytes, scale)
+    } else {
+        Err("not a PNG or BMP image".to_string())
+    }
+}
+
+fn luminance(r: u8, g: u8, b: u8, alpha: u8) -> u8 {
+    let lum = (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000;
+    ((lum * alpha as u32 + 255 * (255 - alpha as u32)) / 255) as u8
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?))
+}
+
+/// Decode a non-interlaced PNG of any color type and bit depth.
+fn decode_png(bytes: &[u8], scale: usize) -> Result<Gray, String> {
+    let truncated = || "truncated PNG".to_string();
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut data = Vec::new();
+    let mut at = PNG_SIGNATURE.len();
+    while at < bytes.len() {
+        let len = u32_be(bytes, at).ok_or_else(truncated)? as usize;
+        let kind = bytes.get(at + 4..at + 8).ok_or_else(truncated)?;
+        let chunk = bytes.get(at + 8..at + 8 + len).ok_or_else(truncated)?;
+        match kind {
+            b"IHDR" if chunk.len() == 13 => header = Some(chunk),
+            b"PLTE" => palette = chunk,
+            b"IDAT" => data.extend_from_slice(chunk),
+            b"IEND" => break,
+            _ => {}
+        }
+        // skip the checksum
+        at += 12 + len;
+    }
+    let header = header.ok_or("PNG without a header")?;
+    let width = u32_be(header, 0).unwrap() as usize;
+    let height = u32_be(header, 4).unwrap() as usize;
+    check_size(width, height, scale)?;
+    let (depth, color, interlace) = (header[8] as usize, header[9], header[12]);
+    if interlace != 0 {
+        return Err("interlaced PNGs are not supported".to_string());
+    }
+    let channels = match color {
+        0 | 3 => 1,
+        4 => 2,
+        2 => 3,
+        6 => 4,
+        _ => return Err(format!("unknown PNG color type {}", color)),
+    };
+    if ![1, 2, 4, 8, 16].contains(&depth) || (channels > 1 && depth < 8) {
+        return Err(format!(
+            "PNGs with a bit depth of {} are not supported",
+            depth
+        ));
+    }
+    let pixel_bits = channels * depth;
+    // bytes a filter looks back, at least one
+    let back = pixel_bits.div_ceil(8);
+    let stride = (width * pixel_bits).div_ceil(8);
+    // no more than the rows take, so a small file can't inflate into a huge one
+    let raw = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(&data, height * (stride + 1))
+        .map_err(|e| format!("broken PNG data: {:?}", e.status))?;
+    if raw.len() < height * (stride + 1) {
+        return Err(truncated());
+    }
+    let mut rows = vec![0u8; height * stride];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        for x in 0..stride {
+            let a = if x >= back {
+                rows[y * stride + x - back]
+            } else {
+                0
+            };
+            let b = if y > 0 { rows[(y - 1) * stride + x] } else { 0 };
+            let c = if x >= back && y > 0 {
+                rows[(y - 1) * stride + x - back]
+            } else {
+                0
+            };
+            let predicted = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(format!("unknown PNG filter {}", filter)),
+            };
+            rows[y * stride + x] = line[x].wrapping_add(predicted);
+        }
+    }
+    // sample `i` of row `y`, scaled to 8 bits unless it is a palette index
+    let sample = |y: usize, i: usize| -> u8 {
+        let row = &rows[y * stride..(y + 1) * stride];
+        match depth {
+            8 => row[i],
+            16 => row[2 * i],
+            _ => {
+                let bit = i * depth;
+                let value = (row[bit / 8] >> (8 - depth - bit % 8)) & ((1 << depth) - 1);
+                if color == 3 {
+                    value
+                } else {
+                    (value as u32 * 255 / ((1 << depth) - 1)) as u8
+                }
+            }
+        }
+    };
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let s = |channel: usize| sample(y, x * channels + channel);
+            pixels.push(match color {
+                0 => s(0),
+                4 => luminance(s(0), s(0), s(0), s(1)),
+                2 => luminance(s(0), s(1), s(2), 255),
+                6 => luminance(s(0), s(1), s(2), s(3)),
+                _ => {
+                    let i = s(0) as usize * 3;
+                    let rgb = palette.get(i..i + 3).ok_or("PNG palette too short")?;
+                    luminance(rgb[0], rgb[1], rgb[2], 255)
+                }
+            });
+        }
+    }
+    Ok(Gray {
+        width,
+        height,
+        pixels,
+    })
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Decode an uncompressed BMP with 1, 4, 8, 24 or 32 bits per pixel.
+fn decode_bmp(bytes: &[u8], scale: usize) -> Result<Gray, String> {
+    let truncated = || "truncated BMP".to_string();
+    let offset = u32_le(bytes, 10).ok_or_else(truncated)? as usize;
+    let header_size = u32_le(bytes, 14).ok_or_else(truncated)? as usize;
+    let width = u32_le(bytes, 18).ok_or_else(truncated)? as i32;
+    let height = u32_le(bytes, 22).ok_or_else(truncated)? as i32;
+    let bits = u16_le(bytes, 28).ok_or_else(truncated)? as usize;
+    let compression = u32_le(bytes, 30).ok_or_else(truncated)?;
+    // 3 are bit fields, which for 32 bits are the usual BGRA in practice
+    if compression != 0 && !(compression == 3 && bits == 32) {
+        return Err("compressed BMPs are not supported".to_string());
+    }
+    if width <= 0 || height == 0 {
+        return Err("BMP without pixels".to_string());
+    }
+    if ![1, 4, 8, 24, 32].contains(&bits) {
+        return Err(format!(
+            "BMPs with {} bits per pixel are not supported",
+            bits
+        ));
+    }
+    let (width, top_down) = (width as usize, height < 0);
+    let height = height.unsigned_abs() as usize;
+    check_size(width, height, scale)?;
+    let colors = match u32_le(bytes, 46) {
+        Some(0) | None if bits <= 8 => 1 << bits,
+        Some(used) => used as usize,
+        None => 0,
+    };
+    let palette = &bytes[(14 + header_size).min(bytes.len())..];
+    let stride = (width * bits).div_ceil(32) * 4;
+    if bytes.len().saturating_sub(offset) / stride < height {
+        return Err(truncated());
+    }
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row_index = if top_down { y } else { height - 1 - y };
+        let start = offset + row_index * stride;
+        let row = bytes.get(start..start + stride).ok_or_else(truncated)?;
+        for x in 0..width {
+            pixels.push(match bits {
+                24 | 32 => {
+                    let p = &row[x * bits / 8..];
+                    let alpha = if bits == 32 && compression == 3 {
+                        p[3]
+                    } else {
+                        255
+                    };
+                    luminance(p[2], p[1], p[0], alpha)
+                }
+                _ => {
+                    let bit = x * bits;
+                    let index = (row[bit / 8] >> (8 - bits - bit % 8)) as usize & ((1 << bits) - 1);
+                    if index >= colors {
+                        return Err("BMP palette too short".to_string());
+                    }
+                    let bgr = palette
+                        .get(4 * index..4 * index + 3)
+                        .ok_or_else(truncated)?;
+                    luminance(bgr[2], bgr[1], bgr[0], 255)
+                }
+            });
+        }
+    }
+    Ok(Gray {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// The maze `gray` shows at `scale` pixels per tile, in a frame of walls.
+/// Free tiles the robot can't reach from the entrance at (1, 1) are walled
+/// up, and their number returned along with the maze.
+fn to_maze(gray: &Gray, scale: usize) -> Result<(Maze, usize), String> {
+    let scale = scale.max(1);
+    check_size(gray.width, gray.height, scale)?;
+    let (w, h) = (gray.width.div_ceil(scale), gray.height.div_ceil(scale));
+    let mut tiles = vec![vec![Tile::Wall; w + 2]; h + 2];
+    for ty in 0..h {
+        for tx in 0..w {
+            let (mut sum, mut count) = (0, 0);
+            for y in ty * scale..((ty + 1) * scale).min(gray.height) {
+                for x in tx * scale..((tx + 1) * scale).min(gray.width) {
+                    sum += gray.pixels[y * gray.width + x] as u32;
+                    count += 1;
+                }
+            }
+            if sum >= THRESHOLD * count {
+                tiles[ty + 1][tx + 1] = Tile::Free;
+            }
+        }
+    }
+    if tiles[1][1] == Tile::Wall {
+        return Err("the top left corner, where the robot starts, is dark".to_string());
+    }
+    let mut reached = vec![vec![false; w + 2]; h + 2];
+    reached[1][1] = true;
+    let mut stack = vec![(1, 1)];
+    while let Some((x, y)) = stack.pop() {
+        for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+            if tiles[ny][nx] == Tile::Free && !reached[ny][nx] {
+                reached[ny][nx] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+    let mut walled = 0;
+    for (row, reached) in tiles.iter_mut().zip(&reached) {
+        for (tile, &reached) in row.iter_mut().zip(reached) {
+            if *tile == Tile::Free && !reached {
+                *tile = Tile::Wall;
+                walled += 1;
+            }
+        }
+    }
+    Ok((Maze { tiles }, walled))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A light 4 x 2 image with a dark pixel at (2, 0) and (3, 1).
+    const LIGHT: [u8; 3] = [250, 240, 230];
+    const DARK: [u8; 3] = [20, 10, 0];
+
+    fn bmp() -> Vec<u8> {
+        let mut bytes = b"BM".to_vec();
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(54u32.to_le_bytes());
+        bytes.extend(40u32.to_le_bytes());
+        bytes.extend(4i32.to_le_bytes());
+        bytes.extend(2i32.to_le_bytes());
+        bytes.extend(1u16.to_le_bytes());
+        bytes.extend(24u16.to_le_bytes());
+        bytes.extend([0; 24]);
+        // bottom row first, in BGR, no padding needed for 12 bytes
+        for row in [[LIGHT, LIGHT, LIGHT, DARK], [LIGHT, LIGHT, DARK, LIGHT]] {
+            for [r, g, b] in row {
+                bytes.extend([b, g, r]);
+            }
+        }
+        bytes
+    }
+
+    fn png() -> Vec<u8> {
+        let mut raw = Vec::new();
+        // one row filtered with sub and one with paeth
+        raw.push(1);
+        let mut prev = [0; 3];
+        for rgb in [LIGHT, LIGHT, DARK, LIGHT] {
+            raw.extend((0..3).map(|i| rgb[i].wrapping_sub(prev[i])));
+            prev = rgb;
+        }
+        raw.push(4);
+        let above = [LIGHT, LIGHT, DARK, LIGHT];
+        let row = [LIGHT, LIGHT, LIGHT, DARK];
+        for x in 0..4 {
+            for i in 0..3 {
+                let a = if x > 0 { row[x - 1][i] } else { 0 };
+                let c = if x > 0 { above[x - 1][i] } else { 0 };
+                raw.push(row[x][i].wrapping_sub(paeth(a, above[x][i], c)));
+            }
+        }
+        let chunk = |bytes: &mut Vec<u8>, kind: &[u8], data: &[u8]| {
+            bytes.extend((data.len() as u32).to_be_bytes());
+            bytes.extend(kind);
+            bytes.extend(data);
+            bytes.extend([0; 4]);
+        };
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        header.extend(4u32.to_be_bytes());
+        header.extend(2u32.to_be_bytes());
+        header.extend([8, 2, 0, 0, 0]);
+        chunk(&mut bytes, b"IHDR", &header);
+        chunk(
+            &mut bytes,
+            b"IDAT",
+            &miniz_oxide::deflate::compress_to_vec_zlib(&raw, 6),
+        );
+        chunk(&mut bytes, b"IEND", &[]);
+        bytes
+    }
+
+    #[test]
+    fn test_decode() {
+        let gray = decode(&bmp(), 1).unwrap();
+        assert_eq!(gray, decode(&png(), 1).unwrap());
+        let (maze, walled) = to_maze(&gray, 1).unwrap();
+        assert_eq!("OOOOOO\nO  OOO\nO   OO\nOOOOOO\n", maze.to_string());
+        // the dark pixels cut off (3, 0), which the robot can't get to
+        assert_eq!(1, walled);
+        let (maze, _) = to_maze(&gray, 2).unwrap();
+        assert_eq!("OOOO\nO OO\nOOOO\n", maze.to_string());
+        assert!(decode(b"GIF89a", 1).is_err());
+    }
+
+    #[test]
+    fn test_decode_huge() {
+        // headers claiming far more pixels than there are bytes for
+        let mut bytes = bmp();
+        bytes[18..22].copy_from_slice(&i32::MAX.to_le_bytes());
+        bytes[22..26].copy_from_slice(&i32::MIN.to_le_bytes());
+        assert!(decode(&bytes, 1).unwrap_err().contains("too big"));
+        let mut bytes = bmp();
+        bytes[18..22].copy_from_slice(&1000i32.to_le_bytes());
+        assert_eq!(Err("truncated BMP".to_string()), decode(&bytes, 1));
+        let mut bytes = png();
+        bytes[16..20].copy_from_slice(&u32::MAX.to_be_bytes());
+        bytes[20..24].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert!(decode(&bytes, 1 << 20).unwrap_err().contains("too big"));
+        let mut bytes = png();
+        bytes[16..20].copy_from_slice(&1000u32.to_be_bytes());
+        assert_eq!(Err("truncated PNG".to_string()), decode(&bytes, 1));
+        // more data than the rows take
+        bytes[16..20].copy_from_slice(&1u32.to_be_bytes());
+        assert!(decode(&bytes, 1)
+            .unwrap_err()
+            .starts_with("broken PNG data"));
+    }
+}
@@ -0,0 +1,72 @@
+use ratatui_counter_app::pathfinding::{self, Point, SearchTree, Step};
+
+/// A jump point search and an A* search for the same path, to be replayed
+/// next to each other, one expanded tile of each per tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Race {
+    /// The jump points in the order they were expanded.
+    pub jumps: SearchTree,
+    /// The tiles A* expanded in order, moving over the same tiles.
+    pub astar: SearchTree,
+    /// The tiles to step into from the start to the target, if it can be
+    /// reached.
+    pub path: Option<Vec<Point>>,
+}
+
+impl Race {
+    /// Search from `start` to `target` on a `size` map over the tiles for
+    /// which `passable` holds.
+    pub fn new(
+        size: (u16, u16),
+        start: Point,
+        target: Point,
+        passable: impl Fn(Point) -> bool,
+    ) -> Self {
+        let steps = |pos| {
+            pathfinding::neighbors(pos, size.0, size.1)
+                .filter(|&next| passable(next))
+                .map(|next| Step {
+                    tile: next,
+                    to: next,
+                    cost: 1,
+                })
+                .collect()
+        };
+        let (_, astar) = pathfinding::astar_search(size, start, target, steps);
+        let (path, jumps) = pathfinding::jps(size, start, target, &passable);
+        Self {
+            jumps,
+            astar,
+            path: path.map(|path| path.steps.iter().map(|step| step.tile).collect()),
+        }
+    }
+
+    /// Ticks the replay takes, until the slower search is done.
+    pub fn ticks(&self) -> usize {
+        self.jumps.len().max(self.astar.len())
+    }
+
+    /// Share of the tiles A* expanded that the jump point search skipped.
+    pub fn pruned(&self) -> f64 {
+        1.0 - self.jumps.len() as f64 / self.astar.len().max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jumps_prune_open_room() {
+        // an empty 9 x 9 room, corner to corner
+        let race = Race::new((9, 9), (0, 0), (8, 8), |_| true);
+        assert_eq!(Some(16), race.path.as_ref().map(Vec::len));
+        assert_eq!(
+            Some((8, 8)),
+            race.path.as_ref().and_then(|path| path.last().copied())
+        );
+        assert!(race.jumps.len() < race.astar.len());
+        assert!(race.pruned() > 0.5);
+        assert_eq!(race.astar.len(), race.ticks());
+    }
+}
@@ -0,0 +1,131 @@
+use std::env;
+use std::io::{self, Write};
+
+use ratatui::style::Color;
+
+/// Base64 characters sent per escape sequence, the most the protocol allows.
+const CHUNK: usize = 4096;
+
+/// Whether the terminal looks like it understands the kitty graphics protocol.
+pub fn supported() -> bool {
+    env::var_os("KITTY_WINDOW_ID").is_some()
+        || env::var("TERM").is_ok_and(|term| term.contains("kitty"))
+        || env::var("TERM_PROGRAM")
+            .is_ok_and(|program| program == "WezTerm" || program == "ghostty")
+}
+
+/// An RGB image with 8 bits per channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+impl Image {
+    /// A black image.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            rgb: vec![0; (width * height * 3) as usize],
+        }
+    }
+
+    /// Fill the square of `size` x `size` pixels whose top left corner is
+    /// at (`x` * `size`, `y` * `size`).
+    pub fn fill_block(&mut self, x: u32, y: u32, size: u32, color: [u8; 3]) {
+        for py in y * size..((y + 1) * size).min(self.height) {
+            for px in x * size..((x + 1) * size).min(self.width) {
+                let i = ((py * self.width + px) * 3) as usize;
+                self.rgb[i..i + 3].copy_from_slice(&color);
+            }
+        }
+    }
+}
+
+/// Show `image` scaled to `cols` x `rows` cells at the cursor, replacing any
+/// earlier image with the same `id`. The cursor stays where it is.
+pub fn show(out: &mut impl Write, image: &Image, id: u32, cols: u16, rows: u16) -> io::Result<()> {
+    let data = base64(&image.rgb);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(CHUNK).collect();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(i + 1 < chunks.len());
+        if i == 0 {
+            write!(
+                out,
+                "\x1b_Ga=T,f=24,s={},v={},c={},r={},i={},C=1,q=2,m={};",
+                image.width, image.height, cols, rows, id, more
+            )?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    Ok(())
+}
+
+/// Remove the image with `id` from the screen and free its data.
+pub fn delete(out: &mut impl Write, id: u32) -> io::Result<()> {
+    write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", id)
+}
+
+/// The RGB value of `color`, with the named colors as xterm shows them.
+pub fn rgb(color: Color) -> [u8; 3] {
+    match color {
+        Color::Rgb(r, g, b) => [r, g, b],
+        Color::Black | Color::Reset => [0, 0, 0],
+        Color::Red => [205, 0, 0],
+        Color::Green => [0, 205, 0],
+        Color::Yellow => [205, 205, 0],
+        Color::Blue => [0, 0, 238],
+        Color::Magenta => [205, 0, 205],
+        Color::Cyan => [0, 205, 205],
+        Color::Gray => [229, 229, 229],
+        Color::DarkGray => [127, 127, 127],
+        Color::LightRed => [255, 0, 0],
+        Color::LightGreen => [0, 255, 0],
+        Color::LightYellow => [255, 255, 0],
+        Color::LightBlue => [92, 92, 255],
+        Color::LightMagenta => [255, 0, 255],
+        Color::LightCyan => [0, 255, 255],
+        Color::White => [255, 255, 255],
+        Color::Indexed(i) => [i, i, i],
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for group in data.chunks(3) {
+        let bytes = [
+            group[0],
+            *group.get(1).unwrap_or(&0),
+            *group.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= group.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!("", base64(b""));
+        assert_eq!("Zg==", base64(b"f"));
+        assert_eq!("Zm8=", base64(b"fo"));
+        assert_eq!("Zm9v", base64(b"foo"));
+        assert_eq!("Zm9vYmFy", base64(b"foobar"));
+    }
+}
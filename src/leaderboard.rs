@@ -0,0 +1,208 @@
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Row, Table, Widget},
+};
+
+use crate::stats::format_duration;
+
+pub const LEADERBOARD_PATH: &str = "leaderboard.tsv";
+
+/// One completed run. Stored as a tab-separated line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunRecord {
+    pub seed: u64,
+    pub generator: String,
+    pub width: usize,
+    pub height: usize,
+    pub solver: String,
+    pub steps: u64,
+    pub time: Duration,
+    /// By the `Scoring` of the settings at the time, missing on records
+    /// from before there were scores.
+    pub score: Option<u64>,
+}
+
+impl RunRecord {
+    fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            self.seed,
+            self.generator,
+            self.width,
+            self.height,
+            self.solver,
+            self.steps,
+            self.time.as_millis(),
+            self.score.map_or(String::new(), |score| score.to_string())
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut fields = line.split('\t');
+        let record = Self {
+            seed: fields.next()?.parse().ok()?,
+            generator: fields.next()?.to_string(),
+            width: fields.next()?.parse().ok()?,
+            height: fields.next()?.parse().ok()?,
+            solver: fields.next()?.to_string(),
+            steps: fields.next()?.parse().ok()?,
+            time: Duration::from_millis(fields.next()?.parse().ok()?),
+            score: match fields.next() {
+                None | Some("") => None,
+                Some(score) => Some(score.parse().ok()?),
+            },
+        };
+        Some(record)
+    }
+
+    /// Runs with the same key were played on the same maze.
+    fn maze_key(&self) -> (u64, &str, usize, usize) {
+        (self.seed, &self.generator, self.width, self.height)
+    }
+}
+
+#[derive(Debug)]
+pub struct Leaderboard {
+    path: PathBuf,
+    records: Vec<RunRecord>,
+    /// First row shown in the table.
+    scroll: usize,
+}
+
+impl Leaderboard {
+    /// Load all records from `path`. A missing file is an empty leaderboard,
+    /// lines that can't be parsed are skipped.
+    pub fn load(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let mut leaderboard = Self::new(path);
+        match fs::read_to_string(&leaderboard.path) {
+            Ok(content) => {
+                leaderboard.records = content.lines().filter_map(RunRecord::from_line).collect()
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        };
+        Ok(leaderboard)
+    }
+
+    /// An empty leaderboard that will be written to `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            records: Vec::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn record(&mut self, record: RunRecord) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", record.to_line())?;
+        self.records.push(record);
+        Ok(())
+    }
+
+    /// Records grouped by maze, best (highest score, then fewest steps)
+    /// first within each group. Records without a score come last.
+    fn sorted(&self) -> Vec<&RunRecord> {
+        let mut records: Vec<&RunRecord> = self.records.iter().collect();
+        records.sort_by(|a, b| {
+            a.maze_key()
+                .cmp(&b.maze_key())
+                .then(b.score.cmp(&a.score))
+                .then(a.steps.cmp(&b.steps))
+                .then(a.time.cmp(&b.time))
+        });
+        records
+    }
+
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.records.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+}
+
+impl Widget for &Leaderboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = Row::new([
+            "seed",
+            "generator",
+            "size",
+            "solver",
+            "steps",
+            "time",
+            "score",
+        ])
+        .style(Style::new().bold());
+        let sorted = self.sorted();
+        let rows = sorted.iter().enumerate().skip(self.scroll).map(|(i, r)| {
+            let best = i == 0 || sorted[i - 1].maze_key() != r.maze_key();
+            let row = Row::new([
+                r.seed.to_string(),
+                r.generator.clone(),
+                format!("{}x{}", r.width, r.height),
+                r.solver.clone(),
+                r.steps.to_string(),
+                format_duration(r.time),
+                r.score.map_or("-".to_string(), |score| score.to_string()),
+            ]);
+            if best {
+                row.style(Style::new().green())
+            } else {
+                row
+            }
+        });
+        let widths = [
+            Constraint::Length(8),
+            Constraint::Length(16),
+            Constraint::Length(8),
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(8),
+            Constraint::Length(6),
+        ];
+        Table::new(rows, widths)
+            .header(header)
+            .block(
+                Block::bordered()
+                    .title(" leaderboard ")
+                    .title_bottom(Line::from(" ↑/↓ scroll  Esc back ").dim()),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_roundtrip() {
+        let record = RunRecord {
+            seed: 42,
+            generator: "drunkard (45%)".to_string(),
+            width: 16,
+            height: 8,
+            solver: "robot-dfs".to_string(),
+            steps: 123,
+            time: Duration::from_millis(4567),
+            score: Some(512),
+        };
+        assert_eq!(
+            Some(record.clone()),
+            RunRecord::from_line(&record.to_line())
+        );
+        let old = "42\tkruskal\t16\t8\trobot-dfs\t123\t4567";
+        assert_eq!(None, RunRecord::from_line(old).unwrap().score);
+        assert_eq!(None, RunRecord::from_line("not\ta\trecord"));
+    }
+}
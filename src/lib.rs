@@ -0,0 +1,9 @@
+//! Maze generation, tile maps, path searches, and tick hooks, shared by the
+//! app, the benchmarks, and generator plugins.
+
+pub mod chunk;
+pub mod hooks;
+pub mod maze;
+pub mod pathfinding;
+pub mod plugin;
+pub mod tile;
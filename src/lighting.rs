@@ -0,0 +1,93 @@
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::kitty;
+
+/// Brightness of tiles outside the light.
+const AMBIENT: f64 = 0.2;
+/// Brightness right next to the robot, a little above the tile's own color.
+const PEAK: f64 = 1.3;
+/// The radii `Light::next_radius` steps through, 0 being no light at all.
+const RADII: [u16; 4] = [0, 4, 8, 12];
+
+/// A light carried by the robot, which brightens the tiles around it and
+/// leaves the rest of the map in the dark.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct Light {
+    /// In tiles, 0 turning the lighting off.
+    pub radius: u16,
+    /// Let the radius waver like the flame of a torch.
+    pub flicker: bool,
+}
+
+impl Light {
+    pub fn is_on(&self) -> bool {
+        self.radius > 0
+    }
+
+    /// The next of the preset radii, for cycling through them at runtime.
+    pub fn next_radius(&self) -> u16 {
+        RADII
+            .into_iter()
+            .find(|&radius| radius > self.radius)
+            .unwrap_or(0)
+    }
+
+    /// How much to scale the colors of a tile `distance` tiles from the
+    /// robot at `tick`. Full brightness fades out linearly to the edge.
+    pub fn brightness(&self, distance: f64, tick: u64) -> f64 {
+        if !self.is_on() {
+            return 1.0;
+        }
+        let radius = self.radius as f64 * if self.flicker { flicker(tick) } else { 1.0 };
+        let lit = (1.0 - distance / radius).clamp(0.0, 1.0);
+        AMBIENT + (PEAK - AMBIENT) * lit
+    }
+}
+
+/// A factor between 0.8 and 1 that jumps around from tick to tick, but is
+/// the same for the same tick so rewinding shows the same frames.
+fn flicker(tick: u64) -> f64 {
+    let mut z = tick.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    z ^= z >> 29;
+    0.8 + 0.2 * (z % 1000) as f64 / 999.0
+}
+
+/// `color` scaled by `brightness`, as RGB. The terminal's default color is
+/// unknown, so it is left alone.
+pub fn scale(color: Color, brightness: f64) -> Color {
+    if color == Color::Reset {
+        return color;
+    }
+    let [r, g, b] = kitty::rgb(color).map(|c| (c as f64 * brightness).round().min(255.0) as u8);
+    Color::Rgb(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_brightness() {
+        let light = Light {
+            radius: 8,
+            flicker: false,
+        };
+        assert_eq!(PEAK, light.brightness(0.0, 0));
+        assert_eq!(AMBIENT, light.brightness(8.0, 0));
+        assert_eq!(AMBIENT, light.brightness(20.0, 0));
+        assert!(light.brightness(3.0, 0) > light.brightness(5.0, 0));
+        assert_eq!(1.0, Light::default().brightness(20.0, 0));
+
+        let torch = Light {
+            flicker: true,
+            ..light
+        };
+        // the flame never reaches further than the plain light
+        assert!((0..100).all(|tick| torch.brightness(4.0, tick) <= light.brightness(4.0, tick)));
+        assert_eq!(torch.brightness(4.0, 7), torch.brightness(4.0, 7));
+
+        assert_eq!(Color::Rgb(100, 50, 0), scale(Color::Rgb(200, 100, 0), 0.5));
+        assert_eq!(Color::Rgb(255, 200, 0), scale(Color::Rgb(200, 100, 0), 2.0));
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+
+use crate::debug;
+
+/// The pane showing the latest lines of the log, the newest at the bottom.
+#[derive(Debug, Default)]
+pub struct LogPane {
+    /// Lines scrolled back from the newest.
+    scroll: usize,
+}
+
+impl LogPane {
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = debug::with_recent(|recent| recent.lines().len()).saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    fn render_lines(&self, lines: &VecDeque<String>, area: Rect, buf: &mut Buffer) {
+        let height = area.height.saturating_sub(2) as usize;
+        let end = lines.len().saturating_sub(self.scroll);
+        let shown: Vec<Line> = lines
+            .range(end.saturating_sub(height)..end)
+            .map(|line| Line::raw(line.as_str()))
+            .collect();
+        let title = match self.scroll {
+            0 => " log ".to_string(),
+            scroll => format!(" log (+{}) ", scroll),
+        };
+        Paragraph::new(shown)
+            .block(Block::bordered().title(title))
+            .render(area, buf);
+    }
+}
+
+impl Widget for &LogPane {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        debug::with_recent(|recent| self.render_lines(recent.lines(), area, buf));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_lines() {
+        let lines: VecDeque<String> = (0..10).map(|i| format!("line {}", i)).collect();
+        let area = Rect::new(0, 0, 12, 5);
+        let mut buf = Buffer::empty(area);
+        let row =
+            |buf: &Buffer, y: u16| -> String { (1..11).map(|x| buf[(x, y)].symbol()).collect() };
+
+        let mut pane = LogPane::default();
+        pane.render_lines(&lines, area, &mut buf);
+        assert_eq!("line 7    ", row(&buf, 1));
+        assert_eq!("line 9    ", row(&buf, 3));
+
+        pane.scroll = 8;
+        let mut buf = Buffer::empty(area);
+        pane.render_lines(&lines, area, &mut buf);
+        assert_eq!("line 0    ", row(&buf, 1));
+        assert_eq!("line 1    ", row(&buf, 2));
+        assert_eq!("          ", row(&buf, 3));
+    }
+}
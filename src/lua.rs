@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+
+use mlua::{Function, Lua, Table};
+use ratatui_counter_app::hooks::{Hook, Phase, TickState};
+
+/// A Lua script defining `pre_tick(state)` and/or `post_tick(state)`.
+///
+/// `state` has the fields `tick`, `x` and `y` of the robot, `goal` as a
+/// table with `x` and `y` if there is one, `width` and `height`. Scripts
+/// wall up tiles by appending `{x = .., y = ..}` to `state.walls`, label
+/// them by appending `{x = .., y = .., text = ..}` to `state.labels` and log
+/// values by setting them in `state.metrics`.
+pub struct LuaHook {
+    lua: Lua,
+}
+
+impl LuaHook {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let lua = Lua::new();
+        lua.load(source)
+            .set_name(path.display().to_string())
+            .exec()
+            .map_err(|e| e.to_string())?;
+        Ok(Self { lua })
+    }
+
+    fn call_lua(&self, name: &str, state: &mut TickState) -> mlua::Result<()> {
+        let Some(function) = self.lua.globals().get::<_, Option<Function>>(name)? else {
+            return Ok(());
+        };
+        let table = self.lua.create_table()?;
+        table.set("tick", state.tick)?;
+        table.set("x", state.robot.0)?;
+        table.set("y", state.robot.1)?;
+        if let Some((x, y)) = state.goal {
+            let goal = self.lua.create_table()?;
+            goal.set("x", x)?;
+            goal.set("y", y)?;
+            table.set("goal", goal)?;
+        }
+        table.set("width", state.width)?;
+        table.set("height", state.height)?;
+        table.set("walls", self.lua.create_table()?)?;
+        table.set("metrics", self.lua.create_table()?)?;
+        table.set("labels", self.lua.create_table()?)?;
+        function.call::<_, ()>(table.clone())?;
+
+        for wall in table.get::<_, Table>("walls")?.sequence_values::<Table>() {
+            let wall = wall?;
+            state.walls.push((wall.get("x")?, wall.get("y")?));
+        }
+        for metric in table.get::<_, Table>("metrics")?.pairs::<String, f64>() {
+            state.metrics.push(metric?);
+        }
+        for label in table.get::<_, Table>("labels")?.sequence_values::<Table>() {
+            let label = label?;
+            state
+                .labels
+                .push(((label.get("x")?, label.get("y")?), label.get("text")?));
+        }
+        Ok(())
+    }
+}
+
+impl Hook for LuaHook {
+    fn call(&mut self, phase: Phase, state: &mut TickState) -> Result<(), String> {
+        let name = match phase {
+            Phase::Pre => "pre_tick",
+            Phase::Post => "post_tick",
+        };
+        self.call_lua(name, state).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui_counter_app::hooks::Hooks;
+
+    #[test]
+    fn test_lua() {
+        let path = std::env::temp_dir().join(format!("hooks-test-{}.lua", std::process::id()));
+        fs::write(
+            &path,
+            r#"
+            function post_tick(state)
+                state.metrics.dist = math.abs(state.goal.x - state.x)
+                if state.tick % 2 == 0 then
+                    table.insert(state.walls, {x = state.x + 1, y = state.y})
+                end
+                table.insert(state.labels, {x = state.x, y = state.y, text = "hi"})
+            end
+            "#,
+        )
+        .unwrap();
+        let mut hooks = Hooks::default();
+        hooks.add(LuaHook::load(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+        let mut calls = 0;
+        hooks.add(move |_: Phase, state: &mut TickState| {
+            calls += 1;
+            state.metrics.push(("calls".to_string(), calls as f64));
+            Ok(())
+        });
+
+        let mut state = TickState {
+            tick: 4,
+            robot: (3, 1),
+            goal: Some((9, 1)),
+            ..TickState::default()
+        };
+        hooks.call(Phase::Pre, &mut state).unwrap();
+        assert_eq!(vec![("calls".to_string(), 1.0)], state.metrics);
+        state.metrics.clear();
+        hooks.call(Phase::Post, &mut state).unwrap();
+        assert_eq!(vec![(4, 1)], state.walls);
+        assert_eq!(vec![((3, 1), "hi".to_string())], state.labels);
+        assert_eq!(
+            vec![("dist".to_string(), 6.0), ("calls".to_string(), 2.0)],
+            state.metrics
+        );
+
+        // no goal to index
+        state.goal = None;
+        assert!(hooks.call(Phase::Post, &mut state).is_err());
+        assert!(hooks.call(Phase::Post, &mut state).is_ok());
+        assert!(!hooks.is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// Starts and stops recording a macro, like `q` in vim, which quits here.
+pub const RECORD_KEY: char = 'Q';
+pub const PLAY_KEY: char = '@';
+/// How deep macros may play macros, so one playing itself ends.
+const MAX_DEPTH: usize = 10;
+
+/// The key after `RECORD_KEY` or `PLAY_KEY` names the register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Record,
+    Play,
+}
+
+/// What to do with a key after the macros had a look at it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MacroKey {
+    /// Handle the key as usual.
+    Pass,
+    /// The key was for the macros, with what they did.
+    Consumed(Option<String>),
+    /// Handle these keys instead, then call `Macros::played`.
+    Play(Vec<KeyEvent>),
+    Error(String),
+}
+
+/// Key sequences recorded into registers named by a letter, to be played
+/// back with `@` and the letter, or `@@` for the last one played.
+#[derive(Debug, Default)]
+pub struct Macros {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<(char, Vec<KeyEvent>)>,
+    pending: Option<Pending>,
+    last_played: Option<char>,
+    /// Macros playing right now, the outermost first.
+    playing: Vec<char>,
+}
+
+impl Macros {
+    /// The register being recorded into, if any.
+    pub fn recording(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    /// Look at a key pressed while nothing takes text, recording it if a
+    /// macro is being recorded.
+    pub fn handle_key(&mut self, key: KeyEvent) -> MacroKey {
+        let KeyCode::Char(c) = key.code else {
+            self.pending = None;
+            self.record(key);
+            return MacroKey::Pass;
+        };
+        match self.pending.take() {
+            Some(Pending::Record) if c.is_ascii_alphabetic() => {
+                self.recording = Some((c, Vec::new()));
+                MacroKey::Consumed(Some(format!("recording @{}", c)))
+            }
+            Some(Pending::Record) => MacroKey::Error(format!("'{}' is no register", c)),
+            Some(Pending::Play) => {
+                let register = match c {
+                    PLAY_KEY => match self.last_played {
+                        Some(register) => register,
+                        None => return MacroKey::Error("no macro played yet".to_string()),
+                    },
+                    c => c,
+                };
+                let Some(keys) = self.registers.get(&register) else {
+                    return MacroKey::Error(format!("nothing recorded in @{}", register));
+                };
+                if self.playing.len() >= MAX_DEPTH {
+                    return MacroKey::Error("macros play each other too deep".to_string());
+                }
+                let keys = keys.clone();
+                // the macro being recorded plays it too, not what it does
+                if self.playing.is_empty() {
+                    if let Some((_, recorded)) = &mut self.recording {
+                        recorded.push(KeyEvent::from(KeyCode::Char(PLAY_KEY)));
+                        recorded.push(key);
+                    }
+                }
+                self.last_played = Some(register);
+                self.playing.push(register);
+                MacroKey::Play(keys)
+            }
+            None if c == RECORD_KEY && self.playing.is_empty() => match self.recording.take() {
+                Some((register, keys)) => {
+                    let message = format!("recorded {} keys into @{}", keys.len(), register);
+                    self.registers.insert(register, keys);
+                    MacroKey::Consumed(Some(message))
+                }
+                None => {
+                    self.pending = Some(Pending::Record);
+                    MacroKey::Consumed(None)
+                }
+            },
+            None if c == PLAY_KEY => {
+                self.pending = Some(Pending::Play);
+                MacroKey::Consumed(None)
+            }
+            None => {
+                self.record(key);
+                MacroKey::Pass
+            }
+        }
+    }
+
+    /// Record a key handled while something takes text, e.g. the command
+    /// line, where the macro keys are just text.
+    pub fn record(&mut self, key: KeyEvent) {
+        if let (Some((_, keys)), true) = (&mut self.recording, self.playing.is_empty()) {
+            keys.push(key);
+        }
+    }
+
+    /// Call once the keys of a `MacroKey::Play` are handled.
+    pub fn played(&mut self) {
+        self.playing.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(macros: &mut Macros, keys: &str) -> Vec<MacroKey> {
+        keys.chars()
+            .map(|c| macros.handle_key(KeyEvent::from(KeyCode::Char(c))))
+            .collect()
+    }
+
+    #[test]
+    fn test_record_and_play() {
+        let mut macros = Macros::default();
+        press(&mut macros, "Qa");
+        assert_eq!(Some('a'), macros.recording());
+        macros.handle_key(KeyEvent::from(KeyCode::Right));
+        press(&mut macros, "mQ");
+        assert_eq!(None, macros.recording());
+
+        let keys = vec![
+            KeyEvent::from(KeyCode::Right),
+            KeyEvent::from(KeyCode::Char('m')),
+        ];
+        assert_eq!(
+            Some(&MacroKey::Play(keys.clone())),
+            press(&mut macros, "@a").last()
+        );
+        macros.played();
+        assert_eq!(Some(&MacroKey::Play(keys)), press(&mut macros, "@@").last());
+        macros.played();
+
+        // macros playing macros are recorded as played, not as what they do
+        press(&mut macros, "Qb@a");
+        macros.played();
+        press(&mut macros, "Q");
+        assert_eq!(
+            Some(&vec![
+                KeyEvent::from(KeyCode::Char('@')),
+                KeyEvent::from(KeyCode::Char('a'))
+            ]),
+            macros.registers.get(&'b')
+        );
+        assert!(matches!(press(&mut macros, "@z")[1], MacroKey::Error(_)));
+        assert!(matches!(press(&mut macros, "Q1")[1], MacroKey::Error(_)));
+    }
+}
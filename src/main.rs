@@ -1,316 +1,6809 @@
+mod annotations;
+mod ansi;
+mod ants;
+mod ascii;
+mod batch;
+mod bidirectional;
+mod cast;
+mod command;
+mod control;
+mod course;
+mod daily;
 mod debug;
-use debug::{debug_print, debug_println};
-mod maze;
-mod tile;
+mod dialog;
+mod events;
+mod evolve;
+mod gallery;
+mod ghost;
+mod gzip;
+mod help;
+mod hint;
+mod history;
+#[cfg(feature = "http")]
+mod http;
+mod iddfs;
+mod image;
+mod jps;
+mod kitty;
+mod leaderboard;
+mod lighting;
+mod log_pane;
+#[cfg(feature = "lua")]
+mod lua;
+mod macros;
+use annotations::Annotations;
+use ants::Colony;
+use batch::{BatchRun, BatchView};
+use bidirectional::{Bidirectional, Side};
+use cast::Cast;
+use chunk::ChunkedMaze;
+use command::Command;
+use control::{Control, ControlMode, Method, Response, RpcError, FAILED, TTY_PATH};
+use course::Course;
+use daily::{DailyHistory, DAILY_PATH};
+use debug::debug_println;
+use dialog::{Answer, Dialog, Outcome};
+use events::{Breakpoint, Event as SimEvent, EventBus, EventStream, Shared, TickTracker};
+use evolve::{Evolution, EvolutionView};
+use gallery::{Gallery, GalleryAction, GALLERY_DIR};
+use ghost::Track;
+use help::Help;
+use hint::HotCold;
+use history::{History, LayerDelta, StackDelta};
+use hooks::{Hooks, Phase, TickState};
+use iddfs::Deepening;
+use jps::Race;
+use leaderboard::{Leaderboard, RunRecord, LEADERBOARD_PATH};
+use record::Recorder;
+mod menu;
+mod minimap;
+mod palette;
+mod pane;
+mod qlearn;
+mod record;
+mod replay;
+mod results;
+mod score;
+mod session;
+mod settings;
+mod share;
+mod sprites;
+mod stats;
+mod swarm;
+mod theme;
+mod thoughts;
+mod toast;
+mod tour;
+mod trail;
+mod view;
+mod visits;
+mod walk;
+mod watch;
+mod weights;
+use lighting::Light;
+use log_pane::LogPane;
+use macros::{MacroKey, Macros};
+use palette::PaletteName;
+use pane::{Pane, PaneLayout};
+use qlearn::{Episodes, QTable};
 use ratatui::layout::Constraint;
 use ratatui::layout::Layout;
-use tile::{AlphaTileMap, TileMap};
+use replay::{maze_hash, Replay, REPLAY_DIR};
+use results::{RunResult, RESULTS_PATH};
+use score::Scoring;
+use session::{Session, View, SESSION_PATH};
+use settings::{FormAction, Settings, SettingsForm, SETTINGS_PATH};
+use share::ShareCode;
+use sprites::{Kind as SpriteKind, SpriteSet};
+use stats::{FrameTimings, RunStats, Stopwatch};
+use swarm::Helper;
+use theme::ThemeFile;
+use thoughts::{Category, Thought, Thoughts, Turn};
+use tile::{AlphaTileMap, Glyph, SignedTileMap, TileMap, TileMapView};
+use toast::{Toaster, Toasts};
+use tour::Tour;
+use trail::Trail;
+use view::{FirstPerson, Slice, VIEW_DEPTH};
+use visits::Visits;
+use walk::Walk;
+use watch::{Probe, Watch};
+use weights::Weights;
 
+use std::borrow::Cow;
+use std::cell::Ref;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::fs;
 use std::io;
 use std::ops;
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
-use crossterm::style::StyledContent;
-use maze::Maze;
-use ratatui::text::Span;
+use clap::Parser;
+use crossterm::event::{
+    self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+    MouseButton, MouseEvent, MouseEventKind,
+};
+use menu::{Menu, MenuAction, Minotaur, Openings, RunConfig, Solver};
+use minimap::Minimap;
+use pathfinding::{Point, SearchTree, Step};
+use rand::{
+    rngs::StdRng,
+    seq::{IndexedRandom, SliceRandom},
+    Rng, SeedableRng,
+};
 use ratatui::{
     buffer::Buffer,
-    layout::Rect,
+    layout::{Position, Rect},
+    prelude::CrosstermBackend,
     style::{Color, Style, Stylize},
-    symbols::border,
-    text::{Line, Text},
-    widgets::{Block, BorderType, Borders, Paragraph, Widget},
-    DefaultTerminal, Frame,
+    text::{Line, Span},
+    widgets::{Block, Clear, LineGauge, Padding, Paragraph, Widget},
+    Frame, Terminal,
 };
+use ratatui_counter_app::{chunk, hooks, maze, pathfinding, plugin, tile};
+use rayon::prelude::*;
+use serde::Serialize;
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum BackgroundTile {
     #[default]
     Free,
     Wall,
+    /// Blocks movement until the robot carries the key of the same color.
+    Door(KeyColor),
+    /// Entering it moves the robot onto its twin at the given coordinates.
+    Teleporter((u16, u16)),
+    /// Can only be passed in the direction of the arrow.
+    OneWay(Direction),
 }
 
 impl From<&BackgroundTile> for ratatui::style::Color {
     fn from(value: &BackgroundTile) -> Self {
+        let palette = palette::current();
         match value {
-            BackgroundTile::Free => Color::Black,
-            BackgroundTile::Wall => Color::DarkGray,
+            BackgroundTile::Free => palette.free,
+            BackgroundTile::Wall => palette.wall,
+            BackgroundTile::Door(color) => color.door_color(),
+            BackgroundTile::Teleporter(_) => palette.teleporter,
+            BackgroundTile::OneWay(_) => palette.free,
+        }
+    }
+}
+
+impl BackgroundTile {
+    fn sprite_kind(&self) -> Option<SpriteKind> {
+        match self {
+            BackgroundTile::Wall => Some(SpriteKind::Wall),
+            BackgroundTile::Door(_) => Some(SpriteKind::Door),
+            BackgroundTile::Teleporter(_) => Some(SpriteKind::Teleporter),
+            BackgroundTile::Free | BackgroundTile::OneWay(_) => None,
+        }
+    }
+}
+
+impl Glyph for BackgroundTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        if let Some(pair) = self.sprite_kind().and_then(sprites::pair) {
+            return Some(pair);
+        }
+        match self {
+            BackgroundTile::OneWay(dir) => Some([dir.arrow(), ' ']),
+            _ if !palette::current().glyphs => None,
+            BackgroundTile::Free => None,
+            BackgroundTile::Wall => Some(['█', '█']),
+            BackgroundTile::Door(color) => Some(['#', color.letter()]),
+            BackgroundTile::Teleporter(_) => Some(['◊', ' ']),
+        }
+    }
+
+    fn wide_glyph(&self) -> Option<char> {
+        self.sprite_kind().and_then(sprites::wide)
+    }
+}
+
+/// Pairs a key with the doors it opens.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyColor {
+    Red,
+    Cyan,
+    White,
+}
+
+const KEY_COLORS: [KeyColor; 3] = [KeyColor::Red, KeyColor::Cyan, KeyColor::White];
+
+impl KeyColor {
+    fn key_color(self) -> Color {
+        palette::current().key(self)
+    }
+
+    fn door_color(self) -> Color {
+        palette::current().door(self)
+    }
+
+    /// Tells the keys and doors apart in the mono palette.
+    fn letter(self) -> char {
+        match self {
+            KeyColor::Red => 'r',
+            KeyColor::Cyan => 'c',
+            KeyColor::White => 'w',
         }
     }
 }
 
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 pub enum VisitedTile {
     #[default]
     Visited,
+    /// On a trail of limited length, from 0 for the newest cell to 255 for
+    /// the oldest.
+    Fading(u8),
+    /// Entered again and again, from 0 for once to 255 for the most often,
+    /// redder the more often.
+    Revisited(u8),
+}
+
+impl From<&VisitedTile> for ratatui::style::Color {
+    fn from(value: &VisitedTile) -> Self {
+        let palette = palette::current();
+        let visited = palette.visited;
+        match value {
+            VisitedTile::Visited => visited,
+            VisitedTile::Fading(fade) => {
+                let fade = *fade as f64 / 255.0;
+                lighting::scale(visited, 1.0 - fade * (1.0 - TRAIL_END_BRIGHTNESS))
+            }
+            VisitedTile::Revisited(heat) => {
+                let (cold, hot) = (kitty::rgb(visited), kitty::rgb(palette.minotaur));
+                let heat = *heat as u16;
+                let mix =
+                    |i: usize| ((cold[i] as u16 * (255 - heat) + hot[i] as u16 * heat) / 255) as u8;
+                Color::Rgb(mix(0), mix(1), mix(2))
+            }
+        }
+    }
+}
+
+impl Glyph for VisitedTile {}
+
+/// Ground that takes the robot longer to cross than a plain free tile.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TerrainTile {
+    #[default]
+    Sand,
+    Mud,
+    Ice,
+}
+
+const TERRAIN_TILES: [TerrainTile; 3] = [TerrainTile::Sand, TerrainTile::Mud, TerrainTile::Ice];
+
+impl TerrainTile {
+    /// Ticks it takes to move onto the tile. A plain free tile costs 1.
+    fn cost(self) -> u32 {
+        match self {
+            TerrainTile::Sand => 2,
+            TerrainTile::Ice => 3,
+            TerrainTile::Mud => 4,
+        }
+    }
+}
+
+impl From<&TerrainTile> for ratatui::style::Color {
+    fn from(value: &TerrainTile) -> Self {
+        let palette = palette::current();
+        match value {
+            TerrainTile::Sand => palette.sand,
+            TerrainTile::Mud => palette.mud,
+            TerrainTile::Ice => palette.ice,
+        }
+    }
+}
+
+/// The glyph keeps the terrain recognizable once the tile is visited.
+impl TerrainTile {
+    fn sprite_kind(self) -> SpriteKind {
+        match self {
+            TerrainTile::Sand => SpriteKind::Sand,
+            TerrainTile::Mud => SpriteKind::Mud,
+            TerrainTile::Ice => SpriteKind::Ice,
+        }
+    }
+}
+
+impl Glyph for TerrainTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        if let Some(pair) = sprites::pair(self.sprite_kind()) {
+            return Some(pair);
+        }
+        Some(match self {
+            TerrainTile::Sand => ['·', '·'],
+            TerrainTile::Mud => ['~', '~'],
+            TerrainTile::Ice => ['*', ' '],
+        })
+    }
+
+    fn wide_glyph(&self) -> Option<char> {
+        sprites::wide(self.sprite_kind())
+    }
+
+    fn glyph_color(&self) -> Color {
+        self.into()
+    }
+}
+
+/// Tiles in a single patch of terrain.
+const TERRAIN_PATCH: usize = 6;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ItemTile {
+    #[default]
+    Coin,
+    Star,
+    Key(KeyColor),
+}
+
+impl ItemTile {
+    /// Points for picking up the item.
+    fn value(self) -> u32 {
+        match self {
+            ItemTile::Coin => 1,
+            ItemTile::Star => 5,
+            ItemTile::Key(_) => 0,
+        }
+    }
+}
+
+impl From<&ItemTile> for ratatui::style::Color {
+    fn from(value: &ItemTile) -> Self {
+        let palette = palette::current();
+        match value {
+            ItemTile::Coin => palette.coin,
+            ItemTile::Star => palette.star,
+            ItemTile::Key(color) => color.key_color(),
+        }
+    }
+}
+
+impl ItemTile {
+    fn sprite_kind(self) -> SpriteKind {
+        match self {
+            ItemTile::Coin => SpriteKind::Coin,
+            ItemTile::Star => SpriteKind::Star,
+            ItemTile::Key(_) => SpriteKind::Key,
+        }
+    }
+}
+
+impl Glyph for ItemTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        if let Some(pair) = sprites::pair(self.sprite_kind()) {
+            return Some(pair);
+        }
+        if !palette::current().glyphs {
+            return None;
+        }
+        Some(match self {
+            ItemTile::Coin => ['$', ' '],
+            ItemTile::Star => ['*', '*'],
+            ItemTile::Key(color) => ['k', color.letter()],
+        })
+    }
+
+    fn wide_glyph(&self) -> Option<char> {
+        sprites::wide(self.sprite_kind())
+    }
+}
+
+/// One in this many placed items is a star.
+const STAR_RARITY: u32 = 5;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum ForegroundTile {
+    #[default]
+    Stack,
+    Robot,
+    Minotaur,
+}
+
+impl From<&ForegroundTile> for ratatui::style::Color {
+    fn from(value: &ForegroundTile) -> Self {
+        let palette = palette::current();
+        match value {
+            ForegroundTile::Stack => palette.stack,
+            ForegroundTile::Robot => palette.robot,
+            ForegroundTile::Minotaur => palette.minotaur,
+        }
+    }
+}
+
+impl ForegroundTile {
+    fn sprite_kind(self) -> SpriteKind {
+        match self {
+            ForegroundTile::Stack => SpriteKind::Stack,
+            ForegroundTile::Robot => SpriteKind::Robot,
+            ForegroundTile::Minotaur => SpriteKind::Minotaur,
+        }
+    }
+}
+
+impl Glyph for ForegroundTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        if let Some(pair) = sprites::pair(self.sprite_kind()) {
+            return Some(pair);
+        }
+        if !palette::current().glyphs {
+            return None;
+        }
+        Some(match self {
+            ForegroundTile::Stack => ['+', ' '],
+            ForegroundTile::Robot => ['@', ' '],
+            ForegroundTile::Minotaur => ['M', ' '],
+        })
+    }
+
+    fn wide_glyph(&self) -> Option<char> {
+        sprites::wide(self.sprite_kind())
+    }
+}
+
+/// A tile of the distance field, colored by how far it is from the goal.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DistanceTile {
+    Goal,
+    /// Distance as a fraction of the largest one, from 0 (next to the goal) to 255.
+    Away(u8),
+}
+
+impl From<&DistanceTile> for ratatui::style::Color {
+    fn from(value: &DistanceTile) -> Self {
+        let t = match value {
+            DistanceTile::Goal => 0,
+            DistanceTile::Away(t) => *t as u16,
+        };
+        let palette = palette::current();
+        let (near, far) = (kitty::rgb(palette.near), kitty::rgb(palette.far));
+        let mix = |i: usize| ((near[i] as u16 * (255 - t) + far[i] as u16 * t) / 255) as u8;
+        Color::Rgb(mix(0), mix(1), mix(2))
+    }
+}
+
+impl Glyph for DistanceTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        match self {
+            DistanceTile::Goal => Some(['⚑', ' ']),
+            DistanceTile::Away(_) => None,
+        }
+    }
+
+    fn glyph_color(&self) -> Color {
+        Color::Black
+    }
+}
+
+/// What a node of a search tree is to the search, to color it by.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum SearchShade {
+    /// Reached searching from the robot.
+    #[default]
+    Start,
+    /// Reached searching from the goal.
+    Goal,
+    /// Reached by a search shown to compare the robot's with.
+    Rival,
+    /// Where a jump point search stopped to look around.
+    Jump,
+    /// On the way down of a depth first search.
+    Way,
+    /// Left out by a depth first search for its depth limit.
+    Limit,
+}
+
+/// A node of the robot's last search tree, drawn as lines to its parent and
+/// children.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SearchTile {
+    /// Which neighbors it is linked to, in the order north, east, south, west.
+    links: [bool; 4],
+    shade: SearchShade,
+}
+
+impl SearchTile {
+    fn link(&mut self, dir: Direction) {
+        self.links[dir as usize] = true;
+    }
+}
+
+impl From<&SearchTile> for ratatui::style::Color {
+    fn from(_value: &SearchTile) -> Self {
+        Color::Reset
+    }
+}
+
+impl Glyph for SearchTile {
+    fn glyph(&self) -> Option<[char; 2]> {
+        let center = match self.links {
+            [false, false, false, false] => '·',
+            [true, false, false, false] => '╵',
+            [false, true, false, false] => '╶',
+            [false, false, true, false] => '╷',
+            [false, false, false, true] => '╴',
+            [true, false, true, false] => '│',
+            [false, true, false, true] => '─',
+            [true, true, false, false] => '└',
+            [false, true, true, false] => '┌',
+            [false, false, true, true] => '┐',
+            [true, false, false, true] => '┘',
+            [true, true, true, false] => '├',
+            [false, true, true, true] => '┬',
+            [true, false, true, true] => '┤',
+            [true, true, false, true] => '┴',
+            [true, true, true, true] => '┼',
+        };
+        let east = if self.links[Direction::E as usize] {
+            '─'
+        } else {
+            ' '
+        };
+        Some([center, east])
+    }
+
+    fn glyph_color(&self) -> Color {
+        let palette = palette::current();
+        match self.shade {
+            SearchShade::Start => palette.search,
+            SearchShade::Goal | SearchShade::Rival => palette.search_back,
+            SearchShade::Jump => palette.star,
+            SearchShade::Way => palette.stack,
+            SearchShade::Limit => palette.highlight,
+        }
+    }
+
+    fn see_through(&self) -> bool {
+        true
+    }
+}
+
+/// What an open dialog asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Prompt {
+    Quit,
+    /// Where to export the maze to.
+    ExportPath,
+    /// The maze size for the menu.
+    Size,
+    /// The theme file for the settings.
+    ThemePath,
+    /// Nothing, the dialog only tells about something.
+    Notice,
+}
+
+/// A message shown in the status bar until the next key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StatusMessage {
+    Info(String),
+    Error(String),
+}
+
+/// The screen the app is currently showing.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Screen {
+    Menu,
+    Running,
+    Paused,
+    Finished,
+    Leaderboard,
+    Evolution,
+    Batch,
+    Gallery,
+    Settings,
+}
+
+#[derive(Debug)]
+pub struct App {
+    exit: bool,
+    screen: Screen,
+    menu: Menu,
+    settings_form: SettingsForm,
+    /// Time between two ticks while the simulation is running.
+    tick_interval: Duration,
+    config: RunConfig,
+    leaderboard: Leaderboard,
+    daily_history: DailyHistory,
+    /// The day whose puzzle is being run, if it is a daily puzzle.
+    daily: Option<u64>,
+    rng: StdRng,
+    last_tick: Instant,
+    layer_bg: TileMap<BackgroundTile>,
+    layer_terrain: AlphaTileMap<TerrainTile>,
+    layer_visited: AlphaTileMap<VisitedTile>,
+    layer_items: AlphaTileMap<ItemTile>,
+    layer_fg: AlphaTileMap<ForegroundTile>,
+    /// Distance of every tile from the goal, shown instead of the terrain and
+    /// visited layers while `show_distances` is set.
+    layer_distance: AlphaTileMap<DistanceTile>,
+    /// The reachable tile farthest from the start, or `None` in infinite mode.
+    goal: Option<Pos>,
+    /// The tree of the robot's last path search, shown while `show_search`
+    /// is set.
+    layer_search: AlphaTileMap<SearchTile>,
+    /// Which robots of the swarm know each tile was explored, bit 0 standing
+    /// for the main robot and bit `i + 1` for helper `i`.
+    layer_known: TileMap<u8>,
+    /// Where the robot starts, and which way it faces there.
+    entrance: Pos,
+    entrance_dir: Direction,
+    /// The opening in the outer wall the robot is to get out through, if
+    /// the maze has one.
+    maze_exit: Option<Pos>,
+    robot_pos: Pos,
+    robot_dir: Direction,
+    robot_stack: Vec<Pos>,
+    /// The tiles the robot remembers being on, relative to the entrance,
+    /// which isn't in any particular corner, so it grows every way.
+    robot_memory: SignedTileMap<bool>,
+    /// The tiles of `robot_memory` the current tick changed, as they were
+    /// before.
+    memory_undo: Vec<((i32, i32), bool)>,
+    /// Keys the robot has picked up so far.
+    robot_keys: Vec<KeyColor>,
+    /// Path the robot follows before resuming its exploration.
+    robot_plan: VecDeque<Pos>,
+    /// Ticks left until the robot has crossed the terrain it stepped onto,
+    /// or has finished turning.
+    robot_busy: u32,
+    /// Half ticks of turning not yet spent, as only whole ticks can be.
+    turn_debt: u32,
+    minotaur: Option<Pos>,
+    /// Where the minotaur came from, so a wandering one doesn't turn back.
+    minotaur_prev: Option<Pos>,
+    /// Where a chasing minotaur last saw the robot.
+    minotaur_target: Option<Pos>,
+    /// Set when the minotaur caught the robot, which ends the run.
+    caught: bool,
+    stats: Shared<RunStats>,
+    /// Set in infinite mode, where the maze is streamed in chunk by chunk.
+    world: Option<ChunkedMaze>,
+    /// Tile shown in the top left corner of the map area.
+    camera: (u16, u16),
+    /// Tiles the camera is moved away from what it follows, with the map
+    /// pane focused.
+    pan: (isize, isize),
+    show_minimap: bool,
+    show_distances: bool,
+    /// Warmer or colder, from the last move of the robot.
+    hot_cold: Shared<HotCold>,
+    show_hint: bool,
+    show_search: bool,
+    /// Labels left on tiles with the `note` command, by solvers and by
+    /// hooks.
+    notes: Annotations,
+    /// The labels the current tick replaced, to put back on rewinding it.
+    notes_undo: Vec<((u16, u16), Option<String>)>,
+    /// What the tiles are labelled with while the annotations are shown.
+    annotation_source: annotations::Source,
+    show_annotations: bool,
+    /// Color the visited tiles by how often the robot entered them.
+    show_revisits: bool,
+    /// Entry of `robot_stack` selected in the side panel, counted from the top.
+    stack_selected: Option<usize>,
+    /// First entry of `robot_stack` shown in the side panel, counted from the top.
+    stack_scroll: usize,
+    /// Where the map was drawn last, or will be after a resize.
+    map_area: Rect,
+    /// Show the whole maze scaled down instead of following the robot.
+    zoomed_out: bool,
+    /// Draw the map as an image with the kitty graphics protocol.
+    graphics: bool,
+    /// The image last shown and where, to skip sending it again unchanged.
+    graphics_frame: Option<(Rect, kitty::Image)>,
+    /// Where simulation events are written to, if anywhere.
+    /// Subscribers to the events of the run other than the app itself, e.g.
+    /// the event stream.
+    bus: EventBus,
+    /// Where the rendered frames are written to as a cast, if anywhere.
+    cast: Option<Cast>,
+    /// Records the map every tick to save it as a GIF when the run ends.
+    recorder: Option<Recorder>,
+    /// Which palette is in use, see `palette::current`.
+    palette: PaletteName,
+    /// What tiles are drawn with besides colors, see `sprites::current`.
+    sprites: SpriteSet,
+    /// The theme file that overrides the palette and sprites while it
+    /// changes, if any.
+    theme: Option<ThemeFile>,
+    /// The light around the robot, off unless it has a radius.
+    light: Light,
+    /// Draw with ASCII characters and the 16 basic colors only.
+    ascii: bool,
+    timings: FrameTimings,
+    show_timings: bool,
+    scoring: Scoring,
+    /// How the robot rates the tiles it could explore next.
+    weights: Weights,
+    show_weights: bool,
+    /// What the robot decided lately and why, for the thoughts pane.
+    thoughts: Thoughts,
+    show_thoughts: bool,
+    /// The latest lines of the log, for the log pane.
+    log_pane: LogPane,
+    /// Which panes the run screen has, and where.
+    layout: PaneLayout,
+    /// The pane resized by the layout keys.
+    focus: Pane,
+    show_view: bool,
+    /// The weight the weights pane changes.
+    weight_field: weights::Field,
+    /// Save the next drawn frame to a file.
+    take_screenshot: bool,
+    show_help: bool,
+    /// What has been typed into the command line while it is open.
+    command_line: Option<String>,
+    status_message: Option<StatusMessage>,
+    /// The dialog over everything else and what it asks for, if any.
+    dialog: Option<(Prompt, Dialog)>,
+    toasts: Shared<Toasts>,
+    /// The tile the robot last bumped into, and when, to flash it.
+    bump_flash: Option<(Pos, Instant)>,
+    /// Where the bidirectional searches met, and when, to flash it.
+    meeting_flash: Option<(Pos, Instant)>,
+    /// Recorded key sequences, to be played back.
+    macros: Macros,
+    /// Digits typed before `→`, stepping that many ticks.
+    step_count: Option<u64>,
+    /// Ticks left to step, taken a frame's worth at a time while paused.
+    steps_remaining: u64,
+    /// Where control requests come from, if the app is controlled.
+    control: Option<Control>,
+    #[cfg(feature = "http")]
+    http: Option<http::StatusServer>,
+    /// Called around every tick, see `run_hooks`.
+    hooks: Hooks,
+    /// What happened during the current tick, e.g. to tell about long
+    /// backtracks only.
+    tracker: Shared<TickTracker>,
+    /// How often the robot entered each tile, for the revisit heatmap.
+    visits: Shared<Visits>,
+    /// The tick a replay pauses at, where it was saved.
+    replay_end: Option<u64>,
+    /// The replay playing, if the run is one, to seek in by playing it again
+    /// from the start.
+    playing: Option<Replay>,
+    /// `replay::maze_hash` of the maze the run started on.
+    maze_hash: u64,
+    /// What the maze of the run is like, unless it is infinite.
+    maze_shape: Option<maze::Shape>,
+    /// How to undo the most recent ticks, newest last.
+    history: History<TickDelta>,
+    /// The layers as they were after the last tick in `history`, or `None`
+    /// in infinite mode and on mazes of more than `HISTORY_MAX_TILES`, where
+    /// ticks can't be undone.
+    shadow: Option<Layers>,
+    /// The latest tick the run has reached, even if it was rewound since.
+    furthest_tick: u64,
+    /// Whether the app runs without a screen, for a batch or an evolution,
+    /// keeping no history and saving nothing of its runs.
+    headless: bool,
+    /// Where the timeline was drawn last, to seek by clicking on it.
+    timeline_area: Option<Rect>,
+    breakpoints: Vec<Breakpoint>,
+    /// The breakpoint hit during the current tick, if any.
+    hit_breakpoint: Option<Breakpoint>,
+    /// Watches, each with whether it held after the last tick, so that it
+    /// pauses only when it starts to hold.
+    watches: Vec<(Watch, bool)>,
+    /// The evolution of robot weights shown on the evolution screen.
+    evolution: Option<EvolutionView>,
+    /// The runs shown on the batch screen.
+    batch: Option<BatchView>,
+    /// The saved mazes and replays shown on the gallery screen.
+    gallery: Option<Gallery>,
+    /// A maze loaded from the gallery or read from stdin, run on instead of
+    /// generating one.
+    fixed_maze: Option<maze::Maze>,
+    /// What the robot learned so far, if it is learning its way to the goal.
+    qtable: Option<QTable>,
+    episodes: Episodes,
+    /// The value of `qtable` the current tick changed, as it was before.
+    q_undo: Option<qlearn::Undo>,
+    /// The ants looking for the goal, if the robot waits for them.
+    colony: Option<Colony>,
+    /// The state of `colony` before the current tick changed it.
+    ants_undo: Option<ants::Undo>,
+    /// The searches from the robot and the goal, if the robot waits for
+    /// them to meet.
+    bidirectional: Option<Bidirectional>,
+    /// The state of `bidirectional` before the current tick changed it.
+    bidirectional_undo: Option<bidirectional::Undo>,
+    /// The deepening search from the robot, if the robot waits for it to
+    /// find the goal.
+    deepening: Option<Deepening>,
+    /// The state of `deepening` before the current tick changed it.
+    deepening_undo: Option<iddfs::Undo>,
+    /// The jump point search and A* search being replayed, if the robot
+    /// waits for them.
+    race: Option<Race>,
+    /// Tiles of each search of `race` replayed so far.
+    race_shown: usize,
+    /// The other robots of the swarm, if the robot isn't alone.
+    helpers: Vec<Helper>,
+    /// The goals the robot visits, if it is on a tour.
+    tour: Option<Tour>,
+    /// Index of the next stop of `tour`.
+    tour_next: usize,
+    /// The checkpoints of a timed run, if it has any.
+    course: Option<Course>,
+    /// Where the robot went in this run so far, unless it is infinite.
+    track: Option<Track>,
+    /// The cells the robot was on last, shown instead of all it visited
+    /// while `trail_length` isn't 0.
+    trail: Trail,
+    trail_length: usize,
+    /// The track of an earlier run on the same maze, shown as a ghost.
+    ghost: Option<Track>,
+    /// The random walk that generated the maze, while it is played back
+    /// before the run starts.
+    walk: Option<Walk>,
+}
+
+/// Everything a tick can change, other than the layers.
+#[derive(Debug, Clone)]
+struct SimState {
+    rng: StdRng,
+    goal: Option<Pos>,
+    robot_pos: Pos,
+    robot_dir: Direction,
+    robot_keys: Vec<KeyColor>,
+    robot_plan: VecDeque<Pos>,
+    robot_busy: u32,
+    turn_debt: u32,
+    minotaur: Option<Pos>,
+    minotaur_prev: Option<Pos>,
+    minotaur_target: Option<Pos>,
+    caught: bool,
+    stats: RunStats,
+    episodes: Episodes,
+    tour_next: usize,
+    race_shown: usize,
+    course: Option<Course>,
+    trail: Trail,
+    helpers: Vec<Helper>,
+    tracker: TickTracker,
+    hot_cold: HotCold,
+}
+
+/// A copy of all layers and of the robot's stack.
+#[derive(Debug, Clone)]
+struct Layers {
+    bg: TileMap<BackgroundTile>,
+    terrain: AlphaTileMap<TerrainTile>,
+    visited: AlphaTileMap<VisitedTile>,
+    items: AlphaTileMap<ItemTile>,
+    fg: AlphaTileMap<ForegroundTile>,
+    distance: AlphaTileMap<DistanceTile>,
+    search: AlphaTileMap<SearchTile>,
+    known: TileMap<u8>,
+    stack: Vec<Pos>,
+}
+
+/// The state before a tick and the tiles it changed, to undo it.
+#[derive(Debug)]
+struct TickDelta {
+    state: SimState,
+    bg: LayerDelta<BackgroundTile>,
+    terrain: LayerDelta<Option<TerrainTile>>,
+    visited: LayerDelta<Option<VisitedTile>>,
+    items: LayerDelta<Option<ItemTile>>,
+    fg: LayerDelta<Option<ForegroundTile>>,
+    distance: LayerDelta<Option<DistanceTile>>,
+    search: LayerDelta<Option<SearchTile>>,
+    known: LayerDelta<u8>,
+    stack: StackDelta<Pos>,
+    memory: Vec<((i32, i32), bool)>,
+    notes: Vec<((u16, u16), Option<String>)>,
+    visits: Vec<(u16, u16)>,
+    q: Option<qlearn::Undo>,
+    ants: Option<ants::Undo>,
+    bidirectional: Option<bidirectional::Undo>,
+    deepening: Option<iddfs::Undo>,
+}
+
+/// Id of the map image in the kitty graphics protocol.
+const GRAPHICS_ID: u32 = 1;
+/// Pixels per side of a tile in the map image.
+const GRAPHICS_BLOCK: u32 = 8;
+/// Episodes the learning robot runs before the run ends.
+const EPISODES: u32 = 100;
+/// An episode ends if it didn't reach the goal after this many steps per
+/// reachable tile.
+const EPISODE_STEPS_PER_TILE: u32 = 10;
+/// Pheromone is shown on tiles with more than this share of the most on
+/// any tile.
+const PHEROMONE_VISIBLE: f64 = 0.02;
+/// Ticks that can be undone.
+const HISTORY_TICKS: usize = 1000;
+/// Tiles of the largest maze whose ticks can be undone. Each tick is
+/// compared against a copy of every layer, which gets too slow beyond it.
+const HISTORY_MAX_TILES: usize = 256 * 256;
+/// Ticks skipped at once on the timeline.
+const TIMELINE_PAGE: u64 = 50;
+/// How bright the oldest cell of a limited trail is.
+const TRAIL_END_BRIGHTNESS: f64 = 0.3;
+/// Tiles the camera moves at once when panning the map.
+const PAN_STEP: isize = 4;
+/// How long a tile the robot bumped into flashes red.
+const BUMP_FLASH: Duration = Duration::from_millis(300);
+/// How long the tile the bidirectional searches met on blinks.
+const MEETING_FLASH: Duration = Duration::from_millis(1500);
+/// Time the meeting tile is lit or dark while it blinks.
+const MEETING_BLINK: Duration = Duration::from_millis(250);
+/// Time between two ticks unless the settings say otherwise.
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+/// Time per frame spent stepping, so many steps don't hold up the app.
+const STEP_BUDGET: Duration = Duration::from_millis(40);
+/// Cells per side of a chunk in infinite mode.
+const CHUNK_CELLS: usize = 8;
+/// New chunks are loaded once the robot gets this close to the edge of the loaded region.
+const STREAM_MARGIN: usize = CHUNK_CELLS;
+/// The minotaur moves once every this many ticks.
+const MINOTAUR_PERIOD: u64 = 2;
+/// How far the minotaur can see.
+const MINOTAUR_SIGHT: u16 = 8;
+/// The minotaur starts at least this many steps (ignoring walls) away from the robot.
+const MINOTAUR_MIN_DISTANCE: usize = 10;
+
+/// Tiles reachable from `start` through tiles for which `passable` holds,
+/// respecting the direction of one-way passages.
+fn reachable_tiles(
+    map: &TileMap<BackgroundTile>,
+    start: Pos,
+    passable: impl Fn(&BackgroundTile) -> bool,
+) -> TileMap<bool> {
+    let grid = pathfinding::grid_steps(map, passable);
+    let cost = pathfinding::distances((map.width(), map.height()), start.into(), |pos| {
+        map_steps(map, pos, &grid)
+    });
+    let mut seen = TileMap::<bool>::with_default(map.width(), map.height());
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            // stepping into a teleporter counts as reaching it
+            seen[(x, y)] = cost[(x, y)].is_some()
+                || matches!(map[(x, y)], BackgroundTile::Teleporter(twin) if cost[twin].is_some());
+        }
+    }
+    seen
+}
+
+/// The moves `grid` allows from `pos`, minus those against one-way passages,
+/// and ending on the twin of any teleporter stepped into.
+fn map_steps(
+    map: &TileMap<BackgroundTile>,
+    pos: Point,
+    grid: impl Fn(Point) -> Vec<Step>,
+) -> Vec<Step> {
+    grid(pos)
+        .into_iter()
+        .filter(|step| !one_way_blocks(map, pos.into(), step.tile.into()))
+        .map(|step| match map[step.tile] {
+            BackgroundTile::Teleporter(twin) => Step { to: twin, ..step },
+            _ => step,
+        })
+        .collect()
+}
+
+/// Number of tiles reachable from `start` when all doors are open, including `start` itself.
+fn count_reachable(map: &TileMap<BackgroundTile>, start: Pos) -> usize {
+    let seen = reachable_tiles(map, start, |tile| !matches!(tile, BackgroundTile::Wall));
+    let mut count = 0;
+    for y in 0..seen.height() {
+        for x in 0..seen.width() {
+            if seen[(x, y)] {
+                count += 1;
+            }
+        }
+    }
+    count
+}
+
+/// Whether a one-way passage at `from` or `to` forbids moving between the two.
+fn one_way_blocks(map: &TileMap<BackgroundTile>, from: Pos, to: Pos) -> bool {
+    let Some(dir) = from.direction_to(to) else {
+        return false;
+    };
+    [from, to].into_iter().any(
+        |pos| matches!(map[pos.into()], BackgroundTile::OneWay(arrow) if arrow == dir.opposite()),
+    )
 }
 
-impl From<&VisitedTile> for ratatui::style::Color {
-    fn from(_value: &VisitedTile) -> Self {
-        Color::Blue
+fn blit_maze(map: &mut TileMap<BackgroundTile>, maze: &maze::Maze, origin: Pos) {
+    for (y, line) in maze.tiles.iter().enumerate() {
+        for (x, tile) in line.iter().enumerate() {
+            map[Pos::new(origin.x + x, origin.y + y).into()] = match tile {
+                maze::Tile::Free => BackgroundTile::Free,
+                maze::Tile::Wall => BackgroundTile::Wall,
+            };
+        }
+    }
+}
+
+impl App {
+    fn new() -> Self {
+        let leaderboard = Leaderboard::load(LEADERBOARD_PATH).unwrap_or_else(|e| {
+            debug_println!("could not load leaderboard: {}", e);
+            Leaderboard::new(LEADERBOARD_PATH)
+        });
+        let daily_history = DailyHistory::load(DAILY_PATH).unwrap_or_else(|e| {
+            debug_println!("could not load daily history: {}", e);
+            DailyHistory::new(DAILY_PATH)
+        });
+        Self::with_files(leaderboard, daily_history)
+    }
+
+    /// An app on the menu with the leaderboard and daily history as given,
+    /// instead of read from their files.
+    fn with_files(leaderboard: Leaderboard, daily_history: DailyHistory) -> Self {
+        let menu = Menu::default();
+        let mut app = Self {
+            exit: false,
+            screen: Screen::Menu,
+            config: menu.config,
+            rng: StdRng::seed_from_u64(menu.config.seed),
+            menu,
+            settings_form: SettingsForm::default(),
+            tick_interval: TICK_INTERVAL,
+            leaderboard,
+            daily_history,
+            daily: None,
+            last_tick: Instant::now(),
+            layer_bg: TileMap::with_default(1, 1),
+            layer_terrain: AlphaTileMap::empty(1, 1),
+            layer_visited: AlphaTileMap::empty(1, 1),
+            layer_items: AlphaTileMap::empty(1, 1),
+            layer_fg: AlphaTileMap::empty(1, 1),
+            layer_distance: AlphaTileMap::empty(1, 1),
+            goal: None,
+            layer_search: AlphaTileMap::empty(1, 1),
+            layer_known: TileMap::with_default(1, 1),
+            entrance: Pos::new(1, 1),
+            entrance_dir: Direction::E,
+            maze_exit: None,
+            robot_pos: Pos::new(1, 1),
+            robot_dir: Direction::E,
+            robot_stack: Vec::new(),
+            robot_memory: SignedTileMap::with_default((0, 0), 1, 1),
+            memory_undo: Vec::new(),
+            robot_keys: Vec::new(),
+            robot_plan: VecDeque::new(),
+            robot_busy: 0,
+            turn_debt: 0,
+            minotaur: None,
+            minotaur_prev: None,
+            minotaur_target: None,
+            caught: false,
+            stats: Shared::new(RunStats::new(0)),
+            world: None,
+            camera: (0, 0),
+            pan: (0, 0),
+            show_minimap: false,
+            show_distances: false,
+            hot_cold: Shared::default(),
+            show_hint: false,
+            show_search: false,
+            notes: Annotations::default(),
+            notes_undo: Vec::new(),
+            annotation_source: annotations::Source::default(),
+            show_annotations: false,
+            show_revisits: false,
+            stack_selected: None,
+            stack_scroll: 0,
+            map_area: Rect::default(),
+            zoomed_out: false,
+            graphics: false,
+            graphics_frame: None,
+            bus: EventBus::default(),
+            cast: None,
+            recorder: None,
+            palette: PaletteName::default(),
+            sprites: SpriteSet::default(),
+            theme: None,
+            light: Light::default(),
+            ascii: false,
+            timings: FrameTimings::default(),
+            show_timings: false,
+            scoring: Scoring::default(),
+            weights: Weights::default(),
+            show_weights: false,
+            thoughts: Thoughts::default(),
+            log_pane: LogPane::default(),
+            show_thoughts: false,
+            layout: PaneLayout::default(),
+            focus: Pane::Stack,
+            show_view: false,
+            weight_field: weights::Field::Heuristic,
+            take_screenshot: false,
+            show_help: false,
+            command_line: None,
+            status_message: None,
+            dialog: None,
+            toasts: Shared::default(),
+            bump_flash: None,
+            meeting_flash: None,
+            macros: Macros::default(),
+            step_count: None,
+            steps_remaining: 0,
+            control: None,
+            #[cfg(feature = "http")]
+            http: None,
+            hooks: Hooks::default(),
+            tracker: Shared::default(),
+            visits: Shared::default(),
+            replay_end: None,
+            playing: None,
+            maze_hash: 0,
+            maze_shape: None,
+            history: History::new(HISTORY_TICKS),
+            shadow: None,
+            furthest_tick: 0,
+            headless: false,
+            timeline_area: None,
+            breakpoints: Vec::new(),
+            hit_breakpoint: None,
+            watches: Vec::new(),
+            evolution: None,
+            batch: None,
+            gallery: None,
+            fixed_maze: None,
+            qtable: None,
+            episodes: Episodes::default(),
+            q_undo: None,
+            colony: None,
+            ants_undo: None,
+            bidirectional: None,
+            bidirectional_undo: None,
+            deepening: None,
+            deepening_undo: None,
+            race: None,
+            race_shown: 0,
+            helpers: Vec::new(),
+            tour: None,
+            tour_next: 0,
+            course: None,
+            track: None,
+            trail: Trail::default(),
+            trail_length: 0,
+            ghost: None,
+            walk: None,
+        };
+        app.bus = app.default_bus();
+        app
+    }
+
+    /// Start a fresh run from `self.config`.
+    fn reinit(&mut self) {
+        debug_println!("starting run: {:?}", self.config);
+        self.playing = None;
+        self.thoughts.clear();
+        // the labels were about the last maze
+        self.notes.clear();
+        self.notes_undo.clear();
+        self.walk = None;
+        self.rng = StdRng::seed_from_u64(self.config.seed);
+        self.world = self
+            .config
+            .infinite
+            .then(|| ChunkedMaze::new(self.config.seed, CHUNK_CELLS));
+        let maze = if self.world.is_some() {
+            None
+        } else {
+            if self.config.fit {
+                (self.config.width, self.config.height) = fit_size(self.map_area);
+            }
+            Some(match &self.fixed_maze {
+                Some(maze) => {
+                    self.config.fit = false;
+                    self.config.width = maze.tiles[0].len() / 2;
+                    self.config.height = maze.tiles.len() / 2;
+                    maze.clone()
+                }
+                // the walk is slow enough to be worth watching, and the run
+                // starts once it is done
+                None if self.config.generator == maze::Generator::AldousBroder => {
+                    self.start_walk();
+                    return;
+                }
+                None => {
+                    let (w, h) = (self.config.width, self.config.height);
+                    match self.config.generator.generate(w, h, &mut self.rng) {
+                        Ok(maze) => maze,
+                        // the run goes on, on a maze the generator doesn't
+                        // decide
+                        Err(e) => {
+                            debug_println!("could not generate the maze: {}", e);
+                            self.status_message = Some(StatusMessage::Error(format!(
+                                "{}, running on a kruskal maze instead",
+                                e
+                            )));
+                            maze::Maze::kruskal(w, h, &mut self.rng)
+                        }
+                    }
+                }
+            })
+        };
+        self.start_on(maze);
+    }
+
+    /// Set every layer to an empty `width` x `height` one.
+    fn clear_layers(&mut self, width: u16, height: u16) {
+        self.layer_bg = TileMap::with_default(width, height);
+        self.layer_terrain = AlphaTileMap::empty(width, height);
+        self.layer_visited = AlphaTileMap::empty(width, height);
+        self.layer_items = AlphaTileMap::empty(width, height);
+        self.layer_fg = AlphaTileMap::empty(width, height);
+        self.layer_distance = AlphaTileMap::empty(width, height);
+        self.layer_search = AlphaTileMap::empty(width, height);
+        self.layer_known = TileMap::with_default(width, height);
+    }
+
+    /// Watch the walk generating the maze before the run starts on it. Till
+    /// then the maze is all walls, carved as the walk goes, and nothing of
+    /// the last run is left to show or rewind.
+    fn start_walk(&mut self) {
+        let (w, h) = (self.config.width, self.config.height);
+        self.clear_layers(2 * w as u16 + 1, 2 * h as u16 + 1);
+        for y in 0..self.layer_bg.height() {
+            for x in 0..self.layer_bg.width() {
+                self.layer_bg[(x, y)] = BackgroundTile::Wall;
+            }
+        }
+        // the walk draws from the run's random numbers, and gives them back
+        let rng = std::mem::replace(&mut self.rng, StdRng::seed_from_u64(self.config.seed));
+        let walk = Walk::new(w, h, rng, &mut self.layer_bg);
+        self.robot_pos = walk.pos().into();
+        self.robot_stack.clear();
+        self.stack_selected = None;
+        self.stats.replace(RunStats::new(0));
+        self.history.clear();
+        self.shadow = None;
+        self.walk = Some(walk);
+        self.camera = (0, 0);
+        self.pan = (0, 0);
+        self.screen = Screen::Running;
+        self.last_tick = Instant::now();
+    }
+
+    /// Start the run on `maze`, or on the infinite world if there is none.
+    fn start_on(&mut self, maze: Option<maze::Maze>) {
+        match maze {
+            None => {
+                self.maze_shape = None;
+                self.clear_layers(1, 1);
+                self.load_chunks(2, 2);
+            }
+            Some(maze) => {
+                self.maze_shape = Some(maze.shape());
+                let (pw, ph) = (maze.tiles[0].len(), maze.tiles.len());
+                self.clear_layers(pw as u16, ph as u16);
+                blit_maze(&mut self.layer_bg, &maze, Pos::new(0, 0));
+            }
+        }
+        self.carve_openings();
+        self.maze_hash = maze_hash(&self.maze_text());
+        // the last run on this maze races the new one
+        let last = self.track.take();
+        if let Some(last) = last.filter(|last| last.maze_hash == self.maze_hash && last.ticks() > 0)
+        {
+            self.ghost = Some(last);
+        } else if self
+            .ghost
+            .as_ref()
+            .is_some_and(|ghost| ghost.maze_hash != self.maze_hash)
+        {
+            self.ghost = None;
+        }
+        if self.world.is_none() {
+            self.track = Some(Track::new(
+                self.maze_hash,
+                self.config.solver.to_string(),
+                self.entrance.into(),
+            ));
+        }
+        self.layer_visited[self.entrance.into()] = Some(VisitedTile::Visited);
+        self.trail.clear();
+        self.trail.visit(self.entrance.into());
+        self.layer_fg[self.entrance.into()] = Some(ForegroundTile::Robot);
+        self.robot_pos = self.entrance;
+        self.robot_dir = self.entrance_dir;
+        self.robot_stack = Vec::new();
+        self.robot_memory = SignedTileMap::with_default((0, 0), 1, 1);
+        self.robot_memory[(0, 0)] = true;
+        self.memory_undo = Vec::new();
+        self.robot_keys = Vec::new();
+        self.robot_plan = VecDeque::new();
+        self.robot_busy = 0;
+        self.turn_debt = 0;
+        self.bump_flash = None;
+        self.meeting_flash = None;
+        self.minotaur = None;
+        self.minotaur_prev = None;
+        self.minotaur_target = None;
+        self.caught = false;
+        self.goal = None;
+        self.tour = None;
+        self.tour_next = 0;
+        self.course = None;
+        self.helpers = if self.config.solver == Solver::Swarm && self.world.is_none() {
+            let helper = Helper::new(self.robot_pos, self.robot_dir, self.config.helper_chassis);
+            vec![helper; self.config.robots - 1]
+        } else {
+            Vec::new()
+        };
+        // all robots of the swarm start out knowing the entrance
+        self.layer_known[self.entrance.into()] = ((1u16 << (self.helpers.len() + 1)) - 1) as u8;
+        let reachable = if self.world.is_some() {
+            0
+        } else {
+            count_reachable(&self.layer_bg, self.robot_pos)
+        };
+        self.stats.replace(RunStats::new(reachable));
+        if self.world.is_none() {
+            self.place_teleporters(self.config.teleporters);
+            self.place_doors(self.config.doors);
+            self.place_one_ways(self.config.one_ways);
+            self.place_terrain(self.config.terrain);
+            self.place_items(self.config.items);
+            self.place_minotaur();
+            self.place_checkpoints(self.config.checkpoints);
+            if self.config.solver == Solver::Tour {
+                self.place_tour(self.config.goals);
+            } else {
+                self.place_goal();
+            }
+        }
+        self.update_distance_field();
+        self.layer_search = AlphaTileMap::empty(self.layer_bg.width(), self.layer_bg.height());
+        self.stack_selected = None;
+        self.stack_scroll = 0;
+        self.pick_zoom(self.map_area);
+        self.emit(SimEvent::Start {
+            seed: self.config.seed,
+            width: self.layer_bg.width(),
+            height: self.layer_bg.height(),
+        });
+        self.visits.borrow_mut().enter(self.entrance.into());
+        if let Some(recorder) = &mut self.recorder {
+            recorder.reset(
+                self.layer_bg.width(),
+                self.layer_bg.height(),
+                self.tick_interval,
+            );
+        }
+        self.record_frame();
+        self.qtable = (self.config.solver == Solver::QLearning && self.goal.is_some())
+            .then(|| QTable::new(self.layer_bg.width(), self.layer_bg.height()));
+        self.episodes = Episodes::default();
+        self.q_undo = None;
+        self.colony = (self.config.solver == Solver::Ants && self.goal.is_some())
+            .then(|| Colony::new(self.layer_bg.width(), self.layer_bg.height()));
+        self.ants_undo = None;
+        self.bidirectional = (self.config.solver == Solver::Bidirectional)
+            .then_some(self.goal)
+            .flatten()
+            .map(|goal| {
+                Bidirectional::new(
+                    self.layer_bg.width(),
+                    self.layer_bg.height(),
+                    self.robot_pos.into(),
+                    goal.into(),
+                )
+            });
+        self.bidirectional_undo = None;
+        self.deepening = (self.config.solver == Solver::Deepening)
+            .then_some(self.goal)
+            .flatten()
+            .map(|goal| Deepening::new(self.robot_pos.into(), goal.into()));
+        self.deepening_undo = None;
+        self.race = (self.config.solver == Solver::JumpPoint)
+            .then_some(self.goal)
+            .flatten()
+            .map(|goal| {
+                let size = (self.layer_bg.width(), self.layer_bg.height());
+                // jumps only work where every move costs the same, so the
+                // race is on plain free tiles
+                Race::new(size, self.robot_pos.into(), goal.into(), |pos| {
+                    self.layer_bg[pos] == BackgroundTile::Free
+                })
+            });
+        self.race_shown = 0;
+        self.history.clear();
+        self.furthest_tick = 0;
+        for (_, held) in &mut self.watches {
+            *held = false;
+        }
+        let tiles = self.layer_bg.width() as usize * self.layer_bg.height() as usize;
+        self.shadow = (!self.headless && self.world.is_none() && tiles <= HISTORY_MAX_TILES)
+            .then(|| self.layers());
+        self.camera = (0, 0);
+        self.pan = (0, 0);
+        self.screen = Screen::Running;
+        self.last_tick = Instant::now();
+    }
+
+    /// Free tiles without an item, other than the robot's.
+    fn empty_tiles(&self) -> Vec<(u16, u16)> {
+        let mut free = Vec::new();
+        // the openings in the outer wall are left alone
+        for y in 1..self.layer_bg.height().saturating_sub(1) {
+            for x in 1..self.layer_bg.width().saturating_sub(1) {
+                if let BackgroundTile::Free = self.layer_bg[(x, y)] {
+                    if Pos::new(x as usize, y as usize) != self.robot_pos
+                        && self.layer_items[(x, y)].is_none()
+                    {
+                        free.push((x, y));
+                    }
+                }
+            }
+        }
+        free
+    }
+
+    /// Carve the entrance and the exit into the outer wall of a fixed size
+    /// maze, as `config.openings` says. Without an entrance, the robot
+    /// starts in the top left cell.
+    fn carve_openings(&mut self) {
+        self.entrance = Pos::new(1, 1);
+        self.entrance_dir = Direction::E;
+        self.maze_exit = None;
+        if self.world.is_some() {
+            return;
+        }
+        let (entrance, exit) = match self.config.openings {
+            Openings::Closed => return,
+            Openings::Corners => {
+                let entrance = self.wall_gaps(Direction::W).first().copied();
+                let exit = self.wall_gaps(Direction::E).last().copied();
+                (entrance.map(|gap| (gap, Direction::W)), exit)
+            }
+            Openings::Sides => {
+                let entrance = self.wall_gaps(Direction::W).choose(&mut self.rng).copied();
+                let exit = self.wall_gaps(Direction::E).choose(&mut self.rng).copied();
+                (entrance.map(|gap| (gap, Direction::W)), exit)
+            }
+            Openings::Random => {
+                let mut sides = [Direction::N, Direction::E, Direction::S, Direction::W];
+                sides.shuffle(&mut self.rng);
+                let entrance = self.wall_gaps(sides[0]).choose(&mut self.rng).copied();
+                let exit = self.wall_gaps(sides[1]).choose(&mut self.rng).copied();
+                (entrance.map(|gap| (gap, sides[0])), exit)
+            }
+        };
+        if let Some((gap, side)) = entrance {
+            self.layer_bg[gap.into()] = BackgroundTile::Free;
+            self.entrance = gap;
+            self.entrance_dir = side.opposite();
+        }
+        if let Some(gap) = exit {
+            self.layer_bg[gap.into()] = BackgroundTile::Free;
+            self.maze_exit = Some(gap);
+        }
+        debug_println!("entrance: {}, exit: {:?}", self.entrance, self.maze_exit);
+    }
+
+    /// The tiles of the outer wall on `side`, from the top or the left,
+    /// that would open into a free tile if they were carved out.
+    fn wall_gaps(&self, side: Direction) -> Vec<Pos> {
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        // each tile of the wall with the one inside it
+        let tiles: Vec<((u16, u16), (u16, u16))> = match side {
+            Direction::N => (1..w - 1).map(|x| ((x, 0), (x, 1))).collect(),
+            Direction::S => (1..w - 1).map(|x| ((x, h - 1), (x, h - 2))).collect(),
+            Direction::W => (1..h - 1).map(|y| ((0, y), (1, y))).collect(),
+            Direction::E => (1..h - 1).map(|y| ((w - 1, y), (w - 2, y))).collect(),
+        };
+        tiles
+            .into_iter()
+            .filter(|&(_, inside)| matches!(self.layer_bg[inside], BackgroundTile::Free))
+            .map(|(wall, _)| wall.into())
+            .collect()
+    }
+
+    /// Place `count` pairs of linked teleporters, never next to each other.
+    fn place_teleporters(&mut self, count: usize) {
+        for _ in 0..count {
+            let mut candidates = self.empty_tiles();
+            candidates.retain(|&pos| {
+                [Direction::N, Direction::E, Direction::S, Direction::W]
+                    .into_iter()
+                    .filter_map(|dir| Pos::from(pos) + RelPos::new(0, -1, dir))
+                    .all(|n| !matches!(self.layer_bg[n.into()], BackgroundTile::Teleporter(_)))
+            });
+            let pair: Vec<(u16, u16)> = candidates
+                .choose_multiple(&mut self.rng, 2)
+                .copied()
+                .collect();
+            let [a, b] = pair[..] else {
+                return;
+            };
+            self.layer_bg[a] = BackgroundTile::Teleporter(b);
+            self.layer_bg[b] = BackgroundTile::Teleporter(a);
+        }
+    }
+
+    /// Place `count` locked doors, each with its key somewhere reachable
+    /// without passing any door, so every maze stays solvable.
+    fn place_doors(&mut self, count: usize) {
+        for &color in KEY_COLORS.iter().take(count) {
+            let Some(&door) = self.empty_tiles().choose(&mut self.rng) else {
+                return;
+            };
+            self.layer_bg[door] = BackgroundTile::Door(color);
+            let reachable = self.layer_bg.flood_fill(self.robot_pos.into(), |tile| {
+                matches!(tile, BackgroundTile::Free)
+            });
+            let candidates: Vec<(u16, u16)> = self
+                .empty_tiles()
+                .into_iter()
+                .filter(|pos| reachable[*pos])
+                .collect();
+            match candidates.choose(&mut self.rng) {
+                Some(&key) => self.layer_items[key] = Some(ItemTile::Key(color)),
+                // the door cut off everything, so it can't have a key
+                None => self.layer_bg[door] = BackgroundTile::Free,
+            }
+        }
+    }
+
+    /// Turn up to `count` corridor tiles into one-way passages. A passage is
+    /// only kept if the tiles behind it can still be left some other way
+    /// without passing a door, so the robot can never get trapped.
+    fn place_one_ways(&mut self, count: usize) {
+        let mut candidates: Vec<((u16, u16), Direction)> = self
+            .empty_tiles()
+            .into_iter()
+            .filter_map(|tile| Some((tile, self.corridor_axis(tile)?)))
+            .collect();
+        candidates.shuffle(&mut self.rng);
+        let mut placed = 0;
+        for (tile, axis) in candidates {
+            if placed == count {
+                break;
+            }
+            let dir = if self.rng.random_bool(0.5) {
+                axis
+            } else {
+                axis.opposite()
+            };
+            let before = (Pos::from(tile) + RelPos::new(0, 1, dir)).unwrap();
+            let after = (Pos::from(tile) + RelPos::new(0, -1, dir)).unwrap();
+            // the way back has to go around the passage
+            if self.reachable_around(tile, after, before) {
+                self.layer_bg[tile] = BackgroundTile::OneWay(dir);
+                placed += 1;
+            }
+        }
+    }
+
+    /// Grow `count` patches of random terrain from random free tiles.
+    fn place_terrain(&mut self, count: usize) {
+        let open = |app: &Self, pos: Pos| {
+            matches!(app.layer_bg[pos.into()], BackgroundTile::Free)
+                && app.layer_terrain[pos.into()].is_none()
+                && pos != app.robot_pos
+        };
+        for _ in 0..count {
+            let free: Vec<(u16, u16)> = self
+                .empty_tiles()
+                .into_iter()
+                .filter(|&tile| open(self, tile.into()))
+                .collect();
+            let Some(&start) = free.choose(&mut self.rng) else {
+                return;
+            };
+            let mut patch = vec![Pos::from(start)];
+            while patch.len() < TERRAIN_PATCH {
+                let neighbors: Vec<Pos> = patch
+                    .iter()
+                    .flat_map(|&pos| {
+                        [Direction::N, Direction::E, Direction::S, Direction::W]
+                            .map(|dir| pos + RelPos::new(0, -1, dir))
+                    })
+                    .flatten()
+                    .filter(|&pos| open(self, pos) && !patch.contains(&pos))
+                    .collect();
+                let Some(&next) = neighbors.choose(&mut self.rng) else {
+                    break;
+                };
+                patch.push(next);
+            }
+            let terrain = *TERRAIN_TILES.choose(&mut self.rng).unwrap();
+            for pos in patch {
+                self.layer_terrain[pos.into()] = Some(terrain);
+            }
+        }
+    }
+
+    /// Ticks it takes to move onto `pos`.
+    fn tile_cost(&self, pos: Pos) -> u32 {
+        self.layer_terrain[pos.into()].map_or(1, TerrainTile::cost)
+    }
+
+    /// If `tile` is in a corridor between two plain free tiles, the
+    /// direction (north or east) the corridor runs in.
+    fn corridor_axis(&self, tile: (u16, u16)) -> Option<Direction> {
+        use BackgroundTile::{Free, Wall};
+        let neighbors = [Direction::N, Direction::E, Direction::S, Direction::W]
+            .map(|dir| Pos::from(tile) + RelPos::new(0, -1, dir))
+            .map(|pos| pos.map(|pos| self.layer_bg[pos.into()]));
+        match neighbors {
+            [Some(Free), Some(Wall), Some(Free), Some(Wall)] => Some(Direction::N),
+            [Some(Wall), Some(Free), Some(Wall), Some(Free)] => Some(Direction::E),
+            _ => None,
+        }
+    }
+
+    /// Whether `to` can be reached from `from` without passing `tile` or
+    /// any door.
+    fn reachable_around(&mut self, tile: (u16, u16), from: Pos, to: Pos) -> bool {
+        let saved = self.layer_bg[tile];
+        self.layer_bg[tile] = BackgroundTile::Wall;
+        let found = {
+            let map = &self.layer_bg;
+            let grid = pathfinding::grid_steps(map, |tile| {
+                !matches!(tile, BackgroundTile::Wall | BackgroundTile::Door(_))
+            });
+            pathfinding::dfs(
+                (map.width(), map.height()),
+                from.into(),
+                |pos| map_steps(map, pos, &grid),
+                |pos| pos == to.into(),
+            )
+            .is_some()
+        };
+        self.layer_bg[tile] = saved;
+        found
+    }
+
+    /// Scatter `count` items on random free tiles other than the robot's.
+    fn place_items(&mut self, count: usize) {
+        let free = self.empty_tiles();
+        for &pos in free.choose_multiple(&mut self.rng, count) {
+            let item = if self.rng.random_ratio(1, STAR_RARITY) {
+                ItemTile::Star
+            } else {
+                ItemTile::Coin
+            };
+            self.layer_items[pos] = Some(item);
+            self.stats.borrow_mut().items_total += 1;
+        }
+    }
+
+    /// Put the minotaur on a random free tile away from the robot, but on
+    /// its side of all doors.
+    fn place_minotaur(&mut self) {
+        if self.config.minotaur == Minotaur::Off {
+            return;
+        }
+        let reachable = reachable_tiles(&self.layer_bg, self.robot_pos, |tile| {
+            !matches!(tile, BackgroundTile::Wall | BackgroundTile::Door(_))
+        });
+        let mut free = self.empty_tiles();
+        free.retain(|&pos| reachable[pos]);
+        let far: Vec<(u16, u16)> = free
+            .iter()
+            .copied()
+            .filter(|&pos| Pos::from(pos).distance(self.robot_pos) >= MINOTAUR_MIN_DISTANCE)
+            .collect();
+        let candidates = if far.is_empty() { free } else { far };
+        let Some(&pos) = candidates.choose(&mut self.rng) else {
+            return;
+        };
+        self.minotaur = Some(pos.into());
+        self.layer_fg[pos] = Some(ForegroundTile::Minotaur);
+    }
+
+    /// Pick up to `count` checkpoints reachable without passing a door,
+    /// numbered from the nearest to the farthest from the robot.
+    fn place_checkpoints(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        let cost = pathfinding::distances(
+            (self.layer_bg.width(), self.layer_bg.height()),
+            self.robot_pos.into(),
+            |pos| self.robot_steps(pos, false),
+        );
+        let mut free = self.empty_tiles();
+        free.retain(|&pos| cost[pos].is_some());
+        let mut checkpoints: Vec<(u16, u16)> = free
+            .choose_multiple(&mut self.rng, count)
+            .copied()
+            .collect();
+        checkpoints.sort_by_key(|&pos| cost[pos]);
+        debug_println!("checkpoints: {:?}", checkpoints);
+        self.course = Some(Course::new(checkpoints));
+    }
+
+    /// Make the exit the goal, or else the free tile farthest from the robot.
+    fn place_goal(&mut self) {
+        if let Some(exit) = self.maze_exit {
+            self.goal = Some(exit);
+            debug_println!("goal: the exit at {}", exit);
+            return;
+        }
+        let cost = pathfinding::distances(
+            (self.layer_bg.width(), self.layer_bg.height()),
+            self.robot_pos.into(),
+            |pos| self.open_steps(pos),
+        );
+        let mut farthest: Option<((u16, u16), u32)> = None;
+        for y in 0..self.layer_bg.height() {
+            for x in 0..self.layer_bg.width() {
+                if let (BackgroundTile::Free, Some(d)) = (self.layer_bg[(x, y)], cost[(x, y)]) {
+                    if farthest.is_none_or(|(_, max)| d > max) {
+                        farthest = Some(((x, y), d));
+                    }
+                }
+            }
+        }
+        self.goal = farthest.map(|(pos, _)| pos.into());
+        debug_println!("goal: {:?}", self.goal);
+    }
+
+    /// Pick up to `count` goals reachable without passing a door and plan
+    /// the order to visit them in, the last one becoming the goal.
+    fn place_tour(&mut self, count: usize) {
+        let reachable = reachable_tiles(&self.layer_bg, self.robot_pos, |tile| {
+            !matches!(tile, BackgroundTile::Wall | BackgroundTile::Door(_))
+        });
+        let mut free = self.empty_tiles();
+        free.retain(|&pos| reachable[pos]);
+        let points: Vec<(u16, u16)> = std::iter::once(self.robot_pos.into())
+            .chain(free.choose_multiple(&mut self.rng, count).copied())
+            .collect();
+        let size = (self.layer_bg.width(), self.layer_bg.height());
+        // the robot has no keys yet, so its moves avoid all doors
+        let cost: Vec<Vec<u32>> = points
+            .iter()
+            .map(|&from| {
+                let cost = pathfinding::distances(size, from, |pos| self.robot_steps(pos, false));
+                points
+                    .iter()
+                    .map(|&to| cost[to].unwrap_or(u32::MAX))
+                    .collect()
+            })
+            .collect();
+        let nearest = tour::nearest_neighbor(&cost);
+        let exact = tour::exact(&cost);
+        let order = exact.as_ref().unwrap_or(&nearest);
+        let mut route = vec![points[0]];
+        for leg in order.windows(2) {
+            let path = pathfinding::astar(size, points[leg[0]], points[leg[1]], |pos| {
+                self.robot_steps(pos, false)
+            });
+            route.extend(
+                path.into_iter()
+                    .flat_map(|path| path.steps)
+                    .flat_map(|step| [step.tile, step.to]),
+            );
+        }
+        route.dedup();
+        let tour = Tour {
+            stops: order[1..].iter().map(|&i| points[i]).collect(),
+            route,
+            nearest_cost: tour::cost_of(&cost, &nearest),
+            exact_cost: exact.as_ref().map(|order| tour::cost_of(&cost, order)),
+        };
+        debug_println!("tour: {:?}", tour.stops);
+        self.goal = tour.stops.last().map(|&pos| pos.into());
+        self.tour = Some(tour);
+    }
+
+    /// The moves from `pos` if all doors were open, costing as much as the
+    /// terrain stepped onto.
+    fn open_steps(&self, pos: Point) -> Vec<Step> {
+        let grid = |pos: Point| {
+            pathfinding::neighbors(pos, self.layer_bg.width(), self.layer_bg.height())
+                .filter(|&next| !matches!(self.layer_bg[next], BackgroundTile::Wall))
+                .map(|next| Step {
+                    tile: next,
+                    to: next,
+                    cost: self.tile_cost(next.into()),
+                })
+                .collect()
+        };
+        map_steps(&self.layer_bg, pos, grid)
+    }
+
+    /// Recompute `layer_distance` for the current maze.
+    fn update_distance_field(&mut self) {
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        self.layer_distance = AlphaTileMap::empty(w, h);
+        let Some(goal) = self.goal else {
+            self.hot_cold.replace(HotCold::default());
+            return;
+        };
+        let cost = pathfinding::distances((w, h), goal.into(), |pos| self.open_steps(pos));
+        self.hot_cold.replace(HotCold::new(cost.clone()));
+        let mut max = 1;
+        for y in 0..h {
+            for x in 0..w {
+                max = max.max(cost[(x, y)].unwrap_or(0));
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                self.layer_distance[(x, y)] = cost[(x, y)].map(|d| match d {
+                    0 => DistanceTile::Goal,
+                    d => DistanceTile::Away((d as u64 * 255 / max as u64) as u8),
+                });
+            }
+        }
+    }
+
+    /// Open a random wall between two free tiles and close another corridor
+    /// tile where the maze stays connected without it. Stale plans and stack
+    /// entries for the closed tile are dropped.
+    fn shift_walls(&mut self) {
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        let free = |pos: Option<Pos>| {
+            pos.is_some_and(|pos| matches!(self.layer_bg[pos.into()], BackgroundTile::Free))
+        };
+        let mut walls = Vec::new();
+        for y in 1..h.saturating_sub(1) {
+            for x in 1..w.saturating_sub(1) {
+                let pos = Pos::new(x as usize, y as usize);
+                let [n, e, s, w] = [Direction::N, Direction::E, Direction::S, Direction::W]
+                    .map(|dir| free(pos + RelPos::new(0, -1, dir)));
+                if let BackgroundTile::Wall = self.layer_bg[(x, y)] {
+                    if (n && s) || (e && w) {
+                        walls.push((x, y));
+                    }
+                }
+            }
+        }
+        let Some(&opened) = walls.choose(&mut self.rng) else {
+            return;
+        };
+        self.layer_bg[opened] = BackgroundTile::Free;
+        // a gap between two explored tiles has nothing new behind it
+        let explored = [Direction::N, Direction::E, Direction::S, Direction::W]
+            .into_iter()
+            .filter_map(|dir| Pos::from(opened) + RelPos::new(0, -1, dir))
+            .filter(|&pos| !matches!(self.layer_bg[pos.into()], BackgroundTile::Wall))
+            .all(|pos| self.layer_visited[pos.into()].is_some());
+        if explored {
+            self.mark_visited(opened.into());
+        }
+
+        let mut corridors: Vec<((u16, u16), Direction)> = self
+            .empty_tiles()
+            .into_iter()
+            .filter(|&tile| tile != opened)
+            .filter(|&tile| ![self.minotaur, self.goal].contains(&Some(tile.into())))
+            .filter(|tile| {
+                !self
+                    .tour
+                    .as_ref()
+                    .is_some_and(|tour| tour.stops.contains(tile))
+            })
+            .filter_map(|tile| Some((tile, self.corridor_axis(tile)?)))
+            .collect();
+        corridors.shuffle(&mut self.rng);
+        let closed = corridors.into_iter().find(|&(tile, axis)| {
+            let a = (Pos::from(tile) + RelPos::new(0, -1, axis)).unwrap();
+            let b = (Pos::from(tile) + RelPos::new(0, 1, axis)).unwrap();
+            self.reachable_around(tile, a, b) && self.reachable_around(tile, b, a)
+        });
+        debug_println!("shifted walls: opened {:?}, closed {:?}", opened, closed);
+        if let Some((tile, _)) = closed {
+            self.wall_up(tile);
+        }
+        self.stats.borrow_mut().reachable = count_reachable(&self.layer_bg, self.robot_pos);
+        self.update_distance_field();
+    }
+
+    /// Turn a free tile into a wall, forgetting everything about it and
+    /// dropping plans through it.
+    fn wall_up(&mut self, tile: (u16, u16)) {
+        self.layer_bg[tile] = BackgroundTile::Wall;
+        self.layer_terrain[tile] = None;
+        if self.layer_visited[tile].take().is_some() {
+            self.stats.borrow_mut().visited -= 1;
+        }
+        self.layer_fg[tile] = None;
+        self.layer_known[tile] = 0;
+        let pos = Pos::from(tile);
+        if self.remembers(pos) {
+            let rel = self.relative(pos);
+            self.robot_memory[rel] = false;
+            self.memory_undo.push((rel, true));
+        }
+        self.robot_stack.retain(|&p| p != pos);
+        if self.robot_plan.contains(&pos) {
+            self.robot_plan.clear();
+        }
+        for helper in &mut self.helpers {
+            if helper.plan.contains(&pos) {
+                helper.plan.clear();
+            }
+        }
+    }
+
+    /// Call the hooks, then wall up the tiles they asked for and log their
+    /// metrics.
+    fn run_hooks(&mut self, phase: Phase) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let (width, height) = (self.layer_bg.width(), self.layer_bg.height());
+        let mut state = TickState {
+            tick: self.ticks(),
+            robot: (self.robot_pos.x, self.robot_pos.y),
+            goal: self.goal.map(|goal| (goal.x, goal.y)),
+            width: width as usize,
+            height: height as usize,
+            ..TickState::default()
+        };
+        if let Err(e) = self.hooks.call(phase, &mut state) {
+            self.status_message = Some(StatusMessage::Error(format!(
+                "hook failed and was removed: {}",
+                e
+            )));
+        }
+        for (name, value) in &state.metrics {
+            debug_println!("tick {}: {} = {}", state.tick, name, value);
+        }
+        let mut walled = false;
+        for (x, y) in state.walls {
+            let pos = Pos::new(x, y);
+            if x >= width as usize
+                || y >= height as usize
+                || [Some(self.robot_pos), self.goal, self.minotaur].contains(&Some(pos))
+                || !matches!(self.layer_bg[pos.into()], BackgroundTile::Free)
+            {
+                continue;
+            }
+            self.wall_up(pos.into());
+            walled = true;
+        }
+        if walled {
+            self.stats.borrow_mut().reachable = count_reachable(&self.layer_bg, self.robot_pos);
+            self.update_distance_field();
+        }
+        for ((x, y), text) in state.labels {
+            if x < width as usize && y < height as usize {
+                self.label((x as u16, y as u16), &text);
+            }
+        }
+    }
+
+    /// Label a tile during a tick, for a solver or a hook. Rewinding the
+    /// tick takes the label off again.
+    fn label(&mut self, pos: (u16, u16), text: &str) {
+        let old = self.notes.set(pos, text);
+        self.notes_undo.push((pos, old));
+    }
+
+    /// Load further chunks in infinite mode when the robot gets close to the
+    /// right or bottom edge of the loaded region.
+    fn stream_chunks(&mut self) {
+        let Some(world) = &self.world else {
+            return;
+        };
+        let cols = (self.robot_pos.x + STREAM_MARGIN) / world.chunk_tiles() + 1;
+        let rows = (self.robot_pos.y + STREAM_MARGIN) / world.chunk_tiles() + 1;
+        self.load_chunks(cols, rows);
+    }
+
+    /// Make sure at least `cols` x `rows` chunks are loaded into the layers.
+    fn load_chunks(&mut self, cols: usize, rows: usize) {
+        let Some(world) = &mut self.world else {
+            return;
+        };
+        let loaded = world.load(cols, rows);
+        if loaded.is_empty() {
+            return;
+        }
+        let (pw, ph) = (world.width() as u16, world.height() as u16);
+        self.layer_bg.resize(pw, ph);
+        self.layer_terrain.resize(pw, ph);
+        self.layer_visited.resize(pw, ph);
+        self.layer_items.resize(pw, ph);
+        self.layer_fg.resize(pw, ph);
+        self.layer_known.resize(pw, ph);
+        for (cx, cy) in loaded {
+            debug_println!("loading chunk ({}, {})", cx, cy);
+            let origin = Pos::new(cx * world.chunk_tiles(), cy * world.chunk_tiles());
+            blit_maze(&mut self.layer_bg, &world.chunk(cx, cy), origin);
+        }
+    }
+
+    pub fn run<W: io::Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> io::Result<()> {
+        let size = terminal.size()?;
+        self.on_resize(size.width, size.height);
+        while !self.exit {
+            let start = Instant::now();
+            if let Some(evolution) = &mut self.evolution {
+                evolution.poll();
+            }
+            if let Some(batch) = &mut self.batch {
+                batch.poll();
+            }
+            self.reload_theme();
+            self.toasts.borrow_mut().update(Instant::now());
+            let frame = terminal.draw(|frame| self.draw(frame))?;
+            if let Some(Err(e)) = self.cast.as_mut().map(|cast| cast.frame(frame.buffer)) {
+                debug_println!("could not write cast, closing it: {}", e);
+                self.cast = None;
+            }
+            if self.graphics {
+                self.draw_graphics(terminal.backend_mut())?;
+            }
+            self.timings.draw.add(start.elapsed());
+            self.handle_events()?;
+            self.handle_control()?;
+            #[cfg(feature = "http")]
+            if let Some(server) = &self.http {
+                server.serve(|| self.control_state(), || self.maze_image());
+            }
+            if self.take_screenshot {
+                self.take_screenshot = false;
+                let frame = terminal.draw(|frame| self.draw(frame))?;
+                match save_screenshot(frame.buffer) {
+                    Ok(path) => self
+                        .toasts
+                        .borrow_mut()
+                        .push(format!("Screenshot saved to {}", path)),
+                    Err(e) => {
+                        self.status_message = Some(StatusMessage::Error(format!(
+                            "could not save screenshot: {}",
+                            e
+                        )))
+                    }
+                }
+            }
+            if self.steps_remaining > 0 {
+                self.spend_steps();
+            }
+            if self.screen == Screen::Running && self.last_tick.elapsed() >= self.tick_interval {
+                self.last_tick = Instant::now();
+                if let Some(walk) = &mut self.walk {
+                    walk.advance(&mut self.layer_bg);
+                    if walk.is_done() {
+                        self.end_walk();
+                    }
+                } else {
+                    self.on_tick();
+                    self.timings.tick.add(self.last_tick.elapsed());
+                }
+            }
+        }
+        if let Some(cast) = &mut self.cast {
+            cast.flush()?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        frame.render_widget(self, frame.area());
+    }
+
+    /// Draw the visible part of the map over its cells as an image, one
+    /// block of pixels per tile. Glyphs don't show up in the image, and
+    /// whenever something is drawn over the map it falls back to the cells.
+    fn draw_graphics(&mut self, out: &mut impl io::Write) -> io::Result<()> {
+        let covered = self.show_help
+            || self.show_minimap
+            || self.show_timings
+            || self.show_weights
+            || self.show_search
+            || self.zoomed_out
+            || self.stack_selected.is_some()
+            || palette::current().glyphs
+            || self.sprites != SpriteSet::None
+            || self.light.is_on()
+            || self.walk.is_some()
+            || self.show_hint;
+        if covered || !matches!(self.screen, Screen::Running | Screen::Paused) {
+            if self.graphics_frame.take().is_some() {
+                kitty::delete(out, GRAPHICS_ID)?;
+                out.flush()?;
+            }
+            return Ok(());
+        }
+        let area = self.map_area;
+        let view = self.viewport(area);
+        let (cols, rows) = (view.width(), view.height());
+        let mut image =
+            kitty::Image::new(cols as u32 * GRAPHICS_BLOCK, rows as u32 * GRAPHICS_BLOCK);
+        for y in 0..rows {
+            for x in 0..cols {
+                let color = self.tile_color((self.camera.0 + x, self.camera.1 + y));
+                image.fill_block(x as u32, y as u32, GRAPHICS_BLOCK, kitty::rgb(color));
+            }
+        }
+        if matches!(&self.graphics_frame, Some((a, i)) if *a == area && *i == image) {
+            return Ok(());
+        }
+        crossterm::queue!(out, crossterm::cursor::MoveTo(area.x, area.y))?;
+        kitty::show(out, &image, GRAPHICS_ID, 2 * cols, rows)?;
+        out.flush()?;
+        self.graphics_frame = Some((area, image));
+        Ok(())
+    }
+
+    /// The whole map as a picture, shrunk as `http::png_scale` says.
+    #[cfg(feature = "http")]
+    fn maze_image(&self) -> kitty::Image {
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        let (block, step) = http::png_scale(w, h);
+        let (cols, rows) = ((w as u32).div_ceil(step), (h as u32).div_ceil(step));
+        let mut image = kitty::Image::new(cols * block, rows * block);
+        for row in 0..rows {
+            for col in 0..cols {
+                let pos = ((col * step) as u16, (row * step) as u16);
+                let color = kitty::rgb(self.tile_color(pos));
+                image.fill_block(col, row, block, color);
+            }
+        }
+        image
+    }
+
+    fn handle_events(&mut self) -> io::Result<()> {
+        let timeout = Duration::from_millis(1000 / 60);
+        if event::poll(timeout)? {
+            // only count the handling, not the wait for an event
+            let start = Instant::now();
+            match event::read()? {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    self.handle_key_event(key_event)
+                }
+                Event::Resize(width, height) => self.on_resize(width, height),
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    column,
+                    row,
+                    ..
+                }) => self.on_click(column, row),
+                _ => {}
+            }
+            self.timings.events.add(start.elapsed());
+        } else {
+            self.timings.events.add(Duration::ZERO);
+        }
+        Ok(())
+    }
+
+    /// Answer the control requests that came in.
+    fn handle_control(&mut self) -> io::Result<()> {
+        let Some(requests) = self.control.as_ref().map(Control::poll) else {
+            return Ok(());
+        };
+        for request in requests {
+            let response = match request {
+                Ok(request) => {
+                    let result = self
+                        .control_call(request.method)
+                        .map_err(|message| RpcError {
+                            code: FAILED,
+                            message,
+                        });
+                    // requests without an id are notifications, which get no
+                    // response
+                    let Some(id) = request.id else {
+                        continue;
+                    };
+                    Response::new(id, result)
+                }
+                Err(response) => response,
+            };
+            if let Some(control) = &self.control {
+                control.respond(&response)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Do what a control request asks for, returning the result to send
+    /// back.
+    fn control_call(&mut self, method: Method) -> Result<serde_json::Value, String> {
+        let in_run = matches!(
+            self.screen,
+            Screen::Running | Screen::Paused | Screen::Finished
+        );
+        match method {
+            Method::Pause if self.screen == Screen::Running => {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+            }
+            Method::Resume if self.screen == Screen::Paused => {
+                self.stats.borrow_mut().clock.resume();
+                self.screen = Screen::Running;
+                self.last_tick = Instant::now();
+            }
+            Method::Pause | Method::Resume => {
+                return Err(format!("can't do that while {}", self.state_label()))
+            }
+            Method::Step { ticks } => {
+                if self.screen != Screen::Paused {
+                    return Err("can only step while paused".to_string());
+                }
+                self.step(ticks)?;
+            }
+            Method::SetSpeed { ticks_per_second } => {
+                if !(1..=1000).contains(&ticks_per_second) {
+                    return Err("ticks_per_second must be from 1 to 1000".to_string());
+                }
+                self.tick_interval = Duration::from_millis(1000 / ticks_per_second as u64);
+            }
+            Method::PlaceGoal { x, y } => {
+                if !in_run {
+                    return Err("no run to place a goal in".to_string());
+                }
+                let (w, h) = (
+                    self.layer_bg.width() as usize,
+                    self.layer_bg.height() as usize,
+                );
+                if x >= w || y >= h {
+                    return Err(format!("({}, {}) is outside the {}x{} map", x, y, w, h));
+                }
+                if !matches!(self.layer_bg[(x as u16, y as u16)], BackgroundTile::Free) {
+                    return Err(format!("({}, {}) is not a free tile", x, y));
+                }
+                self.goal = Some(Pos::new(x, y));
+                self.robot_plan.clear();
+                self.update_distance_field();
+                // rewinding or replaying can't bring the goal back
+                self.forget_history();
+                self.playing = None;
+            }
+            Method::QueryState => {}
+        }
+        Ok(self.control_state())
+    }
+
+    /// Where the app is at, as sent back for every control request.
+    fn control_state(&self) -> serde_json::Value {
+        let pos = |pos: Pos| serde_json::json!({ "x": pos.x, "y": pos.y });
+        serde_json::json!({
+            "state": self.state_label(),
+            "seed": self.config.seed,
+            "tick": self.ticks(),
+            "ticks_per_second": 1000 / self.tick_interval.as_millis().max(1),
+            "robot": pos(self.robot_pos),
+            "goal": self.goal.map(pos),
+            "steps": self.stats.borrow().steps,
+            "visited": self.stats.borrow().visited,
+            "reachable": self.stats.borrow().reachable,
+        })
+    }
+
+    /// Seek on the timeline, or toggle a breakpoint on the clicked tile.
+    fn on_click(&mut self, column: u16, row: u16) {
+        if let Some(area) = self.timeline_area {
+            if area.contains(Position::new(column, row)) {
+                let (start, end) = self.timeline_range();
+                let offset =
+                    (column - area.x) as u64 * (end - start) / (area.width as u64 - 1).max(1);
+                self.seek(start + offset);
+                return;
+            }
+        }
+        let on_map = matches!(self.screen, Screen::Running | Screen::Paused)
+            && self.dialog.is_none()
+            && self.walk.is_none()
+            && !self.zoomed_out;
+        if let Some((x, y)) = on_map.then(|| self.map_tile(column, row)).flatten() {
+            let message = self.toggle_breakpoint(Breakpoint::Tile(x, y));
+            self.status_message = Some(StatusMessage::Info(message));
+        }
+    }
+
+    /// Add the breakpoint, or remove it if it is set already.
+    fn toggle_breakpoint(&mut self, breakpoint: Breakpoint) -> String {
+        if let Some(i) = self.breakpoints.iter().position(|b| *b == breakpoint) {
+            self.breakpoints.remove(i);
+            format!("removed breakpoint: {}", breakpoint)
+        } else {
+            self.breakpoints.push(breakpoint);
+            format!("added breakpoint: {}", breakpoint)
+        }
+    }
+
+    /// Handle a key, unless it records or plays a macro. Keys typed into
+    /// dialogs and the command line are only recorded.
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if self.dialog.is_some() || self.command_line.is_some() {
+            self.macros.record(key_event);
+            self.handle_key(key_event);
+            return;
+        }
+        match self.macros.handle_key(key_event) {
+            MacroKey::Pass => self.handle_key(key_event),
+            MacroKey::Consumed(message) => self.status_message = message.map(StatusMessage::Info),
+            MacroKey::Error(e) => self.status_message = Some(StatusMessage::Error(e)),
+            MacroKey::Play(keys) => {
+                for key in keys {
+                    self.handle_key_event(key);
+                }
+                self.macros.played();
+            }
+        }
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) {
+        self.status_message = None;
+        if let Some((prompt, dialog)) = &mut self.dialog {
+            let prompt = *prompt;
+            match dialog.handle_key(key_event.code) {
+                Outcome::Open => {}
+                Outcome::Cancelled => self.dialog = None,
+                Outcome::Answered(answer) => {
+                    self.dialog = None;
+                    self.answer(prompt, answer);
+                }
+            }
+            return;
+        }
+        if self.command_line.is_some() {
+            self.handle_command_key(key_event.code);
+            return;
+        }
+        if self.show_help {
+            if let KeyCode::Esc | KeyCode::Char('?') = key_event.code {
+                self.show_help = false;
+            }
+            return;
+        }
+        if key_event.code == KeyCode::Char('?') {
+            self.show_help = true;
+            return;
+        }
+        if key_event.code == KeyCode::Char(':') {
+            self.command_line = Some(String::new());
+            return;
+        }
+        if self.screen == Screen::Menu {
+            match self.menu.handle_key(key_event.code) {
+                MenuAction::Start => {
+                    self.config = self.menu.config;
+                    self.fixed_maze = None;
+                    self.daily = None;
+                    self.reinit();
+                }
+                MenuAction::Daily => self.start_daily(),
+                MenuAction::Leaderboard => self.screen = Screen::Leaderboard,
+                MenuAction::Gallery => self.open_gallery(),
+                MenuAction::Settings => {
+                    self.settings_form.settings = self.current_settings();
+                    self.screen = Screen::Settings;
+                }
+                MenuAction::Resume => {
+                    if let Err(e) = self.resume_session() {
+                        self.notice("could not resume", &e);
+                    }
+                }
+                MenuAction::Size => {
+                    let config = &self.menu.config;
+                    let size = format!("{}x{}", config.width, config.height);
+                    let dialog = Dialog::input("size", "Maze width and height in cells", &size);
+                    self.dialog = Some((Prompt::Size, dialog));
+                }
+                MenuAction::Evolve => self.start_evolution(),
+                MenuAction::Batch => self.start_batch(),
+                MenuAction::Quit => self.exit(),
+                MenuAction::None => {}
+            }
+            return;
+        }
+        if self.screen == Screen::Evolution {
+            match key_event.code {
+                KeyCode::Char('q') => self.exit(),
+                KeyCode::Esc => {
+                    self.evolution = None;
+                    self.screen = Screen::Menu;
+                }
+                KeyCode::Enter => self.deploy_evolved(),
+                _ => {}
+            }
+            return;
+        }
+        if self.screen == Screen::Batch {
+            match key_event.code {
+                KeyCode::Char('q') => self.exit(),
+                KeyCode::Esc => {
+                    self.batch = None;
+                    self.screen = Screen::Menu;
+                }
+                _ => {}
+            }
+            return;
+        }
+        if self.screen == Screen::Settings {
+            match self.settings_form.handle_key(key_event.code) {
+                FormAction::Changed => self.apply_settings(&self.settings_form.settings.clone()),
+                FormAction::Save => {
+                    match self.settings_form.settings.save(Path::new(SETTINGS_PATH)) {
+                        Ok(()) => self
+                            .toasts
+                            .borrow_mut()
+                            .push(format!("Settings saved to {}", SETTINGS_PATH)),
+                        Err(e) => {
+                            self.status_message = Some(StatusMessage::Error(format!(
+                                "could not save settings: {}",
+                                e
+                            )))
+                        }
+                    }
+                }
+                FormAction::EditTheme => {
+                    let path = self.settings_form.settings.theme.as_ref();
+                    let path = path.map_or(String::new(), |path| path.display().to_string());
+                    let dialog = Dialog::input("theme", "TOML theme file, empty for none", &path);
+                    self.dialog = Some((Prompt::ThemePath, dialog));
+                }
+                FormAction::Back => self.screen = Screen::Menu,
+                FormAction::Quit => self.exit(),
+                FormAction::None => {}
+            }
+            return;
+        }
+        if self.screen == Screen::Gallery {
+            let Some(gallery) = &mut self.gallery else {
+                return;
+            };
+            match gallery.handle_key(key_event.code) {
+                Ok(GalleryAction::Load(maze)) => {
+                    self.gallery = None;
+                    self.run_on(maze);
+                }
+                Ok(GalleryAction::Play(replay)) => {
+                    self.gallery = None;
+                    if let Err(e) = self.play_replay(replay) {
+                        self.status_message = Some(StatusMessage::Error(e));
+                        self.screen = Screen::Menu;
+                    }
+                }
+                Ok(GalleryAction::Back) => {
+                    self.gallery = None;
+                    self.screen = Screen::Menu;
+                }
+                Ok(GalleryAction::Quit) => self.exit(),
+                Ok(GalleryAction::None) => {}
+                Err(e) => self.status_message = Some(StatusMessage::Error(e)),
+            }
+            return;
+        }
+        if self.screen == Screen::Leaderboard {
+            match key_event.code {
+                KeyCode::Char('q') => self.exit(),
+                KeyCode::Esc | KeyCode::Enter => self.screen = Screen::Menu,
+                KeyCode::Up => self.leaderboard.scroll_by(-1),
+                KeyCode::Down => self.leaderboard.scroll_by(1),
+                KeyCode::PageUp => self.leaderboard.scroll_by(-10),
+                KeyCode::PageDown => self.leaderboard.scroll_by(10),
+                _ => {}
+            }
+            return;
+        }
+        let step_count = self.step_count.take();
+        match (self.screen, key_event.code) {
+            (Screen::Running | Screen::Paused, KeyCode::Enter) if self.walk.is_some() => {
+                self.end_walk()
+            }
+            (Screen::Running | Screen::Paused, KeyCode::Char(c @ '0'..='9')) => {
+                let digit = c.to_digit(10).unwrap() as u64;
+                let count = step_count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit);
+                // a count can't start with 0
+                self.step_count = (count > 0).then_some(count);
+            }
+            (Screen::Running | Screen::Paused, KeyCode::Right) if step_count.is_some() => {
+                let ticks = step_count.unwrap();
+                if let Err(e) = self.step(ticks) {
+                    self.status_message = Some(StatusMessage::Error(e));
+                }
+            }
+            (Screen::Running | Screen::Paused, KeyCode::Char('q')) => {
+                let options = vec![
+                    "quit".to_string(),
+                    "save replay and quit".to_string(),
+                    "save session and quit, to resume later".to_string(),
+                ];
+                let dialog = Dialog::select("quit", "The run is not over yet.", options);
+                self.dialog = Some((Prompt::Quit, dialog));
+            }
+            (_, KeyCode::Char('q')) => self.exit(),
+            (_, KeyCode::Esc) => {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Menu;
+            }
+            (_, KeyCode::Char('m')) => self.show_minimap = !self.show_minimap,
+            (_, KeyCode::Char('d')) => self.show_distances = !self.show_distances,
+            (_, KeyCode::Char('o')) => self.show_hint = !self.show_hint,
+            (_, KeyCode::Char('t')) => self.show_search = !self.show_search,
+            (_, KeyCode::Char('a')) => self.show_annotations = !self.show_annotations,
+            (_, KeyCode::Char('R')) => self.show_revisits = !self.show_revisits,
+            (_, KeyCode::Char('A')) => {
+                self.annotation_source = self.annotation_source.next();
+                self.show_annotations = true;
+            }
+            (_, KeyCode::Char('p')) => self.show_timings = !self.show_timings,
+            (_, KeyCode::Char('w')) => self.show_weights = !self.show_weights,
+            (_, KeyCode::Char('h')) => self.show_thoughts = !self.show_thoughts,
+            (_, KeyCode::Char(c)) if self.show_thoughts && Category::from_key(c).is_some() => {
+                self.thoughts.toggle(Category::from_key(c).unwrap())
+            }
+            (_, KeyCode::Tab) if self.show_weights => self.weight_field = self.weight_field.next(),
+            (_, KeyCode::Tab) => self.focus_next_pane(),
+            (_, KeyCode::Char('v')) => self.show_view = !self.show_view,
+            (_, KeyCode::Char('[')) => self.layout.widen(self.focus, -1),
+            (_, KeyCode::Char(']')) => self.layout.widen(self.focus, 1),
+            (_, KeyCode::Char('{')) => self.layout.heighten(self.focus, -1),
+            (_, KeyCode::Char('}')) => self.layout.heighten(self.focus, 1),
+            (_, KeyCode::Char('+' | '=')) if self.show_weights => {
+                self.weights.adjust(self.weight_field, true)
+            }
+            (_, KeyCode::Char('-')) if self.show_weights => {
+                self.weights.adjust(self.weight_field, false)
+            }
+            (_, KeyCode::Char('z')) => self.zoomed_out = !self.zoomed_out,
+            (_, KeyCode::Char('s')) => self.take_screenshot = true,
+            (_, KeyCode::Char('x')) => {
+                let path = self.gallery_path().display().to_string();
+                let dialog = Dialog::input("export", "Save the maze to", &path);
+                self.dialog = Some((Prompt::ExportPath, dialog));
+            }
+            (_, KeyCode::Char('c')) => self.set_palette(self.palette.next()),
+            (_, KeyCode::Char('e')) => self.set_sprites(self.sprites.next()),
+            (_, KeyCode::Char('i')) => self.light.radius = self.light.next_radius(),
+            (_, KeyCode::Char('I')) => self.light.flicker = !self.light.flicker,
+            // ←/→ rewind and step while another pane is focused
+            (_, KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right)
+                if self.focus == Pane::Map =>
+            {
+                self.pan_camera(key_event.code)
+            }
+            (_, KeyCode::Home) if self.focus == Pane::Map => self.pan = (0, 0),
+            // the newest lines of the log are at the bottom
+            (_, KeyCode::Up) if self.focus == Pane::Log => self.log_pane.scroll_by(1),
+            (_, KeyCode::Down) if self.focus == Pane::Log => self.log_pane.scroll_by(-1),
+            (_, KeyCode::Up) if self.focus == Pane::Thoughts => self.thoughts.scroll_by(-1),
+            (_, KeyCode::Down) if self.focus == Pane::Thoughts => self.thoughts.scroll_by(1),
+            (_, KeyCode::Up) => self.select_stack_entry(-1),
+            (_, KeyCode::Down) => self.select_stack_entry(1),
+            (Screen::Running, KeyCode::Char(' ')) => {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+            }
+            (Screen::Paused, KeyCode::Char(' ')) => {
+                self.stats.borrow_mut().clock.resume();
+                self.screen = Screen::Running;
+                self.last_tick = Instant::now();
+            }
+            (Screen::Paused, KeyCode::Right) => self.on_tick(),
+            (_, KeyCode::Left) => self.rewind(),
+            (_, KeyCode::PageUp) => self.seek(self.ticks().saturating_sub(TIMELINE_PAGE)),
+            (_, KeyCode::PageDown) => self.seek(self.ticks() + TIMELINE_PAGE),
+            (Screen::Finished, KeyCode::Enter) => self.screen = Screen::Menu,
+            (Screen::Finished, KeyCode::Char('l')) => self.screen = Screen::Leaderboard,
+            (Screen::Finished, KeyCode::Char('r')) => self.reinit(),
+            (Screen::Finished, KeyCode::Char('n')) => {
+                self.config.seed = rand::random_range(0..1_000_000);
+                self.menu.config.seed = self.config.seed;
+                self.fixed_maze = None;
+                self.daily = None;
+                self.reinit();
+            }
+            _ => {}
+        }
+    }
+
+    /// Edit the open command line, running the command on Enter.
+    fn handle_command_key(&mut self, code: KeyCode) {
+        let Some(input) = &mut self.command_line else {
+            return;
+        };
+        match code {
+            KeyCode::Esc => self.command_line = None,
+            KeyCode::Enter => {
+                let result = input.parse().and_then(|command| self.run_command(command));
+                self.command_line = None;
+                self.status_message = Some(match result {
+                    Ok(message) => StatusMessage::Info(message),
+                    Err(message) => StatusMessage::Error(message),
+                });
+            }
+            KeyCode::Tab => *input = command::complete(input),
+            KeyCode::Backspace if input.is_empty() => self.command_line = None,
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+    }
+
+    /// Walk the rest of the way at once if the maze is still being
+    /// generated, and start the run on it, paused if the walk was.
+    fn end_walk(&mut self) {
+        if let Some(walk) = self.walk.take() {
+            let paused = self.screen == Screen::Paused;
+            let (maze, rng) = walk.finish();
+            self.rng = rng;
+            self.start_on(Some(maze));
+            if paused {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+            }
+        }
+    }
+
+    /// Pause the run and go on by `ticks`, or until it is over, returning how
+    /// many ticks it went in this frame. The rest are stepped in the next
+    /// frames, see `spend_steps`.
+    fn step(&mut self, ticks: u64) -> Result<u64, String> {
+        match self.screen {
+            Screen::Running => {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+            }
+            Screen::Paused => {}
+            _ => return Err(format!("can't step while {}", self.state_label())),
+        }
+        self.steps_remaining = ticks;
+        Ok(self.spend_steps())
+    }
+
+    /// Step the ticks left to step for as long as a frame allows, stopping
+    /// once the run is no longer paused. Returns how many it stepped.
+    fn spend_steps(&mut self) -> u64 {
+        let start = Instant::now();
+        let mut stepped = 0;
+        while self.steps_remaining > 0 && self.screen == Screen::Paused {
+            self.on_tick();
+            self.steps_remaining -= 1;
+            stepped += 1;
+            if start.elapsed() >= STEP_BUDGET {
+                return stepped;
+            }
+        }
+        self.steps_remaining = 0;
+        stepped
+    }
+
+    /// Run `command`, returning what it did or why it failed.
+    fn run_command(&mut self, command: Command) -> Result<String, String> {
+        match command {
+            Command::Seed(seed) => {
+                self.change_config(|config| config.seed = seed);
+                Ok(format!("seed set to {}", seed))
+            }
+            Command::Algo(generator) => {
+                self.fixed_maze = None;
+                self.change_config(|config| config.generator = generator);
+                Ok(format!("generator set to {}", generator))
+            }
+            Command::Size(width, height) => {
+                menu::check_size(width, height)?;
+                self.fixed_maze = None;
+                self.change_config(|config| {
+                    config.width = width;
+                    config.height = height;
+                    config.fit = false;
+                    config.infinite = false;
+                });
+                Ok(format!("size set to {}x{}", width, height))
+            }
+            Command::Export(path) => {
+                if matches!(
+                    self.screen,
+                    Screen::Menu
+                        | Screen::Leaderboard
+                        | Screen::Evolution
+                        | Screen::Batch
+                        | Screen::Gallery
+                        | Screen::Settings
+                ) {
+                    return Err("no maze to export".to_string());
+                }
+                let path = path.unwrap_or_else(|| self.gallery_path());
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir)
+                        .map_err(|e| format!("could not create {}: {}", dir.display(), e))?;
+                }
+                fs::write(&path, self.maze_text())
+                    .map_err(|e| format!("could not export maze: {}", e))?;
+                Ok(format!("maze written to {}", path.display()))
+            }
+            Command::Break(breakpoint) => Ok(self.toggle_breakpoint(breakpoint)),
+            Command::ClearBreakpoints => {
+                self.breakpoints.clear();
+                Ok("removed all breakpoints".to_string())
+            }
+            Command::Watch(watch) => {
+                if let Some(i) = self.watches.iter().position(|(w, _)| *w == watch) {
+                    self.watches.remove(i);
+                    Ok(format!("removed watch: {}", watch))
+                } else {
+                    let held = watch.holds(&self.probe());
+                    self.watches.push((watch, held));
+                    Ok(format!("added watch: {}", watch))
+                }
+            }
+            Command::ClearWatches => {
+                self.watches.clear();
+                Ok("removed all watches".to_string())
+            }
+            Command::Note(x, y, Some(label)) => {
+                self.notes.set((x, y), &label);
+                self.annotation_source = annotations::Source::Notes;
+                self.show_annotations = true;
+                Ok(format!("labelled ({}, {})", x, y))
+            }
+            Command::Note(x, y, None) => match self.notes.remove((x, y)) {
+                true => Ok(format!("removed the label of ({}, {})", x, y)),
+                false => Err(format!("({}, {}) has no label", x, y)),
+            },
+            Command::ClearNotes => {
+                self.notes.clear();
+                Ok("removed all labels".to_string())
+            }
+            Command::Steps(ticks) => {
+                let stepped = self.step(ticks)?;
+                if self.steps_remaining > 0 {
+                    return Ok(format!("stepping {} ticks", ticks));
+                }
+                if stepped < ticks {
+                    return Ok(format!("stepped {} ticks, the run is over", stepped));
+                }
+                Ok(format!("stepped {} ticks", stepped))
+            }
+            Command::Quit => {
+                self.exit();
+                Ok(String::new())
+            }
+        }
+    }
+
+    /// Breed robot weights in the background, rating them by how fast they
+    /// reach the goal of mazes like the one set up in the menu.
+    fn start_evolution(&mut self) {
+        let config = RunConfig {
+            infinite: false,
+            fit: false,
+            minotaur: Minotaur::Off,
+            solver: Solver::Explorer,
+            goals: 1,
+            shift_interval: 0,
+            ..self.menu.config
+        };
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut evolution = Evolution::new(config.seed);
+            while !evolution.done() {
+                let generation = evolution.step(|weights| evolve_fitness(config, *weights));
+                if sender.send(generation).is_err() {
+                    // the evolution screen was left
+                    return;
+                }
+            }
+        });
+        self.evolution = Some(EvolutionView::new(receiver));
+        self.screen = Screen::Evolution;
+    }
+
+    /// Run the menu's config on many consecutive seeds in the background,
+    /// using all cores.
+    fn start_batch(&mut self) {
+        let config = RunConfig {
+            infinite: false,
+            fit: false,
+            ..self.menu.config
+        };
+        let weights = self.weights;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            // sending fails once the batch screen was left, which stops the
+            // runs not started yet
+            let _ = (0..batch::BATCH_RUNS)
+                .into_par_iter()
+                .map(|i| config.seed.wrapping_add(i))
+                .try_for_each_with(sender, |sender, seed| {
+                    let mut app = headless_app(RunConfig { seed, ..config }, weights);
+                    while app.screen == Screen::Running {
+                        app.on_tick();
+                    }
+                    let stats = app.stats.borrow();
+                    let run = BatchRun {
+                        seed,
+                        steps: stats.steps,
+                        visited: stats.visited,
+                        caught: app.caught,
+                    };
+                    sender.send(run)
+                });
+        });
+        let label = format!(
+            "{} on {} {}x{}, seeds {}..{}",
+            config.solver,
+            config.generator,
+            config.width,
+            config.height,
+            config.seed,
+            config.seed.wrapping_add(batch::BATCH_RUNS)
+        );
+        self.batch = Some(BatchView::new(receiver, label));
+        self.screen = Screen::Batch;
+    }
+
+    /// How the maze of the run came about.
+    fn generator_label(&self) -> String {
+        match self.fixed_maze {
+            Some(_) => "loaded".to_string(),
+            None => self.config.generator.to_string(),
+        }
+    }
+
+    /// The code to run the same maze with, if it is generated from a code.
+    fn share_code(&self) -> Option<ShareCode> {
+        if self.fixed_maze.is_some() {
+            return None;
+        }
+        ShareCode::from_config(&self.config)
+    }
+
+    /// Start a run on `maze` instead of a generated one, with the other
+    /// settings from the menu.
+    fn run_on(&mut self, maze: maze::Maze) {
+        self.config = RunConfig {
+            infinite: false,
+            ..self.menu.config
+        };
+        self.fixed_maze = Some(maze);
+        self.daily = None;
+        self.reinit();
+    }
+
+    /// The run so far.
+    fn replay(&self) -> Replay {
+        Replay {
+            // a fitted size depends on the terminal, so it is pinned down
+            config: RunConfig {
+                fit: false,
+                ..self.config
+            },
+            weights: self.weights,
+            maze: self.fixed_maze.as_ref().map(ToString::to_string),
+            ticks: self.ticks(),
+            // unknown until the walk generating it is done
+            maze_hash: self.walk.is_none().then_some(self.maze_hash),
+        }
+    }
+
+    /// The settings as they are now, to edit or save.
+    fn current_settings(&self) -> Settings {
+        Settings {
+            tick_rate: (1000 / self.tick_interval.as_millis().max(1)) as u32,
+            width: self.menu.config.width,
+            height: self.menu.config.height,
+            generator: self.menu.config.generator,
+            palette: self.palette,
+            sprites: self.sprites,
+            theme: self.theme.as_ref().map(|theme| theme.path.clone()),
+            light: self.light,
+            minimap: self.show_minimap,
+            distances: self.show_distances,
+            search_tree: self.show_search,
+            timings: self.show_timings,
+            scoring: self.scoring,
+            layout: self.layout.clone(),
+            trail: self.trail_length,
+        }
+    }
+
+    fn apply_settings(&mut self, settings: &Settings) {
+        self.tick_interval = Duration::from_millis(1000 / settings.tick_rate.max(1) as u64);
+        let config = &mut self.menu.config;
+        if (config.width, config.height) != (settings.width, settings.height) {
+            config.fit = false;
+        }
+        config.width = settings.width;
+        config.height = settings.height;
+        config.generator = settings.generator;
+        let old_theme = self.theme.as_ref().map(|theme| &theme.path);
+        let reload = old_theme != settings.theme.as_ref() || self.palette != settings.palette;
+        self.set_palette(settings.palette);
+        self.set_sprites(settings.sprites);
+        if reload {
+            // read again, as the palette just replaced its colors
+            self.theme = settings.theme.clone().map(ThemeFile::new);
+            if self.theme.is_none() {
+                sprites::set_custom(Default::default());
+            }
+        }
+        self.light = settings.light;
+        self.show_minimap = settings.minimap;
+        self.show_distances = settings.distances;
+        self.show_search = settings.search_tree;
+        self.show_timings = settings.timings;
+        self.scoring = settings.scoring;
+        self.layout = settings.layout.clone();
+        self.trail_length = settings.trail;
+        if !self.layout.contains(self.focus) {
+            self.focus = Pane::Map;
+        }
+    }
+
+    /// Save the run so far as a replay, and return where it went.
+    fn save_replay(&self) -> io::Result<PathBuf> {
+        let replay = self.replay();
+        let path = PathBuf::from(REPLAY_DIR).join(format!(
+            "{}-{}.json.gz",
+            self.config.seed,
+            self.ticks()
+        ));
+        replay.save(&path)?;
+        Ok(path)
+    }
+
+    /// Play `replay` from the start, pausing where it was saved.
+    fn play_replay(&mut self, replay: Replay) -> Result<(), String> {
+        let maze: Option<maze::Maze> = replay.maze.as_deref().map(str::parse).transpose()?;
+        maze.as_ref().map(menu::check_maze_size).transpose()?;
+        self.fixed_maze = maze;
+        self.config = replay.config;
+        self.weights = replay.weights;
+        self.daily = None;
+        self.reinit();
+        // the maze has to be there to compare it with the replay's
+        self.end_walk();
+        self.replay_end = Some(replay.ticks);
+        if replay.maze_hash.is_some_and(|hash| hash != self.maze_hash) {
+            self.status_message = Some(StatusMessage::Error(
+                "the maze changed since the replay was saved, it may play out differently"
+                    .to_string(),
+            ));
+        }
+        self.playing = Some(replay);
+        Ok(())
+    }
+
+    fn save_session(&self) -> io::Result<()> {
+        let session = Session {
+            replay: self.replay(),
+            view: View {
+                palette: self.palette,
+                sprites: self.sprites,
+                light: self.light,
+                show_minimap: self.show_minimap,
+                show_distances: self.show_distances,
+                show_search: self.show_search,
+                show_weights: self.show_weights,
+                zoomed_out: self.zoomed_out,
+            },
+            elapsed: self.stats.borrow().elapsed(),
+            daily: self.daily,
+        };
+        session.save(Path::new(SESSION_PATH))
+    }
+
+    /// Go on with the saved session, paused where it was left.
+    fn resume_session(&mut self) -> Result<(), String> {
+        self.menu.resumable = false;
+        let Session {
+            replay,
+            view,
+            elapsed,
+            daily,
+        } = Session::take(Path::new(SESSION_PATH))?;
+        let ticks = replay.ticks;
+        self.play_replay(replay)?;
+        self.replay_end = None;
+        self.playing = None;
+        self.daily = daily;
+        // replaying takes no time of the run
+        self.stats.borrow_mut().clock = Stopwatch::paused_at(elapsed);
+        while self.ticks() < ticks && self.screen == Screen::Running {
+            self.on_tick();
+        }
+        if self.screen == Screen::Running {
+            self.stats.borrow_mut().clock.pause();
+            self.screen = Screen::Paused;
+        }
+        self.set_palette(view.palette);
+        self.set_sprites(view.sprites);
+        self.light = view.light;
+        self.show_minimap = view.show_minimap;
+        self.show_distances = view.show_distances;
+        self.show_search = view.show_search;
+        self.show_weights = view.show_weights;
+        self.zoomed_out = view.zoomed_out;
+        // whatever came up on the way is old news
+        self.toasts.replace(Toasts::default());
+        self.toasts.borrow_mut().push("Session resumed");
+        Ok(())
+    }
+
+    /// Start today's daily puzzle with the solver picked in the menu.
+    fn start_daily(&mut self) {
+        let day = daily::today();
+        self.config = daily::config(day, self.menu.config.solver);
+        self.fixed_maze = None;
+        self.daily = Some(day);
+        self.reinit();
+    }
+
+    /// Show the mazes saved in the gallery directory and the saved replays.
+    fn open_gallery(&mut self) {
+        match Gallery::load(GALLERY_DIR, REPLAY_DIR) {
+            Ok(gallery) => {
+                self.gallery = Some(gallery);
+                self.screen = Screen::Gallery;
+            }
+            Err(e) => {
+                self.status_message = Some(StatusMessage::Error(format!(
+                    "could not read {} or {}: {}",
+                    GALLERY_DIR, REPLAY_DIR, e
+                )))
+            }
+        }
+    }
+
+    /// Start a run from the menu with the best weights evolved so far.
+    fn deploy_evolved(&mut self) {
+        let Some(weights) = self.evolution.as_ref().and_then(EvolutionView::best) else {
+            self.status_message = Some(StatusMessage::Error("no generation rated yet".to_string()));
+            return;
+        };
+        self.evolution = None;
+        self.weights = weights;
+        self.show_weights = true;
+        self.config = self.menu.config;
+        self.reinit();
+    }
+
+    /// Apply `change` to the menu's config and, during a run, restart the
+    /// run with it.
+    fn change_config(&mut self, change: impl Fn(&mut RunConfig)) {
+        change(&mut self.menu.config);
+        self.daily = None;
+        if !matches!(
+            self.screen,
+            Screen::Menu
+                | Screen::Leaderboard
+                | Screen::Evolution
+                | Screen::Batch
+                | Screen::Gallery
+                | Screen::Settings
+        ) {
+            change(&mut self.config);
+            self.reinit();
+        }
+    }
+
+    /// The walls of the maze as text, in the format `Maze` is printed in.
+    fn maze_text(&self) -> String {
+        let mut text = String::new();
+        for y in 0..self.layer_bg.height() {
+            for x in 0..self.layer_bg.width() {
+                text.push(match self.layer_bg[(x, y)] {
+                    BackgroundTile::Wall => 'O',
+                    _ => ' ',
+                });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    fn set_palette(&mut self, name: PaletteName) {
+        self.palette = name;
+        palette::set(name.palette());
+    }
+
+    fn set_sprites(&mut self, sprites: SpriteSet) {
+        self.sprites = sprites;
+        sprites::set(sprites);
+    }
+
+    /// Apply the theme file if it changed. Cycling the palette overrides the
+    /// theme's colors until the file changes again.
+    fn reload_theme(&mut self) {
+        let Some(result) = self.theme.as_mut().and_then(ThemeFile::poll) else {
+            return;
+        };
+        match result {
+            Ok(theme) => {
+                palette::set(theme.palette);
+                sprites::set_custom(theme.sprites);
+                self.toasts.borrow_mut().push("Theme loaded");
+            }
+            Err(e) => self.status_message = Some(StatusMessage::Error(e)),
+        }
+    }
+
+    /// Move the stack selection `delta` entries down, towards the bottom of
+    /// the stack. Moving up past the top clears the selection.
+    fn select_stack_entry(&mut self, delta: isize) {
+        let len = self.robot_stack.len();
+        self.stack_selected = match self.stack_selected {
+            None if delta > 0 && len > 0 => Some(0),
+            None => None,
+            Some(i) => i
+                .checked_add_signed(delta)
+                .map(|i| i.min(len.saturating_sub(1))),
+        };
+    }
+
+    /// The stack entry selected in the side panel, if it still exists.
+    fn selected_stack_pos(&self) -> Option<Pos> {
+        let i = self.stack_selected?;
+        self.robot_stack.iter().rev().nth(i).copied()
+    }
+
+    fn record_run(&mut self) {
+        if self.config.infinite {
+            return;
+        }
+        let record = RunRecord {
+            seed: self.config.seed,
+            generator: self.generator_label(),
+            width: self.config.width,
+            height: self.config.height,
+            solver: self.config.solver.to_string(),
+            steps: self.stats.borrow().steps,
+            time: self.stats.borrow().elapsed(),
+            score: self.scoring.score(&self.stats.borrow()),
+        };
+        if let Err(e) = self.leaderboard.record(record) {
+            debug_println!("could not record run: {}", e);
+        }
+        if let (Some(day), false) = (self.daily, self.caught) {
+            if let Err(e) = self.daily_history.record(day, self.stats.borrow().steps) {
+                debug_println!("could not record daily puzzle: {}", e);
+            }
+        }
+    }
+
+    /// Append the statistics of the finished run to the results file.
+    fn export_run(&self) {
+        if self.config.infinite {
+            return;
+        }
+        let result = RunResult {
+            seed: self.config.seed,
+            generator: self.generator_label(),
+            solver: self.config.solver.to_string(),
+            width: self.config.width,
+            height: self.config.height,
+            caught: self.caught,
+            steps: self.stats.borrow().steps,
+            turns: self.stats.borrow().turns,
+            backtracks: self.stats.borrow().backtracks,
+            duration: self.stats.borrow().elapsed(),
+        };
+        if let Err(e) = results::append(RESULTS_PATH, &result) {
+            debug_println!("could not export run: {}", e);
+        }
+    }
+
+    /// Add the current map to the recording. Infinite mazes are not recorded,
+    /// as their map grows while the robot explores.
+    fn record_frame(&mut self) {
+        if self.config.infinite {
+            return;
+        }
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
+        };
+        recorder.capture(|x, y| kitty::rgb(self.tile_color((x, y))));
+        self.recorder = Some(recorder);
+    }
+
+    /// Write the recording of the finished run, ending with its last state.
+    fn save_recording(&mut self) {
+        if self.config.infinite {
+            return;
+        }
+        self.record_frame();
+        if let Some(Err(e)) = self.recorder.as_ref().map(Recorder::save) {
+            debug_println!("could not save recording: {}", e);
+        }
+    }
+
+    /// Keys that do something on the current screen, as (key, action) pairs.
+    fn keybindings(&self) -> &'static [(&'static str, &'static str)] {
+        match self.screen {
+            Screen::Menu => &[
+                ("↑/↓", "select setting"),
+                ("←/→", "change setting"),
+                ("0-9", "type seed"),
+                ("r", "random seed"),
+                ("s", "type size"),
+                ("c", "resume last session"),
+                ("o", "settings"),
+                ("Enter", "start run"),
+                ("d", "daily puzzle"),
+                ("l", "leaderboard"),
+                ("e", "evolve weights"),
+                ("b", "batch run"),
+                ("g", "saved mazes and replays"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Running => &[
+                ("Space", "pause"),
+                ("Enter", "skip generation"),
+                ("m", "toggle minimap"),
+                ("d", "toggle distance field"),
+                ("o", "toggle hot/cold hint"),
+                ("t", "toggle search tree"),
+                ("a/A", "toggle annotations, label tiles with"),
+                ("R", "toggle revisit heatmap"),
+                ("p", "toggle frame timings"),
+                ("w", "toggle weights pane"),
+                ("Tab/+/-", "select and change weight"),
+                ("h", "toggle thoughts pane"),
+                ("M/S/B", "filter thoughts"),
+                ("v", "toggle first-person view"),
+                ("Tab", "focus next pane"),
+                ("←↑↓→", "pan focused map"),
+                ("↑/↓", "scroll focused log or thoughts"),
+                ("Home", "center focused map again"),
+                ("[/]", "narrow or widen focused pane"),
+                ("{/}", "lower or raise focused pane"),
+                ("z", "toggle zoom"),
+                ("s", "save screenshot"),
+                ("x", "export maze"),
+                ("c", "cycle palette"),
+                ("e", "cycle sprites"),
+                ("i/I", "cycle light radius, toggle torch"),
+                ("↑/↓", "select stack entry"),
+                ("←", "rewind one tick, map not focused"),
+                ("N →", "step N ticks and pause"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("click", "toggle tile breakpoint"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Paused => &[
+                ("Space", "resume"),
+                ("Enter", "skip generation"),
+                ("→", "single step, map not focused"),
+                ("N →", "step N ticks"),
+                ("←", "rewind one tick, map not focused"),
+                ("PgUp/PgDn", "seek"),
+                ("m", "toggle minimap"),
+                ("d", "toggle distance field"),
+                ("o", "toggle hot/cold hint"),
+                ("t", "toggle search tree"),
+                ("a/A", "toggle annotations, label tiles with"),
+                ("R", "toggle revisit heatmap"),
+                ("p", "toggle frame timings"),
+                ("w", "toggle weights pane"),
+                ("Tab/+/-", "select and change weight"),
+                ("h", "toggle thoughts pane"),
+                ("M/S/B", "filter thoughts"),
+                ("v", "toggle first-person view"),
+                ("Tab", "focus next pane"),
+                ("←↑↓→", "pan focused map"),
+                ("↑/↓", "scroll focused log or thoughts"),
+                ("Home", "center focused map again"),
+                ("[/]", "narrow or widen focused pane"),
+                ("{/}", "lower or raise focused pane"),
+                ("z", "toggle zoom"),
+                ("s", "save screenshot"),
+                ("x", "export maze"),
+                ("c", "cycle palette"),
+                ("e", "cycle sprites"),
+                ("i/I", "cycle light radius, toggle torch"),
+                ("↑/↓", "select stack entry"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("click", "toggle tile breakpoint"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Finished => &[
+                ("r", "rerun same maze"),
+                ("n", "new maze"),
+                ("l", "leaderboard"),
+                ("Enter", "back to menu"),
+                ("m", "toggle minimap"),
+                ("d", "toggle distance field"),
+                ("o", "toggle hot/cold hint"),
+                ("t", "toggle search tree"),
+                ("a/A", "toggle annotations, label tiles with"),
+                ("R", "toggle revisit heatmap"),
+                ("p", "toggle frame timings"),
+                ("w", "toggle weights pane"),
+                ("Tab/+/-", "select and change weight"),
+                ("h", "toggle thoughts pane"),
+                ("M/S/B", "filter thoughts"),
+                ("v", "toggle first-person view"),
+                ("Tab", "focus next pane"),
+                ("←↑↓→", "pan focused map"),
+                ("↑/↓", "scroll focused log or thoughts"),
+                ("Home", "center focused map again"),
+                ("[/]", "narrow or widen focused pane"),
+                ("{/}", "lower or raise focused pane"),
+                ("z", "toggle zoom"),
+                ("s", "save screenshot"),
+                ("x", "export maze"),
+                ("c", "cycle palette"),
+                ("e", "cycle sprites"),
+                ("i/I", "cycle light radius, toggle torch"),
+                ("↑/↓", "select stack entry"),
+                ("←", "rewind one tick, map not focused"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Settings => &[
+                ("↑/↓", "select setting"),
+                ("←/→", "change setting"),
+                ("Enter", "edit theme path"),
+                ("s", "save settings"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Gallery => &[
+                ("↑/↓", "select maze"),
+                ("Enter", "run on maze"),
+                ("d", "delete maze"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Batch => &[
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Evolution => &[
+                ("Enter", "run with best weights"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+            Screen::Leaderboard => &[
+                ("↑/↓", "scroll"),
+                ("PgUp/PgDn", "scroll page"),
+                ("Esc", "back to menu"),
+                (":", "command line"),
+                ("Q/@", "record, play macro"),
+                ("?", "toggle help"),
+                ("q", "quit"),
+            ],
+        }
+    }
+
+    fn settings(&self) -> Vec<(&'static str, String)> {
+        let config = if self.screen == Screen::Menu {
+            &self.menu.config
+        } else {
+            &self.config
+        };
+        vec![
+            ("generator", config.generator.to_string()),
+            (
+                "size",
+                if config.infinite {
+                    "infinite".to_string()
+                } else {
+                    format!("{}x{}", config.width, config.height)
+                },
+            ),
+            ("seed", config.seed.to_string()),
+            ("items", config.items.to_string()),
+            ("doors", config.doors.to_string()),
+            ("teleporters", config.teleporters.to_string()),
+            ("one-ways", config.one_ways.to_string()),
+            ("terrain", config.terrain.to_string()),
+            ("minotaur", config.minotaur.to_string()),
+            ("solver", config.solver.to_string()),
+            ("chassis", config.chassis.to_string()),
+            (
+                "cover goal",
+                menu::coverage_goal_label(config.coverage_goal),
+            ),
+            ("checkpoints", config.checkpoints.to_string()),
+            ("openings", config.openings.to_string()),
+            ("goals", config.goals.to_string()),
+            ("robots", config.robots.to_string()),
+            ("comm radius", menu::radius_label(config.comm_radius)),
+            ("wall shift", menu::shift_label(config.shift_interval)),
+            ("tick", format!("{} ms", self.tick_interval.as_millis())),
+            (
+                "minimap",
+                if self.show_minimap { "on" } else { "off" }.into(),
+            ),
+            (
+                "distance field",
+                if self.show_distances { "on" } else { "off" }.into(),
+            ),
+            (
+                "hot/cold hint",
+                if self.show_hint { "on" } else { "off" }.into(),
+            ),
+            (
+                "search tree",
+                if self.show_search { "on" } else { "off" }.into(),
+            ),
+            (
+                "frame timings",
+                if self.show_timings { "on" } else { "off" }.into(),
+            ),
+            (
+                "revisit heatmap",
+                if self.show_revisits { "on" } else { "off" }.into(),
+            ),
+            (
+                "trail",
+                match self.trail_length {
+                    0 => "all".to_string(),
+                    length => format!("{} cells", length),
+                },
+            ),
+            (
+                "graphics",
+                if self.graphics { "kitty" } else { "cells" }.into(),
+            ),
+            ("palette", self.palette.to_string()),
+            ("sprites", self.sprites.to_string()),
+            (
+                "lighting",
+                match (self.light.radius, self.light.flicker) {
+                    (0, _) => "off".into(),
+                    (radius, false) => format!("radius {}", radius),
+                    (radius, true) => format!("torch, radius {}", radius),
+                },
+            ),
+            (
+                "theme",
+                self.theme
+                    .as_ref()
+                    .map_or("none".into(), |theme| theme.path.display().to_string()),
+            ),
+            ("breakpoints", self.breakpoints.len().to_string()),
+            ("watches", self.watches.len().to_string()),
+            (
+                "event stream",
+                if self.bus.is_subscribed("events") {
+                    "on"
+                } else {
+                    "off"
+                }
+                .to_string(),
+            ),
+            (
+                "weights",
+                format!(
+                    "heuristic {:.1}, revisit {:.1}, ties {}",
+                    self.weights.heuristic, self.weights.revisit, self.weights.tie_break
+                ),
+            ),
+            (
+                "charset",
+                if self.ascii { "ascii" } else { "unicode" }.into(),
+            ),
+        ]
+    }
+
+    fn exit(&mut self) {
+        self.exit = true;
+    }
+
+    /// Where the maze goes in the gallery.
+    fn gallery_path(&self) -> PathBuf {
+        PathBuf::from(GALLERY_DIR).join(format!(
+            "{}-{}x{}.txt",
+            self.config.seed, self.config.width, self.config.height
+        ))
+    }
+
+    /// Show `message` in a dialog that has to be dismissed, for errors that
+    /// shouldn't go by unseen in the status bar.
+    fn notice(&mut self, title: &str, message: &str) {
+        self.dialog = Some((Prompt::Notice, Dialog::message(title, message)));
+    }
+
+    /// Do what the dialog asking for `prompt` was answered with.
+    fn answer(&mut self, prompt: Prompt, answer: Answer) {
+        let result = match (prompt, answer) {
+            (Prompt::Notice, _) => return,
+            (Prompt::Quit, Answer::Choice(0)) => {
+                self.exit();
+                return;
+            }
+            (Prompt::Quit, Answer::Choice(1)) => match self.save_replay() {
+                Ok(_) => {
+                    self.exit();
+                    return;
+                }
+                Err(e) => Err(format!("could not save replay: {}", e)),
+            },
+            (Prompt::Quit, Answer::Choice(_)) => match self.save_session() {
+                Ok(()) => {
+                    self.exit();
+                    return;
+                }
+                Err(e) => Err(format!("could not save session: {}", e)),
+            },
+            (Prompt::ExportPath, Answer::Text(path)) => {
+                self.run_command(Command::Export(Some(PathBuf::from(path.trim()))))
+            }
+            (Prompt::Size, Answer::Text(size)) => self.set_menu_size(&size),
+            (Prompt::ThemePath, Answer::Text(path)) => {
+                let path = path.trim();
+                self.settings_form.settings.theme = (!path.is_empty()).then(|| PathBuf::from(path));
+                self.apply_settings(&self.settings_form.settings.clone());
+                return;
+            }
+            (prompt, answer) => Err(format!("{:?} is no answer to {:?}", answer, prompt)),
+        };
+        self.status_message = Some(match result {
+            Ok(message) => StatusMessage::Info(message),
+            Err(message) => StatusMessage::Error(message),
+        });
+    }
+
+    /// Set the size in the menu from text like `30x20`.
+    fn set_menu_size(&mut self, size: &str) -> Result<String, String> {
+        let parsed = size
+            .split_once(['x', ' '])
+            .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)));
+        let Some((width, height)) = parsed else {
+            return Err(format!("'{}' is not a size like 30x20", size));
+        };
+        menu::check_size(width, height)?;
+        let config = &mut self.menu.config;
+        config.width = width;
+        config.height = height;
+        config.fit = false;
+        config.infinite = false;
+        Ok(format!("size set to {}x{}", width, height))
+    }
+
+    /// The tile at `offset` from the robot, seen from where it faces, if it
+    /// is on the map.
+    fn robot_pos_with_offset(&self, offset: (isize, isize)) -> Option<Pos> {
+        (self.robot_pos + RelPos::new(offset.0, offset.1, self.robot_dir))
+            .filter(|&pos| self.on_map(pos))
+    }
+
+    fn robot_scan(&mut self) -> [u8; 9] {
+        let mut arr = [0u8; 9];
+        let mut idx = 0;
+        for y_loc in -1..=1 {
+            for x_loc in -1..=1 {
+                let glob = self.robot_pos_with_offset((x_loc, y_loc));
+                arr[idx] = if glob.is_some_and(|glob| self.robot_can_move(self.robot_pos, glob)) {
+                    b'.'
+                } else {
+                    b'O'
+                };
+                idx += 1;
+            }
+        }
+        arr
+    }
+
+    /// Step forward, or bump into what is in the way and stay put, as a
+    /// buggy script may well make the robot do.
+    fn robot_step(&mut self) {
+        // off the map, out of an opening, is as good as a wall
+        let Some(glob) = self.robot_pos_with_offset((0, -1)) else {
+            debug_println!("robot tried to leave the maze at {}", self.robot_pos);
+            return;
+        };
+        if one_way_blocks(&self.layer_bg, self.robot_pos, glob) {
+            debug_println!(
+                "robot tried to pass one-way passage against its direction at {}",
+                glob
+            );
+            self.robot_bump(glob);
+            return;
+        }
+        // can only step into free fields and doors we have the key for
+        if let BackgroundTile::Door(color) = self.layer_bg[glob.into()] {
+            if self.robot_keys.contains(&color) {
+                debug_println!("unlocked {:?} door at {}", color, glob);
+                self.layer_bg[glob.into()] = BackgroundTile::Free;
+            }
+        }
+        match self.layer_bg[glob.into()] {
+            BackgroundTile::Free | BackgroundTile::Teleporter(_) | BackgroundTile::OneWay(_) => {
+                if let Some(ForegroundTile::Robot) = self.layer_fg[self.robot_pos.into()] {
+                    self.layer_fg[self.robot_pos.into()] = None;
+                }
+                self.robot_pos = glob;
+                self.layer_fg[self.robot_pos.into()] = Some(ForegroundTile::Robot);
+                self.emit(SimEvent::Move {
+                    x: glob.x as u16,
+                    y: glob.y as u16,
+                });
+                // the turns before the step take their time on top of it
+                let turning = charge_turns(&mut self.turn_debt);
+                self.robot_busy = self.tile_cost(glob) - 1 + turning;
+                self.stats.borrow_mut().turn_ticks += turning as u64;
+                self.pick_up(glob);
+                if let BackgroundTile::Teleporter(twin) = self.layer_bg[glob.into()] {
+                    self.robot_teleport(twin.into());
+                }
+                self.pass_checkpoint();
+            }
+            BackgroundTile::Wall => {
+                debug_println!("robot tried to move to wall at {}", glob);
+                self.robot_bump(glob);
+            }
+            BackgroundTile::Door(color) => {
+                debug_println!(
+                    "robot tried to move through locked {:?} door at {}",
+                    color,
+                    glob
+                );
+                self.robot_bump(glob);
+            }
+        }
+    }
+
+    /// The robot walked into `pos`, which it can't pass, wasting the tick.
+    fn robot_bump(&mut self, pos: Pos) {
+        self.bump_flash = Some((pos, Instant::now()));
+        self.emit(SimEvent::Bump {
+            x: pos.x as u16,
+            y: pos.y as u16,
+        });
+    }
+
+    /// Pick up the item at `pos`, if there is one.
+    fn pick_up(&mut self, pos: Pos) {
+        match self.layer_items[pos.into()].take() {
+            Some(ItemTile::Key(color)) => {
+                debug_println!("picked up {:?} key at {}", color, pos);
+                self.robot_keys.push(color);
+            }
+            Some(item) => {
+                debug_println!("picked up {:?} at {}", item, pos);
+                self.stats.borrow_mut().items_collected += 1;
+                self.stats.borrow_mut().item_score += item.value();
+            }
+            None => {}
+        }
+    }
+
+    /// Time the checkpoint the robot is on, if it is the next one.
+    fn pass_checkpoint(&mut self) {
+        let (tick, time) = (self.ticks(), self.stats.borrow().elapsed());
+        let Some(course) = &mut self.course else {
+            return;
+        };
+        let Some(n) = course.pass(self.robot_pos.into(), tick, time) else {
+            return;
+        };
+        debug_println!("passed checkpoint {} at {}", n, self.robot_pos);
+        if !self.replaying() {
+            self.toasts.borrow_mut().push(format!(
+                "Checkpoint {} {}",
+                n,
+                stats::format_duration(time)
+            ));
+        }
+    }
+
+    /// Move the robot to `target` without walking, keeping its orientation.
+    fn robot_teleport(&mut self, target: Pos) {
+        debug_println!("teleported from {} to {}", self.robot_pos, target);
+        if let Some(ForegroundTile::Robot) = self.layer_fg[self.robot_pos.into()] {
+            self.layer_fg[self.robot_pos.into()] = None;
+        }
+        self.robot_pos = target;
+        self.layer_fg[self.robot_pos.into()] = Some(ForegroundTile::Robot);
+        self.emit(SimEvent::Teleport {
+            x: target.x as u16,
+            y: target.y as u16,
+        });
+        self.mark_visited(target);
+    }
+
+    /// Turn until the robot faces `target`, which has to be a neighbor.
+    fn robot_face(&mut self, target: Pos) {
+        for _ in 0..4 {
+            if Some(target) == self.robot_pos_with_offset((0, -1)) {
+                return;
+            }
+            self.robot_turn_right();
+        }
+        panic!("robot at {} can't face {}", self.robot_pos, target);
+    }
+
+    fn robot_stack_push(&mut self, pos: Pos) {
+        self.emit(SimEvent::Push {
+            x: pos.x as u16,
+            y: pos.y as u16,
+        });
+        self.robot_stack.push(pos);
+        self.layer_fg[pos.into()] = Some(ForegroundTile::Stack);
+    }
+
+    /// Whether `pos` is on or next to the minotaur.
+    fn minotaur_near(&self, pos: Pos) -> bool {
+        self.minotaur
+            .is_some_and(|minotaur| minotaur.distance(pos) <= 1)
+    }
+
+    /// Where the minotaur can get to in one move. Like the robot it uses
+    /// teleporters, but it can't open doors.
+    fn minotaur_steps(&self, pos: Point) -> Vec<Step> {
+        let grid = pathfinding::grid_steps(&self.layer_bg, |tile| {
+            !matches!(tile, BackgroundTile::Wall | BackgroundTile::Door(_))
+        });
+        map_steps(&self.layer_bg, pos, grid)
+    }
+
+    /// A random neighbor, turning back only at dead ends.
+    fn minotaur_wander(&mut self, pos: Pos) -> Option<Pos> {
+        let mut neighbors: Vec<Pos> = self
+            .minotaur_steps(pos.into())
+            .into_iter()
+            .map(|step| step.to.into())
+            .collect();
+        if neighbors.len() > 1 {
+            neighbors.retain(|&next| Some(next) != self.minotaur_prev);
+        }
+        neighbors.choose(&mut self.rng).copied()
+    }
+
+    /// Whether the minotaur at `pos` can see the robot.
+    fn minotaur_sees_robot(&self, pos: Pos) -> bool {
+        let visible = self
+            .layer_bg
+            .visible_from(pos.into(), MINOTAUR_SIGHT, |tile| {
+                matches!(tile, BackgroundTile::Wall | BackgroundTile::Door(_))
+            });
+        visible[self.robot_pos.into()]
+    }
+
+    /// The first step of a path towards where the minotaur last saw the
+    /// robot. Wanders around while it hasn't seen it yet.
+    fn minotaur_chase(&mut self, pos: Pos) -> Option<Pos> {
+        if self.minotaur_sees_robot(pos) {
+            self.minotaur_target = Some(self.robot_pos);
+        }
+        if self.minotaur_target == Some(pos) {
+            // lost track of it
+            self.minotaur_target = None;
+        }
+        let Some(target) = self.minotaur_target else {
+            return self.minotaur_wander(pos);
+        };
+        let path = pathfinding::astar(
+            (self.layer_bg.width(), self.layer_bg.height()),
+            pos.into(),
+            target.into(),
+            |pos| self.minotaur_steps(pos),
+        );
+        match path.and_then(|path| path.steps.first().copied()) {
+            Some(step) => Some(step.to.into()),
+            // the robot is out of reach, e.g. behind a door
+            None => {
+                self.minotaur_target = None;
+                self.minotaur_wander(pos)
+            }
+        }
+    }
+
+    fn minotaur_move(&mut self) {
+        let Some(pos) = self.minotaur else {
+            return;
+        };
+        let next = match self.config.minotaur {
+            Minotaur::Chase => self.minotaur_chase(pos),
+            _ => self.minotaur_wander(pos),
+        };
+        let Some(next) = next else {
+            return;
+        };
+        self.layer_fg[pos.into()] = self
+            .robot_stack
+            .contains(&pos)
+            .then_some(ForegroundTile::Stack);
+        self.layer_fg[next.into()] = Some(ForegroundTile::Minotaur);
+        self.minotaur_prev = Some(pos);
+        self.minotaur = Some(next);
+        if next == self.robot_pos {
+            debug_println!("caught by the minotaur at {}", next);
+            self.caught = true;
+            self.stats.borrow_mut().clock.pause();
+            if self.saves_run() {
+                self.export_run();
+                self.save_recording();
+            }
+            self.emit(SimEvent::Finish { caught: true });
+            self.screen = Screen::Finished;
+        }
+    }
+
+    fn robot_stack_pop(&mut self) -> Option<Pos> {
+        if let Some(pos) = self.robot_stack.pop() {
+            if let Some(ForegroundTile::Stack) = self.layer_fg[pos.into()] {
+                self.layer_fg[pos.into()] = None;
+            }
+            self.emit(SimEvent::Pop {
+                x: pos.x as u16,
+                y: pos.y as u16,
+            });
+            Some(pos)
+        } else {
+            None
+        }
+    }
+
+    fn robot_turn_right(&mut self) {
+        self.robot_dir = self.robot_dir.right();
+        self.turn_debt += self.config.chassis.turn_cost();
+        self.emit(SimEvent::Turn {
+            dir: self.robot_dir,
+        });
+    }
+
+    fn robot_turn_left(&mut self) {
+        self.robot_dir = self.robot_dir.left();
+        self.turn_debt += self.config.chassis.turn_cost();
+        self.emit(SimEvent::Turn {
+            dir: self.robot_dir,
+        });
+    }
+
+    fn mark_visited(&mut self, pos: Pos) {
+        if self.layer_visited[pos.into()].is_none() {
+            self.emit(SimEvent::Visit {
+                x: pos.x as u16,
+                y: pos.y as u16,
+            });
+        }
+        self.layer_visited[pos.into()] = Some(VisitedTile::Visited);
+        let rel = self.relative(pos);
+        self.robot_memory.extend_to(rel);
+        if !self.robot_memory[rel] {
+            self.robot_memory[rel] = true;
+            self.memory_undo.push((rel, false));
+        }
+    }
+
+    /// Where `pos` is from the entrance.
+    fn relative(&self, pos: Pos) -> (i32, i32) {
+        (
+            pos.x as i32 - self.entrance.x as i32,
+            pos.y as i32 - self.entrance.y as i32,
+        )
+    }
+
+    /// Whether the robot remembers being on `pos`.
+    fn remembers(&self, pos: Pos) -> bool {
+        self.robot_memory
+            .get(self.relative(pos))
+            .is_some_and(|&been| been)
+    }
+
+    /// Try a move picked from what the robot learned so far and learn from
+    /// where it led. An episode ends at the goal, or when it takes too long,
+    /// and the robot starts over from the entrance.
+    fn robot_learn(&mut self) {
+        let (Some(table), Some(goal)) = (&self.qtable, self.goal) else {
+            return;
+        };
+        let pos = self.robot_pos;
+        let action = table.choose(pos.into(), &mut self.rng);
+        let dir = [Direction::N, Direction::E, Direction::S, Direction::W][action];
+        let mut reward = match pos + RelPos::new(0, -1, dir) {
+            Some(target) if self.robot_can_move(pos, target) => {
+                self.robot_face(target);
+                self.mark_visited(target);
+                self.robot_step();
+                qlearn::STEP_REWARD * self.tile_cost(target) as f64
+            }
+            _ => qlearn::BUMP_REWARD,
+        };
+        let reached = self.robot_pos == goal;
+        if reached {
+            reward += qlearn::GOAL_REWARD;
+        }
+        self.episodes.steps += 1;
+        self.episodes.reward += reward;
+        let next = (!reached).then_some(self.robot_pos.into());
+        self.q_undo = self
+            .qtable
+            .as_mut()
+            .map(|table| table.update(pos.into(), action, reward, next));
+        let limit = EPISODE_STEPS_PER_TILE * self.stats.borrow().reachable as u32;
+        if !reached && self.episodes.steps < limit {
+            return;
+        }
+        self.episodes.finish(reached);
+        debug_println!(
+            "episode {} ended with reward {}",
+            self.episodes.finished,
+            self.episodes.reward
+        );
+        if self.episodes.finished < EPISODES {
+            self.robot_restart();
+            return;
+        }
+        debug_println!("finished learning");
+        self.finish();
+    }
+
+    /// Take a step towards the next stop of the tour. The way there is
+    /// planned anew at every stop, so it takes shifted walls into account.
+    fn robot_tour(&mut self) {
+        let Some(&stop) = self
+            .tour
+            .as_ref()
+            .and_then(|tour| tour.stops.get(self.tour_next))
+        else {
+            return;
+        };
+        if self.robot_plan.is_empty() {
+            let (path, tree) = self.robot_find_path(true, |pos| pos == stop.into());
+            self.draw_search_tree(&tree);
+            match path {
+                Some(path) => self.robot_plan = path,
+                None => {
+                    debug_println!("waiting for the minotaur to leave");
+                    return;
+                }
+            }
+        }
+        if self
+            .robot_plan
+            .front()
+            .is_some_and(|&next| self.minotaur_near(next))
+        {
+            return;
+        }
+        self.robot_follow_plan();
+        if self.robot_pos != stop.into() {
+            return;
+        }
+        self.tour_next += 1;
+        debug_println!("reached goal {} at {}", self.tour_next, self.robot_pos);
+        if !self.replaying() {
+            self.toasts
+                .borrow_mut()
+                .push(format!("Goal {} reached", self.tour_next));
+        }
+        if self
+            .tour
+            .as_ref()
+            .is_some_and(|tour| self.tour_next < tour.stops.len())
+        {
+            return;
+        }
+        debug_println!("finished tour");
+        self.finish();
+    }
+
+    /// Send out the next iteration of ants. After the last one the robot
+    /// walks the shortest path they found.
+    fn ants_iterate(&mut self) {
+        let (Some(mut colony), Some(goal)) = (self.colony.take(), self.goal) else {
+            return;
+        };
+        // the ants move like the robot, which borrows all of `self`
+        let mut rng = self.rng.clone();
+        let undo = colony.iterate(
+            self.robot_pos.into(),
+            goal.into(),
+            |pos| self.robot_steps(pos, false),
+            &mut rng,
+        );
+        self.rng = rng;
+        self.ants_undo = Some(undo);
+        debug_println!(
+            "iteration {}: {} ants reached the goal",
+            colony.iteration,
+            colony.reached
+        );
+        if colony.iteration == ants::ITERATIONS {
+            if let Some(best) = &colony.best {
+                self.robot_plan = best.iter().map(|step| step.tile.into()).collect();
+            }
+        }
+        self.colony = Some(colony);
+    }
+
+    /// Grow one of the searches from the robot and the goal by a layer and
+    /// draw what it reached. Once they meet the robot walks the path they
+    /// found.
+    fn bidirectional_grow(&mut self) {
+        let Some(mut search) = self.bidirectional.take() else {
+            return;
+        };
+        let undo = search.grow(|pos| self.robot_steps(pos, false));
+        let shade = |side| match side {
+            Side::Start => SearchShade::Start,
+            Side::Goal => SearchShade::Goal,
+        };
+        if search.layers == 1 {
+            self.layer_search = AlphaTileMap::empty(self.layer_bg.width(), self.layer_bg.height());
+        }
+        for &pos in undo.reached() {
+            let Some((side, parent)) = search.reached(pos) else {
+                continue;
+            };
+            self.link_search_tiles(pos, parent, shade(side));
+        }
+        if let (Some(meeting), Some(path)) = (search.meeting, &search.path) {
+            debug_println!(
+                "the searches met at {:?} after {} layers, {} steps apart",
+                meeting,
+                search.layers,
+                path.len()
+            );
+            // join the searches where they met, to the tile next to it on
+            // the path the other one reached
+            if let (Some((side, _)), Some(i)) = (
+                search.reached(meeting),
+                path.iter().position(|&tile| tile == meeting),
+            ) {
+                let before = i.checked_sub(1).map_or(self.robot_pos.into(), |i| path[i]);
+                let other = [Some(before), path.get(i + 1).copied()]
+                    .into_iter()
+                    .flatten()
+                    .find(|&pos| search.reached(pos).is_some_and(|(other, _)| other != side));
+                self.link_search_tiles(meeting, other, shade(side));
+            }
+            self.robot_plan = path.iter().map(|&tile| tile.into()).collect();
+            self.meeting_flash = Some((meeting.into(), Instant::now()));
+        }
+        self.bidirectional_undo = Some(undo);
+        self.bidirectional = Some(search);
+    }
+
+    /// Let the deepening search from the robot make its moves for the tick
+    /// and draw its current sweep, the tiles it left out for the depth
+    /// limit and its way down in their own colors. Once it found the goal
+    /// the robot walks the path.
+    fn deepening_advance(&mut self) {
+        let Some(mut search) = self.deepening.take() else {
+            return;
+        };
+        let sweeps = search.sweeps;
+        let found = search.path.is_some();
+        let undo = search.advance(iddfs::MOVES_PER_TICK, |pos| self.robot_steps(pos, false));
+        if search.sweeps != sweeps {
+            debug_println!("sweep {} down to depth {}", search.sweeps, search.limit);
+        }
+        let way_down: HashSet<Point> = search.way_down().collect();
+        if undo.swept() {
+            self.layer_search = AlphaTileMap::empty(self.layer_bg.width(), self.layer_bg.height());
+            for &pos in search.reached().keys() {
+                self.draw_deepening_tile(&search, &way_down, pos);
+            }
+        } else {
+            // the tiles reached from one that changed get their link from it
+            let touched: HashSet<Point> = undo
+                .touched()
+                .flat_map(|pos| {
+                    let parent = search
+                        .reached()
+                        .get(&pos)
+                        .and_then(|reached| reached.parent);
+                    std::iter::once(pos).chain(parent)
+                })
+                .collect();
+            for pos in touched {
+                self.draw_deepening_tile(&search, &way_down, pos);
+            }
+        }
+        if let Some(path) = &search.path {
+            debug_println!(
+                "found the goal {} steps away after {} sweeps",
+                path.len(),
+                search.sweeps
+            );
+            self.robot_plan = path.iter().map(|&tile| tile.into()).collect();
+            // the moves to each tile of the path, for the annotations
+            if !found {
+                for (moves, &tile) in path.iter().enumerate() {
+                    self.label(tile, &annotations::number(moves as u64 + 1));
+                }
+            }
+        }
+        self.deepening_undo = Some(undo);
+        self.deepening = Some(search);
+    }
+
+    /// Replay the next tile of both searches of the race: the jump to the
+    /// next jump point, and the tile A* expanded, unless a jump went over
+    /// it. Once both are replayed the robot walks the path.
+    fn race_step(&mut self) {
+        let Some(race) = self.race.take() else {
+            return;
+        };
+        let i = self.race_shown;
+        if let Some(&(pos, parent)) = race.astar.get(i) {
+            if self.layer_search[pos].is_none() {
+                self.link_search_tiles(pos, parent, SearchShade::Rival);
+            }
+        }
+        if let Some(&(pos, parent)) = race.jumps.get(i) {
+            // the straight line of tiles jumped over
+            let mut tile = parent.unwrap_or(pos);
+            while tile != pos {
+                let next = (
+                    (tile.0 as i32 + (pos.0 as i32 - tile.0 as i32).signum()) as u16,
+                    (tile.1 as i32 + (pos.1 as i32 - tile.1 as i32).signum()) as u16,
+                );
+                self.link_search_tiles(next, Some(tile), SearchShade::Start);
+                tile = next;
+            }
+            self.layer_search[pos].get_or_insert_default().shade = SearchShade::Jump;
+        }
+        self.race_shown += 1;
+        if self.race_shown == race.ticks() {
+            debug_println!(
+                "{} jump points against {} tiles of a*",
+                race.jumps.len(),
+                race.astar.len()
+            );
+            if let Some(path) = &race.path {
+                self.robot_plan = path.iter().map(|&tile| tile.into()).collect();
+            }
+        }
+        self.race = Some(race);
+    }
+
+    /// Draw `pos` in `layer_search` as far as the iterative deepening
+    /// `search` reached it, linked to the neighbors it was reached from or
+    /// that were reached from it.
+    fn draw_deepening_tile(&mut self, search: &Deepening, way_down: &HashSet<Point>, pos: Point) {
+        let reached = search.reached();
+        let mut tile = reached.get(&pos).map(|reached| SearchTile {
+            shade: match reached.depth == search.limit {
+                true => SearchShade::Limit,
+                false => SearchShade::Start,
+            },
+            ..SearchTile::default()
+        });
+        if let Some(tile) = &mut tile {
+            let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+            for next in pathfinding::neighbors(pos, w, h) {
+                let linked = reached[&pos].parent == Some(next)
+                    || reached.get(&next).is_some_and(|r| r.parent == Some(pos));
+                if let (true, Some(dir)) = (linked, Pos::from(pos).direction_to(next.into())) {
+                    tile.link(dir);
+                }
+            }
+        }
+        if way_down.contains(&pos) {
+            tile.get_or_insert_default().shade = SearchShade::Way;
+        }
+        self.layer_search[pos] = tile;
+    }
+
+    /// Mark `pos` in `layer_search`, linked to `parent` if it is a neighbor.
+    fn link_search_tiles(&mut self, pos: Point, parent: Option<Point>, shade: SearchShade) {
+        let node = self.layer_search[pos].get_or_insert_default();
+        node.shade = shade;
+        let Some(dir) = parent.and_then(|parent| Pos::from(pos).direction_to(parent.into())) else {
+            return;
+        };
+        node.link(dir);
+        if let Some(parent) = parent {
+            self.layer_search[parent]
+                .get_or_insert_default()
+                .link(dir.opposite());
+        }
+    }
+
+    /// Move every robot of the swarm one step. Robots within talking range
+    /// share what they explored first. Those that have nowhere to go, or
+    /// that learned their target was explored by another robot, head for
+    /// the closest tile they don't know was explored that the others in
+    /// range leave to them. The run ends once none of them has anything
+    /// left to explore.
+    fn swarm_tick(&mut self) {
+        let groups = self.swarm_sync();
+        let group_of = |robot: usize| {
+            groups
+                .iter()
+                .copied()
+                .find(|group| group & (1 << robot) != 0)
+                .unwrap_or_default()
+        };
+        let stale = |app: &Self, robot: usize, plan: &VecDeque<Pos>| {
+            plan.back()
+                .is_none_or(|&target| app.layer_known[target.into()] & (1 << robot) != 0)
+        };
+        if stale(self, 0, &self.robot_plan) {
+            self.robot_plan = self
+                .swarm_plan(0, self.robot_pos, group_of(0))
+                .unwrap_or_default();
+        }
+        for i in 0..self.helpers.len() {
+            if stale(self, i + 1, &self.helpers[i].plan) {
+                self.helpers[i].plan = self
+                    .swarm_plan(i + 1, self.helpers[i].pos, group_of(i + 1))
+                    .unwrap_or_default();
+            }
+        }
+        if self.robot_plan.is_empty() && self.helpers.iter().all(|h| h.plan.is_empty()) {
+            let unexplored = pathfinding::bfs(
+                (self.layer_bg.width(), self.layer_bg.height()),
+                self.robot_pos.into(),
+                |pos| self.robot_steps(pos, false),
+                |pos| self.layer_visited[pos].is_none(),
+            );
+            if unexplored.is_some() {
+                debug_println!("waiting for the minotaur to leave");
+                return;
+            }
+            debug_println!("swarm finished");
+            self.finish();
+            return;
+        }
+        if let Some(&next) = self.robot_plan.front() {
+            if !self.minotaur_near(next) {
+                self.robot_follow_plan();
+                self.layer_known[next.into()] |= 1;
+                self.layer_known[self.robot_pos.into()] |= 1;
+            }
+        }
+        for i in 0..self.helpers.len() {
+            self.helper_step(i);
+        }
+    }
+
+    /// Where the robots of the swarm are, the main robot first.
+    fn swarm_positions(&self) -> Vec<Pos> {
+        std::iter::once(self.robot_pos)
+            .chain(self.helpers.iter().map(|helper| helper.pos))
+            .collect()
+    }
+
+    /// Let the robots in each group that can talk tell each other what they
+    /// explored, and return the groups.
+    fn swarm_sync(&mut self) -> Vec<u8> {
+        let groups = swarm::groups(&self.swarm_positions(), self.config.comm_radius);
+        for y in 0..self.layer_known.height() {
+            for x in 0..self.layer_known.width() {
+                let known = &mut self.layer_known[(x, y)];
+                for &group in &groups {
+                    if *known & group != 0 {
+                        *known |= group;
+                    }
+                }
+            }
+        }
+        groups
+    }
+
+    /// The way from `from` to the closest tile robot `robot` doesn't know
+    /// was explored that isn't left to another robot of its `group`, or
+    /// else to any such tile. `robot` is 0 for the main robot and `i + 1`
+    /// for helper `i`.
+    fn swarm_plan(&self, robot: usize, from: Pos, group: u8) -> Option<VecDeque<Pos>> {
+        let targets: Vec<Pos> = std::iter::once(&self.robot_plan)
+            .chain(self.helpers.iter().map(|helper| &helper.plan))
+            .enumerate()
+            .filter(|&(i, _)| i != robot && group & (1 << i) != 0)
+            .filter_map(|(_, plan)| plan.back().copied())
+            .collect();
+        let search = |goal: &dyn Fn(Pos) -> bool| {
+            let (path, _) = pathfinding::dijkstra(
+                (self.layer_bg.width(), self.layer_bg.height()),
+                from.into(),
+                |pos| self.robot_steps(pos, true),
+                |pos| Pos::from(pos) != from && goal(pos.into()),
+            );
+            path.map(|path| path.steps.iter().map(|step| step.tile.into()).collect())
+        };
+        let unexplored = |pos: Pos| self.layer_known[pos.into()] & (1 << robot) == 0;
+        search(&|pos| unexplored(pos) && !swarm::claimed(&targets, pos))
+            .or_else(|| search(&unexplored))
+    }
+
+    /// Take one step along the plan of helper `i`, unless the minotaur is in
+    /// the way.
+    fn helper_step(&mut self, i: usize) {
+        if self.helpers[i].busy > 0 {
+            self.helpers[i].busy -= 1;
+            return;
+        }
+        let Some(&next) = self.helpers[i].plan.front() else {
+            return;
+        };
+        if self.minotaur_near(next) {
+            return;
+        }
+        let helper = &mut self.helpers[i];
+        helper.plan.pop_front();
+        // helpers turn without a word, so their turns are counted here
+        if let Some(dir) = helper.pos.direction_to(next) {
+            let turns = match dir {
+                _ if dir == helper.dir => 0,
+                _ if dir == helper.dir.opposite() => 2,
+                _ => 1,
+            };
+            helper.dir = dir;
+            helper.turn_debt += turns * helper.chassis.turn_cost();
+            self.stats.borrow_mut().turns += turns as u64;
+        }
+        let turning = charge_turns(&mut helper.turn_debt);
+        helper.busy = turning;
+        self.stats.borrow_mut().turn_ticks += turning as u64;
+        // the plan only leads through doors the swarm has the key for
+        if let BackgroundTile::Door(color) = self.layer_bg[next.into()] {
+            debug_println!("helper {} unlocked {:?} door at {}", i + 1, color, next);
+            self.layer_bg[next.into()] = BackgroundTile::Free;
+        }
+        self.mark_visited(next);
+        self.pick_up(next);
+        let to = match self.layer_bg[next.into()] {
+            BackgroundTile::Teleporter(twin) => twin.into(),
+            _ => next,
+        };
+        self.mark_visited(to);
+        self.layer_known[next.into()] |= 1 << (i + 1);
+        self.layer_known[to.into()] |= 1 << (i + 1);
+        self.helpers[i].pos = to;
+        self.stats.borrow_mut().steps += 1;
+    }
+
+    /// End the run without the robot having been caught.
+    fn finish(&mut self) {
+        self.stats.borrow_mut().clock.pause();
+        if self.saves_run() {
+            self.record_run();
+            self.export_run();
+            self.save_recording();
+        }
+        self.emit(SimEvent::Finish { caught: false });
+        self.screen = Screen::Finished;
+    }
+
+    /// Whether the run has a coverage goal and visited enough of the maze
+    /// for it.
+    fn coverage_goal_reached(&self) -> bool {
+        let goal = self.config.coverage_goal;
+        goal > 0
+            && self
+                .stats
+                .borrow()
+                .coverage()
+                .is_some_and(|c| c * 100.0 >= goal as f64)
+    }
+
+    /// Put the robot back at the entrance, facing into the maze.
+    fn robot_restart(&mut self) {
+        if let Some(ForegroundTile::Robot) = self.layer_fg[self.robot_pos.into()] {
+            self.layer_fg[self.robot_pos.into()] = None;
+        }
+        self.robot_pos = self.entrance;
+        self.robot_dir = self.entrance_dir;
+        self.layer_fg[self.robot_pos.into()] = Some(ForegroundTile::Robot);
+    }
+
+    /// The learned values or the ants' pheromone as a heat map, from the
+    /// best (near) to the worst (far) tile, with the goal marked. `None` if
+    /// the solver doesn't learn.
+    fn heatmap(&self) -> Option<AlphaTileMap<DistanceTile>> {
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        let mut heatmap = AlphaTileMap::empty(w, h);
+        if let Some(table) = &self.qtable {
+            if let Some((lo, hi)) = table.range() {
+                for y in 0..h {
+                    for x in 0..w {
+                        heatmap[(x, y)] = table.value((x, y)).map(|v| {
+                            let t = if hi > lo { (hi - v) / (hi - lo) } else { 0.0 };
+                            DistanceTile::Away((t * 255.0) as u8)
+                        });
+                    }
+                }
+            }
+        } else if let Some(colony) = &self.colony {
+            let max = colony.max();
+            for y in 0..h {
+                for x in 0..w {
+                    // nearly evaporated trails fade out
+                    let share = colony.pheromone((x, y)) / max;
+                    heatmap[(x, y)] = (share > PHEROMONE_VISIBLE)
+                        .then_some(DistanceTile::Away(((1.0 - share) * 255.0) as u8));
+                }
+            }
+        } else {
+            return None;
+        }
+        if let Some(goal) = self.goal {
+            heatmap[goal.into()] = Some(DistanceTile::Goal);
+        }
+        Some(heatmap)
+    }
+
+    /// The tiles the robot entered, by how often it did, while the revisit
+    /// heatmap is shown. `None` on an infinite run.
+    fn revisits(&self) -> Option<Ref<'_, Visits>> {
+        (self.show_revisits && self.world.is_none()).then(|| self.visits.borrow())
+    }
+
+    /// Number of explored tiles next to `pos`, not counting the robot's.
+    fn explored_neighbors(&self, pos: Pos) -> usize {
+        pathfinding::neighbors(pos.into(), self.layer_bg.width(), self.layer_bg.height())
+            .map(Pos::from)
+            .filter(|&next| next != self.robot_pos && self.layer_visited[next.into()].is_some())
+            .count()
+    }
+
+    /// Whether `pos` is on the map, which the tiles next to an opening of
+    /// the outer wall aren't.
+    fn on_map(&self, pos: Pos) -> bool {
+        pos.x < self.layer_bg.width() as usize && pos.y < self.layer_bg.height() as usize
+    }
+
+    /// Whether the robot can enter `pos` with the keys it carries.
+    fn robot_can_enter(&self, pos: Pos) -> bool {
+        if !self.on_map(pos) {
+            return false;
+        }
+        match self.layer_bg[pos.into()] {
+            BackgroundTile::Wall => false,
+            BackgroundTile::Door(color) => self.robot_keys.contains(&color),
+            BackgroundTile::Free | BackgroundTile::Teleporter(_) | BackgroundTile::OneWay(_) => {
+                true
+            }
+        }
+    }
+
+    /// Whether the robot can step from `from` into its neighbor `to`.
+    fn robot_can_move(&self, from: Pos, to: Pos) -> bool {
+        self.robot_can_enter(to) && !one_way_blocks(&self.layer_bg, from, to)
+    }
+
+    /// Plan a path through explored tiles to the nearest unexplored tile the
+    /// robot can enter, e.g. a door it found the key for in the meantime.
+    ///
+    /// Returns false if there is no such tile.
+    fn robot_plan_to_frontier(&mut self) -> bool {
+        // the search stops at the first unexplored tile, so it never leads
+        // through one
+        let (path, tree) =
+            self.robot_find_path(true, |pos| self.layer_visited[pos.into()].is_none());
+        self.draw_search_tree(&tree);
+        match path {
+            Some(path) => {
+                self.robot_plan = path;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Plan the way back to the next checkpoint if the robot has been there
+    /// before, passing it out of order. The exploration starts over from
+    /// there, with the frontier nearest to it.
+    fn robot_plan_to_checkpoint(&mut self) -> bool {
+        let Some(next) = self.course.as_ref().and_then(Course::next) else {
+            return false;
+        };
+        if self.layer_visited[next].is_none() || self.robot_pos == next.into() {
+            return false;
+        }
+        let (path, tree) = self.robot_find_path(true, |pos| pos == next.into());
+        let Some(path) = path else {
+            return false;
+        };
+        self.draw_search_tree(&tree);
+        for pos in self.robot_stack.drain(..) {
+            if let Some(ForegroundTile::Stack) = self.layer_fg[pos.into()] {
+                self.layer_fg[pos.into()] = None;
+            }
+        }
+        self.robot_plan = path;
+        true
+    }
+
+    /// The moves the robot can make from `pos` with the keys it carries.
+    /// With `avoid_minotaur` it keeps clear of the minotaur.
+    fn robot_steps(&self, pos: Point, avoid_minotaur: bool) -> Vec<Step> {
+        let from = Pos::from(pos);
+        let grid = |pos: Point| {
+            pathfinding::neighbors(pos, self.layer_bg.width(), self.layer_bg.height())
+                .map(Pos::from)
+                .filter(|&next| self.robot_can_move(from, next))
+                .filter(|&next| !(avoid_minotaur && self.minotaur_near(next)))
+                .map(|next| Step {
+                    tile: next.into(),
+                    to: next.into(),
+                    cost: self.tile_cost(next),
+                })
+                .collect()
+        };
+        map_steps(&self.layer_bg, pos, grid)
+    }
+
+    /// The cheapest path to a position for which `goal` holds, as the tiles
+    /// to step into, and the tree of the search for it. With
+    /// `avoid_minotaur` it keeps clear of the minotaur.
+    fn robot_find_path(
+        &self,
+        avoid_minotaur: bool,
+        goal: impl Fn(Pos) -> bool,
+    ) -> (Option<VecDeque<Pos>>, SearchTree) {
+        let (path, tree) = pathfinding::dijkstra(
+            (self.layer_bg.width(), self.layer_bg.height()),
+            self.robot_pos.into(),
+            |pos| self.robot_steps(pos, avoid_minotaur),
+            |pos| Pos::from(pos) != self.robot_pos && goal(pos.into()),
+        );
+        debug_println!(
+            "expanded {} tiles, planned {:?} steps to {:?}",
+            tree.len(),
+            path.as_ref().map(|path| path.steps.len()),
+            path.as_ref().and_then(|path| path.end())
+        );
+        let path = path.map(|path| path.steps.iter().map(|step| step.tile.into()).collect());
+        (path, tree)
+    }
+
+    /// Replace `layer_search` with the lines of `tree`.
+    fn draw_search_tree(&mut self, tree: &SearchTree) {
+        self.layer_search = AlphaTileMap::empty(self.layer_bg.width(), self.layer_bg.height());
+        for &(tile, parent) in tree {
+            let node = self.layer_search[tile].get_or_insert_default();
+            let Some(parent) = parent else {
+                continue;
+            };
+            // teleports leave a gap in the tree
+            let Some(dir) = Pos::from(tile).direction_to(parent.into()) else {
+                continue;
+            };
+            node.link(dir);
+            self.layer_search[parent]
+                .get_or_insert_default()
+                .link(dir.opposite());
+        }
+    }
+
+    /// Take one step along `robot_plan`.
+    fn robot_follow_plan(&mut self) {
+        let Some(next) = self.robot_plan.pop_front() else {
+            return;
+        };
+        self.robot_face(next);
+        self.mark_visited(next);
+        self.robot_step();
+    }
+
+    /// Note a decision of the robot for the thoughts pane, and in the log.
+    fn think(&mut self, thought: Thought) {
+        debug_println!("{}", thought);
+        self.thoughts.push(self.ticks(), thought);
+    }
+
+    /// The subscribers every app starts out with: the parts of the app that
+    /// follow the run, in a fixed order, and the log.
+    fn default_bus(&self) -> EventBus {
+        let mut bus = EventBus::default();
+        bus.subscribe("stats", self.stats.clone());
+        bus.subscribe("tracker", self.tracker.clone());
+        bus.subscribe("visits", self.visits.clone());
+        bus.subscribe("hint", self.hot_cold.clone());
+        let toaster = Toaster::new(self.toasts.clone(), self.tracker.clone());
+        bus.subscribe("toasts", toaster);
+        bus.subscribe("log", |tick: u64, event: &SimEvent| {
+            if *event != SimEvent::Tick {
+                debug_println!("tick {}: {:?}", tick, event);
+            }
+            Ok(())
+        });
+        bus
+    }
+
+    /// Publish `event` of the current tick to the subscribers on the bus.
+    fn emit(&mut self, event: SimEvent) {
+        let tick = self.ticks();
+        if self.hit_breakpoint.is_none() {
+            self.hit_breakpoint = self.breakpoints.iter().copied().find(|b| b.hit(&event));
+        }
+        for (name, e) in self.bus.publish(tick, &event) {
+            debug_println!("unsubscribed {} from events: {}", name, e);
+        }
+    }
+
+    fn on_tick(&mut self) {
+        self.end_walk();
+        let before = self.shadow.is_some().then(|| self.sim_state());
+        self.stream_chunks();
+        self.stats.borrow_mut().ticks += 1;
+        self.emit(SimEvent::Tick);
+        let interval = self.config.shift_interval;
+        if interval > 0 && self.world.is_none() && self.ticks().is_multiple_of(interval) {
+            self.shift_walls();
+        }
+        self.run_hooks(Phase::Pre);
+        self.robot_tick();
+        let tick = self.ticks();
+        if let Some(track) = &mut self.track {
+            track.record(tick, self.robot_pos.into());
+        }
+        self.trail.visit(self.robot_pos.into());
+        if self.screen != Screen::Finished && self.coverage_goal_reached() {
+            debug_println!("covered {}% of the maze", self.config.coverage_goal);
+            self.finish();
+        }
+        if self.screen != Screen::Finished && self.course.as_ref().is_some_and(Course::is_done) {
+            debug_println!("passed all checkpoints");
+            self.finish();
+        }
+        if self.screen != Screen::Finished && self.ticks().is_multiple_of(MINOTAUR_PERIOD) {
+            self.minotaur_move();
+        }
+        self.run_hooks(Phase::Post);
+        if self.screen != Screen::Finished {
+            self.record_frame();
+        }
+        match before {
+            Some(state) => self.remember_tick(state),
+            // nothing to undo the changes of the tick in
+            None => {
+                self.memory_undo.clear();
+                self.notes_undo.clear();
+            }
+        }
+        if let Some(breakpoint) = self.hit_breakpoint.take() {
+            if self.screen == Screen::Running {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+                self.status_message = Some(StatusMessage::Info(format!(
+                    "paused at breakpoint: {}",
+                    breakpoint
+                )));
+            }
+        }
+        self.check_watches();
+        if self.replay_end == Some(self.ticks()) {
+            self.replay_end = None;
+            if self.screen == Screen::Running {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+            }
+            self.toasts.borrow_mut().push("End of replay");
+        }
+        for (name, e) in self.bus.flush() {
+            debug_println!("unsubscribed {} from events: {}", name, e);
+        }
+    }
+
+    /// What the watches look at after the current tick.
+    fn probe(&self) -> Probe {
+        Probe {
+            stack_depth: self.robot_stack.len(),
+            revisited: self.tracker.borrow().revisited,
+            coverage: self.stats.borrow().coverage(),
+        }
+    }
+
+    /// Pause if a watch started to hold during the current tick.
+    fn check_watches(&mut self) {
+        let probe = self.probe();
+        let mut triggered = None;
+        for (watch, held) in &mut self.watches {
+            let holds = watch.holds(&probe);
+            if holds && !*held && triggered.is_none() {
+                triggered = Some(*watch);
+            }
+            *held = holds;
+        }
+        if let Some(watch) = triggered {
+            if self.screen == Screen::Running {
+                self.stats.borrow_mut().clock.pause();
+                self.screen = Screen::Paused;
+                self.status_message =
+                    Some(StatusMessage::Info(format!("paused by watch: {}", watch)));
+            }
+        }
+    }
+
+    fn sim_state(&self) -> SimState {
+        SimState {
+            rng: self.rng.clone(),
+            goal: self.goal,
+            robot_pos: self.robot_pos,
+            robot_dir: self.robot_dir,
+            robot_keys: self.robot_keys.clone(),
+            robot_plan: self.robot_plan.clone(),
+            robot_busy: self.robot_busy,
+            turn_debt: self.turn_debt,
+            minotaur: self.minotaur,
+            minotaur_prev: self.minotaur_prev,
+            minotaur_target: self.minotaur_target,
+            caught: self.caught,
+            stats: self.stats.borrow().clone(),
+            episodes: self.episodes,
+            tour_next: self.tour_next,
+            race_shown: self.race_shown,
+            course: self.course.clone(),
+            trail: self.trail.clone(),
+            helpers: self.helpers.clone(),
+            tracker: self.tracker.borrow().clone(),
+            hot_cold: self.hot_cold.borrow().clone(),
+        }
+    }
+
+    fn layers(&self) -> Layers {
+        Layers {
+            bg: self.layer_bg.clone(),
+            terrain: self.layer_terrain.clone(),
+            visited: self.layer_visited.clone(),
+            items: self.layer_items.clone(),
+            fg: self.layer_fg.clone(),
+            distance: self.layer_distance.clone(),
+            search: self.layer_search.clone(),
+            known: self.layer_known.clone(),
+            stack: self.robot_stack.clone(),
+        }
+    }
+
+    /// Start the history over from the current tick, after the run was
+    /// changed between ticks where no tick can undo it.
+    fn forget_history(&mut self) {
+        if self.shadow.is_some() {
+            self.history.clear();
+            self.furthest_tick = self.ticks();
+            self.shadow = Some(self.layers());
+        }
+    }
+
+    /// Add the tick that just ran, which started from `state`, to the history.
+    fn remember_tick(&mut self, state: SimState) {
+        let tick = self.ticks();
+        let Some(shadow) = &mut self.shadow else {
+            return;
+        };
+        self.furthest_tick = self.furthest_tick.max(tick);
+        let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+        self.history.push(TickDelta {
+            state,
+            bg: LayerDelta::diff(&mut shadow.bg, &self.layer_bg, w, h),
+            terrain: LayerDelta::diff(&mut shadow.terrain, &self.layer_terrain, w, h),
+            visited: LayerDelta::diff(&mut shadow.visited, &self.layer_visited, w, h),
+            items: LayerDelta::diff(&mut shadow.items, &self.layer_items, w, h),
+            fg: LayerDelta::diff(&mut shadow.fg, &self.layer_fg, w, h),
+            distance: LayerDelta::diff(&mut shadow.distance, &self.layer_distance, w, h),
+            search: LayerDelta::diff(&mut shadow.search, &self.layer_search, w, h),
+            known: LayerDelta::diff(&mut shadow.known, &self.layer_known, w, h),
+            stack: StackDelta::diff(&mut shadow.stack, &self.robot_stack),
+            memory: std::mem::take(&mut self.memory_undo),
+            notes: std::mem::take(&mut self.notes_undo),
+            visits: self.visits.borrow_mut().take_undo(),
+            q: self.q_undo.take(),
+            ants: self.ants_undo.take(),
+            bidirectional: self.bidirectional_undo.take(),
+            deepening: self.deepening_undo.take(),
+        });
+    }
+
+    /// Undo the last tick and pause, or tell the user there's nothing to undo.
+    fn rewind(&mut self) {
+        let (Some(shadow), Some(delta)) = (&mut self.shadow, self.history.pop()) else {
+            self.status_message = Some(StatusMessage::Error("nothing to rewind".to_string()));
+            return;
+        };
+        delta.bg.undo(&mut self.layer_bg, &mut shadow.bg);
+        delta
+            .terrain
+            .undo(&mut self.layer_terrain, &mut shadow.terrain);
+        delta
+            .visited
+            .undo(&mut self.layer_visited, &mut shadow.visited);
+        delta.items.undo(&mut self.layer_items, &mut shadow.items);
+        delta.fg.undo(&mut self.layer_fg, &mut shadow.fg);
+        delta
+            .distance
+            .undo(&mut self.layer_distance, &mut shadow.distance);
+        delta
+            .search
+            .undo(&mut self.layer_search, &mut shadow.search);
+        delta.known.undo(&mut self.layer_known, &mut shadow.known);
+        delta.stack.undo(&mut self.robot_stack, &mut shadow.stack);
+        for (rel, been) in delta.memory.into_iter().rev() {
+            self.robot_memory[rel] = been;
+        }
+        for (pos, label) in delta.notes.into_iter().rev() {
+            self.notes.restore(pos, label);
+        }
+        self.visits.borrow_mut().undo(delta.visits);
+        let state = delta.state;
+        self.rng = state.rng;
+        self.goal = state.goal;
+        self.robot_pos = state.robot_pos;
+        self.robot_dir = state.robot_dir;
+        self.robot_keys = state.robot_keys;
+        self.robot_plan = state.robot_plan;
+        self.robot_busy = state.robot_busy;
+        self.turn_debt = state.turn_debt;
+        self.minotaur = state.minotaur;
+        self.minotaur_prev = state.minotaur_prev;
+        self.minotaur_target = state.minotaur_target;
+        self.caught = state.caught;
+        self.stats.replace(state.stats);
+        self.episodes = state.episodes;
+        self.tour_next = state.tour_next;
+        self.race_shown = state.race_shown;
+        self.course = state.course;
+        self.trail = state.trail;
+        self.helpers = state.helpers;
+        self.tracker.replace(state.tracker);
+        self.hot_cold.replace(state.hot_cold);
+        self.thoughts.forget_after(self.ticks());
+        // forgets where the robot went after
+        let tick = self.ticks();
+        if let Some(track) = &mut self.track {
+            track.record(tick, self.robot_pos.into());
+        }
+        if let (Some(table), Some(undo)) = (&mut self.qtable, delta.q) {
+            table.undo(undo);
+        }
+        if let (Some(colony), Some(undo)) = (&mut self.colony, delta.ants) {
+            colony.undo(undo);
+        }
+        if let (Some(search), Some(undo)) = (&mut self.bidirectional, delta.bidirectional) {
+            search.undo(undo);
+        }
+        if let (Some(search), Some(undo)) = (&mut self.deepening, delta.deepening) {
+            search.undo(undo);
+        }
+        self.stats.borrow_mut().clock.pause();
+        self.stack_selected = None;
+        self.screen = Screen::Paused;
+    }
+
+    /// The current tick of the run.
+    fn ticks(&self) -> u64 {
+        self.stats.borrow().ticks
+    }
+
+    /// Whether the current tick was reached before and then rewound, so the
+    /// end of the run has already been recorded.
+    fn replaying(&self) -> bool {
+        self.ticks() <= self.furthest_tick
+    }
+
+    /// Whether the end of the run is to be saved, which it isn't without a
+    /// screen or when it has already been.
+    fn saves_run(&self) -> bool {
+        !self.headless && !self.replaying()
+    }
+
+    /// The first tick that can be rewound to.
+    fn earliest_tick(&self) -> u64 {
+        self.ticks() - self.history.len() as u64
+    }
+
+    /// The ticks the timeline goes from and to: all of a replay playing,
+    /// or else the history.
+    fn timeline_range(&self) -> (u64, u64) {
+        match &self.playing {
+            Some(replay) => (0, replay.ticks),
+            None => (self.earliest_tick(), self.furthest_tick),
+        }
+    }
+
+    /// Rewind or replay the run until it is at `tick`, as far as the history
+    /// and the run allow, and pause it. A replay playing is played again
+    /// from the start to get to ticks before the history.
+    fn seek(&mut self, tick: u64) {
+        if self.shadow.is_none() && self.playing.is_none() {
+            self.status_message = Some(StatusMessage::Error("no history to seek in".to_string()));
+            return;
+        }
+        let (start, end) = self.timeline_range();
+        let tick = tick.clamp(start, end);
+        if tick < self.earliest_tick() {
+            if let Some(replay) = self.playing.clone() {
+                // the run started over isn't the ghost of itself
+                let ghost = self.ghost.take();
+                let played = self.play_replay(replay);
+                self.ghost = ghost;
+                if let Err(e) = played {
+                    self.status_message = Some(StatusMessage::Error(e));
+                    return;
+                }
+            }
+        }
+        while self.ticks() > tick {
+            self.rewind();
+        }
+        if self.screen == Screen::Running {
+            self.stats.borrow_mut().clock.pause();
+            self.screen = Screen::Paused;
+        }
+        while self.ticks() < tick && self.screen == Screen::Paused {
+            self.on_tick();
+        }
+    }
+
+    fn robot_tick(&mut self) {
+        if self.robot_busy > 0 {
+            self.robot_busy -= 1;
+            return;
+        }
+        if self.qtable.is_some() {
+            self.robot_learn();
+            return;
+        }
+        if self.tour.is_some() {
+            self.robot_tour();
+            return;
+        }
+        if !self.helpers.is_empty() {
+            self.swarm_tick();
+            return;
+        }
+        if self
+            .colony
+            .as_ref()
+            .is_some_and(|colony| colony.iteration < ants::ITERATIONS)
+        {
+            self.ants_iterate();
+            return;
+        }
+        if self
+            .bidirectional
+            .as_ref()
+            .is_some_and(|search| !search.is_done())
+        {
+            self.bidirectional_grow();
+            return;
+        }
+        if self.deepening.as_ref().is_some_and(|search| !search.done) {
+            self.deepening_advance();
+            return;
+        }
+        if self
+            .race
+            .as_ref()
+            .is_some_and(|race| self.race_shown < race.ticks())
+        {
+            self.race_step();
+            return;
+        }
+        if let Some(&next) = self.robot_plan.front() {
+            // wait for the minotaur to clear the way
+            if !self.minotaur_near(next) {
+                self.robot_follow_plan();
+            }
+            return;
+        }
+        if self.colony.is_some() {
+            debug_println!("walked the ants' best path");
+            self.finish();
+            return;
+        }
+        if self.bidirectional.is_some() {
+            debug_println!("walked the path the searches met on");
+            self.finish();
+            return;
+        }
+        if self.deepening.is_some() {
+            debug_println!("walked the path the deepening search found");
+            self.finish();
+            return;
+        }
+        if self.race.is_some() {
+            debug_println!("walked the path the jump point search found");
+            self.finish();
+            return;
+        }
+        if self.robot_plan_to_checkpoint() {
+            self.robot_follow_plan();
+            return;
+        }
+        let scan = self.robot_scan();
+        let right = scan[5];
+        let front = scan[1];
+        let left = scan[3];
+        let back = scan[7];
+        // a tile off the map, next to an opening, is scanned as a wall, so
+        // it never gets picked
+        let coords_at = |offset| self.robot_pos_with_offset(offset).unwrap_or(self.robot_pos);
+        let front_coords = coords_at((0, -1));
+        let left_coords = coords_at((-1, 0));
+        let right_coords = coords_at((1, 0));
+        let back_coords = coords_at((0, 1));
+
+        // behind is usually where we came from, except right after a teleport
+        let unexplored = |scanned: u8, coords: Pos| {
+            scanned == b'.' && !self.remembers(coords) && !self.minotaur_near(coords)
+        };
+        let coords = [front_coords, right_coords, left_coords, back_coords];
+        let free = [
+            unexplored(front, front_coords),
+            unexplored(right, right_coords),
+            unexplored(left, left_coords),
+            unexplored(back, back_coords),
+        ];
+        // prefer the best rated tiles, by default the cheapest ground
+        let scores: [Option<f64>; 4] = std::array::from_fn(|i| {
+            free[i].then(|| {
+                self.weights.score(
+                    self.tile_cost(coords[i]),
+                    self.goal.map(|goal| goal.distance(coords[i])),
+                    self.explored_neighbors(coords[i]),
+                )
+            })
+        });
+        self.think(Thought::Scanned {
+            pos: self.robot_pos.into(),
+            dir: self.robot_dir,
+            unexplored: scores.iter().flatten().count() as u8,
+        });
+        let best = scores.iter().flatten().copied().reduce(f64::min);
+        let free: [bool; 4] = std::array::from_fn(|i| scores[i].is_some() && scores[i] == best);
+        if free.iter().any(|f| *f) {
+            let choice = match self.weights.tie_break {
+                weights::TieBreak::Random => select_idx(&free[..], &mut self.rng),
+                weights::TieBreak::Fixed => free.iter().position(|f| *f).unwrap(),
+            };
+            match choice {
+                0 => {
+                    self.think(Thought::Moved {
+                        turn: Turn::Front,
+                        to: front_coords.into(),
+                    });
+                    self.mark_visited(front_coords);
+                    self.robot_stack_push(self.robot_pos);
+                    self.robot_step();
+                }
+                1 => {
+                    self.think(Thought::Moved {
+                        turn: Turn::Right,
+                        to: right_coords.into(),
+                    });
+                    self.mark_visited(right_coords);
+                    self.robot_stack_push(self.robot_pos);
+                    self.robot_turn_right();
+                    self.robot_step();
+                }
+                2 => {
+                    self.think(Thought::Moved {
+                        turn: Turn::Left,
+                        to: left_coords.into(),
+                    });
+                    self.mark_visited(left_coords);
+                    self.robot_stack_push(self.robot_pos);
+                    self.robot_turn_left();
+                    self.robot_step();
+                }
+                3 => {
+                    self.think(Thought::Moved {
+                        turn: Turn::Back,
+                        to: back_coords.into(),
+                    });
+                    self.mark_visited(back_coords);
+                    self.robot_stack_push(self.robot_pos);
+                    self.robot_turn_right();
+                    self.robot_turn_right();
+                    self.robot_step();
+                }
+                _ => unreachable!(),
+            }
+        } else {
+            self.think(Thought::DeadEnd);
+            if self
+                .robot_stack
+                .last()
+                .is_some_and(|&back| self.minotaur_near(back))
+            {
+                self.think(Thought::Waiting);
+                return;
+            }
+            // backtrack
+            let back = match self.robot_stack_pop() {
+                Some(it) => it,
+                None if self.robot_plan_to_frontier() => {
+                    self.robot_follow_plan();
+                    return;
+                }
+                None if pathfinding::bfs(
+                    (self.layer_bg.width(), self.layer_bg.height()),
+                    self.robot_pos.into(),
+                    |pos| self.robot_steps(pos, false),
+                    |pos| self.layer_visited[pos].is_none(),
+                )
+                .is_some() =>
+                {
+                    self.think(Thought::Waiting);
+                    return;
+                }
+                None => {
+                    self.think(Thought::Finished);
+                    self.finish();
+                    return;
+                }
+            };
+            self.stats.borrow_mut().backtracks += 1;
+            self.think(Thought::Backtracked { to: back.into() });
+            if !self.robot_pos.is_neighbor(back) {
+                // we arrived here by teleporter, so leave the same way and
+                // look around the twin before backtracking any further
+                match self.layer_bg[self.robot_pos.into()] {
+                    BackgroundTile::Teleporter(twin) if Pos::from(twin).is_neighbor(back) => {
+                        self.robot_teleport(twin.into());
+                        self.robot_stack_push(back);
+                        return;
+                    }
+                    _ => {}
+                }
+            }
+            if !self.robot_pos.is_neighbor(back) || !self.robot_can_move(self.robot_pos, back) {
+                // we came through a one-way passage, or the walls shifted
+                // since, so go around
+                let (path, tree) = self.robot_find_path(true, |pos| pos == back);
+                self.draw_search_tree(&tree);
+                match path {
+                    Some(path) => {
+                        self.robot_plan = path;
+                        self.robot_follow_plan();
+                    }
+                    // the minotaur is in the way, try again later
+                    None => self.robot_stack_push(back),
+                }
+                return;
+            }
+            self.robot_face(back);
+            self.robot_step();
+        }
+    }
+}
+
+fn select_idx<R: Rng + ?Sized>(values: &[bool], rng: &mut R) -> usize {
+    let ntrue = values.iter().copied().filter(|t| *t).count();
+    if ntrue == 0 {
+        panic!("ntrue == 0");
+    }
+    let n = rng.random_range(0..ntrue);
+    let mut m = 0;
+    let mut idx = 0;
+    loop {
+        if values[idx] {
+            if n == m {
+                break idx;
+            }
+            m += 1;
+        }
+        idx += 1;
+    }
+}
+
+impl Widget for &mut App {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [main_area, status_area] = screen_layout(area);
+        match self.screen {
+            Screen::Menu => self.menu.render(main_area, buf),
+            Screen::Leaderboard => self.leaderboard.render(main_area, buf),
+            Screen::Evolution => {
+                if let Some(evolution) = &self.evolution {
+                    evolution.render(main_area, buf);
+                }
+            }
+            Screen::Batch => {
+                if let Some(batch) = &self.batch {
+                    batch.render(main_area, buf);
+                }
+            }
+            Screen::Gallery => {
+                if let Some(gallery) = &self.gallery {
+                    gallery.render(main_area, buf);
+                }
+            }
+            Screen::Settings => self.settings_form.render(main_area, buf),
+            _ => self.render_run(main_area, buf),
+        }
+        self.render_status(status_area, buf);
+        self.toasts.borrow().render(main_area, buf);
+        if let Some((_, dialog)) = &self.dialog {
+            dialog.render(main_area, buf);
+        }
+        if self.show_help {
+            Help::new(self.keybindings(), self.settings()).render(area, buf);
+        }
+        if self.ascii {
+            ascii::downgrade(buf);
+        }
+    }
+}
+
+impl App {
+    fn render_run(&mut self, area: Rect, buf: &mut Buffer) {
+        let panes = self.pane_areas(area);
+        let map_area = pane_area(&panes, Pane::Map).unwrap_or(area);
+        self.map_area = map_area;
+        self.update_camera(map_area);
+        if let Some(walk) = &self.walk {
+            self.render_walk(walk, map_area, buf);
+        } else if self.zoomed_out {
+            self.render_overview(map_area, buf);
+        } else {
+            self.render_layers(map_area, buf);
+        }
+        if self.show_minimap
+            && !self.layout.contains(Pane::Minimap)
+            && !self.zoomed_out
+            && self.walk.is_none()
+        {
+            self.render_minimap(map_area, buf);
+        }
+        if self.show_timings {
+            self.render_timings(map_area, buf);
+        }
+        if self.show_weights {
+            self.render_weights(map_area, buf);
+        }
+        for &(pane, pane_area) in &panes {
+            match pane {
+                Pane::Map => {}
+                Pane::Stats => self.render_info(pane_area, buf),
+                Pane::Stack => self.render_stack(pane_area, buf),
+                Pane::Log => self.log_pane.render(pane_area, buf),
+                Pane::Thoughts => self.thoughts.render(pane_area, buf),
+                Pane::Minimap => self.minimap().render(pane_area, buf),
+                Pane::View => FirstPerson::new(self.view_slices()).render(pane_area, buf),
+            }
+        }
+        if let Some(focus_area) = pane_area(&panes, self.focus) {
+            highlight_border(focus_area, palette::current().highlight, buf);
+        }
+        self.timeline_area = None;
+        if self.screen == Screen::Paused && (self.shadow.is_some() || self.playing.is_some()) {
+            self.render_timeline(map_area, buf);
+        }
+        if self.screen == Screen::Finished {
+            self.render_summary(map_area, buf);
+        }
+    }
+
+    /// A gauge along the bottom of `map_area` showing where in the history
+    /// the run is.
+    fn render_timeline(&mut self, map_area: Rect, buf: &mut Buffer) {
+        let area = Rect {
+            y: map_area.bottom().saturating_sub(1),
+            height: map_area.height.min(1),
+            ..map_area
+        };
+        let (earliest, furthest) = self.timeline_range();
+        let ratio = if furthest > earliest {
+            (self.ticks().min(furthest) - earliest) as f64 / (furthest - earliest) as f64
+        } else {
+            1.0
+        };
+        Clear.render(area, buf);
+        LineGauge::default()
+            .ratio(ratio)
+            .label(format!("tick {}/{} ", self.ticks(), furthest))
+            .filled_style(Style::new().cyan())
+            .unfilled_style(Style::new().dark_gray())
+            .render(area, buf);
+        self.timeline_area = Some(area);
+    }
+
+    /// Point the camera at the selected stack entry, or else the robot,
+    /// moved by the pan, without scrolling past the edges of the maze.
+    fn update_camera(&mut self, map_area: Rect) {
+        let ((x, y), (max_x, max_y)) = self.camera_range(map_area);
+        self.camera = (
+            x.saturating_add_signed(self.pan.0 as i16).min(max_x),
+            y.saturating_add_signed(self.pan.1 as i16).min(max_y),
+        );
+    }
+
+    /// Where the camera is when it isn't panned, and how far it can go.
+    fn camera_range(&self, map_area: Rect) -> ((u16, u16), (u16, u16)) {
+        let focus = match &self.walk {
+            Some(walk) => walk.pos().into(),
+            None => self.selected_stack_pos().unwrap_or(self.robot_pos),
+        };
+        let (width, height) = (self.layer_bg.width(), self.layer_bg.height());
+        (
+            (
+                follow(focus.x, map_area.width / 2, width),
+                follow(focus.y, map_area.height, height),
+            ),
+            (
+                width.saturating_sub(map_area.width / 2),
+                height.saturating_sub(map_area.height),
+            ),
+        )
+    }
+
+    /// Move the camera a few tiles the way of the arrow `code`, as far as
+    /// the edges of the maze allow.
+    fn pan_camera(&mut self, code: KeyCode) {
+        let (dx, dy) = match code {
+            KeyCode::Up => (0, -1),
+            KeyCode::Down => (0, 1),
+            KeyCode::Left => (-1, 0),
+            _ => (1, 0),
+        };
+        let ((x, y), (max_x, max_y)) = self.camera_range(self.map_area);
+        let (x, y, max_x, max_y) = (x as isize, y as isize, max_x as isize, max_y as isize);
+        self.pan = (
+            (self.pan.0 + dx * PAN_STEP).clamp(-x, max_x - x),
+            (self.pan.1 + dy * PAN_STEP).clamp(-y, max_y - y),
+        );
+        self.update_camera(self.map_area);
+    }
+
+    /// Whether the whole maze fits into `map_area` at full scale.
+    fn maze_fits(&self, map_area: Rect) -> bool {
+        2 * self.layer_bg.width() <= map_area.width && self.layer_bg.height() <= map_area.height
+    }
+
+    /// Zoom out if the maze doesn't fit into `map_area`, and back in once it
+    /// does. An infinite maze never fits, so it always stays at full scale.
+    fn pick_zoom(&mut self, map_area: Rect) {
+        self.zoomed_out = self.world.is_none() && !self.maze_fits(map_area);
+    }
+
+    /// Relayout for the new terminal size.
+    fn on_resize(&mut self, width: u16, height: u16) {
+        let [main_area, _] = screen_layout(Rect::new(0, 0, width, height));
+        let panes = self.pane_areas(main_area);
+        let map_area = pane_area(&panes, Pane::Map).unwrap_or(main_area);
+        debug_println!("resized to {}x{}", width, height);
+        self.map_area = map_area;
+        self.pick_zoom(map_area);
+        self.update_camera(map_area);
+    }
+
+    /// All layers at full scale, as seen through the camera.
+    fn render_layers(&self, map_area: Rect, buf: &mut Buffer) {
+        let selected = self.selected_stack_pos();
+        self.layer_bg.render_window(self.camera, map_area, buf);
+        if self.show_distances {
+            self.layer_distance
+                .render_window(self.camera, map_area, buf);
+        } else if let Some(heatmap) = self.heatmap() {
+            self.layer_terrain.render_window(self.camera, map_area, buf);
+            heatmap.render_window(self.camera, map_area, buf);
+        } else if let Some(visits) = self.revisits() {
+            self.layer_terrain.render_window(self.camera, map_area, buf);
+            self.layer_visited
+                .render_window_with(self.camera, map_area, buf, |pos, _| {
+                    Some(VisitedTile::Revisited(visits.heat(pos)?))
+                });
+        } else if let Some(fades) = self.trail_fades() {
+            self.layer_terrain.render_window(self.camera, map_area, buf);
+            self.layer_visited
+                .render_window_with(self.camera, map_area, buf, |pos, _| {
+                    let fade = fades.get(&pos)?;
+                    Some(VisitedTile::Fading((fade * 255.0) as u8))
+                });
+        } else {
+            self.layer_terrain.render_window(self.camera, map_area, buf);
+            self.layer_visited.render_window(self.camera, map_area, buf);
+        }
+        self.layer_items.render_window(self.camera, map_area, buf);
+        self.render_tour(map_area, buf);
+        self.render_course(map_area, buf);
+        self.render_ghost(map_area, buf);
+        match self.trail_fades() {
+            // the stack is left out where the trail has faded
+            Some(fades) => {
+                self.layer_fg
+                    .render_window_with(self.camera, map_area, buf, |pos, &tile| {
+                        (tile != ForegroundTile::Stack || fades.contains_key(&pos)).then_some(tile)
+                    })
+            }
+            None => self.layer_fg.render_window(self.camera, map_area, buf),
+        }
+        self.render_helpers(map_area, buf);
+        if self.show_annotations {
+            self.render_annotations(map_area, buf);
+        }
+        if self.show_hint {
+            self.render_hint(map_area, buf);
+        }
+        // the searches are all there is to see of these solvers
+        if self.show_search
+            || self.bidirectional.is_some()
+            || self.deepening.is_some()
+            || self.race.is_some()
+        {
+            self.layer_search.render_window(self.camera, map_area, buf);
+        }
+        self.render_lighting(map_area, buf);
+        self.render_breakpoints(map_area, buf);
+        self.render_bump(map_area, buf);
+        self.render_meeting(map_area, buf);
+        if let Some(pos) = selected {
+            self.render_highlight(pos, map_area, buf);
+        }
+    }
+
+    /// The maze as far as `walk` has carved it, and where the walk is.
+    fn render_walk(&self, walk: &Walk, map_area: Rect, buf: &mut Buffer) {
+        if self.zoomed_out {
+            let (w, h) = (self.layer_bg.width(), self.layer_bg.height());
+            Minimap::new(w, h, |x, y| (&self.layer_bg[(x, y)]).into())
+                .marker(walk.pos(), palette::current().highlight)
+                .render(map_area, buf);
+        } else {
+            self.layer_bg.render_window(self.camera, map_area, buf);
+            self.render_highlight(walk.pos().into(), map_area, buf);
+        }
+    }
+
+    /// The whole maze scaled down to fit into `map_area`.
+    fn render_overview(&self, map_area: Rect, buf: &mut Buffer) {
+        let heatmap = self.heatmap();
+        let revisits = self.revisits();
+        let fades = self.trail_fades();
+        let mut overview =
+            Minimap::new(
+                self.layer_bg.width(),
+                self.layer_bg.height(),
+                |x, y| match heatmap
+                    .as_ref()
+                    .and_then(|heatmap| heatmap[(x, y)].as_ref())
+                {
+                    Some(heat) => heat.into(),
+                    None => match revisits.as_ref().and_then(|visits| visits.heat((x, y))) {
+                        Some(heat) => (&VisitedTile::Revisited(heat)).into(),
+                        None => self.trail_color((x, y), fades.as_ref()),
+                    },
+                },
+            );
+        if let Some(pos) = self.selected_stack_pos() {
+            overview = overview.marker(pos.into(), palette::current().highlight);
+        }
+        if let Some(pos) = self.minotaur {
+            overview = overview.marker(pos.into(), (&ForegroundTile::Minotaur).into());
+        }
+        for helper in &self.helpers {
+            overview = overview.marker(helper.pos.into(), (&ForegroundTile::Robot).into());
+        }
+        for &stop in self
+            .tour
+            .iter()
+            .flat_map(|tour| &tour.stops[self.tour_next..])
+        {
+            overview = overview.marker(stop, palette::current().near);
+        }
+        overview
+            .marker(self.robot_pos.into(), (&ForegroundTile::Robot).into())
+            .render(map_area, buf);
+    }
+}
+
+/// The largest maze size, in cells, that fits into `map_area` at full scale.
+fn fit_size(map_area: Rect) -> (usize, usize) {
+    // a maze is 2 * size + 1 tiles, and a tile is two cells wide
+    let width = (map_area.width as usize / 2).saturating_sub(1) / 2;
+    let height = (map_area.height as usize).saturating_sub(1) / 2;
+    (
+        width.clamp(menu::MIN_SIZE, menu::MAX_SIZE),
+        height.clamp(menu::MIN_SIZE, menu::MAX_SIZE),
+    )
+}
+
+/// The area of the current screen and the status bar below it.
+fn screen_layout(area: Rect) -> [Rect; 2] {
+    Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area)
+}
+
+/// Where `pane` is among the areas of the panes, if it is shown.
+fn pane_area(panes: &[(Pane, Rect)], pane: Pane) -> Option<Rect> {
+    panes
+        .iter()
+        .find(|(p, _)| *p == pane)
+        .map(|&(_, area)| area)
+}
+
+/// Color the border around `area` to show the pane in it has the focus.
+fn highlight_border(area: Rect, color: Color, buf: &mut Buffer) {
+    let area = area.intersection(buf.area);
+    if area.is_empty() {
+        return;
+    }
+    for x in area.left()..area.right() {
+        buf[(x, area.top())].set_fg(color);
+        buf[(x, area.bottom() - 1)].set_fg(color);
+    }
+    for y in area.top()..area.bottom() {
+        buf[(area.left(), y)].set_fg(color);
+        buf[(area.right() - 1, y)].set_fg(color);
+    }
+}
+
+impl App {
+    fn pane_shown(&self, pane: Pane) -> bool {
+        match pane {
+            Pane::Thoughts => self.show_thoughts,
+            Pane::Minimap => self.show_minimap,
+            Pane::View => self.show_view,
+            Pane::Map | Pane::Stats | Pane::Stack | Pane::Log => true,
+        }
+    }
+
+    /// Where the shown panes of the run screen go in `area`.
+    fn pane_areas(&self, area: Rect) -> Vec<(Pane, Rect)> {
+        self.layout
+            .areas(area, |pane| self.pane_shown(pane), self.info_height())
+    }
+
+    /// Move the focus to the next shown pane.
+    fn focus_next_pane(&mut self) {
+        let shown: Vec<Pane> = self
+            .layout
+            .panes()
+            .filter(|&pane| self.pane_shown(pane))
+            .collect();
+        let i = shown.iter().position(|&pane| pane == self.focus);
+        self.focus = shown[i.map_or(0, |i| (i + 1) % shown.len())];
+    }
+
+    /// The corridor ahead of the robot, up to the first wall.
+    fn view_slices(&self) -> Vec<Slice> {
+        let blocked = |dx: isize, dy: isize| {
+            self.robot_pos_with_offset((dx, dy))
+                .is_none_or(|pos| !self.robot_can_enter(pos))
+        };
+        let mut slices = Vec::new();
+        for depth in 0..VIEW_DEPTH as isize {
+            let front = blocked(0, -depth - 1);
+            slices.push(Slice {
+                left: blocked(-1, -depth),
+                right: blocked(1, -depth),
+                front,
+            });
+            if front {
+                break;
+            }
+        }
+        slices
+    }
+}
+
+impl App {
+    /// Color of the topmost layer at `pos`, as shown on the map.
+    fn tile_color(&self, pos: (u16, u16)) -> Color {
+        if let Some(tile) = &self.layer_fg[pos] {
+            return tile.into();
+        }
+        if let Some(item) = &self.layer_items[pos] {
+            return item.into();
+        }
+        if self.show_distances {
+            return match &self.layer_distance[pos] {
+                Some(distance) => distance.into(),
+                None => (&self.layer_bg[pos]).into(),
+            };
+        }
+        self.explored_color(pos)
+    }
+
+    /// How faded the cells of the trail are, while its length is limited.
+    fn trail_fades(&self) -> Option<HashMap<(u16, u16), f64>> {
+        (self.trail_length > 0).then(|| self.trail.fades(self.trail_length))
+    }
+
+    /// Like `explored_color`, but with visited tiles off the trail left
+    /// out while its length is limited.
+    fn trail_color(&self, pos: (u16, u16), fades: Option<&HashMap<(u16, u16), f64>>) -> Color {
+        let Some(fades) = fades else {
+            return self.explored_color(pos);
+        };
+        match (
+            fades.get(&pos),
+            &self.layer_visited[pos],
+            &self.layer_terrain[pos],
+        ) {
+            (Some(fade), Some(_), _) => (&VisitedTile::Fading((fade * 255.0) as u8)).into(),
+            (_, _, Some(terrain)) => terrain.into(),
+            (_, _, None) => (&self.layer_bg[pos]).into(),
+        }
+    }
+
+    /// Color of a tile as seen from above, ignoring the robot and its stack.
+    fn explored_color(&self, pos: (u16, u16)) -> Color {
+        match (&self.layer_visited[pos], &self.layer_terrain[pos]) {
+            (Some(tile), _) => tile.into(),
+            (None, Some(terrain)) => terrain.into(),
+            (None, None) => (&self.layer_bg[pos]).into(),
+        }
+    }
+
+    fn render_summary(&self, area: Rect, buf: &mut Buffer) {
+        let stats = self.stats.borrow();
+        let coverage = match stats.coverage() {
+            Some(coverage) => format!("{:.1}%", coverage * 100.0),
+            None => "-".to_string(),
+        };
+        let (title, headline) = if self.caught {
+            (" caught ", "the minotaur got the robot")
+        } else if self.coverage_goal_reached() {
+            (" finished ", "coverage goal reached")
+        } else if self.course.as_ref().is_some_and(Course::is_done) {
+            (" finished ", "all checkpoints passed")
+        } else if self.qtable.is_some() {
+            (" finished ", "all episodes done")
+        } else if self.tour.is_some() {
+            (" finished ", "all goals visited")
+        } else if self.colony.is_some() {
+            (" finished ", "walked the ants' best path")
+        } else if let Some(search) = &self.bidirectional {
+            match search.meeting {
+                Some(_) => (" finished ", "walked the path the searches met on"),
+                None => (" finished ", "the searches never met"),
+            }
+        } else if let Some(search) = &self.deepening {
+            match search.path {
+                Some(_) => (" finished ", "walked the path the deepening search found"),
+                None => (" finished ", "the deepening search can't reach the goal"),
+            }
+        } else if let Some(race) = &self.race {
+            match race.path {
+                Some(_) => (" finished ", "walked the path the jump point search found"),
+                None => (" finished ", "no jumps lead to the goal"),
+            }
+        } else {
+            (" finished ", "maze fully explored")
+        };
+        let mut lines = vec![
+            Line::from(headline).bold().centered(),
+            Line::default(),
+            Line::from(format!("steps       {}", stats.steps)),
+            Line::from(format!("backtracks  {}", stats.backtracks)),
+            Line::from(format!(
+                "turns       {} ({} ticks)",
+                stats.turns, stats.turn_ticks
+            )),
+            Line::from(format!("bumps       {}", stats.bumps)),
+            Line::from(format!("ticks       {}", stats.ticks)),
+            Line::from(format!(
+                "time        {}",
+                stats::format_duration(stats.elapsed())
+            )),
+            Line::from(format!(
+                "coverage    {} ({}/{})",
+                coverage, stats.visited, stats.reachable
+            )),
+            Line::from(format!(
+                "found       {}",
+                match stats.coverage_efficiency() {
+                    Some(efficiency) => format!("{:.2} tiles per step", efficiency),
+                    None => "-".to_string(),
+                }
+            )),
+            Line::from(format!(
+                "items       {}/{} ({} pts)",
+                stats.items_collected, stats.items_total, stats.item_score
+            )),
+        ];
+        if let Some(course) = &self.course {
+            lines.push(Line::from(format!(
+                "course      {}/{} in {}",
+                course.splits().len(),
+                course.checkpoints().len(),
+                match course.splits().last() {
+                    Some(split) if course.is_done() => stats::format_duration(split.time),
+                    _ => "-".to_string(),
+                }
+            )));
+        }
+        if let Some(score) = self.scoring.score(&stats) {
+            lines.push(Line::from(format!("score       {}", score)).bold());
+        }
+        if let Some(day) = self.daily {
+            let streak = self.daily_history.streak(day);
+            let days = if streak == 1 { "day" } else { "days" };
+            lines.push(Line::from(format!("streak      {} {}", streak, days)));
+        }
+        lines.extend([
+            Line::default(),
+            Line::from("r rerun  n new maze  Enter menu")
+                .dim()
+                .centered(),
+        ]);
+        let area = menu::centered(area, 36, lines.len() as u16 + 2);
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .padding(Padding::horizontal(1)),
+            )
+            .render(area, buf);
+    }
+
+    /// What the app is doing, as shown to the user.
+    fn state_label(&self) -> &'static str {
+        match self.screen {
+            Screen::Menu => "menu",
+            Screen::Running => "running",
+            Screen::Paused => "paused",
+            Screen::Finished if self.caught => "caught",
+            Screen::Finished => "finished",
+            Screen::Leaderboard => "leaderboard",
+            Screen::Evolution => "evolving",
+            Screen::Batch => "batch",
+            Screen::Gallery => "gallery",
+            Screen::Settings => "settings",
+        }
+    }
+
+    /// The open command line or the last message, or else one line with the
+    /// state, speed, and seed, followed by as many of the current keybindings
+    /// as fit.
+    fn render_status(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(input) = &self.command_line {
+            Line::from(vec![
+                Span::raw(format!(":{}", input)),
+                Span::raw(" ").reversed(),
+            ])
+            .render(area, buf);
+            return;
+        }
+        match &self.status_message {
+            Some(StatusMessage::Info(message)) => {
+                Line::from(format!(" {}", message)).render(area, buf);
+                return;
+            }
+            Some(StatusMessage::Error(message)) => {
+                Line::from(format!(" {}", message))
+                    .red()
+                    .bold()
+                    .render(area, buf);
+                return;
+            }
+            None => {}
+        }
+        let speed = 1000 / self.tick_interval.as_millis().max(1);
+        let mut spans = vec![
+            Span::raw(format!(" {} ", self.state_label().to_uppercase()))
+                .reversed()
+                .bold(),
+            Span::raw(format!(" {} ticks/s  seed {} ", speed, self.config.seed)),
+        ];
+        if let Some(register) = self.macros.recording() {
+            spans.push(Span::raw(format!(" REC @{} ", register)).red().bold());
+        }
+        if let Some((step, steps)) = self.walk.as_ref().map(Walk::progress) {
+            spans.push(Span::raw(format!(" WALK {}/{} ", step, steps)).bold());
+        }
+        if let Some(count) = self.step_count {
+            spans.push(Span::raw(format!(" {}→ ", count)).bold());
+        }
+        let mut width: usize = spans.iter().map(Span::width).sum();
+        for (key, action) in self.keybindings() {
+            let hint = [
+                Span::raw(format!(" {}", key)).bold(),
+                Span::raw(format!(" {} ", action)).black().on_cyan(),
+            ];
+            width += hint.iter().map(Span::width).sum::<usize>();
+            if width > area.width as usize {
+                break;
+            }
+            spans.extend(hint);
+        }
+        Line::from(spans).render(area, buf);
+    }
+
+    /// The lines of the run info.
+    fn info_lines(&self) -> Vec<Line<'static>> {
+        let state = self.state_label();
+        let size = if self.config.infinite {
+            "infinite".to_string()
+        } else {
+            format!("{}x{}", self.config.width, self.config.height)
+        };
+        let stats = self.stats.borrow();
+        let mut lines = vec![
+            Line::from(format!("state:     {}", state)),
+            Line::from(format!("generator: {}", self.generator_label())),
+            Line::from(format!("size:      {}", size)),
+            Line::from(format!("seed:      {}", self.config.seed)),
+            Line::from(format!("minotaur:  {}", self.config.minotaur)),
+        ];
+        if let Some(code) = self.share_code() {
+            lines.push(Line::from(format!("code:      {}", code)));
+        }
+        if let Some(shape) = self.maze_shape {
+            lines.push(Line::from(format!("shape:     {}", shape)));
+        }
+        if let Some(ghost) = &self.ghost {
+            lines.push(Line::from(format!(
+                "ghost:     {}, {} ticks",
+                ghost.solver,
+                ghost.ticks()
+            )));
+        }
+        if let Some(day) = self.daily {
+            lines.push(Line::from(format!(
+                "daily:     day {}, streak {}",
+                day,
+                self.daily_history.streak(day)
+            )));
+        }
+        lines.extend([
+            Line::default(),
+            Line::from(format!(
+                "time:      {}",
+                stats::format_duration(stats.elapsed())
+            )),
+            Line::from(format!("ticks:     {}", stats.ticks)),
+            Line::from(format!("steps:     {}", stats.steps)),
+            Line::from(format!(
+                "turns:     {} ({} ticks)",
+                stats.turns, stats.turn_ticks
+            )),
+            Line::from(format!("steps/s:   {:.1}", stats.steps_per_second())),
+            Line::from(format!(
+                "items:     {}/{} ({} pts)",
+                stats.items_collected, stats.items_total, stats.item_score
+            )),
+            Line::from(
+                std::iter::once(Span::raw("keys:      "))
+                    .chain(
+                        self.robot_keys
+                            .iter()
+                            .map(|color| Span::styled("■ ", Style::new().fg(color.key_color()))),
+                    )
+                    .collect::<Vec<_>>(),
+            ),
+        ]);
+        if self.qtable.is_some() {
+            let episodes = &self.episodes;
+            let or_dash = |value: Option<String>| value.unwrap_or_else(|| "-".to_string());
+            lines.extend([
+                Line::default(),
+                Line::from(format!(
+                    "episode:   {}/{}",
+                    (episodes.finished + 1).min(EPISODES),
+                    EPISODES
+                )),
+                Line::from(format!("reward:    {:.0}", episodes.reward)),
+                Line::from(format!(
+                    "last:      {}",
+                    or_dash(episodes.last_reward.map(|r| format!("{:.0}", r)))
+                )),
+                Line::from(format!(
+                    "best:      {}",
+                    or_dash(episodes.best_steps.map(|s| format!("{} steps", s)))
+                )),
+            ]);
+        }
+        if !self.helpers.is_empty() {
+            let exploring = std::iter::once(&self.robot_plan)
+                .chain(self.helpers.iter().map(|helper| &helper.plan))
+                .filter(|plan| !plan.is_empty())
+                .count();
+            lines.extend([
+                Line::default(),
+                Line::from(format!("robots:    {}", self.helpers.len() + 1)),
+                Line::from(format!("exploring: {}", exploring)),
+            ]);
+            if self.config.comm_radius > 0 {
+                let groups = swarm::groups(&self.swarm_positions(), self.config.comm_radius);
+                lines.push(Line::from(format!("in touch:  {} groups", groups.len())));
+            }
+        }
+        if let Some(colony) = &self.colony {
+            lines.extend([
+                Line::default(),
+                Line::from(format!(
+                    "iteration: {}/{}",
+                    colony.iteration,
+                    ants::ITERATIONS
+                )),
+                Line::from(format!("arrived:   {}/{}", colony.reached, ants::ANTS)),
+                Line::from(format!(
+                    "best:      {}",
+                    match &colony.best {
+                        Some(best) => format!("{} steps", best.len()),
+                        None => "-".to_string(),
+                    }
+                )),
+            ]);
+        }
+        if let Some(search) = &self.bidirectional {
+            let [from_start, from_goal] = search.counts();
+            lines.extend([
+                Line::default(),
+                Line::from(format!("layers:    {}", search.layers)),
+                Line::from(format!("start:     {}", from_start)).fg(palette::current().search),
+                Line::from(format!("goal:      {}", from_goal)).fg(palette::current().search_back),
+                Line::from(format!(
+                    "met at:    {}",
+                    match search.meeting {
+                        Some(pos) => Pos::from(pos).to_string(),
+                        None => "-".to_string(),
+                    }
+                )),
+            ]);
+        }
+        if let Some(search) = &self.deepening {
+            lines.extend([
+                Line::default(),
+                Line::from(format!("limit:     {}", search.limit)).fg(palette::current().highlight),
+                Line::from(format!("sweep:     {}", search.sweeps)),
+                Line::from(format!("reached:   {}", search.reached().len())),
+                Line::from(format!("entered:   {}", search.expanded)),
+            ]);
+        }
+        if let Some(race) = &self.race {
+            let shown = |tree: &SearchTree| self.race_shown.min(tree.len());
+            lines.extend([
+                Line::default(),
+                Line::from(format!(
+                    "jumps:     {}/{}",
+                    shown(&race.jumps),
+                    race.jumps.len()
+                ))
+                .fg(palette::current().star),
+                Line::from(format!(
+                    "a*:        {}/{}",
+                    shown(&race.astar),
+                    race.astar.len()
+                ))
+                .fg(palette::current().search_back),
+                Line::from(format!("pruned:    {:.0}%", race.pruned() * 100.0)),
+            ]);
+        }
+        if let Some(course) = &self.course {
+            lines.push(Line::default());
+            for i in 0..course.checkpoints().len() {
+                let split = match (course.splits().get(i), course.lap(i)) {
+                    (Some(split), Some(lap)) => format!(
+                        "{}  +{}",
+                        stats::format_duration(split.time),
+                        stats::format_duration(lap)
+                    ),
+                    _ => "-".to_string(),
+                };
+                lines.push(Line::from(format!("split {}:{:<3}{}", i + 1, "", split)));
+            }
+        }
+        if let Some(tour) = &self.tour {
+            lines.extend([
+                Line::default(),
+                Line::from(format!(
+                    "goals:     {}/{}",
+                    self.tour_next,
+                    tour.stops.len()
+                )),
+                Line::from(format!("nearest:   {} ticks", tour.nearest_cost)),
+                Line::from(format!(
+                    "exact:     {}",
+                    match tour.exact_cost {
+                        Some(cost) => format!("{} ticks", cost),
+                        None => "-".to_string(),
+                    }
+                )),
+            ]);
+        }
+        lines
+    }
+
+    /// Whether the run info shows how close the coverage goal is.
+    fn shows_coverage_gauge(&self) -> bool {
+        self.config.coverage_goal > 0 && self.stats.borrow().reachable > 0
     }
-}
 
-#[derive(Debug, Default, Copy, Clone)]
-pub enum ForegroundTile {
-    #[default]
-    Stack,
-    Robot,
-}
+    /// Rows the run info takes.
+    fn info_height(&self) -> u16 {
+        self.info_lines().len() as u16 + 2 + self.shows_coverage_gauge() as u16
+    }
 
-impl From<&ForegroundTile> for ratatui::style::Color {
-    fn from(value: &ForegroundTile) -> Self {
-        match value {
-            ForegroundTile::Stack => Color::Yellow,
-            ForegroundTile::Robot => Color::Green,
+    /// Render the run info at the top of `area`, with the coverage gauge
+    /// below it.
+    fn render_info(&self, area: Rect, buf: &mut Buffer) {
+        let lines = self.info_lines();
+        let [area, rest] = Layout::vertical([
+            Constraint::Length(lines.len() as u16 + 2),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+        Paragraph::new(lines)
+            .block(Block::bordered().title(" run "))
+            .render(area, buf);
+        if !self.shows_coverage_gauge() {
+            return;
         }
+        let [gauge_area, _] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(rest);
+        let goal = self.config.coverage_goal as f64 / 100.0;
+        let coverage = self.stats.borrow().coverage().unwrap_or(0.0);
+        LineGauge::default()
+            .ratio((coverage / goal).min(1.0))
+            .label(format!(
+                "cover {:.0}/{}% ",
+                coverage * 100.0,
+                self.config.coverage_goal
+            ))
+            .filled_style(Style::new().green())
+            .unfilled_style(Style::new().dark_gray())
+            .render(gauge_area, buf);
     }
-}
 
-#[derive(Debug)]
-pub struct App {
-    exit: bool,
-    layer_bg: TileMap<BackgroundTile>,
-    layer_visited: AlphaTileMap<VisitedTile>,
-    layer_fg: AlphaTileMap<ForegroundTile>,
-    robot_pos: Pos,
-    robot_dir: Direction,
-    robot_stack: Vec<Pos>,
-}
+    /// Render `robot_stack` as a list, most recent entry on top.
+    fn render_stack(&mut self, area: Rect, buf: &mut Buffer) {
+        let rows = area.height.saturating_sub(2) as usize;
+        if let Some(selected) = self.stack_selected {
+            // keep the selected entry in view
+            self.stack_scroll = self
+                .stack_scroll
+                .min(selected)
+                .max((selected + 1).saturating_sub(rows));
+        } else {
+            self.stack_scroll = 0;
+        }
+        let lines: Vec<Line> = self
+            .robot_stack
+            .iter()
+            .rev()
+            .enumerate()
+            .skip(self.stack_scroll)
+            .take(rows)
+            .map(|(i, pos)| {
+                let line = Line::from(format!("{:>5}  {}", self.robot_stack.len() - i, pos));
+                if Some(i) == self.stack_selected {
+                    line.reversed()
+                } else {
+                    line
+                }
+            })
+            .collect();
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(format!(" stack ({}) ", self.robot_stack.len()))
+                    .title_bottom(Line::from(" ↑/↓ select ").dim()),
+            )
+            .render(area, buf);
+    }
 
-impl App {
-    fn reinit(&mut self) {
-        let (w, h) = (16, 16);
-        let (pw, ph) = (2 * w + 1, 2 * h + 1);
-        let maze = Maze::kruskal(w, h);
-        let mut map = TileMap::with_default(pw as u16, ph as u16);
-        for cy in 0..ph {
-            for cx in 0..pw {
-                map[Pos::new(cx, cy).into()] = match maze.tiles[cy][cx] {
-                    maze::Tile::Free => BackgroundTile::Free,
-                    maze::Tile::Wall => BackgroundTile::Wall,
-                };
-            }
+    /// Mark the tile at `pos` in the map, if it is in view.
+    fn render_highlight(&self, pos: Pos, map_area: Rect, buf: &mut Buffer) {
+        let Some((x, y)) = self.screen_cell(pos, map_area) else {
+            return;
+        };
+        for (i, c) in ['[', ']'].into_iter().enumerate() {
+            buf[(x + i as u16, y)]
+                .set_char(c)
+                .set_fg(Color::Black)
+                .set_bg(palette::current().highlight);
         }
-        self.layer_bg = map;
-        self.layer_visited = AlphaTileMap::empty(pw as u16, ph as u16);
-        self.layer_visited[(1, 1)] = Some(VisitedTile::Visited);
-        self.layer_fg = AlphaTileMap::empty(pw as u16, ph as u16);
-        self.layer_fg[(1, 1)] = Some(ForegroundTile::Robot);
-        self.robot_pos = Pos::new(1, 1);
-        self.robot_dir = Direction::E;
-        self.robot_stack = Vec::new();
     }
 
-    pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
-        while !self.exit {
-            terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
+    /// Color the tile of the robot warm if its last move took it closer to
+    /// the goal, and cold if it took it further away.
+    fn render_hint(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some(heat) = self.hot_cold.borrow().heat else {
+            return;
+        };
+        let Some((x, y)) = self.screen_cell(self.robot_pos, map_area) else {
+            return;
+        };
+        for i in 0..2 {
+            buf[(x + i, y)].set_bg(heat.into());
         }
-        Ok(())
     }
 
-    fn draw(&mut self, frame: &mut Frame) {
-        frame.render_widget(self, frame.area());
+    /// The tiles the camera sees in `map_area`.
+    fn viewport(&self, map_area: Rect) -> TileMapView<'_, BackgroundTile> {
+        let (x, y) = self.camera;
+        self.layer_bg
+            .view(Rect::new(x, y, map_area.width / 2, map_area.height))
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
-        let timeout = Duration::from_millis(1000 / 60);
-        if event::poll(timeout)? {
-            match event::read()? {
-                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                    self.handle_key_event(key_event)
+    /// Brighten the tiles around the robot and darken the others, by
+    /// scaling the colors already drawn.
+    fn render_lighting(&self, map_area: Rect, buf: &mut Buffer) {
+        if !self.light.is_on() {
+            return;
+        }
+        let view = self.viewport(map_area);
+        let (cols, rows) = (view.width(), view.height());
+        for cy in 0..rows {
+            for cx in 0..cols {
+                let pos = Pos::new((self.camera.0 + cx) as usize, (self.camera.1 + cy) as usize);
+                let (dx, dy) = (
+                    pos.x.abs_diff(self.robot_pos.x) as f64,
+                    pos.y.abs_diff(self.robot_pos.y) as f64,
+                );
+                let brightness = self.light.brightness(dx.hypot(dy), self.ticks());
+                for i in 0..2 {
+                    let cell = &mut buf[(map_area.x + 2 * cx + i, map_area.y + cy)];
+                    cell.fg = lighting::scale(cell.fg, brightness);
+                    cell.bg = lighting::scale(cell.bg, brightness);
                 }
-                _ => {}
             }
         }
-        Ok(())
     }
 
-    fn handle_key_event(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Right => self.on_tick(),
-            _ => {}
+    /// The planned route as dots and the goals numbered in the order they
+    /// are visited, the ones already reached dimmed.
+    fn render_tour(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some(tour) = &self.tour else {
+            return;
+        };
+        let palette = palette::current();
+        for &pos in &tour.route {
+            if let Some(cell) = self.screen_cell(pos.into(), map_area) {
+                buf[cell].set_char('·').set_fg(palette.search);
+            }
+        }
+        for (i, &pos) in tour.stops.iter().enumerate() {
+            let Some((x, y)) = self.screen_cell(pos.into(), map_area) else {
+                continue;
+            };
+            let style = if i < self.tour_next {
+                Style::new().fg(palette.near).dim()
+            } else {
+                Style::new().fg(palette.near).bold()
+            };
+            buf.set_string(x, y, format!("{:<2}", i + 1), style);
         }
     }
 
-    fn exit(&mut self) {
-        self.exit = true;
+    /// The checkpoints numbered in the order they are passed in, the next
+    /// one highlighted and the ones already passed dimmed.
+    fn render_course(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some(course) = &self.course else {
+            return;
+        };
+        let palette = palette::current();
+        let passed = course.splits().len();
+        for (i, &pos) in course.checkpoints().iter().enumerate() {
+            let Some((x, y)) = self.screen_cell(pos.into(), map_area) else {
+                continue;
+            };
+            let style = match i.cmp(&passed) {
+                std::cmp::Ordering::Less => Style::new().fg(palette.star).dim(),
+                std::cmp::Ordering::Equal => Style::new().fg(palette.star).bold().reversed(),
+                std::cmp::Ordering::Greater => Style::new().fg(palette.star).bold(),
+            };
+            buf.set_string(x, y, format!("{:<2}", i + 1), style);
+        }
     }
 
-    fn robot_pos_with_offset(&self, offset: (isize, isize)) -> Option<Pos> {
-        self.robot_pos + RelPos::new(offset.0, offset.1, self.robot_dir)
+    /// The robot of an earlier run on the maze where it was at this tick,
+    /// drawn dim over what is there, unless the robot is there too.
+    fn render_ghost(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some(ghost) = &self.ghost else {
+            return;
+        };
+        let pos = Pos::from(ghost.at(self.ticks()));
+        if pos == self.robot_pos {
+            return;
+        }
+        let Some((x, y)) = self.screen_cell(pos, map_area) else {
+            return;
+        };
+        let glyph = ForegroundTile::Robot.glyph().unwrap_or(['@', ' ']);
+        let style = Style::new().fg(palette::current().robot).dim();
+        for (i, c) in glyph.into_iter().enumerate() {
+            buf[(x + i as u16, y)].set_char(c).set_style(style);
+        }
     }
 
-    fn robot_scan(&mut self) -> [u8; 9] {
-        let mut arr = [0u8; 9];
-        let mut idx = 0;
-        for y_loc in -1..=1 {
-            for x_loc in -1..=1 {
-                let glob = self.robot_pos_with_offset((x_loc, y_loc)).unwrap();
-                arr[idx] = match self.layer_bg[glob.into()] {
-                    BackgroundTile::Free => b'.',
-                    BackgroundTile::Wall => b'O',
-                };
-                idx += 1;
+    /// The labels of the tiles, as picked with `annotation_source`. Labels
+    /// worked out for drawing are only there for the tiles in `map_area`.
+    fn annotations(&self, map_area: Rect) -> Cow<'_, Annotations> {
+        let mut annotations = Annotations::default();
+        let (x0, y0) = self.camera;
+        let x1 = (x0 + map_area.width / 2).min(self.layer_bg.width());
+        let y1 = (y0 + map_area.height).min(self.layer_bg.height());
+        let visible = (y0..y1).flat_map(|y| (x0..x1).map(move |x| (x, y)));
+        match self.annotation_source {
+            annotations::Source::Notes => return Cow::Borrowed(&self.notes),
+            annotations::Source::Distances => {
+                for pos in visible {
+                    if let Some(cost) = self.hot_cold.borrow().cost(pos) {
+                        annotations.set_number(pos, cost as u64);
+                    }
+                }
+            }
+            annotations::Source::Visits => {
+                let visits = self.visits.borrow();
+                for pos in visible.filter(|&pos| visits.count(pos) > 0) {
+                    annotations.set_number(pos, visits.count(pos) as u64);
+                }
             }
         }
-        arr
+        Cow::Owned(annotations)
     }
 
-    fn robot_step(&mut self) {
-        let glob = self.robot_pos_with_offset((0, -1)).unwrap();
-        // can only step into free fields
-        match self.layer_bg[glob.into()] {
-            BackgroundTile::Free => {
-                if let Some(ForegroundTile::Robot) = self.layer_fg[self.robot_pos.into()] {
-                    self.layer_fg[self.robot_pos.into()] = None;
+    /// The labels of the tiles over their colors, in black or white,
+    /// whichever stands out more.
+    fn render_annotations(&self, map_area: Rect, buf: &mut Buffer) {
+        for (pos, label) in self.annotations(map_area).iter() {
+            let Some((x, y)) = self.screen_cell(pos.into(), map_area) else {
+                continue;
+            };
+            let label = format!("{:>width$}", label, width = annotations::LABEL_WIDTH);
+            for (i, c) in label.chars().enumerate() {
+                let cell = &mut buf[(x + i as u16, y)];
+                let [r, g, b] = kitty::rgb(cell.bg).map(u32::from);
+                let light = 299 * r + 587 * g + 114 * b > 128_000;
+                cell.set_char(c)
+                    .set_fg(if light { Color::Black } else { Color::White });
+            }
+        }
+    }
+
+    /// The other robots of the swarm, numbered from 1, and dotted lines
+    /// between the robots close enough to talk if their range is limited.
+    fn render_helpers(&self, map_area: Rect, buf: &mut Buffer) {
+        let radius = self.config.comm_radius;
+        if radius > 0 {
+            let positions = self.swarm_positions();
+            for (i, &a) in positions.iter().enumerate() {
+                for &b in positions[i + 1..]
+                    .iter()
+                    .filter(|&&b| b != a && swarm::in_range(a, b, radius))
+                {
+                    let line = tile::line(a.into(), b.into());
+                    for &pos in &line[1..line.len() - 1] {
+                        if let Some(cell) = self.screen_cell(pos.into(), map_area) {
+                            buf[cell].set_char('·').set_fg(Color::LightCyan);
+                        }
+                    }
                 }
-                self.robot_pos = glob;
-                self.layer_fg[self.robot_pos.into()] = Some(ForegroundTile::Robot);
             }
-            BackgroundTile::Wall => panic!("robot tried to move to wall at {}", glob),
+        }
+        for (i, helper) in self.helpers.iter().enumerate() {
+            if let Some((x, y)) = self.screen_cell(helper.pos, map_area) {
+                let style = Style::new().fg(Color::Black).bg(palette::current().robot);
+                buf.set_string(x, y, format!("{:<2}", i + 1), style);
+            }
         }
     }
 
-    fn robot_stack_push(&mut self, pos: Pos) {
-        self.robot_stack.push(pos);
-        self.layer_fg[pos.into()] = Some(ForegroundTile::Stack);
+    /// A dot on every tile with a breakpoint.
+    fn render_breakpoints(&self, map_area: Rect, buf: &mut Buffer) {
+        for breakpoint in &self.breakpoints {
+            let Breakpoint::Tile(x, y) = *breakpoint else {
+                continue;
+            };
+            if let Some(cell) = self.screen_cell(Pos::new(x as usize, y as usize), map_area) {
+                buf[cell].set_char('●').set_fg(Color::LightRed);
+            }
+        }
     }
 
-    fn robot_stack_pop(&mut self) -> Option<Pos> {
-        if let Some(pos) = self.robot_stack.pop() {
-            if let Some(ForegroundTile::Stack) = self.layer_fg[pos.into()] {
-                self.layer_fg[pos.into()] = None;
+    /// Flash the tile the robot just bumped into.
+    fn render_bump(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some((pos, at)) = self.bump_flash else {
+            return;
+        };
+        if at.elapsed() >= BUMP_FLASH {
+            return;
+        }
+        if let Some((x, y)) = self.screen_cell(pos, map_area) {
+            for i in 0..2 {
+                buf[(x + i, y)].set_bg(Color::Red);
             }
-            Some(pos)
-        } else {
-            None
         }
     }
 
-    fn robot_turn_right(&mut self) {
-        self.robot_dir = self.robot_dir.right();
+    /// Blink the tile the bidirectional searches met on, for a while after
+    /// they did.
+    fn render_meeting(&self, map_area: Rect, buf: &mut Buffer) {
+        let Some((pos, at)) = self.meeting_flash else {
+            return;
+        };
+        let elapsed = at.elapsed();
+        if elapsed >= MEETING_FLASH || (elapsed.as_millis() / MEETING_BLINK.as_millis()) % 2 == 1 {
+            return;
+        }
+        if let Some((x, y)) = self.screen_cell(pos, map_area) {
+            for i in 0..2 {
+                buf[(x + i, y)].set_bg(palette::current().highlight);
+            }
+        }
     }
 
-    fn robot_turn_left(&mut self) {
-        self.robot_dir = self.robot_dir.left();
+    /// The first of the two cells showing the tile at `pos` in `map_area`,
+    /// if the tile is visible.
+    fn screen_cell(&self, pos: Pos, map_area: Rect) -> Option<(u16, u16)> {
+        let dx = u16::try_from(pos.x).ok()?.checked_sub(self.camera.0)?;
+        let dy = u16::try_from(pos.y).ok()?.checked_sub(self.camera.1)?;
+        if 2 * dx + 1 >= map_area.width || dy >= map_area.height {
+            return None;
+        }
+        Some((map_area.x + 2 * dx, map_area.y + dy))
     }
 
-    fn on_tick(&mut self) {
-        debug_println(format!("current position: {}", self.robot_pos));
-        debug_println(format!("current orientation: {:?}", self.robot_dir));
-        let scan = self.robot_scan();
-        let right = scan[5];
-        let front = scan[1];
-        let left = scan[3];
-        let front_coords = self.robot_pos_with_offset((0, -1)).unwrap();
-        let left_coords = self.robot_pos_with_offset((-1, 0)).unwrap();
-        let right_coords = self.robot_pos_with_offset((1, 0)).unwrap();
+    /// The tile shown at the screen cell, the inverse of `screen_cell`.
+    fn map_tile(&self, column: u16, row: u16) -> Option<(u16, u16)> {
+        let view = self.viewport(self.map_area);
+        let dx = column.checked_sub(self.map_area.x)? / 2;
+        let dy = row.checked_sub(self.map_area.y)?;
+        (dx < view.width() && dy < view.height()).then(|| (self.camera.0 + dx, self.camera.1 + dy))
+    }
 
-        let free = [
-            front == b'.' && self.layer_visited[front_coords.into()].is_none(),
-            right == b'.' && self.layer_visited[right_coords.into()].is_none(),
-            left == b'.' && self.layer_visited[left_coords.into()].is_none(),
+    /// Average frame timings in the top right corner of the map.
+    fn render_timings(&self, map_area: Rect, buf: &mut Buffer) {
+        let ms = |average: &stats::RollingAverage| average.average().as_secs_f64() * 1000.0;
+        let lines = vec![
+            Line::from(format!("draw    {:>7.2} ms", ms(&self.timings.draw))),
+            Line::from(format!("tick    {:>7.2} ms", ms(&self.timings.tick))),
+            Line::from(format!("events  {:>7.2} ms", ms(&self.timings.events))),
         ];
-        if free[0] || free[1] || free[2] {
-            match select_idx(&free[..]) {
-                0 => {
-                    debug_println("move front".to_string());
-                    self.layer_visited[front_coords.into()] = Some(VisitedTile::Visited);
-                    self.robot_stack_push(self.robot_pos);
-                    self.robot_step();
-                }
-                1 => {
-                    debug_println("move right".to_string());
-                    self.layer_visited[right_coords.into()] = Some(VisitedTile::Visited);
-                    self.robot_stack_push(self.robot_pos);
-                    self.robot_turn_right();
-                    self.robot_step();
-                }
-                2 => {
-                    debug_println("move left".to_string());
-                    self.layer_visited[left_coords.into()] = Some(VisitedTile::Visited);
-                    self.robot_stack_push(self.robot_pos);
-                    self.robot_turn_left();
-                    self.robot_step();
-                }
-                _ => unreachable!(),
-            }
-        } else {
-            debug_println("backtrack".to_string());
-            // backtrack
-            let back = match self.robot_stack_pop() {
-                Some(it) => it,
-                None => {
-                    self.reinit();
-                    return;
+        let (w, h) = (20, lines.len() as u16 + 2);
+        let area = Rect::new(
+            map_area.right().saturating_sub(w),
+            map_area.y,
+            w.min(map_area.width),
+            h.min(map_area.height),
+        );
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(Block::bordered().title(" timings "))
+            .render(area, buf);
+    }
+
+    /// The robot's weights in the top left corner of the map, with the one
+    /// the keys change highlighted.
+    fn render_weights(&self, map_area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = weights::Field::ALL
+            .into_iter()
+            .map(|field| {
+                let line = Line::from(format!(
+                    "{:<10}{:>8}",
+                    field.label(),
+                    self.weights.value(field)
+                ));
+                if field == self.weight_field {
+                    line.reversed()
+                } else {
+                    line
                 }
-            };
-            while back != self.robot_pos_with_offset((0, -1)).unwrap() {
-                self.robot_turn_right();
-            }
-            self.robot_step();
-        }
+            })
+            .collect();
+        let (w, h) = (20, lines.len() as u16 + 2);
+        let area = Rect::new(
+            map_area.x,
+            map_area.y,
+            w.min(map_area.width),
+            h.min(map_area.height),
+        );
+        Clear.render(area, buf);
+        Paragraph::new(lines)
+            .block(Block::bordered().title(" weights "))
+            .render(area, buf);
     }
-}
 
-fn select_idx(values: &[bool]) -> usize {
-    let ntrue = values.iter().copied().filter(|t| *t).count();
-    if ntrue == 0 {
-        panic!("ntrue == 0");
+    fn render_minimap(&self, map_area: Rect, buf: &mut Buffer) {
+        let minimap = self.minimap();
+        let (w, h) = minimap.size_within(map_area.width / 3, map_area.height / 3);
+        let area = Rect::new(
+            map_area.right().saturating_sub(w),
+            map_area.bottom().saturating_sub(h),
+            w.min(map_area.width),
+            h.min(map_area.height),
+        );
+        minimap.render(area, buf);
     }
-    let n = rand::random_range(0..ntrue);
-    let mut m = 0;
-    let mut idx = 0;
-    loop {
-        if values[idx] {
-            if n == m {
-                break idx;
-            }
-            m += 1;
-        }
-        idx += 1;
+
+    /// The explored part of the maze, with the robot on it.
+    fn minimap(&self) -> Minimap<'_, impl Fn(u16, u16) -> Color + '_> {
+        let fades = self.trail_fades();
+        Minimap::new(
+            self.layer_bg.width(),
+            self.layer_bg.height(),
+            move |x, y| self.trail_color((x, y), fades.as_ref()),
+        )
+        .marker(self.robot_pos.into(), (&ForegroundTile::Robot).into())
+        .block(Block::bordered().title("minimap"))
     }
 }
 
-impl Widget for &mut App {
-    fn render(self, area: Rect, buf: &mut Buffer) {
-        let layout = Layout::default()
-            .direction(ratatui::layout::Direction::Horizontal)
-            .constraints(vec![Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
-            .split(area);
-        self.layer_bg.render(layout[1], buf);
-        self.layer_visited.render(layout[1], buf);
-        self.layer_fg.render(layout[1], buf);
+/// Mazes each individual of an evolution is rated on.
+const EVOLVE_SEEDS: u64 = 4;
+
+/// Mean ticks the robot with `weights` needs to reach the goal, over mazes
+/// like `config` with a few consecutive seeds. Infinite, the worst, if the
+/// robot misses the goal on any of them.
+fn evolve_fitness(config: RunConfig, weights: Weights) -> f64 {
+    let total: Option<u64> = (0..EVOLVE_SEEDS)
+        .map(|i| {
+            ticks_to_goal(
+                RunConfig {
+                    seed: config.seed.wrapping_add(i),
+                    ..config
+                },
+                weights,
+            )
+        })
+        .sum();
+    total.map_or(f64::INFINITY, |total| total as f64 / EVOLVE_SEEDS as f64)
+}
+
+/// An app running `config` with `weights` without a screen, to tick until
+/// the run is over.
+fn headless_app(config: RunConfig, weights: Weights) -> App {
+    let mut app = App::with_files(
+        Leaderboard::new(LEADERBOARD_PATH),
+        DailyHistory::new(DAILY_PATH),
+    );
+    app.headless = true;
+    app.config = config;
+    app.weights = weights;
+    app.reinit();
+    app.end_walk();
+    app
+}
+
+/// Ticks the robot with `weights` needs to reach the goal of the maze
+/// `config` describes, run without a screen. `None` if the maze has no
+/// goal or the run ended before the robot got there.
+fn ticks_to_goal(config: RunConfig, weights: Weights) -> Option<u64> {
+    let mut app = headless_app(config, weights);
+    let goal = app.goal?;
+    while app.screen == Screen::Running && app.layer_visited[goal.into()].is_none() {
+        app.on_tick();
     }
+    app.layer_visited[goal.into()].map(|_| app.stats.borrow().ticks)
+}
+
+/// Whole ticks of turning owed from `turn_debt` in half ticks, keeping the
+/// odd half tick for the next step.
+fn charge_turns(turn_debt: &mut u32) -> u32 {
+    let ticks = *turn_debt / 2;
+    *turn_debt %= 2;
+    ticks
+}
+
+/// Camera coordinate along one axis that keeps `pos` centered,
+/// without scrolling past either end of the map.
+fn follow(pos: usize, visible: u16, size: u16) -> u16 {
+    let max = size.saturating_sub(visible);
+    (pos as u16).saturating_sub(visible / 2).min(max)
+}
+
+/// Write `buffer` with its colors to a file named after the current time,
+/// and return the file name.
+fn save_screenshot(buffer: &Buffer) -> io::Result<String> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let path = format!("screenshot-{}.ans", millis);
+    fs::write(&path, ansi::lines(buffer))?;
+    debug_println!("saved screenshot to {}", path);
+    Ok(path)
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Watch a robot explore a maze")]
+struct Args {
+    /// Write every simulation event as a line of JSON to this file or named pipe.
+    #[arg(long, value_name = "PATH")]
+    events: Option<PathBuf>,
+    /// Draw the map as an image if the terminal supports the kitty graphics
+    /// protocol.
+    #[arg(long)]
+    graphics: bool,
+    /// Write the rendered frames as an asciinema cast to this file.
+    #[arg(long, value_name = "PATH")]
+    cast: Option<PathBuf>,
+    /// Save each run as an animated GIF at this path when it ends.
+    #[arg(long, value_name = "PATH")]
+    record: Option<PathBuf>,
+    /// Generate mazes with this generator, instead of the one in
+    /// settings.toml. Its parameters follow in percent after colons, like
+    /// `drunkard:60` or `growing-tree:90:30` (newest, straight).
+    #[arg(long, value_name = "NAME")]
+    generator: Option<String>,
+    /// Colors to draw the map with, instead of the one in settings.toml.
+    #[arg(long, value_enum)]
+    palette: Option<PaletteName>,
+    /// Draw tiles with emoji or pairs of characters on top of their colors.
+    #[arg(long, value_enum)]
+    sprites: Option<SpriteSet>,
+    /// Read colors and sprites from this TOML file, and again whenever it
+    /// changes.
+    #[arg(long, value_name = "PATH")]
+    theme: Option<PathBuf>,
+    /// Light up this many tiles around the robot and leave the rest dark.
+    #[arg(long, value_name = "TILES")]
+    light_radius: Option<u16>,
+    /// Let the light flicker like a torch.
+    #[arg(long)]
+    torch: bool,
+    /// Only use ASCII characters and the 16 basic colors. This is the
+    /// default if the terminal doesn't seem to support Unicode.
+    #[arg(long)]
+    ascii: bool,
+    /// Write debug messages to this file.
+    #[arg(long, value_name = "PATH", default_value = "debug.txt")]
+    log: PathBuf,
+    /// Append to the debug log instead of replacing it.
+    #[arg(long)]
+    log_append: bool,
+    /// Start a new debug log once it grows past this many megabytes.
+    #[arg(long, value_name = "MB", default_value_t = 10)]
+    log_max_size: u64,
+    /// Number of full debug logs to keep, as debug.txt.1, debug.txt.2, ...
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    log_keep: usize,
+    /// Don't write a debug log.
+    #[arg(long, conflicts_with_all = ["log", "log_append", "log_max_size", "log_keep"])]
+    no_log: bool,
+    /// Read a maze from standard input, in the format `:export` writes, and
+    /// run on it right away.
+    #[arg(long, conflicts_with_all = ["image", "replay"])]
+    stdin: bool,
+    /// Run on the maze drawn in this PNG or BMP image, dark pixels being
+    /// walls and light ones free. The robot starts in the top left corner.
+    #[arg(long, value_name = "PATH")]
+    image: Option<PathBuf>,
+    /// Pixels per side of the block that makes up one tile of the maze
+    /// drawn in the image.
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "image")]
+    image_scale: usize,
+    /// Run the maze of a code shown next to the maze of someone else's run.
+    #[arg(long, value_name = "CODE", conflicts_with_all = ["stdin", "image", "replay"])]
+    code: Option<ShareCode>,
+    /// Play a replay saved when quitting during a run.
+    #[arg(long, value_name = "PATH", conflicts_with = "image")]
+    replay: Option<PathBuf>,
+    /// Load the maze generators in the shared libraries in this directory.
+    /// They run with all the rights of the app, so only point it at
+    /// libraries you trust.
+    #[arg(long, value_name = "DIR")]
+    plugins: Option<PathBuf>,
+    /// Call `pre_tick(state)` and `post_tick(state)` in this Lua script
+    /// around every tick, to log metrics or wall up tiles.
+    #[cfg(feature = "lua")]
+    #[arg(long, value_name = "PATH")]
+    script: Option<PathBuf>,
+    /// Take JSON-RPC requests (pause, resume, step, set_speed, place_goal,
+    /// query_state) one per line on stdin and answer them on stdout,
+    /// drawing to /dev/tty instead.
+    #[arg(long, value_enum, value_name = "MODE", conflicts_with = "stdin")]
+    control: Option<ControlMode>,
+    /// Serve the run state as JSON at /state and a picture of the maze at
+    /// /maze.png on this address, like 127.0.0.1:8080.
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "ADDR")]
+    http: Option<String>,
 }
 
 fn main() -> io::Result<()> {
     // println!("{}", maze);
     // return Ok(());
-    let mut terminal = ratatui::init();
-    let mut app = App {
-        exit: false,
-        layer_bg: TileMap::with_default(1, 1),
-        layer_visited: AlphaTileMap::empty(1, 1),
-        layer_fg: AlphaTileMap::empty(1, 1),
-        robot_pos: Pos::new(1, 1),
-        robot_dir: Direction::E,
-        robot_stack: Vec::new(),
+    let args = Args::parse();
+    if !args.no_log {
+        if let Err(e) = debug::open(
+            &args.log,
+            args.log_append,
+            args.log_max_size * 1024 * 1024,
+            args.log_keep,
+        ) {
+            eprintln!("could not open {}, not logging: {}", args.log.display(), e);
+        }
+    }
+    debug::keep_recent();
+    let events = args.events.map(EventStream::create).transpose()?;
+    let cast = args.cast.map(Cast::create).transpose()?;
+    // before the terminal is taken over, so errors can be printed
+    let mut notice = None;
+    let replay = args
+        .replay
+        .as_deref()
+        .map(|path| {
+            Replay::load(path).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no replay in {}: {}", path.display(), e),
+                )
+            })
+        })
+        .transpose()?;
+    #[cfg(feature = "lua")]
+    let script = args
+        .script
+        .as_deref()
+        .map(|path| {
+            lua::LuaHook::load(path).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("could not load {}: {}", path.display(), e),
+                )
+            })
+        })
+        .transpose()?;
+    #[cfg(feature = "http")]
+    let http = args
+        .http
+        .as_deref()
+        .map(|addr| {
+            http::StatusServer::bind(addr).map_err(|e| {
+                io::Error::new(e.kind(), format!("could not serve on {}: {}", addr, e))
+            })
+        })
+        .transpose()?;
+    let loaded_maze = if args.stdin {
+        let text = io::read_to_string(io::stdin())?;
+        let maze = text
+            .parse::<maze::Maze>()
+            .and_then(|maze| menu::check_maze_size(&maze).map(|()| maze))
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("no maze on stdin: {}", e),
+                )
+            })?;
+        Some(maze)
+    } else if let Some(path) = &args.image {
+        let (maze, walled) = image::load(path, args.image_scale).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("no maze in {}: {}", path.display(), e),
+            )
+        })?;
+        if walled > 0 {
+            notice = Some(format!("walled up {} tiles the robot can't reach", walled));
+        }
+        Some(maze)
+    } else {
+        None
     };
-    app.reinit();
-    let app_result = app.run(&mut terminal);
-    ratatui::restore();
-    app_result
+    // before the settings, which may pick one of the plugins
+    let plugin_errors = args
+        .plugins
+        .as_deref()
+        .map(plugin::load_dir)
+        .unwrap_or_default();
+    let (mut settings, settings_error) = match Settings::load(Path::new(SETTINGS_PATH)) {
+        Ok(settings) => (settings, None),
+        Err(e) => (Settings::default(), Some(e)),
+    };
+    if let Some(generator) = &args.generator {
+        settings.generator = generator
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    }
+    settings.palette = args.palette.unwrap_or(settings.palette);
+    settings.sprites = args.sprites.unwrap_or(settings.sprites);
+    settings.theme = args.theme.or(settings.theme);
+    settings.light.radius = args.light_radius.unwrap_or(settings.light.radius);
+    settings.light.flicker |= args.torch;
+    let mut app = App::new();
+    app.menu.resumable = Path::new(SESSION_PATH).exists();
+    if let Some(events) = events {
+        app.bus.subscribe("events", events);
+    }
+    app.cast = cast;
+    app.ascii = args.ascii || ascii::detect();
+    app.graphics = args.graphics && !app.ascii && kitty::supported();
+    app.recorder = args.record.map(Recorder::new);
+    #[cfg(feature = "lua")]
+    if let Some(script) = script {
+        app.hooks.add(script);
+    }
+    #[cfg(feature = "http")]
+    {
+        app.http = http;
+    }
+    app.apply_settings(&settings);
+    if let Some(e) = plugin_errors.first() {
+        app.status_message = Some(StatusMessage::Error(format!("could not load plugin {}", e)));
+    }
+    if let Some(e) = settings_error {
+        app.status_message = Some(StatusMessage::Error(format!(
+            "could not load {}: {}",
+            SETTINGS_PATH, e
+        )));
+    }
+    if let Some(maze) = loaded_maze {
+        app.run_on(maze);
+        app.status_message = notice.map(StatusMessage::Info);
+    }
+    if let Some(code) = args.code {
+        code.apply(&mut app.menu.config);
+        app.config = app.menu.config;
+        app.reinit();
+    }
+    if let Some(replay) = replay {
+        if let Err(e) = app.play_replay(replay) {
+            app.notice("bad replay", &e);
+        }
+    }
+    match args.control {
+        Some(ControlMode::Stdio) => {
+            app.control = Some(Control::stdio());
+            run_on_tty(&mut app)
+        }
+        None => {
+            let mut terminal = ratatui::init();
+            crossterm::execute!(io::stdout(), EnableMouseCapture)?;
+            let app_result = app.run(&mut terminal);
+            crossterm::execute!(io::stdout(), DisableMouseCapture)?;
+            ratatui::restore();
+            app_result
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Direction {
+/// Run the app on the terminal at `TTY_PATH`, leaving stdin and stdout to
+/// the control requests.
+fn run_on_tty(app: &mut App) -> io::Result<()> {
+    let tty = fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(TTY_PATH)?;
+    crossterm::terminal::enable_raw_mode()?;
+    let _guard = TtyGuard;
+    // the message of a panic is printed before the guard is dropped
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_tty();
+        hook(info);
+    }));
+    let mut backend = CrosstermBackend::new(tty);
+    crossterm::execute!(
+        backend,
+        crossterm::terminal::EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    let mut terminal = Terminal::new(backend)?;
+    app.run(&mut terminal)
+}
+
+/// Put the terminal at `TTY_PATH` back the way it was before the app.
+fn restore_tty() {
+    if let Ok(mut tty) = fs::OpenOptions::new().write(true).open(TTY_PATH) {
+        let _ = crossterm::execute!(
+            tty,
+            DisableMouseCapture,
+            crossterm::terminal::LeaveAlternateScreen,
+            crossterm::cursor::Show
+        );
+    }
+    let _ = crossterm::terminal::disable_raw_mode();
+}
+
+/// Restores the terminal at `TTY_PATH` when dropped, however `run_on_tty`
+/// ends.
+struct TtyGuard;
+
+impl Drop for TtyGuard {
+    fn drop(&mut self) {
+        restore_tty();
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub enum Direction {
     N,
     E,
     S,
@@ -337,9 +6830,22 @@ impl Direction {
             D::W => D::S,
         }
     }
+
+    fn opposite(self) -> Self {
+        self.right().right()
+    }
+
+    fn arrow(self) -> char {
+        match self {
+            Direction::N => '↑',
+            Direction::E => '→',
+            Direction::S => '↓',
+            Direction::W => '←',
+        }
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 struct Pos {
     x: usize,
     y: usize,
@@ -351,10 +6857,32 @@ impl From<Pos> for (u16, u16) {
     }
 }
 
+impl From<(u16, u16)> for Pos {
+    fn from(value: (u16, u16)) -> Self {
+        Self::new(value.0 as usize, value.1 as usize)
+    }
+}
+
 impl Pos {
     fn new(x: usize, y: usize) -> Self {
         Self { x, y }
     }
+
+    fn is_neighbor(self, other: Pos) -> bool {
+        self.distance(other) == 1
+    }
+
+    /// Manhattan distance, ignoring walls.
+    fn distance(self, other: Pos) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The direction of a step from `self` to its neighbor `other`.
+    fn direction_to(self, other: Pos) -> Option<Direction> {
+        [Direction::N, Direction::E, Direction::S, Direction::W]
+            .into_iter()
+            .find(|&dir| self + RelPos::new(0, -1, dir) == Some(other))
+    }
 }
 
 impl fmt::Display for Pos {
@@ -405,6 +6933,8 @@ impl RelPos {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -424,4 +6954,198 @@ mod tests {
         let rp = RelPos::new(5, -3, D::S);
         assert_eq!(RelPos::new(3, 5, D::E), rp.reorient(D::E));
     }
+
+    #[test]
+    fn test_fit_size() {
+        let mut app = App::new();
+        app.config.fit = true;
+        for (w, h) in [(73, 36), (74, 37), (120, 20)] {
+            app.map_area = Rect::new(0, 0, w, h);
+            app.reinit();
+            assert!(app.maze_fits(app.map_area));
+            assert!(!app.maze_fits(Rect::new(0, 0, w - 4, h - 2)));
+        }
+        assert_eq!((17, 17), fit_size(Rect::new(0, 0, 73, 36)));
+    }
+
+    #[test]
+    fn test_share_code() {
+        let mut app = App::new();
+        app.config.openings = Openings::Sides;
+        app.config.doors = 2;
+        app.config.teleporters = 1;
+        app.config.one_ways = 5;
+        app.config.terrain = 3;
+        app.reinit();
+        let code: ShareCode = app.share_code().unwrap().to_string().parse().unwrap();
+        let tiles = |app: &App| format!("{:?} {:?}", app.layer_bg, app.layer_items);
+
+        let mut other = App::new();
+        other.config.items = 0;
+        other.config.one_ways = 1;
+        code.apply(&mut other.config);
+        other.reinit();
+        assert_eq!(app.maze_hash, other.maze_hash);
+        assert_eq!(tiles(&app), tiles(&other));
+    }
+
+    #[test]
+    fn test_ticks_to_goal() {
+        let config = RunConfig {
+            seed: 3,
+            ..Menu::default().config
+        };
+        let app = headless_app(config, Weights::default());
+        assert!(app.shadow.is_none());
+        assert!(!app.saves_run());
+        assert!(ticks_to_goal(config, Weights::default()).is_some_and(|ticks| ticks > 0));
+        // an infinite maze has no goal
+        let infinite = RunConfig {
+            infinite: true,
+            ..config
+        };
+        assert_eq!(None, ticks_to_goal(infinite, Weights::default()));
+        assert_eq!(f64::INFINITY, evolve_fitness(infinite, Weights::default()));
+    }
+
+    #[test]
+    fn test_click_breakpoint() {
+        let config = RunConfig {
+            seed: 3,
+            ..Menu::default().config
+        };
+        let mut app = headless_app(config, Weights::default());
+        app.map_area = Rect::new(2, 1, 200, 100);
+        app.pick_zoom(app.map_area);
+        app.update_camera(app.map_area);
+        let (x, y) = app.camera;
+        // the second cell of the tile one right of the corner
+        app.on_click(5, 1);
+        assert_eq!(vec![Breakpoint::Tile(x + 1, y)], app.breakpoints);
+        assert_eq!(
+            Some((5 - 1, 1)),
+            app.screen_cell(Pos::new(x as usize + 1, y as usize), app.map_area)
+        );
+        app.on_click(4, 1);
+        assert!(app.breakpoints.is_empty());
+        // outside the map
+        app.on_click(0, 0);
+        assert!(app.breakpoints.is_empty());
+    }
+
+    #[test]
+    fn test_turn_ticks() {
+        let mut debt = 3;
+        assert_eq!(1, charge_turns(&mut debt));
+        // the half tick left is owed with the next turn
+        assert_eq!(1, debt);
+        debt += 1;
+        assert_eq!(1, charge_turns(&mut debt));
+        assert_eq!(0, debt);
+
+        let run = |config: RunConfig| {
+            let mut app = headless_app(config, Weights::default());
+            for _ in 0..300 {
+                app.on_tick();
+            }
+            let stats = app.stats.borrow().clone();
+            stats
+        };
+        let config = RunConfig {
+            seed: 3,
+            ..Menu::default().config
+        };
+        assert_eq!(0, run(config).turn_ticks);
+        let treads = run(RunConfig {
+            chassis: menu::Chassis::Treads,
+            ..config
+        });
+        assert!(treads.turn_ticks > 0 && treads.turn_ticks <= treads.turns);
+        // only the helpers of the swarm take time to turn
+        let swarm = RunConfig {
+            solver: Solver::Swarm,
+            ..config
+        };
+        assert_eq!(0, run(swarm).turn_ticks);
+        let helpers = run(RunConfig {
+            helper_chassis: menu::Chassis::Treads,
+            ..swarm
+        });
+        assert!(helpers.turn_ticks > 0);
+    }
+
+    fn direction() -> impl Strategy<Value = Direction> {
+        prop_oneof![
+            Just(Direction::N),
+            Just(Direction::E),
+            Just(Direction::S),
+            Just(Direction::W),
+        ]
+    }
+
+    proptest! {
+        /// Reorienting can always be undone, so it maps positions one to one.
+        #[test]
+        fn prop_reorient_bijection(
+            x in -100..100isize,
+            y in -100..100isize,
+            from in direction(),
+            to in direction(),
+        ) {
+            let rp = RelPos::new(x, y, from);
+            let there = rp.reorient(to);
+            prop_assert_eq!(to, there.dir);
+            prop_assert_eq!(rp, there.reorient(from));
+            prop_assert_eq!(x.abs() + y.abs(), there.x.abs() + there.y.abs());
+        }
+
+        /// Four right turns are a full turn.
+        #[test]
+        fn prop_reorient_right_four_times(
+            x in -100..100isize,
+            y in -100..100isize,
+            dir in direction(),
+        ) {
+            let rp = RelPos::new(x, y, dir);
+            let mut turned = rp;
+            for _ in 0..4 {
+                turned = turned.reorient_right();
+                prop_assert_eq!(x.abs() + y.abs(), turned.x.abs() + turned.y.abs());
+            }
+            prop_assert_eq!(rp, turned);
+        }
+
+        /// Adding an offset and then its opposite gets back to where it
+        /// started, and reorienting the offset doesn't change where it leads.
+        #[test]
+        fn prop_add_round_trip(
+            px in 0..1000usize,
+            py in 0..1000usize,
+            x in -100..100isize,
+            y in -100..100isize,
+            dir in direction(),
+            other in direction(),
+        ) {
+            let pos = Pos::new(px, py);
+            let rp = RelPos::new(x, y, dir);
+            let there = pos + rp;
+            prop_assert_eq!(there, pos + rp.reorient(other));
+            match there {
+                Some(there) => {
+                    prop_assert_eq!(Some(pos), there + RelPos::new(-x, -y, dir));
+                    prop_assert_eq!(x.unsigned_abs() + y.unsigned_abs(), pos.distance(there));
+                }
+                // only offsets past the top or the left edge don't add up
+                None => {
+                    let north = rp.reorient(Direction::N);
+                    prop_assert!(north.x < -(px as isize) || north.y < -(py as isize));
+                }
+            }
+            if x.abs() + y.abs() == 1 {
+                if let Some(there) = there {
+                    prop_assert_eq!(Some(there), pos + RelPos::new(0, -1, pos.direction_to(there).unwrap()));
+                }
+            }
+        }
+    }
 }
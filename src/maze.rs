@@ -1,31 +1,46 @@
 use std::fmt;
+use std::iter;
+use std::ops::Range;
+use std::str::FromStr;
 
-use rand::{seq::SliceRandom, Rng, RngCore};
+use rand::{
+    rngs::StdRng,
+    seq::{IndexedRandom, SliceRandom},
+    Rng, SeedableRng,
+};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::plugin;
+
+/// Disjoint sets of the numbers `0..n`.
 #[derive(Debug)]
-struct UnionFind {
+pub struct UnionFind {
     reps: Vec<usize>,
 }
 
 impl UnionFind {
-    fn new(n: usize) -> Self {
+    /// `n` sets with one number each.
+    pub fn new(n: usize) -> Self {
         Self {
             reps: Vec::from_iter(0..n),
         }
     }
 
-    fn rep(&self, mut a: usize) -> usize {
+    /// The number representing the set `a` is in.
+    pub fn rep(&self, mut a: usize) -> usize {
         while self.reps[a] != a {
             a = self.reps[a];
         }
         a
     }
 
-    fn in_same_set(&self, a: usize, b: usize) -> bool {
+    pub fn in_same_set(&self, a: usize, b: usize) -> bool {
         self.rep(a) == self.rep(b)
     }
 
-    fn join(&mut self, a: usize, b: usize) {
+    /// Merge the set `b` is in into the set `a` is in.
+    pub fn join(&mut self, a: usize, b: usize) {
         let rep_b = self.rep(b);
         self.reps[rep_b] = a;
     }
@@ -58,12 +73,13 @@ impl fmt::Display for UnionFind {
     }
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Tile {
     Free,
     Wall,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Maze {
     pub tiles: Vec<Vec<Tile>>,
 }
@@ -72,10 +88,10 @@ pub struct Maze {
 struct Pos(usize, usize);
 
 fn is_horizontal_edge(pos: Pos) -> bool {
-    pos.0 % 2 == 0
+    pos.0.is_multiple_of(2)
 }
 
-fn node_to_idx(pos: Pos, nx: usize, ny: usize) -> usize {
+fn node_to_idx(pos: Pos, nx: usize) -> usize {
     (pos.1 / 2) * nx + (pos.0) / 2
 }
 
@@ -98,7 +114,168 @@ impl Maze {
         maze
     }
 
-    pub fn kruskal(nx: usize, ny: usize) -> Self {
+    pub fn width(&self) -> usize {
+        self.tiles.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.tiles.len()
+    }
+
+    /// A `width` x `height` maze with each tile taken from the tile of this
+    /// one at `source(x, y)`.
+    fn transformed(
+        &self,
+        width: usize,
+        height: usize,
+        source: impl Fn(usize, usize) -> (usize, usize),
+    ) -> Self {
+        let tiles = (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| {
+                        let (sx, sy) = source(x, y);
+                        self.tiles[sy][sx]
+                    })
+                    .collect()
+            })
+            .collect();
+        Maze { tiles }
+    }
+
+    /// The maze turned a quarter clockwise. Like the other transforms, it
+    /// keeps the cells of a generated maze on odd coordinates, so (1, 1) is
+    /// still a cell, if not the same one.
+    pub fn rotate90(&self) -> Self {
+        let h = self.height();
+        self.transformed(h, self.width(), |x, y| (y, h - 1 - x))
+    }
+
+    pub fn rotate180(&self) -> Self {
+        let (w, h) = (self.width(), self.height());
+        self.transformed(w, h, |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    /// The maze turned a quarter counterclockwise.
+    pub fn rotate270(&self) -> Self {
+        let w = self.width();
+        self.transformed(self.height(), w, |x, y| (w - 1 - y, x))
+    }
+
+    /// The maze mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.width();
+        self.transformed(w, self.height(), |x, y| (w - 1 - x, y))
+    }
+
+    /// The maze mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.height();
+        self.transformed(self.width(), h, |x, y| (x, h - 1 - y))
+    }
+
+    /// The maze mirrored along the diagonal from (0, 0), so rows become
+    /// columns.
+    fn transpose(&self) -> Self {
+        self.transformed(self.height(), self.width(), |x, y| (y, x))
+    }
+
+    /// Put `other` to the right of this maze, the two sharing the wall
+    /// between them, and open a doorway in that wall within each of `spans`
+    /// of rows where there are free tiles on both sides. Spans without any
+    /// are skipped, but there has to be at least one doorway.
+    fn join<R: Rng + ?Sized>(
+        &self,
+        other: &Maze,
+        spans: impl IntoIterator<Item = Range<usize>>,
+        rng: &mut R,
+    ) -> Result<Maze, String> {
+        if self.height() != other.height() {
+            return Err(format!(
+                "the mazes don't line up, one is {} tiles across and the other {}",
+                self.height(),
+                other.height()
+            ));
+        }
+        if self.width() < 3 || other.width() < 3 {
+            return Err("a maze needs at least 3 x 3 tiles".to_string());
+        }
+        let wall = self.width() - 1;
+        let mut tiles = self.tiles.clone();
+        for (row, other_row) in tiles.iter_mut().zip(&other.tiles) {
+            row.extend(&other_row[1..]);
+        }
+        let mut doors = 0;
+        for span in spans {
+            let candidates: Vec<usize> = span
+                .filter(|&y| tiles[y][wall - 1] == Tile::Free && tiles[y][wall + 1] == Tile::Free)
+                .collect();
+            if let Some(&y) = candidates.choose(rng) {
+                tiles[y][wall] = Tile::Free;
+                doors += 1;
+            }
+        }
+        if doors == 0 {
+            return Err("there is no place for a doorway between the mazes".to_string());
+        }
+        Ok(Maze { tiles })
+    }
+
+    /// This maze with `other` to its right, joined by a doorway at a random
+    /// row. Both have to be the same height.
+    pub fn concat_horizontal<R: Rng + ?Sized>(
+        &self,
+        other: &Maze,
+        rng: &mut R,
+    ) -> Result<Maze, String> {
+        let rows = 1..self.height().saturating_sub(1);
+        self.join(other, iter::once(rows), rng)
+    }
+
+    /// This maze with `other` below it, joined by a doorway at a random
+    /// column. Both have to be the same width.
+    pub fn concat_vertical<R: Rng + ?Sized>(
+        &self,
+        other: &Maze,
+        rng: &mut R,
+    ) -> Result<Maze, String> {
+        let columns = 1..self.width().saturating_sub(1);
+        Ok(self
+            .transpose()
+            .join(&other.transpose(), iter::once(columns), rng)?
+            .transpose())
+    }
+
+    /// One big maze from `rows` of smaller ones, e.g. made by different
+    /// generators, with a doorway between every two that are next to each
+    /// other. The mazes in a row have to be the same height and the rows
+    /// the same width. As long as the smaller mazes are connected, the big
+    /// one is too.
+    pub fn tiled<R: Rng + ?Sized>(rows: &[Vec<Maze>], rng: &mut R) -> Result<Maze, String> {
+        let mut world: Option<Maze> = None;
+        for row in rows {
+            let (first, rest) = row.split_first().ok_or("a row without mazes")?;
+            let mut strip = first.clone();
+            // the columns each maze of the row is in, without its sides
+            let mut spans = Vec::with_capacity(row.len());
+            spans.push(1..first.width().saturating_sub(1));
+            for maze in rest {
+                let start = strip.width();
+                strip = strip.concat_horizontal(maze, rng)?;
+                spans.push(start..strip.width() - 1);
+            }
+            world = Some(match world {
+                None => strip,
+                Some(world) => world
+                    .transpose()
+                    .join(&strip.transpose(), spans, rng)?
+                    .transpose(),
+            });
+        }
+        world.ok_or_else(|| "no mazes to tile".to_string())
+    }
+
+    pub fn kruskal<R: Rng + ?Sized>(nx: usize, ny: usize, rng: &mut R) -> Self {
         let mut maze = Self::empty(nx, ny);
         let mut edges = Vec::new();
         // horizontal
@@ -113,7 +290,7 @@ impl Maze {
                 edges.push(Pos(2 * x + 1, 2 * y));
             }
         }
-        edges.shuffle(&mut rand::rng());
+        edges.shuffle(rng);
 
         let mut sets = UnionFind::new(nx * ny);
         let mut unused_edges = Vec::new();
@@ -124,7 +301,7 @@ impl Maze {
             } else {
                 (Pos(edge.0, edge.1 - 1), Pos(edge.0, edge.1 + 1))
             };
-            let (index_a, index_b) = (node_to_idx(pos_a, nx, ny), node_to_idx(pos_b, nx, ny));
+            let (index_a, index_b) = (node_to_idx(pos_a, nx), node_to_idx(pos_b, nx));
             if !sets.in_same_set(index_a, index_b) {
                 sets.join(index_a, index_b);
                 maze.tiles[edge.1][edge.0] = Tile::Free;
@@ -134,8 +311,8 @@ impl Maze {
         }
 
         // remove some random edges taht are still standing
-        unused_edges.shuffle(&mut rand::rng());
-        let n = (nx * ny) / 2;
+        unused_edges.shuffle(rng);
+        let n = ((nx * ny) / 2).min(unused_edges.len());
         for edge in &unused_edges[..n] {
             maze.tiles[edge.1][edge.0] = Tile::Free;
         }
@@ -154,6 +331,438 @@ impl Maze {
 
         maze
     }
+
+    /// Like `kruskal`, but generates square regions of the maze in parallel
+    /// and joins neighbouring regions through random gaps in the walls
+    /// between them.
+    pub fn kruskal_parallel<R: Rng + ?Sized>(nx: usize, ny: usize, rng: &mut R) -> Self {
+        let (rx, ry) = (nx.div_ceil(REGION_CELLS), ny.div_ceil(REGION_CELLS));
+        // seeds are drawn up front so the maze doesn't depend on scheduling
+        let seeds: Vec<u64> = (0..rx * ry).map(|_| rng.random()).collect();
+        let regions: Vec<Maze> = seeds
+            .par_iter()
+            .enumerate()
+            .map(|(i, &seed)| {
+                let (x0, y0) = ((i % rx) * REGION_CELLS, (i / rx) * REGION_CELLS);
+                let (w, h) = (REGION_CELLS.min(nx - x0), REGION_CELLS.min(ny - y0));
+                Maze::kruskal(w, h, &mut StdRng::seed_from_u64(seed))
+            })
+            .collect();
+
+        let mut maze = Self::empty(nx, ny);
+        for (i, region) in regions.iter().enumerate() {
+            let (x0, y0) = ((i % rx) * REGION_CELLS, (i / rx) * REGION_CELLS);
+            for (ty, line) in region.tiles.iter().enumerate() {
+                maze.tiles[2 * y0 + ty][2 * x0..2 * x0 + line.len()].copy_from_slice(line);
+            }
+        }
+
+        // every region is connected, so one gap to each neighbour connects
+        // the maze; about half of the other edges are opened as in `kruskal`
+        let mut open_border = |edges: Vec<Pos>, rng: &mut R| {
+            let always = rng.random_range(0..edges.len());
+            for (i, edge) in edges.into_iter().enumerate() {
+                if i == always || rng.random_bool(0.5) {
+                    maze.tiles[edge.1][edge.0] = Tile::Free;
+                }
+            }
+        };
+        for y in 0..ry {
+            let cells = y * REGION_CELLS..ny.min((y + 1) * REGION_CELLS);
+            for x in 1..rx {
+                let bx = 2 * x * REGION_CELLS;
+                open_border(cells.clone().map(|cy| Pos(bx, 2 * cy + 1)).collect(), rng);
+            }
+        }
+        for x in 0..rx {
+            let cells = x * REGION_CELLS..nx.min((x + 1) * REGION_CELLS);
+            for y in 1..ry {
+                let by = 2 * y * REGION_CELLS;
+                open_border(cells.clone().map(|cx| Pos(2 * cx + 1, by)).collect(), rng);
+            }
+        }
+        maze
+    }
+
+    /// Carve an open cavern with a random walk starting at (1, 1).
+    ///
+    /// The walk stops once `coverage` (0.0 to 1.0) of the inner tiles are free.
+    /// The outer border always stays solid.
+    pub fn drunkard<R: Rng + ?Sized>(nx: usize, ny: usize, coverage: f64, rng: &mut R) -> Self {
+        let (w, h) = (2 * nx + 1, 2 * ny + 1);
+        let mut maze = Maze {
+            tiles: vec![vec![Tile::Wall; w]; h],
+        };
+        let inner = (w - 2) * (h - 2);
+        let target = ((inner as f64 * coverage.clamp(0.0, 1.0)).ceil() as usize).max(1);
+
+        let (mut x, mut y) = (1, 1);
+        maze.tiles[y][x] = Tile::Free;
+        let mut carved = 1;
+        while carved < target {
+            match rng.random_range(0..4) {
+                0 if x > 1 => x -= 1,
+                1 if x < w - 2 => x += 1,
+                2 if y > 1 => y -= 1,
+                3 if y < h - 2 => y += 1,
+                _ => continue,
+            }
+            if let Tile::Wall = maze.tiles[y][x] {
+                maze.tiles[y][x] = Tile::Free;
+                carved += 1;
+            }
+        }
+        maze
+    }
+
+    /// A perfect maze, chosen uniformly from all of them, by a random walk
+    /// that carves its way into every cell it enters for the first time.
+    pub fn aldous_broder<R: Rng + ?Sized>(nx: usize, ny: usize, rng: &mut R) -> Self {
+        let mut walk = AldousBroder::new(nx, ny, rng);
+        while !walk.is_done() {
+            walk.step(rng);
+        }
+        walk.into_maze()
+    }
+
+    /// A perfect maze grown from a random cell, by carving from a cell of
+    /// the ones grown so far into a neighbour that isn't yet. The cell is
+    /// the newest one with probability `newest` and a random one otherwise:
+    /// always the newest gives long winding corridors like a river, never
+    /// the newest many short dead ends. With probability `straight` the
+    /// corridor goes on the way it came if it can, making it less twisty.
+    pub fn growing_tree<R: Rng + ?Sized>(
+        nx: usize,
+        ny: usize,
+        newest: f64,
+        straight: f64,
+        rng: &mut R,
+    ) -> Self {
+        let mut maze = Self::empty(nx, ny);
+        let mut grown = vec![vec![false; nx]; ny];
+        let start = (rng.random_range(0..nx), rng.random_range(0..ny));
+        grown[start.1][start.0] = true;
+        // with the way each cell was entered
+        let mut cells = vec![(start, None)];
+        while !cells.is_empty() {
+            let i = if rng.random_bool(newest.clamp(0.0, 1.0)) {
+                cells.len() - 1
+            } else {
+                rng.random_range(0..cells.len())
+            };
+            let ((x, y), came) = cells[i];
+            let neighbours: Vec<((usize, usize), (isize, isize))> =
+                [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                    .into_iter()
+                    .filter_map(|(dx, dy)| {
+                        let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+                        (next.0 < nx && next.1 < ny && !grown[next.1][next.0])
+                            .then_some((next, (dx, dy)))
+                    })
+                    .collect();
+            let ahead = neighbours.iter().find(|&&(_, dir)| Some(dir) == came);
+            let next = match ahead {
+                Some(&ahead) if rng.random_bool(straight.clamp(0.0, 1.0)) => ahead,
+                _ => match neighbours.choose(rng) {
+                    Some(&next) => next,
+                    None => {
+                        cells.swap_remove(i);
+                        continue;
+                    }
+                },
+            };
+            let ((next_x, next_y), dir) = next;
+            grown[next_y][next_x] = true;
+            maze.tiles[next_y + y + 1][next_x + x + 1] = Tile::Free;
+            cells.push(((next_x, next_y), Some(dir)));
+        }
+        maze
+    }
+
+    /// What the maze is like, to compare the mazes of different generators
+    /// and parameters.
+    pub fn shape(&self) -> Shape {
+        let mut shape = Shape::default();
+        // the passages between cells always go straight, so only the cells
+        // tell how twisty the maze is
+        for y in (1..self.height().saturating_sub(1)).step_by(2) {
+            for x in (1..self.width().saturating_sub(1)).step_by(2) {
+                if self.tiles[y][x] == Tile::Wall {
+                    continue;
+                }
+                let free = |x: usize, y: usize| self.tiles[y][x] == Tile::Free;
+                let (left, right, up, down) = (
+                    free(x - 1, y),
+                    free(x + 1, y),
+                    free(x, y - 1),
+                    free(x, y + 1),
+                );
+                shape.free += 1;
+                match [left, right, up, down].into_iter().filter(|&f| f).count() {
+                    1 => shape.dead_ends += 1,
+                    2 if (left && right) || (up && down) => shape.straight += 1,
+                    2 => shape.bends += 1,
+                    _ => {}
+                }
+            }
+        }
+        shape
+    }
+}
+
+/// Counts of the free cells of a maze, the tiles at odd coordinates, by
+/// how many of their neighbours are free and where.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Shape {
+    pub free: usize,
+    pub dead_ends: usize,
+    /// Corridor cells going on the way they came.
+    pub straight: usize,
+    /// Corridor cells turning a corner.
+    pub bends: usize,
+}
+
+impl Shape {
+    /// The share of the free cells that are dead ends.
+    pub fn dead_end_ratio(&self) -> f64 {
+        self.dead_ends as f64 / self.free.max(1) as f64
+    }
+
+    /// The share of the corridor cells that go straight on, the less twisty
+    /// the maze the more.
+    pub fn straightness(&self) -> f64 {
+        self.straight as f64 / (self.straight + self.bends).max(1) as f64
+    }
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.0}% dead ends, {:.0}% straight",
+            self.dead_end_ratio() * 100.0,
+            self.straightness() * 100.0
+        )
+    }
+}
+
+/// The random walk of `Maze::aldous_broder`, a step at a time, to watch it.
+/// The walk takes long to find the last cells, it is several times as long
+/// as there are cells.
+#[derive(Clone, Debug)]
+pub struct AldousBroder {
+    maze: Maze,
+    nx: usize,
+    ny: usize,
+    /// The cell the walk is on.
+    cell: (usize, usize),
+    unvisited: usize,
+}
+
+impl AldousBroder {
+    /// Start the walk on a random cell of an `nx` x `ny` maze of walls.
+    pub fn new<R: Rng + ?Sized>(nx: usize, ny: usize, rng: &mut R) -> Self {
+        let mut maze = Maze {
+            tiles: vec![vec![Tile::Wall; 2 * nx + 1]; 2 * ny + 1],
+        };
+        let (x, y) = (rng.random_range(0..nx), rng.random_range(0..ny));
+        maze.tiles[2 * y + 1][2 * x + 1] = Tile::Free;
+        Self {
+            maze,
+            nx,
+            ny,
+            cell: (x, y),
+            unvisited: nx * ny - 1,
+        }
+    }
+
+    /// Where the walk is, as tile coordinates.
+    pub fn pos(&self) -> (usize, usize) {
+        (2 * self.cell.0 + 1, 2 * self.cell.1 + 1)
+    }
+
+    /// Cells carved so far, and cells there are.
+    pub fn progress(&self) -> (usize, usize) {
+        let cells = self.nx * self.ny;
+        (cells - self.unvisited, cells)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.unvisited == 0
+    }
+
+    /// Walk into a random neighbour, returning the tiles of the passage and
+    /// of the cell if it is entered for the first time and carved.
+    pub fn step<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<[(usize, usize); 2]> {
+        let (x, y) = self.cell;
+        let neighbours = [
+            (x > 0).then(|| (x - 1, y)),
+            (x + 1 < self.nx).then_some((x + 1, y)),
+            (y > 0).then(|| (x, y - 1)),
+            (y + 1 < self.ny).then_some((x, y + 1)),
+        ];
+        let neighbours: Vec<(usize, usize)> = neighbours.into_iter().flatten().collect();
+        let &(next_x, next_y) = neighbours.choose(rng)?;
+        self.cell = (next_x, next_y);
+        let (cell, passage) = (self.pos(), (next_x + x + 1, next_y + y + 1));
+        if self.maze.tiles[cell.1][cell.0] == Tile::Wall {
+            self.maze.tiles[cell.1][cell.0] = Tile::Free;
+            self.maze.tiles[passage.1][passage.0] = Tile::Free;
+            self.unvisited -= 1;
+            return Some([passage, cell]);
+        }
+        None
+    }
+
+    /// The maze as far as the walk has carved it.
+    pub fn into_maze(self) -> Maze {
+        self.maze
+    }
+}
+
+pub const DEFAULT_COVERAGE: f64 = 0.45;
+pub const DEFAULT_NEWEST: f64 = 0.75;
+pub const DEFAULT_STRAIGHT: f64 = 0.0;
+
+/// Cells per side of the regions `Maze::kruskal_parallel` generates.
+const REGION_CELLS: usize = 16;
+
+/// Cells from which on `Generator::Kruskal` generates in parallel. Smaller
+/// mazes are quick enough in one piece.
+const PARALLEL_CELLS: usize = 128 * 128;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Generator {
+    Kruskal,
+    Drunkard {
+        coverage: f64,
+    },
+    #[serde(rename = "aldous-broder")]
+    AldousBroder,
+    #[serde(rename = "growing-tree")]
+    GrowingTree {
+        newest: f64,
+        straight: f64,
+    },
+    /// One of the generators loaded with `plugin::load`.
+    Plugin {
+        #[serde(deserialize_with = "plugin::deserialize_name")]
+        name: plugin::Name,
+    },
+}
+
+impl Generator {
+    /// Generate an `nx` x `ny` maze. Only plugins can fail to.
+    pub fn generate<R: Rng + ?Sized>(
+        self,
+        nx: usize,
+        ny: usize,
+        rng: &mut R,
+    ) -> Result<Maze, String> {
+        Ok(match self {
+            Generator::Kruskal if nx * ny >= PARALLEL_CELLS => Maze::kruskal_parallel(nx, ny, rng),
+            Generator::Kruskal => Maze::kruskal(nx, ny, rng),
+            Generator::Drunkard { coverage } => Maze::drunkard(nx, ny, coverage, rng),
+            Generator::AldousBroder => Maze::aldous_broder(nx, ny, rng),
+            Generator::GrowingTree { newest, straight } => {
+                Maze::growing_tree(nx, ny, newest, straight, rng)
+            }
+            Generator::Plugin { name } => plugin::generate(name, nx, ny, rng.random())?,
+        })
+    }
+
+    /// Cycle to the next generator, going through the plugins after the
+    /// built-in ones.
+    pub fn next(self) -> Self {
+        let plugins = plugin::names();
+        let next_plugin = match self {
+            Generator::Kruskal => {
+                return Generator::Drunkard {
+                    coverage: DEFAULT_COVERAGE,
+                }
+            }
+            Generator::Drunkard { .. } => return Generator::AldousBroder,
+            Generator::AldousBroder => {
+                return Generator::GrowingTree {
+                    newest: DEFAULT_NEWEST,
+                    straight: DEFAULT_STRAIGHT,
+                }
+            }
+            Generator::GrowingTree { .. } => plugins.first(),
+            Generator::Plugin { name } => plugins.iter().skip_while(|&&n| n != name).nth(1),
+        };
+        next_plugin.map_or(Generator::Kruskal, |&name| Generator::Plugin { name })
+    }
+}
+
+impl fmt::Display for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Generator::Kruskal => write!(f, "kruskal"),
+            Generator::Drunkard { coverage } => {
+                write!(f, "drunkard ({:.0}%)", coverage * 100.0)
+            }
+            Generator::AldousBroder => write!(f, "aldous-broder"),
+            Generator::GrowingTree { newest, straight } => write!(
+                f,
+                "growing-tree ({:.0}/{:.0})",
+                newest * 100.0,
+                straight * 100.0
+            ),
+            Generator::Plugin { name } => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Names of the built-in generators, accepted by `Generator::from_str`
+/// along with the names of the plugins.
+pub const GENERATOR_NAMES: [&str; 4] = ["kruskal", "drunkard", "aldous-broder", "growing-tree"];
+
+/// Parses a generator name, followed by its parameters in percent after
+/// colons: `drunkard:60` for the coverage, `growing-tree:90:30` for
+/// `newest` and `straight`. Parameters left out take their defaults.
+impl FromStr for Generator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let mut percents = parts.map(|part| match part.trim_end_matches('%').parse::<u8>() {
+            Ok(percent @ 0..=100) => Ok(percent as f64 / 100.0),
+            _ => Err(format!("'{}' is no percentage from 0 to 100", part)),
+        });
+        let mut param = |default: f64| percents.next().unwrap_or(Ok(default));
+        let generator = match name {
+            "kruskal" => Generator::Kruskal,
+            "drunkard" => Generator::Drunkard {
+                coverage: param(DEFAULT_COVERAGE)?,
+            },
+            "aldous-broder" => Generator::AldousBroder,
+            "growing-tree" => Generator::GrowingTree {
+                newest: param(DEFAULT_NEWEST)?,
+                straight: param(DEFAULT_STRAIGHT)?,
+            },
+            _ => match plugin::find(name) {
+                Some(name) => Generator::Plugin { name },
+                None => {
+                    return Err(format!(
+                        "unknown generator '{}', expected one of: {}",
+                        name,
+                        GENERATOR_NAMES
+                            .into_iter()
+                            .chain(plugin::names())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ))
+                }
+            },
+        };
+        match percents.next() {
+            Some(_) => Err(format!("too many parameters for {}", name)),
+            None => Ok(generator),
+        }
+    }
 }
 
 impl fmt::Display for Maze {
@@ -170,3 +779,248 @@ impl fmt::Display for Maze {
         Ok(())
     }
 }
+
+/// Parses a maze in the format it is printed in, also accepting `#` for
+/// walls and `.` for free tiles. The maze has to be a rectangle enclosed by
+/// walls, with the entrance at (1, 1) free.
+impl FromStr for Maze {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<&str> = s.lines().filter(|line| !line.trim().is_empty()).collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        if width < 3 || lines.len() < 3 {
+            return Err("a maze needs at least 3 x 3 tiles".to_string());
+        }
+        let mut tiles = Vec::with_capacity(lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            let mut row = Vec::with_capacity(width);
+            for (x, c) in line.chars().enumerate() {
+                row.push(match c {
+                    'O' | '#' => Tile::Wall,
+                    ' ' | '.' => Tile::Free,
+                    _ => return Err(format!("unexpected '{}' at ({}, {})", c, x, y)),
+                });
+            }
+            if row.len() != width {
+                return Err(format!("line {} is not {} tiles long", y, width));
+            }
+            tiles.push(row);
+        }
+        let height = tiles.len();
+        let enclosed = (0..width)
+            .all(|x| tiles[0][x] == Tile::Wall && tiles[height - 1][x] == Tile::Wall)
+            && tiles
+                .iter()
+                .all(|row| row[0] == Tile::Wall && row[width - 1] == Tile::Wall);
+        if !enclosed {
+            return Err("the maze is not enclosed by walls".to_string());
+        }
+        if tiles[1][1] == Tile::Wall {
+            return Err("the entrance at (1, 1) is a wall".to_string());
+        }
+        Ok(Maze { tiles })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn generator() -> impl Strategy<Value = Generator> {
+        prop_oneof![
+            Just(Generator::Kruskal),
+            (0.05..=1.0).prop_map(|coverage| Generator::Drunkard { coverage }),
+            Just(Generator::AldousBroder),
+            (0.0..=1.0, 0.0..=1.0)
+                .prop_map(|(newest, straight)| Generator::GrowingTree { newest, straight }),
+        ]
+    }
+
+    /// Whether all free tiles are connected to each other.
+    fn connected(maze: &Maze) -> bool {
+        let free: Vec<(usize, usize)> = (0..maze.tiles.len())
+            .flat_map(|y| (0..maze.tiles[y].len()).map(move |x| (x, y)))
+            .filter(|&(x, y)| matches!(maze.tiles[y][x], Tile::Free))
+            .collect();
+        let Some(&start) = free.first() else {
+            return true;
+        };
+        let mut seen = vec![vec![false; maze.tiles[0].len()]; maze.tiles.len()];
+        seen[start.1][start.0] = true;
+        let mut stack = vec![start];
+        let mut count = 0;
+        while let Some((x, y)) = stack.pop() {
+            count += 1;
+            for (nx, ny) in [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)] {
+                if matches!(maze.tiles[ny][nx], Tile::Free) && !seen[ny][nx] {
+                    seen[ny][nx] = true;
+                    stack.push((nx, ny));
+                }
+            }
+        }
+        count == free.len()
+    }
+
+    proptest! {
+        #[test]
+        fn prop_generators(generator in generator(), nx in 1..40usize, ny in 1..40usize, seed: u64) {
+            let maze = generator.generate(nx, ny, &mut StdRng::seed_from_u64(seed)).unwrap();
+            let (w, h) = (2 * nx + 1, 2 * ny + 1);
+            prop_assert_eq!(h, maze.tiles.len());
+            for line in &maze.tiles {
+                prop_assert_eq!(w, line.len());
+                prop_assert!(matches!(line[0], Tile::Wall));
+                prop_assert!(matches!(line[w - 1], Tile::Wall));
+            }
+            prop_assert!(maze.tiles[0].iter().all(|t| matches!(t, Tile::Wall)));
+            prop_assert!(maze.tiles[h - 1].iter().all(|t| matches!(t, Tile::Wall)));
+            prop_assert!(connected(&maze));
+        }
+    }
+
+    #[test]
+    fn test_kruskal_parallel() {
+        // three by two regions, the last column and row of them cut short
+        let (nx, ny) = (2 * REGION_CELLS + 5, REGION_CELLS + 1);
+        let maze = Maze::kruskal_parallel(nx, ny, &mut StdRng::seed_from_u64(3));
+        assert_eq!(2 * ny + 1, maze.tiles.len());
+        assert!(maze.tiles.iter().all(|line| line.len() == 2 * nx + 1));
+        assert!(connected(&maze));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let maze = Maze::kruskal(5, 3, &mut StdRng::seed_from_u64(2));
+        let rotated = maze.rotate90();
+        assert_eq!((7, 11), (rotated.width(), rotated.height()));
+        assert!(connected(&rotated));
+        assert_eq!(maze, rotated.rotate270());
+        assert_eq!(maze, rotated.rotate90().rotate90().rotate90());
+        assert_eq!(maze.rotate180(), maze.flip_vertical().flip_horizontal());
+        assert_eq!(maze, maze.flip_horizontal().flip_horizontal());
+        assert_eq!(maze.tiles[1][2], maze.flip_horizontal().tiles[1][8]);
+        for transformed in [rotated, maze.rotate180(), maze.flip_vertical()] {
+            assert_eq!(Ok(transformed.clone()), transformed.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn test_tiled() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let a = Maze::kruskal(3, 2, &mut rng);
+        let b = Maze::drunkard(4, 2, 1.0, &mut rng);
+        let wide = a.concat_horizontal(&b, &mut rng).unwrap();
+        assert_eq!((15, 5), (wide.width(), wide.height()));
+        assert!(connected(&wide));
+        // exactly one doorway in the shared wall
+        assert_eq!(
+            1,
+            (1..4).filter(|&y| wide.tiles[y][6] == Tile::Free).count()
+        );
+        assert!(a.concat_vertical(&b, &mut rng).is_err());
+        let tall = a.concat_vertical(&a.rotate180(), &mut rng).unwrap();
+        assert_eq!((7, 9), (tall.width(), tall.height()));
+        assert!(connected(&tall));
+
+        let c = Maze::kruskal(7, 3, &mut rng);
+        let world = Maze::tiled(&[vec![a.clone(), b.clone()], vec![c]], &mut rng).unwrap();
+        assert_eq!((15, 11), (world.width(), world.height()));
+        assert!(connected(&world));
+        assert_eq!(Ok(world.clone()), world.to_string().parse());
+        assert!(Maze::tiled(&[vec![a.clone()], vec![b]], &mut rng).is_err());
+        assert!(Maze::tiled(&[], &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_aldous_broder() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut walk = AldousBroder::new(6, 4, &mut rng);
+        let mut carved = vec![walk.pos()];
+        while !walk.is_done() {
+            let from = walk.pos();
+            let step = walk.step(&mut rng);
+            let to = walk.pos();
+            assert_eq!(2, from.0.abs_diff(to.0) + from.1.abs_diff(to.1));
+            carved.extend(step.into_iter().flatten());
+        }
+        assert_eq!((6 * 4, 6 * 4), walk.progress());
+        let maze = walk.into_maze();
+        let free = maze.tiles.iter().flatten().filter(|&&t| t == Tile::Free);
+        // a perfect maze has a passage less than it has cells
+        assert_eq!(6 * 4 + 6 * 4 - 1, free.count());
+        assert_eq!(carved.len(), 6 * 4 + 6 * 4 - 1);
+        assert!(carved.iter().all(|&(x, y)| maze.tiles[y][x] == Tile::Free));
+        assert_eq!(
+            maze,
+            Maze::aldous_broder(6, 4, &mut StdRng::seed_from_u64(5))
+        );
+        assert_eq!(
+            Maze::empty(1, 1),
+            Maze::aldous_broder(1, 1, &mut rand::rng())
+        );
+    }
+
+    #[test]
+    fn test_growing_tree() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let river = Maze::growing_tree(20, 20, 1.0, 0.0, &mut rng).shape();
+        let twisty = Maze::growing_tree(20, 20, 0.0, 0.0, &mut rng).shape();
+        let straight = Maze::growing_tree(20, 20, 1.0, 0.9, &mut rng).shape();
+        assert_eq!(20 * 20, river.free);
+        assert!(river.dead_end_ratio() < twisty.dead_end_ratio());
+        assert!(straight.straightness() > river.straightness());
+
+        assert_eq!(
+            Ok(Generator::GrowingTree {
+                newest: 0.9,
+                straight: DEFAULT_STRAIGHT
+            }),
+            "growing-tree:90".parse()
+        );
+        assert_eq!(
+            Ok(Generator::Drunkard { coverage: 0.6 }),
+            "drunkard:60%".parse()
+        );
+        assert!("drunkard:160".parse::<Generator>().is_err());
+        assert!("kruskal:10".parse::<Generator>().is_err());
+        assert!("growing-tree:1:2:3".parse::<Generator>().is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        let maze = Maze::kruskal(6, 4, &mut StdRng::seed_from_u64(1));
+        assert_eq!(Ok(maze.clone()), maze.to_string().parse());
+        let drawn: Maze = "#####\n#. .#\n### #\n\n#   #\n#####\n".parse().unwrap();
+        assert_eq!(5, drawn.tiles.len());
+        assert_eq!(Tile::Free, drawn.tiles[1][3]);
+        assert!("#####\n#   #\n#   \n#####".parse::<Maze>().is_err());
+        assert!("#####\n#   #\n#   ##\n#####".parse::<Maze>().is_err());
+        assert!("#####\n## ##\n#####".parse::<Maze>().is_err());
+        assert!("#####\n# x #\n#####".parse::<Maze>().is_err());
+    }
+
+    #[test]
+    fn test_drunkard_coverage() {
+        let maze = Maze::drunkard(10, 8, 0.4, &mut rand::rng());
+        let (w, h) = (21, 17);
+        let free = maze
+            .tiles
+            .iter()
+            .flatten()
+            .filter(|t| matches!(t, Tile::Free))
+            .count();
+        assert!(free >= ((w - 2) * (h - 2)) * 4 / 10);
+        for x in 0..w {
+            assert!(matches!(maze.tiles[0][x], Tile::Wall));
+            assert!(matches!(maze.tiles[h - 1][x], Tile::Wall));
+        }
+        for y in 0..h {
+            assert!(matches!(maze.tiles[y][0], Tile::Wall));
+            assert!(matches!(maze.tiles[y][w - 1], Tile::Wall));
+        }
+    }
+}
@@ -0,0 +1,675 @@
+use std::fmt;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::maze::{Generator, Maze};
+
+/// Everything needed to start a run. Two runs with the same config are identical.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RunConfig {
+    pub generator: Generator,
+    /// Stream an unbounded maze in chunks instead of generating a fixed size one.
+    pub infinite: bool,
+    /// Pick the size so the maze fills the map area of the terminal, which
+    /// then overrides `width` and `height`.
+    pub fit: bool,
+    /// Maze width in cells (the tile map is `2 * width + 1` wide).
+    pub width: usize,
+    /// Maze height in cells (the tile map is `2 * height + 1` high).
+    pub height: usize,
+    pub seed: u64,
+    /// Number of collectible items scattered in the maze.
+    pub items: usize,
+    /// Number of locked door and key pairs.
+    pub doors: usize,
+    /// Number of linked teleporter pairs.
+    pub teleporters: usize,
+    /// Number of one-way passages.
+    pub one_ways: usize,
+    /// Number of sand, mud, or ice patches that slow the robot down.
+    pub terrain: usize,
+    pub minotaur: Minotaur,
+    pub solver: Solver,
+    /// Number of goals the tour solver visits.
+    pub goals: usize,
+    /// Number of robots in a swarm, the main one included.
+    pub robots: usize,
+    /// Tiles within which robots of a swarm share their maps, or 0 for any
+    /// distance.
+    pub comm_radius: u16,
+    /// Ticks between two wall shifts, or 0 for a maze that never changes.
+    pub shift_interval: u64,
+    /// How long the robot takes to turn. Missing in replays from before
+    /// turns took time.
+    #[serde(default)]
+    pub chassis: Chassis,
+    /// How long the helpers of a swarm take to turn. Missing in replays
+    /// from before helpers turned.
+    #[serde(default)]
+    pub helper_chassis: Chassis,
+    /// Percent of the reachable tiles to visit for the run to be over, or 0
+    /// to go on as the solver does. Missing in replays from before.
+    #[serde(default)]
+    pub coverage_goal: u8,
+    /// Number of checkpoints to pass in order for a timed run, which ends
+    /// at the last one. Missing in replays from before.
+    #[serde(default)]
+    pub checkpoints: usize,
+    /// Where the entrance and the exit are carved into the outer wall.
+    /// Missing in replays from before.
+    #[serde(default)]
+    pub openings: Openings,
+}
+
+impl Default for RunConfig {
+    fn default() -> Self {
+        Self {
+            generator: Generator::Kruskal,
+            infinite: false,
+            fit: false,
+            width: 16,
+            height: 16,
+            seed: rand::random_range(0..1_000_000),
+            items: 10,
+            doors: 0,
+            teleporters: 0,
+            one_ways: 0,
+            terrain: 0,
+            minotaur: Minotaur::Off,
+            solver: Solver::Explorer,
+            goals: 4,
+            robots: 3,
+            comm_radius: 0,
+            shift_interval: 0,
+            chassis: Chassis::Legs,
+            helper_chassis: Chassis::Legs,
+            coverage_goal: 0,
+            checkpoints: 0,
+            openings: Openings::Closed,
+        }
+    }
+}
+
+/// How the minotaur hunting the robot moves, if there is one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Minotaur {
+    Off,
+    /// Walks around randomly, rarely turning back.
+    Wander,
+    /// Hunts the robot down once it has seen it.
+    Chase,
+}
+
+impl Minotaur {
+    /// Cycle to the next mode.
+    pub fn next(self) -> Self {
+        match self {
+            Minotaur::Off => Minotaur::Wander,
+            Minotaur::Wander => Minotaur::Chase,
+            Minotaur::Chase => Minotaur::Off,
+        }
+    }
+}
+
+impl fmt::Display for Minotaur {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Minotaur::Off => write!(f, "off"),
+            Minotaur::Wander => write!(f, "wander"),
+            Minotaur::Chase => write!(f, "chase"),
+        }
+    }
+}
+
+/// How the robot finds its way through the maze.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Solver {
+    /// Explores the whole maze depth first.
+    Explorer,
+    /// Learns the way from the entrance to the goal over many episodes.
+    QLearning,
+    /// Plans the order to visit several goals in and walks the route.
+    Tour,
+    /// Sends out ants whose pheromone trails converge on a short way to the
+    /// goal, which the robot then walks.
+    Ants,
+    /// Several robots sharing what they explored, each heading for a
+    /// different part of the maze.
+    Swarm,
+    /// Searches from the entrance and the goal at once until the searches
+    /// meet, then walks the way they found.
+    Bidirectional,
+    /// Searches depth first from the entrance, over and over with a deeper
+    /// limit until it finds the goal, then walks the way it found.
+    Deepening,
+    /// Races a jump point search against A* over the free tiles, then walks
+    /// the way they found.
+    JumpPoint,
+}
+
+impl Solver {
+    /// Cycle to the next solver.
+    pub fn next(self) -> Self {
+        match self {
+            Solver::Explorer => Solver::QLearning,
+            Solver::QLearning => Solver::Tour,
+            Solver::Tour => Solver::Ants,
+            Solver::Ants => Solver::Swarm,
+            Solver::Swarm => Solver::Bidirectional,
+            Solver::Bidirectional => Solver::Deepening,
+            Solver::Deepening => Solver::JumpPoint,
+            Solver::JumpPoint => Solver::Explorer,
+        }
+    }
+}
+
+impl fmt::Display for Solver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Solver::Explorer => write!(f, "robot-dfs"),
+            Solver::QLearning => write!(f, "q-learning"),
+            Solver::Tour => write!(f, "tour"),
+            Solver::Ants => write!(f, "ants"),
+            Solver::Swarm => write!(f, "swarm"),
+            Solver::Bidirectional => write!(f, "bidirectional"),
+            Solver::Deepening => write!(f, "iddfs"),
+            Solver::JumpPoint => write!(f, "jps"),
+        }
+    }
+}
+
+/// What the robot moves on, which decides how long it takes to turn.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Chassis {
+    /// Turns on the spot in no time.
+    #[default]
+    Legs,
+    /// Takes half a tick to turn.
+    Wheels,
+    /// Takes a tick to turn.
+    Treads,
+}
+
+impl Chassis {
+    /// Cycle to the next chassis.
+    pub fn next(self) -> Self {
+        match self {
+            Chassis::Legs => Chassis::Wheels,
+            Chassis::Wheels => Chassis::Treads,
+            Chassis::Treads => Chassis::Legs,
+        }
+    }
+
+    /// Half ticks a quarter turn takes.
+    pub fn turn_cost(self) -> u32 {
+        match self {
+            Chassis::Legs => 0,
+            Chassis::Wheels => 1,
+            Chassis::Treads => 2,
+        }
+    }
+}
+
+impl fmt::Display for Chassis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Chassis::Legs => write!(f, "legs, free turns"),
+            Chassis::Wheels => write!(f, "wheels, ½ tick turns"),
+            Chassis::Treads => write!(f, "treads, 1 tick turns"),
+        }
+    }
+}
+
+/// Where the robot comes into the maze and where it gets out. The robot
+/// starts in the entrance, and the exit becomes the goal.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Openings {
+    /// The outer wall is solid, the robot starts in the top left cell.
+    #[default]
+    Closed,
+    /// The entrance at the top of the west wall, the exit at the bottom of
+    /// the east wall.
+    Corners,
+    /// The entrance somewhere in the west wall, the exit somewhere in the
+    /// east wall.
+    Sides,
+    /// Anywhere in two different walls.
+    Random,
+}
+
+impl Openings {
+    /// Cycle to the next placement.
+    pub fn next(self) -> Self {
+        match self {
+            Openings::Closed => Openings::Corners,
+            Openings::Corners => Openings::Sides,
+            Openings::Sides => Openings::Random,
+            Openings::Random => Openings::Closed,
+        }
+    }
+}
+
+impl fmt::Display for Openings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Openings::Closed => write!(f, "closed"),
+            Openings::Corners => write!(f, "corners"),
+            Openings::Sides => write!(f, "west and east"),
+            Openings::Random => write!(f, "random"),
+        }
+    }
+}
+
+pub const MIN_SIZE: usize = 2;
+pub const MAX_SIZE: usize = 1000;
+pub const MAX_ITEMS: usize = 100;
+pub const MAX_DOORS: usize = 3;
+pub const MAX_TELEPORTERS: usize = 5;
+pub const MAX_ONE_WAYS: usize = 20;
+pub const MAX_TERRAIN: usize = 20;
+pub const MAX_GOALS: usize = 10;
+pub const MAX_CHECKPOINTS: usize = 9;
+pub const MAX_ROBOTS: usize = 6;
+pub const MAX_COMM_RADIUS: u16 = 40;
+pub const MAX_SHIFT_INTERVAL: u64 = 500;
+/// The shift interval changes in steps of this many ticks.
+const SHIFT_INTERVAL_STEP: u64 = 10;
+/// The coverage goal changes in steps of this many percent.
+const COVERAGE_GOAL_STEP: u8 = 5;
+
+/// Whether a maze of `width` x `height` cells can be generated.
+pub fn check_size(width: usize, height: usize) -> Result<(), String> {
+    let range = MIN_SIZE..=MAX_SIZE;
+    if !range.contains(&width) || !range.contains(&height) {
+        return Err(format!(
+            "size must be between {} and {}",
+            MIN_SIZE, MAX_SIZE
+        ));
+    }
+    Ok(())
+}
+
+/// Whether `maze`, read from text, is no bigger than the menu could make
+/// it. Smaller ones are fine down to the 3x3 tiles any maze needs.
+pub fn check_maze_size(maze: &Maze) -> Result<(), String> {
+    // a maze of n cells a side is 2n + 1 tiles
+    let cells = |tiles: usize| (tiles / 2).max(MIN_SIZE);
+    check_size(cells(maze.width()), cells(maze.height())).map_err(|e| {
+        format!(
+            "a {}x{} tile maze is out of range, {} cells",
+            maze.width(),
+            maze.height(),
+            e
+        )
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Generator,
+    /// The coverage of `drunkard` or how often `growing-tree` grows from
+    /// the newest cell.
+    Parameter,
+    Straight,
+    Infinite,
+    Fit,
+    Width,
+    Height,
+    Seed,
+    Items,
+    Doors,
+    Teleporters,
+    OneWays,
+    Terrain,
+    Minotaur,
+    Solver,
+    Chassis,
+    CoverageGoal,
+    Checkpoints,
+    Openings,
+    Goals,
+    Robots,
+    Radius,
+    HelperChassis,
+    Shift,
+}
+
+const ROWS: [Row; 24] = [
+    Row::Generator,
+    Row::Parameter,
+    Row::Straight,
+    Row::Infinite,
+    Row::Fit,
+    Row::Width,
+    Row::Height,
+    Row::Seed,
+    Row::Items,
+    Row::Doors,
+    Row::Teleporters,
+    Row::OneWays,
+    Row::Terrain,
+    Row::Minotaur,
+    Row::Solver,
+    Row::Chassis,
+    Row::CoverageGoal,
+    Row::Checkpoints,
+    Row::Openings,
+    Row::Goals,
+    Row::Robots,
+    Row::Radius,
+    Row::HelperChassis,
+    Row::Shift,
+];
+
+/// What the app should do after the menu handled a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    None,
+    Start,
+    /// Start today's daily puzzle.
+    Daily,
+    Leaderboard,
+    /// Evolve the robot's weights over many runs.
+    Evolve,
+    /// Run the solver on many seeds and show how it did.
+    Batch,
+    /// Pick a saved maze to run on.
+    Gallery,
+    /// Type in the maze size.
+    Size,
+    /// Go on with the session saved when quitting.
+    Resume,
+    /// Edit the settings kept between launches.
+    Settings,
+    Quit,
+}
+
+/// The start menu for choosing generator, size, and seed.
+#[derive(Debug, Default)]
+pub struct Menu {
+    selected: usize,
+    pub config: RunConfig,
+    /// Whether there is a saved session to resume.
+    pub resumable: bool,
+}
+
+impl Menu {
+    pub fn handle_key(&mut self, code: KeyCode) -> MenuAction {
+        match code {
+            KeyCode::Char('q') => return MenuAction::Quit,
+            KeyCode::Enter => return MenuAction::Start,
+            KeyCode::Char('l') => return MenuAction::Leaderboard,
+            KeyCode::Char('e') => return MenuAction::Evolve,
+            KeyCode::Char('b') => return MenuAction::Batch,
+            KeyCode::Char('g') => return MenuAction::Gallery,
+            KeyCode::Char('d') => return MenuAction::Daily,
+            KeyCode::Char('s') => return MenuAction::Size,
+            KeyCode::Char('o') => return MenuAction::Settings,
+            KeyCode::Char('c') if self.resumable => return MenuAction::Resume,
+            KeyCode::Up => self.selected = (self.selected + ROWS.len() - 1) % ROWS.len(),
+            KeyCode::Down => self.selected = (self.selected + 1) % ROWS.len(),
+            KeyCode::Left => self.change(-1),
+            KeyCode::Right => self.change(1),
+            KeyCode::Char('r') => self.config.seed = rand::random_range(0..1_000_000),
+            KeyCode::Char(c) if c.is_ascii_digit() && ROWS[self.selected] == Row::Seed => {
+                let digit = c.to_digit(10).unwrap() as u64;
+                self.config.seed = self.config.seed.saturating_mul(10).saturating_add(digit);
+            }
+            KeyCode::Backspace if ROWS[self.selected] == Row::Seed => self.config.seed /= 10,
+            _ => {}
+        }
+        MenuAction::None
+    }
+
+    fn change(&mut self, delta: isize) {
+        let config = &mut self.config;
+        match ROWS[self.selected] {
+            Row::Generator => config.generator = config.generator.next(),
+            Row::Parameter => match &mut config.generator {
+                Generator::Drunkard { coverage } => {
+                    *coverage = (*coverage + 0.05 * delta as f64).clamp(0.05, 1.0)
+                }
+                Generator::GrowingTree { newest, .. } => {
+                    *newest = (*newest + 0.05 * delta as f64).clamp(0.0, 1.0)
+                }
+                _ => {}
+            },
+            Row::Straight => {
+                if let Generator::GrowingTree { straight, .. } = &mut config.generator {
+                    *straight = (*straight + 0.05 * delta as f64).clamp(0.0, 1.0);
+                }
+            }
+            Row::Infinite => config.infinite = !config.infinite,
+            Row::Fit => config.fit = !config.fit,
+            Row::Width => config.width = step_size(config.width, delta),
+            Row::Height => config.height = step_size(config.height, delta),
+            Row::Seed => config.seed = config.seed.saturating_add_signed(delta as i64),
+            Row::Items => config.items = config.items.saturating_add_signed(delta).min(MAX_ITEMS),
+            Row::Doors => config.doors = config.doors.saturating_add_signed(delta).min(MAX_DOORS),
+            Row::Teleporters => {
+                config.teleporters = config
+                    .teleporters
+                    .saturating_add_signed(delta)
+                    .min(MAX_TELEPORTERS)
+            }
+            Row::OneWays => {
+                config.one_ways = config
+                    .one_ways
+                    .saturating_add_signed(delta)
+                    .min(MAX_ONE_WAYS)
+            }
+            Row::Terrain => {
+                config.terrain = config.terrain.saturating_add_signed(delta).min(MAX_TERRAIN)
+            }
+            Row::Minotaur => config.minotaur = config.minotaur.next(),
+            Row::Solver => config.solver = config.solver.next(),
+            Row::Chassis => config.chassis = config.chassis.next(),
+            Row::CoverageGoal => {
+                config.coverage_goal = config
+                    .coverage_goal
+                    .saturating_add_signed(delta as i8 * COVERAGE_GOAL_STEP as i8)
+                    .min(100)
+            }
+            Row::Checkpoints => {
+                config.checkpoints = config
+                    .checkpoints
+                    .saturating_add_signed(delta)
+                    .min(MAX_CHECKPOINTS)
+            }
+            Row::Openings => config.openings = config.openings.next(),
+            Row::Goals => {
+                config.goals = config
+                    .goals
+                    .saturating_add_signed(delta)
+                    .clamp(1, MAX_GOALS)
+            }
+            Row::Robots => {
+                config.robots = config
+                    .robots
+                    .saturating_add_signed(delta)
+                    .clamp(2, MAX_ROBOTS)
+            }
+            Row::Radius => {
+                config.comm_radius = config
+                    .comm_radius
+                    .saturating_add_signed(delta as i16)
+                    .min(MAX_COMM_RADIUS)
+            }
+            Row::HelperChassis => config.helper_chassis = config.helper_chassis.next(),
+            Row::Shift => {
+                config.shift_interval = config
+                    .shift_interval
+                    .saturating_add_signed(delta as i64 * SHIFT_INTERVAL_STEP as i64)
+                    .min(MAX_SHIFT_INTERVAL)
+            }
+        }
+    }
+
+    fn row_line(&self, row: Row) -> Line<'_> {
+        let config = &self.config;
+        let (label, value) = match row {
+            Row::Generator => (
+                "generator",
+                match config.generator {
+                    Generator::Kruskal => "kruskal".to_string(),
+                    Generator::Drunkard { .. } => "drunkard".to_string(),
+                    Generator::AldousBroder => "aldous-broder".to_string(),
+                    Generator::GrowingTree { .. } => "growing-tree".to_string(),
+                    Generator::Plugin { name } => name.to_string(),
+                },
+            ),
+            Row::Parameter => match config.generator {
+                Generator::Drunkard { coverage } => {
+                    ("coverage", format!("{:.0}%", coverage * 100.0))
+                }
+                Generator::GrowingTree { newest, .. } => {
+                    ("newest", format!("{:.0}%", newest * 100.0))
+                }
+                _ => ("coverage", "-".to_string()),
+            },
+            Row::Straight => (
+                "straight",
+                match config.generator {
+                    Generator::GrowingTree { straight, .. } => {
+                        format!("{:.0}%", straight * 100.0)
+                    }
+                    _ => "-".to_string(),
+                },
+            ),
+            Row::Infinite => (
+                "infinite",
+                if config.infinite { "on" } else { "off" }.into(),
+            ),
+            Row::Fit => ("fit screen", if config.fit { "on" } else { "off" }.into()),
+            Row::Width if config.fit => ("width", "-".to_string()),
+            Row::Height if config.fit => ("height", "-".to_string()),
+            Row::Width => ("width", config.width.to_string()),
+            Row::Height => ("height", config.height.to_string()),
+            Row::Seed => ("seed", config.seed.to_string()),
+            Row::Items => ("items", config.items.to_string()),
+            Row::Doors => ("doors", config.doors.to_string()),
+            Row::Teleporters => ("teleporters", config.teleporters.to_string()),
+            Row::OneWays => ("one-ways", config.one_ways.to_string()),
+            Row::Terrain => ("terrain", config.terrain.to_string()),
+            Row::Minotaur => ("minotaur", config.minotaur.to_string()),
+            Row::Solver => ("solver", config.solver.to_string()),
+            Row::Chassis => ("chassis", config.chassis.to_string()),
+            Row::CoverageGoal => ("cover goal", coverage_goal_label(config.coverage_goal)),
+            Row::Checkpoints => ("checkpoints", config.checkpoints.to_string()),
+            Row::Openings => (
+                "openings",
+                match config.infinite {
+                    true => "-".to_string(),
+                    false => config.openings.to_string(),
+                },
+            ),
+            Row::Goals => (
+                "goals",
+                match config.solver {
+                    Solver::Tour => config.goals.to_string(),
+                    _ => "-".to_string(),
+                },
+            ),
+            Row::Robots => (
+                "robots",
+                match config.solver {
+                    Solver::Swarm => config.robots.to_string(),
+                    _ => "-".to_string(),
+                },
+            ),
+            Row::Radius => (
+                "comm radius",
+                match config.solver {
+                    Solver::Swarm => radius_label(config.comm_radius),
+                    _ => "-".to_string(),
+                },
+            ),
+            Row::HelperChassis => (
+                "helpers",
+                match config.solver {
+                    Solver::Swarm => config.helper_chassis.to_string(),
+                    _ => "-".to_string(),
+                },
+            ),
+            Row::Shift => ("wall shift", shift_label(config.shift_interval)),
+        };
+        let line = Line::from(format!("{:<12} < {} >", label, value));
+        if ROWS[self.selected] == row {
+            line.reversed()
+        } else {
+            line
+        }
+    }
+}
+
+/// How the wall shift interval is shown to the user.
+pub fn shift_label(interval: u64) -> String {
+    if interval == 0 {
+        "off".to_string()
+    } else {
+        format!("every {} ticks", interval)
+    }
+}
+
+/// How the coverage goal is shown to the user.
+pub fn coverage_goal_label(percent: u8) -> String {
+    if percent == 0 {
+        "off".to_string()
+    } else {
+        format!("{}% of the maze", percent)
+    }
+}
+
+pub fn radius_label(radius: u16) -> String {
+    if radius == 0 {
+        "unlimited".to_string()
+    } else {
+        format!("{} tiles", radius)
+    }
+}
+
+pub fn step_size(size: usize, delta: isize) -> usize {
+    size.saturating_add_signed(delta).clamp(MIN_SIZE, MAX_SIZE)
+}
+
+/// A `width` x `height` rectangle centered in `area`, clamped to its size.
+pub fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let [area] = Layout::horizontal([Constraint::Length(width)])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(area);
+    let [area] = Layout::vertical([Constraint::Length(height)])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(area);
+    area
+}
+
+impl Widget for &Menu {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = ROWS.iter().map(|row| self.row_line(*row)).collect();
+        lines.push(Line::default());
+        lines.push(Line::from("↑/↓ select  ←/→ change  s type size").style(Style::new().dim()));
+        lines.push(Line::from("r random seed").style(Style::new().dim()));
+        lines.push(
+            Line::from("Enter start  d daily puzzle  l leaderboard").style(Style::new().dim()),
+        );
+        lines.push(
+            Line::from("e evolve  b batch run  g gallery  o settings").style(Style::new().dim()),
+        );
+        lines.push(Line::from("q quit").style(Style::new().dim()));
+        if self.resumable {
+            lines.push(Line::from("c resume the last session").bold());
+        }
+        let block = Block::bordered().title(" ratatui-fun ");
+        let area = centered(area, 48, lines.len() as u16 + 2);
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
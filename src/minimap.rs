@@ -0,0 +1,111 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, Clear, Widget},
+};
+
+/// A downscaled view of a whole map where one terminal cell covers `n` x `n` tiles.
+///
+/// Each cell gets the color that occurs most often in its block of tiles.
+/// Markers are drawn on top and always win over the majority color.
+pub struct Minimap<'a, F> {
+    width: u16,
+    height: u16,
+    color_at: F,
+    markers: Vec<((u16, u16), Color)>,
+    block: Option<Block<'a>>,
+}
+
+impl<'a, F> Minimap<'a, F>
+where
+    F: Fn(u16, u16) -> Color,
+{
+    pub fn new(width: u16, height: u16, color_at: F) -> Self {
+        Self {
+            width,
+            height,
+            color_at,
+            markers: Vec::new(),
+            block: None,
+        }
+    }
+
+    pub fn marker(mut self, pos: (u16, u16), color: Color) -> Self {
+        self.markers.push((pos, color));
+        self
+    }
+
+    pub fn block(mut self, block: Block<'a>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Smallest scale at which the whole map fits into `width` x `height` cells.
+    pub fn scale_for(&self, width: u16, height: u16) -> u16 {
+        let sx = self.width.div_ceil(width.max(1));
+        let sy = self.height.div_ceil(height.max(1));
+        sx.max(sy).max(1)
+    }
+
+    /// Outer size (including the block border) needed when fitting into `max`.
+    pub fn size_within(&self, max_width: u16, max_height: u16) -> (u16, u16) {
+        let border = if self.block.is_some() { 2 } else { 0 };
+        let n = self.scale_for(
+            max_width.saturating_sub(border),
+            max_height.saturating_sub(border),
+        );
+        (
+            self.width.div_ceil(n) + border,
+            self.height.div_ceil(n) + border,
+        )
+    }
+
+    fn majority(&self, x0: u16, y0: u16, n: u16) -> Color {
+        let mut counts: Vec<(Color, usize)> = Vec::new();
+        for y in y0..(y0 + n).min(self.height) {
+            for x in x0..(x0 + n).min(self.width) {
+                let color = (self.color_at)(x, y);
+                match counts.iter_mut().find(|(c, _)| *c == color) {
+                    Some((_, count)) => *count += 1,
+                    None => counts.push((color, 1)),
+                }
+            }
+        }
+        counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(color, _)| color)
+            .unwrap_or(Color::Reset)
+    }
+}
+
+impl<F> Widget for Minimap<'_, F>
+where
+    F: Fn(u16, u16) -> Color,
+{
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let inner = match &self.block {
+            Some(block) => {
+                let inner = block.inner(area);
+                block.clone().render(area, buf);
+                inner
+            }
+            None => area,
+        };
+        let n = self.scale_for(inner.width, inner.height);
+        for my in 0..self.height.div_ceil(n).min(inner.height) {
+            for mx in 0..self.width.div_ceil(n).min(inner.width) {
+                let color = self.majority(mx * n, my * n, n);
+                buf[(inner.x + mx, inner.y + my)].set_bg(color);
+            }
+        }
+        for ((x, y), color) in &self.markers {
+            let (mx, my) = (x / n, y / n);
+            if mx < inner.width && my < inner.height {
+                buf[(inner.x + mx, inner.y + my)].set_bg(*color);
+            }
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::sync::RwLock;
+
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::KeyColor;
+
+/// The available palettes, selected with `--palette` or cycled at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PaletteName {
+    #[default]
+    Default,
+    /// Avoids telling things apart by red and green alone.
+    Deuteranopia,
+    /// Saturated colors on black.
+    HighContrast,
+    /// Shades of gray, with characters telling the tiles apart.
+    Mono,
+}
+
+impl PaletteName {
+    /// Cycle to the next palette.
+    pub fn next(self) -> Self {
+        match self {
+            PaletteName::Default => PaletteName::Deuteranopia,
+            PaletteName::Deuteranopia => PaletteName::HighContrast,
+            PaletteName::HighContrast => PaletteName::Mono,
+            PaletteName::Mono => PaletteName::Default,
+        }
+    }
+
+    pub fn palette(self) -> Palette {
+        match self {
+            PaletteName::Default => Palette::DEFAULT,
+            PaletteName::Deuteranopia => Palette::DEUTERANOPIA,
+            PaletteName::HighContrast => Palette::HIGH_CONTRAST,
+            PaletteName::Mono => Palette::MONO,
+        }
+    }
+}
+
+impl fmt::Display for PaletteName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteName::Default => write!(f, "default"),
+            PaletteName::Deuteranopia => write!(f, "deuteranopia"),
+            PaletteName::HighContrast => write!(f, "high contrast"),
+            PaletteName::Mono => write!(f, "mono"),
+        }
+    }
+}
+
+/// The colors of everything drawn on the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    pub free: Color,
+    pub wall: Color,
+    pub teleporter: Color,
+    pub visited: Color,
+    pub stack: Color,
+    pub robot: Color,
+    pub minotaur: Color,
+    pub coin: Color,
+    pub star: Color,
+    /// Key colors, indexed like `KEY_COLORS`.
+    pub keys: [Color; 3],
+    /// Door colors, indexed like `KEY_COLORS`.
+    pub doors: [Color; 3],
+    pub sand: Color,
+    pub mud: Color,
+    pub ice: Color,
+    pub search: Color,
+    /// A second search, e.g. from the goal, next to the one from the start.
+    pub search_back: Color,
+    /// Ends of the distance field gradient, next to and far from the goal.
+    pub near: Color,
+    pub far: Color,
+    /// The selected stack entry.
+    pub highlight: Color,
+    /// Give every tile a glyph, so tiles can be told apart without colors.
+    pub glyphs: bool,
+}
+
+impl Palette {
+    pub const DEFAULT: Palette = Palette {
+        free: Color::Black,
+        wall: Color::DarkGray,
+        teleporter: Color::Magenta,
+        visited: Color::Blue,
+        stack: Color::Yellow,
+        robot: Color::Green,
+        minotaur: Color::Red,
+        coin: Color::LightYellow,
+        star: Color::LightMagenta,
+        keys: [Color::LightRed, Color::LightCyan, Color::White],
+        doors: [Color::Red, Color::Cyan, Color::Gray],
+        sand: Color::Rgb(194, 178, 128),
+        mud: Color::Rgb(110, 75, 40),
+        ice: Color::Rgb(170, 220, 255),
+        search: Color::LightGreen,
+        search_back: Color::LightCyan,
+        near: Color::Rgb(255, 210, 60),
+        far: Color::Rgb(20, 40, 120),
+        highlight: Color::LightMagenta,
+        glyphs: false,
+    };
+
+    /// Built from the Okabe-Ito colors, which stay distinct for all common
+    /// kinds of color blindness.
+    pub const DEUTERANOPIA: Palette = Palette {
+        free: Color::Black,
+        wall: Color::DarkGray,
+        teleporter: Color::Rgb(0, 158, 115),
+        visited: Color::Rgb(0, 114, 178),
+        stack: Color::Rgb(240, 228, 66),
+        robot: Color::White,
+        minotaur: Color::Rgb(213, 94, 0),
+        coin: Color::Rgb(230, 159, 0),
+        star: Color::Rgb(204, 121, 167),
+        keys: [
+            Color::Rgb(255, 150, 110),
+            Color::Rgb(86, 180, 233),
+            Color::White,
+        ],
+        doors: [
+            Color::Rgb(150, 60, 0),
+            Color::Rgb(40, 100, 150),
+            Color::Gray,
+        ],
+        sand: Color::Rgb(194, 178, 128),
+        mud: Color::Rgb(110, 75, 40),
+        ice: Color::Rgb(170, 220, 255),
+        search: Color::Rgb(86, 180, 233),
+        search_back: Color::Rgb(230, 159, 0),
+        near: Color::Rgb(255, 210, 60),
+        far: Color::Rgb(20, 40, 120),
+        highlight: Color::Rgb(204, 121, 167),
+        glyphs: false,
+    };
+
+    pub const HIGH_CONTRAST: Palette = Palette {
+        free: Color::Black,
+        wall: Color::White,
+        teleporter: Color::Rgb(170, 0, 255),
+        visited: Color::Rgb(0, 70, 255),
+        stack: Color::Rgb(255, 255, 0),
+        robot: Color::Rgb(0, 255, 0),
+        minotaur: Color::Rgb(255, 0, 0),
+        coin: Color::Rgb(255, 150, 0),
+        star: Color::Rgb(255, 0, 200),
+        keys: [
+            Color::Rgb(255, 90, 90),
+            Color::Rgb(0, 255, 255),
+            Color::White,
+        ],
+        doors: [Color::Rgb(180, 0, 0), Color::Rgb(0, 170, 170), Color::Gray],
+        sand: Color::Rgb(255, 220, 120),
+        mud: Color::Rgb(150, 90, 30),
+        ice: Color::Rgb(200, 240, 255),
+        search: Color::White,
+        search_back: Color::Rgb(255, 150, 0),
+        near: Color::Rgb(255, 255, 0),
+        far: Color::Rgb(0, 0, 160),
+        highlight: Color::Rgb(255, 0, 255),
+        glyphs: false,
+    };
+
+    pub const MONO: Palette = Palette {
+        free: Color::Black,
+        wall: Color::White,
+        teleporter: Color::Black,
+        visited: Color::Rgb(60, 60, 60),
+        stack: Color::Black,
+        robot: Color::Black,
+        minotaur: Color::Black,
+        coin: Color::Black,
+        star: Color::Black,
+        keys: [Color::Black; 3],
+        doors: [Color::Black; 3],
+        sand: Color::Rgb(150, 150, 150),
+        mud: Color::Rgb(100, 100, 100),
+        ice: Color::Rgb(200, 200, 200),
+        search: Color::White,
+        search_back: Color::Gray,
+        near: Color::Rgb(230, 230, 230),
+        far: Color::Rgb(40, 40, 40),
+        highlight: Color::White,
+        glyphs: true,
+    };
+
+    pub fn key(&self, color: KeyColor) -> Color {
+        self.keys[color as usize]
+    }
+
+    pub fn door(&self, color: KeyColor) -> Color {
+        self.doors[color as usize]
+    }
+}
+
+lazy_static! {
+    /// The palette tiles are drawn with. It is global, as the tile colors
+    /// come from `From` impls without any access to the app.
+    static ref CURRENT: RwLock<Palette> = RwLock::new(Palette::DEFAULT);
+}
+
+/// The palette in use.
+pub fn current() -> Palette {
+    *CURRENT.read().unwrap()
+}
+
+pub fn set(palette: Palette) {
+    *CURRENT.write().unwrap() = palette;
+}
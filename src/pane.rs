@@ -0,0 +1,236 @@
+use std::fmt;
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+/// What the run screen can show, each in a pane of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Pane {
+    Map,
+    /// The run info, always as high as it needs to be.
+    Stats,
+    Stack,
+    /// The latest lines of the log.
+    Log,
+    /// The thoughts of the robot, left out while they are hidden.
+    Thoughts,
+    /// Left out while the minimap is hidden, which is then drawn over the
+    /// map if there is no such pane.
+    Minimap,
+    /// The corridor ahead as the robot sees it, left out while hidden.
+    View,
+}
+
+impl fmt::Display for Pane {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Pane::Map => "map",
+            Pane::Stats => "stats",
+            Pane::Stack => "stack",
+            Pane::Log => "log",
+            Pane::Thoughts => "thoughts",
+            Pane::Minimap => "minimap",
+            Pane::View => "view",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Panes stacked from top to bottom.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Column {
+    /// Share of the width, relative to the other columns.
+    pub width: u16,
+    pub panes: Vec<Pane>,
+    /// Shares of the height of the panes, relative to each other, 1 for
+    /// those without one. The stats take what they need first.
+    #[serde(default)]
+    pub heights: Vec<u16>,
+}
+
+impl Column {
+    fn height(&self, i: usize) -> u16 {
+        self.heights.get(i).copied().unwrap_or(1)
+    }
+}
+
+/// Columns of panes from left to right, set in the `[layout]` table of
+/// the settings file and resized while running.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PaneLayout {
+    pub columns: Vec<Column>,
+}
+
+/// Widths and heights change in steps of this much.
+const WIDTH_STEP: u16 = 5;
+const MAX_WIDTH: u16 = 100;
+const MAX_HEIGHT: u16 = 10;
+
+impl Default for PaneLayout {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                Column {
+                    width: 33,
+                    panes: vec![
+                        Pane::Stats,
+                        Pane::Stack,
+                        Pane::Log,
+                        Pane::Thoughts,
+                        Pane::View,
+                    ],
+                    heights: Vec::new(),
+                },
+                Column {
+                    width: 67,
+                    panes: vec![Pane::Map],
+                    heights: Vec::new(),
+                },
+            ],
+        }
+    }
+}
+
+impl PaneLayout {
+    /// Whether the layout has the map, and every other pane once at most.
+    pub fn check(&self) -> Result<(), String> {
+        let panes: Vec<Pane> = self.panes().collect();
+        if !panes.contains(&Pane::Map) {
+            return Err("the layout has no map".to_string());
+        }
+        if let Some(pane) = panes
+            .iter()
+            .enumerate()
+            .find_map(|(i, pane)| panes[..i].contains(pane).then_some(pane))
+        {
+            return Err(format!("the layout has more than one {} pane", pane));
+        }
+        if self.columns.iter().any(|column| column.width == 0) {
+            return Err("a column of the layout has no width".to_string());
+        }
+        Ok(())
+    }
+
+    /// All panes, column by column.
+    pub fn panes(&self) -> impl Iterator<Item = Pane> + '_ {
+        self.columns
+            .iter()
+            .flat_map(|column| column.panes.iter().copied())
+    }
+
+    pub fn contains(&self, pane: Pane) -> bool {
+        self.position(pane).is_some()
+    }
+
+    /// The column of `pane` and where it is in the column.
+    fn position(&self, pane: Pane) -> Option<(usize, usize)> {
+        self.columns.iter().enumerate().find_map(|(c, column)| {
+            let i = column.panes.iter().position(|&p| p == pane)?;
+            Some((c, i))
+        })
+    }
+
+    /// Where the panes for which `shown` holds go in `area`, with the stats
+    /// `stats_height` high. Columns without a shown pane are left out.
+    pub fn areas(
+        &self,
+        area: Rect,
+        shown: impl Fn(Pane) -> bool,
+        stats_height: u16,
+    ) -> Vec<(Pane, Rect)> {
+        let columns: Vec<(&Column, Vec<usize>)> = self
+            .columns
+            .iter()
+            .map(|column| {
+                let shown = (0..column.panes.len())
+                    .filter(|&i| shown(column.panes[i]))
+                    .collect();
+                (column, shown)
+            })
+            .filter(|(_, shown): &(_, Vec<usize>)| !shown.is_empty())
+            .collect();
+        let widths = columns
+            .iter()
+            .map(|(column, _)| Constraint::Fill(column.width));
+        let column_areas = Layout::horizontal(widths).split(area);
+        let mut areas = Vec::new();
+        for ((column, shown), &column_area) in columns.iter().zip(column_areas.iter()) {
+            let heights = shown.iter().map(|&i| match column.panes[i] {
+                Pane::Stats => Constraint::Length(stats_height),
+                _ => Constraint::Fill(column.height(i)),
+            });
+            let pane_areas = Layout::vertical(heights).split(column_area);
+            areas.extend(
+                shown
+                    .iter()
+                    .map(|&i| column.panes[i])
+                    .zip(pane_areas.iter().copied()),
+            );
+        }
+        areas
+    }
+
+    /// Make the column of `pane` wider, or narrower for a negative `steps`.
+    pub fn widen(&mut self, pane: Pane, steps: i16) {
+        if let Some((c, _)) = self.position(pane) {
+            let width = &mut self.columns[c].width;
+            *width = width
+                .saturating_add_signed(steps * WIDTH_STEP as i16)
+                .clamp(WIDTH_STEP, MAX_WIDTH);
+        }
+    }
+
+    /// Make `pane` higher, or lower for a negative `steps`, at the cost of
+    /// the others in its column.
+    pub fn heighten(&mut self, pane: Pane, steps: i16) {
+        if let Some((c, i)) = self.position(pane) {
+            let column = &mut self.columns[c];
+            let height = column
+                .height(i)
+                .saturating_add_signed(steps)
+                .clamp(1, MAX_HEIGHT);
+            if column.heights.len() <= i {
+                column.heights.resize(i + 1, 1);
+            }
+            column.heights[i] = height;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_areas() {
+        let mut layout = PaneLayout::default();
+        assert_eq!(Ok(()), layout.check());
+        let area = Rect::new(0, 0, 90, 40);
+        let areas = layout.areas(area, |pane| pane != Pane::Thoughts, 10);
+        assert_eq!(
+            vec![
+                (Pane::Stats, Rect::new(0, 0, 30, 10)),
+                (Pane::Stack, Rect::new(0, 10, 30, 10)),
+                (Pane::Log, Rect::new(0, 20, 30, 10)),
+                (Pane::View, Rect::new(0, 30, 30, 10)),
+                (Pane::Map, Rect::new(30, 0, 60, 40)),
+            ],
+            areas
+        );
+
+        layout.heighten(Pane::View, 2);
+        layout.widen(Pane::Map, -20);
+        assert_eq!(vec![1, 1, 1, 1, 3], layout.columns[0].heights);
+        assert_eq!(WIDTH_STEP, layout.columns[1].width);
+        let areas = layout.areas(area, |pane| pane == Pane::Map, 10);
+        assert_eq!(vec![(Pane::Map, area)], areas);
+
+        layout.columns[0].panes.push(Pane::Stack);
+        assert!(layout.check().is_err());
+        layout.columns.remove(1);
+        assert!(layout.check().is_err());
+    }
+}
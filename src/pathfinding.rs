@@ -0,0 +1,468 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use crate::tile::TileMap;
+
+/// Tile coordinates, as used to index a `TileMap`.
+pub type Point = (u16, u16);
+
+/// One move in a search.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    /// The tile stepped into.
+    pub tile: Point,
+    /// Where the move ends, which differs from `tile` e.g. at teleporters.
+    pub to: Point,
+    pub cost: u32,
+}
+
+/// A path found by one of the searches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// The moves from the start to the goal. Empty if the start is a goal.
+    pub steps: Vec<Step>,
+    /// Sum of the costs of all steps.
+    pub cost: u32,
+}
+
+impl Path {
+    /// Where the path ends.
+    pub fn end(&self) -> Option<Point> {
+        self.steps.last().map(|step| step.to)
+    }
+}
+
+/// The four direct neighbors of `pos` that lie inside a `width` x `height` map.
+pub fn neighbors(pos: Point, width: u16, height: u16) -> impl Iterator<Item = Point> {
+    let (x, y) = pos;
+    [
+        y.checked_sub(1).map(|y| (x, y)),
+        (x + 1 < width).then(|| (x + 1, y)),
+        (y + 1 < height).then(|| (x, y + 1)),
+        x.checked_sub(1).map(|x| (x, y)),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Moves of cost 1 between neighboring tiles for which `passable` holds.
+pub fn grid_steps<'a, T>(
+    map: &'a TileMap<T>,
+    passable: impl Fn(&T) -> bool + 'a,
+) -> impl Fn(Point) -> Vec<Step> + 'a {
+    move |pos| {
+        neighbors(pos, map.width(), map.height())
+            .filter(|&next| passable(&map[next]))
+            .map(|next| Step {
+                tile: next,
+                to: next,
+                cost: 1,
+            })
+            .collect()
+    }
+}
+
+/// Manhattan distance between two tiles.
+pub fn manhattan(a: Point, b: Point) -> u32 {
+    (a.0.abs_diff(b.0) + a.1.abs_diff(b.1)) as u32
+}
+
+/// The tiles a search expanded in order, each with the tile it was reached
+/// from. The start has no parent.
+pub type SearchTree = Vec<(Point, Option<Point>)>;
+
+/// Where each reached tile was reached from, and with which step.
+type Parents = TileMap<Option<(Point, Step)>>;
+
+fn trace(parent: &Parents, start: Point, end: Point) -> Path {
+    let mut steps = Vec::new();
+    let mut cur = end;
+    while cur != start {
+        let (prev, step) = parent[cur].unwrap();
+        steps.push(step);
+        cur = prev;
+    }
+    steps.reverse();
+    let cost = steps.iter().map(|step| step.cost).sum();
+    Path { steps, cost }
+}
+
+/// Search ignoring costs, either breadth first (fewest steps) or depth first.
+fn uninformed(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+    goal: impl Fn(Point) -> bool,
+    depth_first: bool,
+) -> Option<Path> {
+    let mut parent: Parents = TileMap::with_default(size.0, size.1);
+    let mut seen: TileMap<bool> = TileMap::with_default(size.0, size.1);
+    let mut open = VecDeque::from([start]);
+    seen[start] = true;
+    loop {
+        let pos = if depth_first {
+            open.pop_back()
+        } else {
+            open.pop_front()
+        }?;
+        if goal(pos) {
+            return Some(trace(&parent, start, pos));
+        }
+        for step in steps(pos) {
+            if !seen[step.to] {
+                seen[step.to] = true;
+                parent[step.to] = Some((pos, step));
+                open.push_back(step.to);
+            }
+        }
+    }
+}
+
+/// The path with the fewest steps from `start` to a tile for which `goal` holds.
+pub fn bfs(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+    goal: impl Fn(Point) -> bool,
+) -> Option<Path> {
+    uninformed(size, start, steps, goal, false)
+}
+
+/// Some path from `start` to a tile for which `goal` holds. Cheap when only
+/// the existence of a path matters.
+pub fn dfs(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+    goal: impl Fn(Point) -> bool,
+) -> Option<Path> {
+    uninformed(size, start, steps, goal, true)
+}
+
+/// Best first search ordered by cost so far plus `heuristic`.
+fn best_first(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+    goal: impl Fn(Point) -> bool,
+    heuristic: impl Fn(Point) -> u32,
+    tree: &mut SearchTree,
+) -> Option<Path> {
+    let mut parent: Parents = TileMap::with_default(size.0, size.1);
+    let mut cost: TileMap<Option<u32>> = TileMap::with_default(size.0, size.1);
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start))]);
+    cost[start] = Some(0);
+    while let Some(Reverse((_, pos_cost, pos))) = open.pop() {
+        if cost[pos] != Some(pos_cost) {
+            // already reached more cheaply
+            continue;
+        }
+        tree.push((pos, parent[pos].map(|(prev, _)| prev)));
+        if goal(pos) {
+            return Some(trace(&parent, start, pos));
+        }
+        for step in steps(pos) {
+            let next_cost = pos_cost + step.cost;
+            if cost[step.to].is_some_and(|c| c <= next_cost) {
+                continue;
+            }
+            cost[step.to] = Some(next_cost);
+            parent[step.to] = Some((pos, step));
+            open.push(Reverse((
+                next_cost + heuristic(step.to),
+                next_cost,
+                step.to,
+            )));
+        }
+    }
+    None
+}
+
+/// The cheapest path from `start` to a tile for which `goal` holds, along
+/// with the tree of tiles the search expanded on the way.
+pub fn dijkstra(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+    goal: impl Fn(Point) -> bool,
+) -> (Option<Path>, SearchTree) {
+    let mut tree = Vec::new();
+    let path = best_first(size, start, steps, goal, |_| 0, &mut tree);
+    (path, tree)
+}
+
+/// The cheapest path from `start` to `target`, guided by the manhattan
+/// distance. Only guaranteed to be the cheapest if no step covers more
+/// distance than it costs, which teleporters do.
+pub fn astar(
+    size: (u16, u16),
+    start: Point,
+    target: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+) -> Option<Path> {
+    astar_search(size, start, target, steps).0
+}
+
+/// Like `astar`, along with the tree of tiles the search expanded on the
+/// way.
+pub fn astar_search(
+    size: (u16, u16),
+    start: Point,
+    target: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+) -> (Option<Path>, SearchTree) {
+    let mut tree = Vec::new();
+    let path = best_first(
+        size,
+        start,
+        steps,
+        |pos| pos == target,
+        |pos| manhattan(pos, target),
+        &mut tree,
+    );
+    (path, tree)
+}
+
+/// The tile one move from `pos` in direction `(dx, dy)`, if it lies inside
+/// a `size` map and `passable` holds for it.
+fn offset(
+    size: (u16, u16),
+    pos: Point,
+    (dx, dy): (i32, i32),
+    passable: &impl Fn(Point) -> bool,
+) -> Option<Point> {
+    let x = u16::try_from(pos.0 as i32 + dx).ok()?;
+    let y = u16::try_from(pos.1 as i32 + dy).ok()?;
+    (x < size.0 && y < size.1 && passable((x, y))).then_some((x, y))
+}
+
+/// Where moving straight from `pos` in direction `dir` has to stop to
+/// consider turning, and how many moves away. Moving sideways it stops
+/// next to a wall ending beside it, and moving up or down also wherever
+/// moving sideways from it would stop. `None` if it runs into a wall first.
+fn jump(
+    size: (u16, u16),
+    pos: Point,
+    dir: (i32, i32),
+    target: Point,
+    passable: &impl Fn(Point) -> bool,
+) -> Option<(Point, u32)> {
+    let sides = match dir {
+        (_, 0) => [(0, -1), (0, 1)],
+        _ => [(-1, 0), (1, 0)],
+    };
+    let mut cur = pos;
+    let mut moves = 0;
+    loop {
+        let prev = cur;
+        cur = offset(size, cur, dir, passable)?;
+        moves += 1;
+        if cur == target {
+            return Some((cur, moves));
+        }
+        for side in sides {
+            // a wall beside the way ends here, opening a new way to turn to
+            if offset(size, cur, side, passable).is_some()
+                && offset(size, prev, side, passable).is_none()
+            {
+                return Some((cur, moves));
+            }
+        }
+        if dir.0 == 0
+            && sides
+                .into_iter()
+                .any(|side| jump(size, cur, side, target, passable).is_some())
+        {
+            return Some((cur, moves));
+        }
+    }
+}
+
+/// The path with the fewest steps from `start` to `target` over tiles for
+/// which `passable` holds, found by jump point search, along with the tree
+/// of jump points it expanded. Rather than adding each neighbor of a tile
+/// to the open list, it jumps straight on to where the path could have to
+/// turn, so on maps with open areas it expands far fewer tiles than
+/// `astar`.
+pub fn jps(
+    size: (u16, u16),
+    start: Point,
+    target: Point,
+    passable: impl Fn(Point) -> bool,
+) -> (Option<Path>, SearchTree) {
+    let mut parent: TileMap<Option<Point>> = TileMap::with_default(size.0, size.1);
+    let mut cost: TileMap<Option<u32>> = TileMap::with_default(size.0, size.1);
+    let mut open = BinaryHeap::from([Reverse((manhattan(start, target), 0, start))]);
+    let mut tree = Vec::new();
+    cost[start] = Some(0);
+    while let Some(Reverse((_, pos_cost, pos))) = open.pop() {
+        if cost[pos] != Some(pos_cost) {
+            continue;
+        }
+        tree.push((pos, parent[pos]));
+        if pos == target {
+            return (Some(trace_jumps(&parent, start, target)), tree);
+        }
+        // jumps go straight, so never back the way they came
+        let back = parent[pos].map(|prev| {
+            (
+                (prev.0 as i32 - pos.0 as i32).signum(),
+                (prev.1 as i32 - pos.1 as i32).signum(),
+            )
+        });
+        for dir in [(0, -1), (1, 0), (0, 1), (-1, 0)] {
+            if Some(dir) == back {
+                continue;
+            }
+            let Some((next, moves)) = jump(size, pos, dir, target, &passable) else {
+                continue;
+            };
+            let next_cost = pos_cost + moves;
+            if cost[next].is_some_and(|c| c <= next_cost) {
+                continue;
+            }
+            cost[next] = Some(next_cost);
+            parent[next] = Some(pos);
+            open.push(Reverse((
+                next_cost + manhattan(next, target),
+                next_cost,
+                next,
+            )));
+        }
+    }
+    (None, tree)
+}
+
+/// The moves from `start` to `end` along the straight jumps between the
+/// jump points in `parent`.
+fn trace_jumps(parent: &TileMap<Option<Point>>, start: Point, end: Point) -> Path {
+    let mut steps = Vec::new();
+    let mut cur = end;
+    while cur != start {
+        let prev = parent[cur].unwrap();
+        let mut tile = cur;
+        while tile != prev {
+            steps.push(Step {
+                tile,
+                to: tile,
+                cost: 1,
+            });
+            tile = (
+                (tile.0 as i32 + (prev.0 as i32 - tile.0 as i32).signum()) as u16,
+                (tile.1 as i32 + (prev.1 as i32 - tile.1 as i32).signum()) as u16,
+            );
+        }
+        cur = prev;
+    }
+    steps.reverse();
+    let cost = steps.len() as u32;
+    Path { steps, cost }
+}
+
+/// The cost of the cheapest path from `start` to every tile, or `None` for
+/// tiles that can't be reached.
+pub fn distances(
+    size: (u16, u16),
+    start: Point,
+    steps: impl Fn(Point) -> Vec<Step>,
+) -> TileMap<Option<u32>> {
+    let mut cost: TileMap<Option<u32>> = TileMap::with_default(size.0, size.1);
+    let mut open = BinaryHeap::from([Reverse((0, start))]);
+    cost[start] = Some(0);
+    while let Some(Reverse((pos_cost, pos))) = open.pop() {
+        if cost[pos] != Some(pos_cost) {
+            continue;
+        }
+        for step in steps(pos) {
+            let next_cost = pos_cost + step.cost;
+            if cost[step.to].is_some_and(|c| c <= next_cost) {
+                continue;
+            }
+            cost[step.to] = Some(next_cost);
+            open.push(Reverse((next_cost, step.to)));
+        }
+    }
+    cost
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// An open 5x5 room with a wall from (2, 0) to (2, 3).
+    fn room() -> TileMap<bool> {
+        let mut map = TileMap::with_default(5, 5);
+        for y in 0..4 {
+            map[(2, y)] = true;
+        }
+        map
+    }
+
+    #[test]
+    fn test_searches_agree() {
+        let map = room();
+        let steps = grid_steps(&map, |wall| !wall);
+        let size = (map.width(), map.height());
+        let target = (4, 0);
+        let bfs = bfs(size, (0, 0), &steps, |pos| pos == target).unwrap();
+        let (dijkstra, tree) = dijkstra(size, (0, 0), &steps, |pos| pos == target);
+        let dijkstra = dijkstra.unwrap();
+        assert_eq!(((0, 0), None), tree[0]);
+        assert_eq!(Some(target), tree.last().map(|&(tile, _)| tile));
+        let astar = astar(size, (0, 0), target, &steps).unwrap();
+        assert_eq!(12, bfs.cost);
+        assert_eq!(bfs.cost, dijkstra.cost);
+        assert_eq!(bfs.cost, astar.cost);
+        assert_eq!(Some(target), astar.end());
+        let dfs = dfs(size, (0, 0), &steps, |pos| pos == target).unwrap();
+        assert!(dfs.cost >= bfs.cost);
+        assert_eq!(Some(12), distances(size, (0, 0), &steps)[target]);
+        assert_eq!(None, distances(size, (0, 0), &steps)[(2, 0)]);
+    }
+
+    #[test]
+    fn test_jps_jumps() {
+        let map = room();
+        let passable = |pos: Point| !map[pos];
+        let size = (map.width(), map.height());
+        let (path, tree) = jps(size, (0, 0), (4, 0), passable);
+        let path = path.unwrap();
+        assert_eq!(12, path.cost);
+        assert_eq!(Some((4, 0)), path.end());
+        // down, across below the wall and up, each in one jump
+        let (_, astar_tree) = astar_search(size, (0, 0), (4, 0), grid_steps(&map, |wall| !wall));
+        assert!(tree.len() < astar_tree.len());
+        // inside the wall
+        assert_eq!(None, jps(size, (0, 0), (2, 0), passable).0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_jps_is_shortest(
+            walls in prop::collection::vec(prop::bool::weighted(0.3), 64),
+            start in (0..8u16, 0..8u16),
+            target in (0..8u16, 0..8u16),
+        ) {
+            let mut map: TileMap<bool> = TileMap::with_default(8, 8);
+            for (i, &wall) in walls.iter().enumerate() {
+                map[((i % 8) as u16, (i / 8) as u16)] = wall;
+            }
+            map[start] = false;
+            map[target] = false;
+            let bfs = bfs((8, 8), start, grid_steps(&map, |wall| !wall), |pos| pos == target);
+            let (path, _) = jps((8, 8), start, target, |pos| !map[pos]);
+            prop_assert_eq!(bfs.map(|path| path.cost), path.as_ref().map(|path| path.cost));
+            if let Some(path) = path {
+                let mut pos = start;
+                for step in &path.steps {
+                    prop_assert_eq!(1, manhattan(pos, step.tile));
+                    prop_assert!(!map[step.tile]);
+                    pos = step.tile;
+                }
+                prop_assert_eq!(target, pos);
+            }
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Maze generators loaded from shared libraries, so new ones can be handed
+//! around without building the app.
+//!
+//! A plugin is a `cdylib` exporting [`ENTRY_SYMBOL`] as an [`EntryFn`], which
+//! returns a [`PluginInfo`] describing the generator. See
+//! `examples/plugin.rs` for one written in Rust; any language that can
+//! export a C function works just as well.
+
+use std::ffi::{c_char, CStr};
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+
+use lazy_static::lazy_static;
+use libloading::{Library, Symbol};
+
+use crate::maze::{Maze, Tile, GENERATOR_NAMES};
+
+/// The version of the interface below. Plugins built against another
+/// version are not loaded.
+pub const ABI_VERSION: u32 = 1;
+
+/// The name of the function every plugin exports.
+pub const ENTRY_SYMBOL: &str = "maze_generator_plugin";
+
+/// Carve a maze of `nx` by `ny` cells into `tiles`, drawing randomness from
+/// `seed` only.
+///
+/// `tiles` holds `2 * ny + 1` rows of `2 * nx + 1` tiles each, row by row,
+/// 1 for a wall and 0 for a free tile. It starts out as the grid of cells
+/// of `Maze::empty`. The outer border has to stay solid and (1, 1) free.
+/// Returns 0 on success, anything else on failure.
+pub type GenerateFn = extern "C" fn(nx: usize, ny: usize, seed: u64, tiles: *mut u8) -> i32;
+
+/// The name of a loaded generator. Plugins are never unloaded, so neither
+/// is their name.
+pub type Name = &'static str;
+
+/// The function a plugin exports as [`ENTRY_SYMBOL`].
+pub type EntryFn = extern "C" fn() -> *const PluginInfo;
+
+/// What a plugin provides.
+#[repr(C)]
+#[derive(Debug)]
+pub struct PluginInfo {
+    /// Always [`ABI_VERSION`].
+    pub abi_version: u32,
+    /// The NUL-terminated name the generator is picked by, as with `algo`.
+    pub name: *const c_char,
+    pub generate: GenerateFn,
+}
+
+// only ever points at a string constant, so a plugin can keep its info in a
+// `static`
+unsafe impl Sync for PluginInfo {}
+
+struct Plugin {
+    name: Name,
+    generate: GenerateFn,
+    /// Kept open for as long as `generate` may be called, which is until
+    /// the app exits.
+    _library: Option<Library>,
+}
+
+lazy_static! {
+    /// The generators loaded so far. Global, as `Generator` only carries a
+    /// plugin's name.
+    static ref PLUGINS: RwLock<Vec<Plugin>> = RwLock::new(Vec::new());
+}
+
+fn add(name: &str, generate: GenerateFn, library: Option<Library>) -> Result<Name, String> {
+    let mut plugins = PLUGINS.write().unwrap();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return Err(format!("bad generator name '{}'", name));
+    }
+    if GENERATOR_NAMES.contains(&name) || plugins.iter().any(|plugin| plugin.name == name) {
+        return Err(format!("there already is a generator named '{}'", name));
+    }
+    let name: Name = Box::leak(name.to_string().into_boxed_str());
+    plugins.push(Plugin {
+        name,
+        generate,
+        _library: library,
+    });
+    Ok(name)
+}
+
+/// Add a generator under `name` without going through a shared library,
+/// returning the name as `Generator::Plugin` carries it.
+pub fn register(name: &str, generate: GenerateFn) -> Result<Name, String> {
+    add(name, generate, None)
+}
+
+/// Load the plugin in the shared library at `path`.
+pub fn load(path: &Path) -> Result<Name, String> {
+    // SAFETY: there is no way to check what a library does when it is
+    // loaded, or that the symbol has the right type; plugins are trusted
+    // as much as the app itself
+    unsafe {
+        let library = Library::new(path).map_err(|e| e.to_string())?;
+        let entry: Symbol<EntryFn> = library
+            .get(ENTRY_SYMBOL.as_bytes())
+            .map_err(|e| e.to_string())?;
+        let info = entry();
+        if info.is_null() {
+            return Err(format!("{} returned nothing", ENTRY_SYMBOL));
+        }
+        let info = &*info;
+        if info.abi_version != ABI_VERSION {
+            return Err(format!(
+                "built for plugin interface {}, not {}",
+                info.abi_version, ABI_VERSION
+            ));
+        }
+        if info.name.is_null() {
+            return Err("the generator has no name".to_string());
+        }
+        let name = CStr::from_ptr(info.name)
+            .to_str()
+            .map_err(|e| e.to_string())?
+            .to_string();
+        let generate = info.generate;
+        add(&name, generate, Some(library))
+    }
+}
+
+/// Load every shared library in `dir`, returning what went wrong with the
+/// ones that could not be loaded. A missing directory has no plugins.
+pub fn load_dir(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext == std::env::consts::DLL_EXTENSION)
+        })
+        .collect();
+    // so the generators always cycle in the same order
+    paths.sort();
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            load(&path)
+                .err()
+                .map(|e| format!("{}: {}", path.display(), e))
+        })
+        .collect()
+}
+
+/// The names of the loaded generators, in the order they were loaded.
+pub fn names() -> Vec<Name> {
+    PLUGINS
+        .read()
+        .unwrap()
+        .iter()
+        .map(|plugin| plugin.name)
+        .collect()
+}
+
+/// The name of the loaded generator called `name`, if there is one.
+pub fn find(name: &str) -> Option<Name> {
+    names().into_iter().find(|&plugin| plugin == name)
+}
+
+/// Generate a maze with the plugin called `name`, checking that what it
+/// carved is a maze the app can run on.
+pub fn generate(name: &str, nx: usize, ny: usize, seed: u64) -> Result<Maze, String> {
+    let generate = PLUGINS
+        .read()
+        .unwrap()
+        .iter()
+        .find(|plugin| plugin.name == name)
+        .map(|plugin| plugin.generate)
+        .ok_or_else(|| format!("no generator named '{}'", name))?;
+    let maze = Maze::empty(nx, ny);
+    let (w, h) = (2 * nx + 1, 2 * ny + 1);
+    let mut tiles: Vec<u8> = maze
+        .tiles
+        .iter()
+        .flatten()
+        .map(|tile| (*tile == Tile::Wall) as u8)
+        .collect();
+    let status = generate(nx, ny, seed, tiles.as_mut_ptr());
+    if status != 0 {
+        return Err(format!("{} failed with {}", name, status));
+    }
+    let mut rows = Vec::with_capacity(h);
+    for (y, row) in tiles.chunks(w).enumerate() {
+        let mut line = Vec::with_capacity(w);
+        for (x, &tile) in row.iter().enumerate() {
+            let border = x == 0 || y == 0 || x == w - 1 || y == h - 1;
+            line.push(match tile {
+                0 if border => return Err(format!("{} opened the border at ({}, {})", name, x, y)),
+                0 => Tile::Free,
+                1 => Tile::Wall,
+                _ => return Err(format!("{} left {} at ({}, {})", name, tile, x, y)),
+            });
+        }
+        rows.push(line);
+    }
+    if rows[1][1] == Tile::Wall {
+        return Err(format!("{} walled up the start", name));
+    }
+    Ok(Maze { tiles: rows })
+}
+
+/// Deserialize the name of a loaded generator, failing for any other.
+pub fn deserialize_name<'de, D>(deserializer: D) -> Result<Name, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let name = <String as serde::Deserialize>::deserialize(deserializer)?;
+    find(&name).ok_or_else(|| serde::de::Error::custom(format!("no generator named '{}'", name)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Opens every cell of the top row and the first column.
+    extern "C" fn comb(nx: usize, ny: usize, _seed: u64, tiles: *mut u8) -> i32 {
+        let w = 2 * nx + 1;
+        let tiles = unsafe { std::slice::from_raw_parts_mut(tiles, w * (2 * ny + 1)) };
+        for x in 1..w - 1 {
+            tiles[w + x] = 0;
+        }
+        for x in (1..w - 1).step_by(2) {
+            for y in 1..2 * ny {
+                tiles[y * w + x] = 0;
+            }
+        }
+        0
+    }
+
+    extern "C" fn broken(nx: usize, _ny: usize, _seed: u64, tiles: *mut u8) -> i32 {
+        unsafe { *tiles.add(nx) = 0 };
+        0
+    }
+
+    #[test]
+    fn test_register() {
+        let name = register("comb", comb).unwrap();
+        assert!(register("comb", comb).is_err());
+        assert!(register("kruskal", comb).is_err());
+        assert_eq!(Some(name), find("comb"));
+
+        let maze = generate("comb", 3, 2, 0).unwrap();
+        assert_eq!(
+            "OOOOOOO\nO     O\nO O O O\nO O O O\nOOOOOOO\n",
+            maze.to_string()
+        );
+
+        register("broken", broken).unwrap();
+        assert!(generate("broken", 3, 2, 0).is_err());
+        assert!(generate("missing", 3, 2, 0).is_err());
+        assert!(load_dir(Path::new("no-such-dir")).is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use rand::Rng;
+
+/// How far a value moves towards each new estimate.
+const ALPHA: f64 = 0.5;
+/// How much a reward one step later is worth now.
+const GAMMA: f64 = 0.95;
+/// Chance of trying a random action instead of the best known one.
+const EPSILON: f64 = 0.1;
+
+/// Reward for a step onto a tile, per tick it costs to cross.
+pub const STEP_REWARD: f64 = -1.0;
+/// Reward for walking into a wall or a locked door.
+pub const BUMP_REWARD: f64 = -5.0;
+/// Reward for reaching the goal.
+pub const GOAL_REWARD: f64 = 100.0;
+
+/// Learned values of the four moves, north, east, south and west, from
+/// every tile of a map.
+#[derive(Debug, Clone)]
+pub struct QTable {
+    width: u16,
+    height: u16,
+    values: Vec<f64>,
+    /// Whether a value was ever learned for the tile.
+    learned: Vec<bool>,
+}
+
+/// A value of the table as it was before an update, to undo it.
+pub type Undo = (usize, f64, bool);
+
+impl QTable {
+    pub fn new(width: u16, height: u16) -> Self {
+        let tiles = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            values: vec![0.0; tiles * 4],
+            learned: vec![false; tiles],
+        }
+    }
+
+    fn tile(&self, pos: (u16, u16)) -> usize {
+        assert!(pos.0 < self.width && pos.1 < self.height);
+        pos.1 as usize * self.width as usize + pos.0 as usize
+    }
+
+    fn actions(&self, pos: (u16, u16)) -> &[f64] {
+        let i = self.tile(pos) * 4;
+        &self.values[i..i + 4]
+    }
+
+    /// Value of the best action at `pos`, or `None` if nothing was learned
+    /// there yet.
+    pub fn value(&self, pos: (u16, u16)) -> Option<f64> {
+        self.learned[self.tile(pos)]
+            .then(|| self.actions(pos).iter().copied().fold(f64::MIN, f64::max))
+    }
+
+    /// The best known action at `pos`, or a random one now and then to keep
+    /// exploring. Ties are broken randomly.
+    pub fn choose(&self, pos: (u16, u16), rng: &mut impl Rng) -> usize {
+        if rng.random_bool(EPSILON) {
+            return rng.random_range(0..4);
+        }
+        let actions = self.actions(pos);
+        let best = actions.iter().copied().fold(f64::MIN, f64::max);
+        let ties: Vec<usize> = (0..4).filter(|&a| actions[a] == best).collect();
+        ties[rng.random_range(0..ties.len())]
+    }
+
+    /// Move the value of `action` at `pos` towards `reward` plus the
+    /// discounted value of `next`, the tile the action led to, or `None` if
+    /// the episode ended.
+    pub fn update(
+        &mut self,
+        pos: (u16, u16),
+        action: usize,
+        reward: f64,
+        next: Option<(u16, u16)>,
+    ) -> Undo {
+        let future = next.map_or(0.0, |next| {
+            self.actions(next).iter().copied().fold(f64::MIN, f64::max)
+        });
+        let tile = self.tile(pos);
+        let i = tile * 4 + action;
+        let undo = (i, self.values[i], self.learned[tile]);
+        self.values[i] += ALPHA * (reward + GAMMA * future - self.values[i]);
+        self.learned[tile] = true;
+        undo
+    }
+
+    pub fn undo(&mut self, (i, value, learned): Undo) {
+        self.values[i] = value;
+        self.learned[i / 4] = learned;
+    }
+
+    /// The smallest and largest value learned for any tile.
+    pub fn range(&self) -> Option<(f64, f64)> {
+        (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .filter_map(|pos| self.value(pos))
+            .fold(None, |range, v| match range {
+                None => Some((v, v)),
+                Some((lo, hi)) => Some((f64::min(lo, v), f64::max(hi, v))),
+            })
+    }
+}
+
+/// Progress of the learning over the episodes of a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Episodes {
+    /// Number of finished episodes.
+    pub finished: u32,
+    /// Steps and reward of the episode in progress.
+    pub steps: u32,
+    pub reward: f64,
+    /// Reward of the last finished episode.
+    pub last_reward: Option<f64>,
+    /// Fewest steps an episode needed to reach the goal.
+    pub best_steps: Option<u32>,
+}
+
+impl Episodes {
+    /// End the episode in progress, `reached` telling whether it reached
+    /// the goal.
+    pub fn finish(&mut self, reached: bool) {
+        self.finished += 1;
+        self.last_reward = Some(self.reward);
+        if reached {
+            self.best_steps = Some(
+                self.best_steps
+                    .map_or(self.steps, |best| best.min(self.steps)),
+            );
+        }
+        self.steps = 0;
+        self.reward = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_learn_corridor() {
+        // a corridor from (0, 0) to the goal at (3, 0), learned by walking east
+        let mut table = QTable::new(4, 1);
+        for _ in 0..50 {
+            for x in 0..3 {
+                let goal = x + 1 == 3;
+                let reward = if goal { GOAL_REWARD } else { STEP_REWARD };
+                table.update((x, 0), 1, reward, (!goal).then_some((x + 1, 0)));
+                table.update((x, 0), 3, BUMP_REWARD, Some((x, 0)));
+            }
+        }
+        let values: Vec<f64> = (0..3).map(|x| table.value((x, 0)).unwrap()).collect();
+        assert!(values[0] < values[1] && values[1] < values[2]);
+        assert_eq!(None, table.value((3, 0)));
+        let mut rng = StdRng::seed_from_u64(1);
+        let east = (0..100)
+            .filter(|_| table.choose((0, 0), &mut rng) == 1)
+            .count();
+        assert!(east > 85);
+        let undo = table.update((3, 0), 0, 1.0, None);
+        table.undo(undo);
+        assert_eq!(None, table.value((3, 0)));
+    }
+}
@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use gif::{Encoder, Frame, Repeat};
+
+/// Pixels per side of a tile in the recording.
+const BLOCK: u16 = 4;
+/// Most tiles kept in memory over all frames. Beyond that every other frame
+/// is dropped, and the remaining ones are shown twice as long.
+const MAX_TILES: usize = 1 << 28;
+
+/// Records the map once per tick and writes it as an animated GIF.
+#[derive(Debug)]
+pub struct Recorder {
+    path: PathBuf,
+    width: u16,
+    height: u16,
+    /// How long each frame is shown.
+    delay: Duration,
+    /// Only every `stride`th captured frame is kept.
+    stride: usize,
+    captured: usize,
+    /// The colors used so far, at most 256.
+    palette: Vec<[u8; 3]>,
+    /// One palette index per tile for every frame.
+    frames: Vec<Vec<u8>>,
+}
+
+impl Recorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            width: 0,
+            height: 0,
+            delay: Duration::ZERO,
+            stride: 1,
+            captured: 0,
+            palette: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Drop all frames and start over for a `width` x `height` map that
+    /// changes every `interval`.
+    pub fn reset(&mut self, width: u16, height: u16, interval: Duration) {
+        self.width = width;
+        self.height = height;
+        self.delay = interval;
+        self.stride = 1;
+        self.captured = 0;
+        self.palette.clear();
+        self.frames.clear();
+    }
+
+    /// Add a frame with the color of every tile.
+    pub fn capture(&mut self, color_at: impl Fn(u16, u16) -> [u8; 3]) {
+        self.captured += 1;
+        if !(self.captured - 1).is_multiple_of(self.stride) {
+            return;
+        }
+        let mut frame = Vec::with_capacity(self.width as usize * self.height as usize);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let color = color_at(x, y);
+                frame.push(self.palette_index(color));
+            }
+        }
+        let frame_len = frame.len();
+        self.frames.push(frame);
+        if self.frames.len() > 1 && self.frames.len() * frame_len > MAX_TILES {
+            self.frames = self.frames.iter().step_by(2).cloned().collect();
+            self.stride *= 2;
+        }
+    }
+
+    /// The palette entry for `color`, or the closest one once the palette is full.
+    fn palette_index(&mut self, color: [u8; 3]) -> u8 {
+        if let Some(i) = self.palette.iter().position(|&c| c == color) {
+            return i as u8;
+        }
+        if self.palette.len() < 256 {
+            self.palette.push(color);
+            return (self.palette.len() - 1) as u8;
+        }
+        let distance = |c: &[u8; 3]| {
+            (0..3)
+                .map(|i| (c[i] as i32 - color[i] as i32).pow(2))
+                .sum::<i32>()
+        };
+        (0..self.palette.len())
+            .min_by_key(|&i| distance(&self.palette[i]))
+            .unwrap() as u8
+    }
+
+    /// Write all frames to the GIF file.
+    pub fn save(&self) -> io::Result<()> {
+        let (w, h) = (self.width * BLOCK, self.height * BLOCK);
+        let file = File::create(&self.path)?;
+        let mut encoder =
+            Encoder::new(file, w, h, self.palette.as_flattened()).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        // in hundredths of a second
+        let delay = (self.delay.as_millis() as usize * self.stride / 10).max(1) as u16;
+        for tiles in &self.frames {
+            let mut pixels = Vec::with_capacity(w as usize * h as usize);
+            for row in tiles.chunks(self.width as usize) {
+                let line: Vec<u8> = row.iter().flat_map(|&i| [i; BLOCK as usize]).collect();
+                for _ in 0..BLOCK {
+                    pixels.extend_from_slice(&line);
+                }
+            }
+            let frame = Frame {
+                width: w,
+                height: h,
+                delay,
+                buffer: Cow::Owned(pixels),
+                ..Frame::default()
+            };
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save() {
+        let path = std::env::temp_dir().join(format!("record-{}.gif", std::process::id()));
+        let mut recorder = Recorder::new(&path);
+        recorder.reset(3, 2, Duration::from_millis(50));
+        recorder.capture(|x, _| [x as u8 * 100, 0, 0]);
+        recorder.capture(|_, y| [0, 0, y as u8 * 100]);
+        assert_eq!(4, recorder.palette.len());
+        recorder.save().unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(File::open(&path).unwrap()).unwrap();
+        assert_eq!((3 * BLOCK, 2 * BLOCK), (decoder.width(), decoder.height()));
+        // the palette index of the second tile in each frame
+        let mut second = Vec::new();
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(5, frame.delay);
+            second.push(frame.buffer[BLOCK as usize]);
+        }
+        assert_eq!(vec![1, 0], second);
+        std::fs::remove_file(path).unwrap();
+    }
+}
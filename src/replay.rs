@@ -0,0 +1,283 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::gzip;
+use crate::menu::RunConfig;
+use crate::weights::Weights;
+
+/// Where replays are saved to.
+pub const REPLAY_DIR: &str = "replays";
+
+/// The version of the replay format `save` writes. Bump it whenever what a
+/// replay holds changes, and add a step to `MIGRATIONS` taking replays of
+/// the previous version to it.
+pub const VERSION: u32 = 3;
+
+/// Steps taking a replay file of version `i`, as JSON, to version `i + 1`.
+const MIGRATIONS: [fn(Value) -> Result<Value, String>; VERSION as usize] =
+    [from_unversioned, without_checksum, with_run_options];
+
+/// A run up to some tick. Runs with the same config are identical, so this
+/// is all it takes to play the run again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub config: RunConfig,
+    pub weights: Weights,
+    /// The maze as text, if the run was on a loaded maze rather than a
+    /// generated one.
+    pub maze: Option<String>,
+    /// How far the run had gone.
+    pub ticks: u64,
+    /// `maze_hash` of the maze the run started on, if known. It is kept in
+    /// the header of a replay file.
+    #[serde(skip)]
+    pub maze_hash: Option<u64>,
+}
+
+/// What a replay file starts with, to tell what is in it without
+/// understanding all of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Header {
+    /// The format version the file was written in.
+    pub version: u32,
+    pub seed: u64,
+    /// `maze_hash` of the maze the run started on, in hex. Missing in
+    /// replays saved before there were versions.
+    pub maze_hash: Option<String>,
+    /// `gzip::crc32` of the replay as compact JSON, as stored rather than
+    /// as this build would write it, covering the config and the maze, in
+    /// hex. Missing in replays saved before version 2.
+    pub checksum: Option<String>,
+}
+
+/// A replay file, as gzipped JSON:
+///
+/// ```json
+/// {
+///   "header": {
+///     "version": 3,
+///     "seed": 42,
+///     "maze_hash": "af63dc4c8601ec8c",
+///     "checksum": "cbf43926"
+///   },
+///   "replay": { "config": { ... }, "weights": { ... }, "maze": null, "ticks": 120 }
+/// }
+/// ```
+///
+/// Files from before the header are the bare `replay` object, and count as
+/// version 0. Files from before version 2 are not compressed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ReplayFile {
+    header: Header,
+    replay: Replay,
+}
+
+/// The 64 bit FNV-1a hash of a maze as text, which unlike the hashers of
+/// the standard library stays the same across builds.
+pub fn maze_hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Version 0 to 1: wrap the bare replay into a file with a header.
+fn from_unversioned(replay: Value) -> Result<Value, String> {
+    let seed = replay
+        .pointer("/config/seed")
+        .and_then(Value::as_u64)
+        .ok_or("replay without a seed")?;
+    Ok(serde_json::json!({
+        "header": { "version": 1, "seed": seed, "maze_hash": null },
+        "replay": replay,
+    }))
+}
+
+/// Version 1 to 2: there is no checksum to check.
+fn without_checksum(mut file: Value) -> Result<Value, String> {
+    file["header"]["checksum"] = Value::Null;
+    file["header"]["version"] = 2.into();
+    Ok(file)
+}
+
+/// Version 2 to 3: the config gained the chassis, the coverage goal, the
+/// checkpoints and the openings, which runs went without before. Sessions
+/// aren't migrated, so `RunConfig` still defaults them as well.
+fn with_run_options(mut file: Value) -> Result<Value, String> {
+    let defaults = serde_json::to_value(RunConfig::default()).map_err(|e| e.to_string())?;
+    let config = file
+        .pointer_mut("/replay/config")
+        .and_then(Value::as_object_mut)
+        .ok_or("replay without a config")?;
+    for key in ["chassis", "coverage_goal", "checkpoints", "openings"] {
+        config.entry(key).or_insert_with(|| defaults[key].clone());
+    }
+    file["header"]["version"] = 3.into();
+    Ok(file)
+}
+
+/// The checksum of a replay as JSON. It is taken over the JSON as stored,
+/// so fields added later, filled in with their defaults when reading it,
+/// don't change it.
+fn checksum(replay: &Value) -> u32 {
+    gzip::crc32(replay.to_string().as_bytes())
+}
+
+impl Replay {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = ReplayFile {
+            header: Header {
+                version: VERSION,
+                seed: self.config.seed,
+                maze_hash: self.maze_hash.map(|hash| format!("{:016x}", hash)),
+                checksum: Some(format!("{:08x}", checksum(&serde_json::to_value(self)?))),
+            },
+            replay: self.clone(),
+        };
+        gzip::write(path, &serde_json::to_string_pretty(&file)?)
+    }
+
+    /// Load the replay at `path`, migrating it from older versions, and
+    /// refusing it if it doesn't match its checksum.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        Self::parse(&gzip::read_to_string(path)?)
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let mut json: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let version = match json.pointer("/header/version") {
+            Some(version) => version.as_u64().ok_or("bad version")? as u32,
+            None => 0,
+        };
+        if version > VERSION {
+            return Err(format!(
+                "replay version {} is newer than the {} this build reads",
+                version, VERSION
+            ));
+        }
+        if let Some(expected) = json.pointer("/header/checksum").and_then(Value::as_str) {
+            let actual = format!("{:08x}", checksum(&json["replay"]));
+            if expected != actual {
+                return Err(format!(
+                    "the replay doesn't match its checksum ({} instead of {}), it was changed or corrupted",
+                    actual, expected
+                ));
+            }
+        }
+        for migrate in &MIGRATIONS[version as usize..] {
+            json = migrate(json)?;
+        }
+        let file: ReplayFile = serde_json::from_value(json).map_err(|e| e.to_string())?;
+        let maze_hash = file
+            .header
+            .maze_hash
+            .map(|hash| u64::from_str_radix(&hash, 16).map_err(|e| format!("bad maze hash: {}", e)))
+            .transpose()?;
+        Ok(Self {
+            maze_hash,
+            ..file.replay
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let replay = Replay {
+            config: RunConfig::default(),
+            weights: Weights::default(),
+            maze: Some("OOO\nO O\nOOO\n".to_string()),
+            ticks: 42,
+            maze_hash: Some(maze_hash("OOO\nO O\nOOO\n")),
+        };
+        let path = std::env::temp_dir().join(format!("replay-test-{}.json", std::process::id()));
+        replay.save(&path).unwrap();
+        assert_eq!(Ok(replay.clone()), Replay::load(&path));
+        fs::remove_file(&path).unwrap();
+        assert!(Replay::load(&path).is_err());
+
+        // as saved before there were versions
+        let mut old = serde_json::to_value(&replay).unwrap();
+        let unversioned = Replay::parse(&old.to_string()).unwrap();
+        assert_eq!(None, unversioned.maze_hash);
+        assert_eq!(replay.config, unversioned.config);
+
+        old = serde_json::json!({ "header": { "version": VERSION + 1, "seed": 1 } });
+        assert!(Replay::parse(&old.to_string()).is_err());
+
+        let mut file = serde_json::to_value(ReplayFile {
+            header: Header {
+                version: VERSION,
+                seed: 0,
+                maze_hash: None,
+                checksum: Some(format!(
+                    "{:08x}",
+                    checksum(&serde_json::to_value(&replay).unwrap())
+                )),
+            },
+            replay: replay.clone(),
+        })
+        .unwrap();
+        assert!(Replay::parse(&file.to_string()).is_ok());
+        file["replay"]["ticks"] = 43.into();
+        assert!(Replay::parse(&file.to_string()).is_err());
+    }
+
+    #[test]
+    fn test_checksum_of_older_config() {
+        // saved by a build whose config had no `openings` yet
+        let replay = Replay {
+            config: RunConfig::default(),
+            weights: Weights::default(),
+            maze: None,
+            ticks: 7,
+            maze_hash: None,
+        };
+        let mut stored = serde_json::to_value(&replay).unwrap();
+        stored["config"].as_object_mut().unwrap().remove("openings");
+        let file = serde_json::json!({
+            "header": {
+                "version": 2,
+                "seed": replay.config.seed,
+                "maze_hash": null,
+                "checksum": format!("{:08x}", checksum(&stored)),
+            },
+            "replay": stored,
+        });
+        assert_eq!(Ok(replay), Replay::parse(&file.to_string()));
+    }
+
+    #[test]
+    fn test_migrates_run_options() {
+        let file = serde_json::json!({
+            "header": { "version": 2, "seed": 5, "maze_hash": null, "checksum": null },
+            "replay": { "config": { "seed": 5 } },
+        });
+        let file = with_run_options(file).unwrap();
+        assert_eq!(
+            Some(3),
+            file.pointer("/header/version").and_then(Value::as_u64)
+        );
+        let config = &file["replay"]["config"];
+        assert_eq!(5, config["seed"]);
+        assert_eq!("Legs", config["chassis"]);
+        assert_eq!(0, config["coverage_goal"]);
+        assert_eq!(0, config["checkpoints"]);
+        assert_eq!("Closed", config["openings"]);
+    }
+
+    #[test]
+    fn test_maze_hash() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, maze_hash(""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, maze_hash("a"));
+    }
+}
@@ -0,0 +1,87 @@
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+pub const RESULTS_PATH: &str = "results.csv";
+
+const HEADER: &str =
+    "seed,generator,solver,width,height,outcome,steps,turns,backtracks,duration_ms";
+
+/// Statistics of one finished run, stored as a row of a CSV file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunResult {
+    pub seed: u64,
+    pub generator: String,
+    pub solver: String,
+    pub width: usize,
+    pub height: usize,
+    /// Set if the minotaur caught the robot before it explored the maze.
+    pub caught: bool,
+    pub steps: u64,
+    pub turns: u64,
+    pub backtracks: u64,
+    pub duration: Duration,
+}
+
+impl RunResult {
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            self.seed,
+            csv_field(&self.generator),
+            csv_field(&self.solver),
+            self.width,
+            self.height,
+            if self.caught { "caught" } else { "explored" },
+            self.steps,
+            self.turns,
+            self.backtracks,
+            self.duration.as_millis()
+        )
+    }
+}
+
+/// Quote `value` if it contains anything that would break the row apart.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Append `result` to the CSV file at `path`, starting it with a header
+/// line if it is new.
+pub fn append(path: impl AsRef<Path>, result: &RunResult) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", HEADER)?;
+    }
+    writeln!(file, "{}", result.to_csv())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let result = RunResult {
+            seed: 7,
+            generator: "drunkard (40%)".to_string(),
+            solver: "robot, \"dfs\"".to_string(),
+            width: 16,
+            height: 8,
+            caught: false,
+            steps: 120,
+            turns: 64,
+            backtracks: 30,
+            duration: Duration::from_millis(1500),
+        };
+        assert_eq!(
+            "7,drunkard (40%),\"robot, \"\"dfs\"\"\",16,8,explored,120,64,30,1500",
+            result.to_csv()
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+use crate::stats::RunStats;
+
+/// How a finished run is scored, to compare solvers and modes by one
+/// number. Set in the `[scoring]` table of the settings file.
+///
+/// Steps and ticks count per reachable tile, so a score means about the
+/// same on a small maze as on a large one: exploring a whole maze in as
+/// many steps and ticks as it has tiles scores `coverage - steps - ticks`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Scoring {
+    /// Points for exploring all of the maze, a share of them for less.
+    pub coverage: f64,
+    /// Points lost per step, per reachable tile.
+    pub steps: f64,
+    /// Points lost per tick, per reachable tile.
+    pub ticks: f64,
+}
+
+impl Default for Scoring {
+    fn default() -> Self {
+        Self {
+            coverage: 1000.0,
+            steps: 100.0,
+            ticks: 100.0,
+        }
+    }
+}
+
+impl Scoring {
+    /// The score of a run, never below 0, or `None` if there is no telling
+    /// how much of the maze it explored (infinite mode).
+    pub fn score(&self, stats: &RunStats) -> Option<u64> {
+        let coverage = stats.coverage()?;
+        let reachable = stats.reachable as f64;
+        let score = self.coverage * coverage
+            - self.steps * stats.steps as f64 / reachable
+            - self.ticks * stats.ticks as f64 / reachable;
+        Some(score.round().max(0.0) as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score() {
+        let mut stats = RunStats::new(0);
+        assert_eq!(None, Scoring::default().score(&stats));
+
+        stats = RunStats::new(100);
+        stats.visited = 100;
+        stats.steps = 200;
+        stats.ticks = 300;
+        assert_eq!(Some(500), Scoring::default().score(&stats));
+        stats.visited = 50;
+        assert_eq!(Some(0), Scoring::default().score(&stats));
+        let scoring = Scoring {
+            ticks: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(Some(300), scoring.score(&stats));
+    }
+}
@@ -0,0 +1,71 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::gzip;
+use crate::lighting::Light;
+use crate::palette::PaletteName;
+use crate::replay::Replay;
+use crate::sprites::SpriteSet;
+
+/// Where a session is saved to when quitting, to be resumed from the menu.
+pub const SESSION_PATH: &str = "session.json.gz";
+
+/// How the map was looked at.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct View {
+    pub palette: PaletteName,
+    pub sprites: SpriteSet,
+    pub light: Light,
+    pub show_minimap: bool,
+    pub show_distances: bool,
+    pub show_search: bool,
+    pub show_weights: bool,
+    pub zoomed_out: bool,
+}
+
+/// A run left unfinished, with the view on it. The run itself is kept as a
+/// replay and played up to where it was left on resuming.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    pub replay: Replay,
+    pub view: View,
+    /// Time spent running, which the replay doesn't know.
+    #[serde(default)]
+    pub elapsed: Duration,
+    /// The day of the daily puzzle, if it was one.
+    #[serde(default)]
+    pub daily: Option<u64>,
+}
+
+impl Session {
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        gzip::write(path, &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load the session at `path` and remove the file, so it is resumed only
+    /// once. A file that can't be read is kept.
+    pub fn take(path: &Path) -> Result<Self, String> {
+        let text = gzip::read_to_string(path)?;
+        let session = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_keeps_broken() {
+        let path = std::env::temp_dir().join(format!("session-{}.json.gz", std::process::id()));
+        gzip::write(&path, "{\"replay\": 3}").unwrap();
+        assert!(Session::take(&path).is_err());
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,321 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Widget},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::lighting::Light;
+use crate::maze::Generator;
+use crate::menu::{self, centered};
+use crate::palette::PaletteName;
+use crate::pane::PaneLayout;
+use crate::score::Scoring;
+use crate::sprites::SpriteSet;
+use crate::trail::TRAIL_LENGTHS;
+
+/// Where the settings are read from on startup and saved to.
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+/// The tick rates to pick from, in ticks per second.
+const TICK_RATES: [u32; 9] = [1, 2, 5, 10, 20, 30, 50, 100, 200];
+
+/// What is kept between launches. Missing entries in the file take their
+/// defaults.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Ticks per second while the simulation is running.
+    pub tick_rate: u32,
+    /// Maze size in cells the menu starts with.
+    pub width: usize,
+    pub height: usize,
+    pub generator: Generator,
+    pub palette: PaletteName,
+    pub sprites: SpriteSet,
+    /// The theme file to follow, if any.
+    pub theme: Option<PathBuf>,
+    pub light: Light,
+    pub minimap: bool,
+    pub distances: bool,
+    pub search_tree: bool,
+    pub timings: bool,
+    pub scoring: Scoring,
+    pub layout: PaneLayout,
+    /// Cells of the trail behind the robot shown, 0 for all it visited.
+    pub trail: usize,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tick_rate: 20,
+            width: 16,
+            height: 16,
+            generator: Generator::Kruskal,
+            palette: PaletteName::default(),
+            sprites: SpriteSet::default(),
+            theme: None,
+            light: Light::default(),
+            minimap: false,
+            distances: false,
+            search_tree: false,
+            timings: false,
+            scoring: Scoring::default(),
+            layout: PaneLayout::default(),
+            trail: 0,
+        }
+    }
+}
+
+impl Settings {
+    /// The settings in `path`, or the defaults if there is no such file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.to_string()),
+        };
+        let settings: Settings = toml::from_str(&text).map_err(|e| e.message().to_string())?;
+        if !TICK_RATES.contains(&settings.tick_rate) {
+            return Err(format!(
+                "tick_rate must be one of {:?}, not {}",
+                TICK_RATES, settings.tick_rate
+            ));
+        }
+        if !TRAIL_LENGTHS.contains(&settings.trail) {
+            return Err(format!(
+                "trail must be one of {:?}, not {}",
+                TRAIL_LENGTHS, settings.trail
+            ));
+        }
+        menu::check_size(settings.width, settings.height)?;
+        settings.layout.check()?;
+        Ok(settings)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, text)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    TickRate,
+    Width,
+    Height,
+    Generator,
+    Palette,
+    Sprites,
+    Theme,
+    Light,
+    Torch,
+    Minimap,
+    Distances,
+    SearchTree,
+    Timings,
+    Trail,
+}
+
+const FIELDS: [Field; 14] = [
+    Field::TickRate,
+    Field::Width,
+    Field::Height,
+    Field::Generator,
+    Field::Palette,
+    Field::Sprites,
+    Field::Theme,
+    Field::Light,
+    Field::Torch,
+    Field::Minimap,
+    Field::Distances,
+    Field::SearchTree,
+    Field::Timings,
+    Field::Trail,
+];
+
+/// What the app should do after the settings form handled a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormAction {
+    None,
+    /// A setting changed and should take effect.
+    Changed,
+    Save,
+    /// Ask for the path of the theme file.
+    EditTheme,
+    Back,
+    Quit,
+}
+
+/// A form to edit the settings in, one field per line.
+#[derive(Debug, Default)]
+pub struct SettingsForm {
+    selected: usize,
+    pub settings: Settings,
+}
+
+impl SettingsForm {
+    pub fn handle_key(&mut self, code: KeyCode) -> FormAction {
+        let field = FIELDS[self.selected];
+        match code {
+            KeyCode::Char('q') => FormAction::Quit,
+            KeyCode::Esc => FormAction::Back,
+            KeyCode::Char('s') => FormAction::Save,
+            KeyCode::Up => {
+                self.selected = (self.selected + FIELDS.len() - 1) % FIELDS.len();
+                FormAction::None
+            }
+            KeyCode::Down => {
+                self.selected = (self.selected + 1) % FIELDS.len();
+                FormAction::None
+            }
+            KeyCode::Enter if field == Field::Theme => FormAction::EditTheme,
+            KeyCode::Backspace | KeyCode::Delete if field == Field::Theme => {
+                self.settings.theme = None;
+                FormAction::Changed
+            }
+            KeyCode::Left => self.change(-1),
+            KeyCode::Right | KeyCode::Enter => self.change(1),
+            _ => FormAction::None,
+        }
+    }
+
+    fn change(&mut self, delta: isize) -> FormAction {
+        let settings = &mut self.settings;
+        match FIELDS[self.selected] {
+            Field::TickRate => {
+                let i = TICK_RATES
+                    .iter()
+                    .position(|&rate| rate == settings.tick_rate)
+                    .unwrap_or(0);
+                let i = i.saturating_add_signed(delta).min(TICK_RATES.len() - 1);
+                settings.tick_rate = TICK_RATES[i];
+            }
+            Field::Width => settings.width = menu::step_size(settings.width, delta),
+            Field::Height => settings.height = menu::step_size(settings.height, delta),
+            Field::Generator => settings.generator = settings.generator.next(),
+            Field::Palette => settings.palette = settings.palette.next(),
+            Field::Sprites => settings.sprites = settings.sprites.next(),
+            Field::Theme => return FormAction::EditTheme,
+            Field::Light => settings.light.radius = settings.light.next_radius(),
+            Field::Torch => settings.light.flicker = !settings.light.flicker,
+            Field::Minimap => settings.minimap = !settings.minimap,
+            Field::Distances => settings.distances = !settings.distances,
+            Field::SearchTree => settings.search_tree = !settings.search_tree,
+            Field::Timings => settings.timings = !settings.timings,
+            Field::Trail => {
+                let i = TRAIL_LENGTHS
+                    .iter()
+                    .position(|&length| length == settings.trail)
+                    .unwrap_or(0);
+                let i = i.saturating_add_signed(delta).min(TRAIL_LENGTHS.len() - 1);
+                settings.trail = TRAIL_LENGTHS[i];
+            }
+        }
+        FormAction::Changed
+    }
+
+    fn field_line(&self, field: Field) -> Line<'_> {
+        let settings = &self.settings;
+        let on_off = |on: bool| if on { "on" } else { "off" }.to_string();
+        let (label, value) = match field {
+            Field::TickRate => ("tick rate", format!("{}/s", settings.tick_rate)),
+            Field::Width => ("width", settings.width.to_string()),
+            Field::Height => ("height", settings.height.to_string()),
+            Field::Generator => (
+                "generator",
+                match settings.generator {
+                    Generator::Kruskal => "kruskal".to_string(),
+                    Generator::Drunkard { .. } => "drunkard".to_string(),
+                    Generator::AldousBroder => "aldous-broder".to_string(),
+                    Generator::GrowingTree { .. } => "growing-tree".to_string(),
+                    Generator::Plugin { name } => name.to_string(),
+                },
+            ),
+            Field::Palette => ("palette", settings.palette.to_string()),
+            Field::Sprites => ("sprites", settings.sprites.to_string()),
+            Field::Theme => (
+                "theme",
+                settings
+                    .theme
+                    .as_ref()
+                    .map_or("none".to_string(), |path| path.display().to_string()),
+            ),
+            Field::Light => (
+                "light",
+                match settings.light.radius {
+                    0 => "off".to_string(),
+                    radius => radius.to_string(),
+                },
+            ),
+            Field::Torch => ("torch", on_off(settings.light.flicker)),
+            Field::Minimap => ("minimap", on_off(settings.minimap)),
+            Field::Distances => ("distances", on_off(settings.distances)),
+            Field::SearchTree => ("search tree", on_off(settings.search_tree)),
+            Field::Timings => ("timings", on_off(settings.timings)),
+            Field::Trail => (
+                "trail",
+                match settings.trail {
+                    0 => "all".to_string(),
+                    length => length.to_string(),
+                },
+            ),
+        };
+        let line = Line::from(format!("{:<12} < {} >", label, value));
+        if FIELDS[self.selected] == field {
+            line.reversed()
+        } else {
+            line
+        }
+    }
+}
+
+impl Widget for &SettingsForm {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = FIELDS.iter().map(|field| self.field_line(*field)).collect();
+        lines.push(Line::default());
+        lines.push(Line::from("↑/↓ select  ←/→ change  Enter edit").style(Style::new().dim()));
+        lines.push(
+            Line::from(format!("s save to {}  Esc back", SETTINGS_PATH)).style(Style::new().dim()),
+        );
+        let block = Block::bordered().title(" settings ");
+        let area = centered(area, 46, lines.len() as u16 + 2);
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut form = SettingsForm::default();
+        form.handle_key(KeyCode::Right);
+        for _ in 0..4 {
+            form.handle_key(KeyCode::Down);
+        }
+        form.handle_key(KeyCode::Right);
+        assert_eq!(30, form.settings.tick_rate);
+        assert_eq!(PaletteName::Deuteranopia, form.settings.palette);
+
+        let path = std::env::temp_dir().join(format!("settings-test-{}.toml", std::process::id()));
+        form.settings.theme = Some(PathBuf::from("themes/dark.toml"));
+        form.settings.save(&path).unwrap();
+        assert_eq!(Ok(form.settings.clone()), Settings::load(&path));
+        fs::write(&path, "tick_rate = 7").unwrap();
+        assert!(Settings::load(&path).is_err());
+        fs::write(&path, "width = 40").unwrap();
+        assert_eq!(40, Settings::load(&path).unwrap().width);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Ok(Settings::default()), Settings::load(&path));
+    }
+}
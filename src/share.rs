@@ -0,0 +1,305 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui_counter_app::maze::Generator;
+
+use crate::gzip::crc32;
+use crate::menu::{
+    Openings, RunConfig, MAX_DOORS, MAX_ITEMS, MAX_ONE_WAYS, MAX_SIZE, MAX_TELEPORTERS,
+    MAX_TERRAIN, MIN_SIZE,
+};
+
+/// The version of the layout of a code, kept in its first byte.
+const VERSION: u8 = 2;
+
+const OPENINGS: [Openings; 4] = [
+    Openings::Closed,
+    Openings::Corners,
+    Openings::Sides,
+    Openings::Random,
+];
+
+/// Crockford's base32, which leaves out the letters easily mistaken for
+/// digits.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// What it takes to generate the same maze as someone else, as a short
+/// code to send them: the generator with its coverage, the size, the seed
+/// and whatever is placed in the maze, in base32.
+///
+/// The bytes are the version, the openings and the generator in one, the
+/// generator's parameters in percent (the coverage of `drunkard`, `newest`
+/// and `straight` of `growing-tree`), then the width, height, seed and the
+/// numbers of items, doors, teleporters, one-ways and terrain patches as
+/// LEB128 and a check byte, the low byte of their `crc32`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ShareCode {
+    generator: Generator,
+    width: usize,
+    height: usize,
+    seed: u64,
+    openings: Openings,
+    items: usize,
+    doors: usize,
+    teleporters: usize,
+    one_ways: usize,
+    terrain: usize,
+}
+
+impl ShareCode {
+    /// The code of a run with `config`, unless its maze can't be shared
+    /// like this, being infinite or from a plugin.
+    pub fn from_config(config: &RunConfig) -> Option<Self> {
+        if config.infinite || matches!(config.generator, Generator::Plugin { .. }) {
+            return None;
+        }
+        Some(Self {
+            generator: config.generator,
+            width: config.width,
+            height: config.height,
+            seed: config.seed,
+            openings: config.openings,
+            items: config.items,
+            doors: config.doors,
+            teleporters: config.teleporters,
+            one_ways: config.one_ways,
+            terrain: config.terrain,
+        })
+    }
+
+    /// Set `config` up to generate the maze of the code.
+    pub fn apply(&self, config: &mut RunConfig) {
+        config.generator = self.generator;
+        config.width = self.width;
+        config.height = self.height;
+        config.seed = self.seed;
+        config.openings = self.openings;
+        config.items = self.items;
+        config.doors = self.doors;
+        config.teleporters = self.teleporters;
+        config.one_ways = self.one_ways;
+        config.terrain = self.terrain;
+        config.infinite = false;
+        config.fit = false;
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        let openings = OPENINGS.iter().position(|&o| o == self.openings).unwrap() as u8;
+        let head = VERSION << 4 | openings << 2;
+        match self.generator {
+            Generator::Kruskal => bytes.push(head),
+            Generator::Drunkard { coverage } => {
+                bytes.push(head | 1);
+                bytes.push((coverage * 100.0).round() as u8);
+            }
+            Generator::AldousBroder => bytes.push(head | 2),
+            Generator::GrowingTree { newest, straight } => {
+                bytes.push(head | 3);
+                bytes.push((newest * 100.0).round() as u8);
+                bytes.push((straight * 100.0).round() as u8);
+            }
+            Generator::Plugin { .. } => unreachable!("plugins have no code"),
+        }
+        for n in [
+            self.width as u64,
+            self.height as u64,
+            self.seed,
+            self.items as u64,
+            self.doors as u64,
+            self.teleporters as u64,
+            self.one_ways as u64,
+            self.terrain as u64,
+        ] {
+            write_leb128(&mut bytes, n);
+        }
+        bytes.push(crc32(&bytes) as u8);
+        bytes
+    }
+}
+
+fn write_leb128(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+fn read_leb128(bytes: &mut impl Iterator<Item = u8>) -> Option<u64> {
+    let mut n = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes.next()?;
+        n |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Some(n);
+        }
+    }
+    None
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (mut bits, mut len) = (0u32, 0);
+        for byte in self.to_bytes() {
+            bits = bits << 8 | byte as u32;
+            len += 8;
+            while len >= 5 {
+                len -= 5;
+                write!(f, "{}", ALPHABET[(bits >> len) as usize & 31] as char)?;
+            }
+        }
+        if len > 0 {
+            write!(f, "{}", ALPHABET[(bits << (5 - len)) as usize & 31] as char)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a code in any case, reading the letters Crockford's base32 left
+/// out as the digits they look like, and skipping dashes.
+impl FromStr for ShareCode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mut bits, mut len) = (0u32, 0);
+        let mut bytes = Vec::new();
+        for c in s.trim().chars().filter(|&c| c != '-') {
+            let c = match c.to_ascii_uppercase() {
+                'O' => '0',
+                'I' | 'L' => '1',
+                c => c,
+            };
+            let Some(value) = ALPHABET.iter().position(|&a| a as char == c) else {
+                return Err(format!("'{}' can't be in a code", c));
+            };
+            bits = bits << 5 | value as u32;
+            len += 5;
+            if len >= 8 {
+                len -= 8;
+                bytes.push((bits >> len) as u8);
+            }
+        }
+        let invalid = || "not a valid code, maybe it was mistyped".to_string();
+        let (&check, data) = bytes.split_last().ok_or_else(invalid)?;
+        if check != crc32(data) as u8 {
+            return Err(invalid());
+        }
+        let mut data = data.iter().copied();
+        let head = data.next().ok_or_else(invalid)?;
+        if head >> 4 != VERSION {
+            return Err(format!("code version {} is unknown", head >> 4));
+        }
+        let mut percent = || match data.next() {
+            Some(percent @ 0..=100) => Ok(percent as f64 / 100.0),
+            _ => Err(invalid()),
+        };
+        let openings = OPENINGS[(head >> 2 & 3) as usize];
+        let generator = match head & 3 {
+            0 => Generator::Kruskal,
+            1 => Generator::Drunkard {
+                coverage: percent()?,
+            },
+            2 => Generator::AldousBroder,
+            3 => Generator::GrowingTree {
+                newest: percent()?,
+                straight: percent()?,
+            },
+            _ => return Err(invalid()),
+        };
+        let mut number = || read_leb128(&mut data).ok_or_else(invalid);
+        let (width, height, seed) = (number()? as usize, number()? as usize, number()?);
+        // no more than the menu allows
+        let mut count = |max: usize| match number()? {
+            n if n <= max as u64 => Ok(n as usize),
+            _ => Err(invalid()),
+        };
+        let (items, doors, teleporters, one_ways, terrain) = (
+            count(MAX_ITEMS)?,
+            count(MAX_DOORS)?,
+            count(MAX_TELEPORTERS)?,
+            count(MAX_ONE_WAYS)?,
+            count(MAX_TERRAIN)?,
+        );
+        if data.next().is_some() {
+            return Err(invalid());
+        }
+        if ![width, height]
+            .iter()
+            .all(|n| (MIN_SIZE..=MAX_SIZE).contains(n))
+        {
+            return Err(format!(
+                "a {}x{} maze is too small or too large",
+                width, height
+            ));
+        }
+        Ok(Self {
+            generator,
+            width,
+            height,
+            seed,
+            openings,
+            items,
+            doors,
+            teleporters,
+            one_ways,
+            terrain,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let code = ShareCode {
+            generator: Generator::Drunkard { coverage: 0.45 },
+            width: 40,
+            height: 300,
+            seed: u64::MAX,
+            openings: Openings::Random,
+            items: MAX_ITEMS,
+            doors: 1,
+            teleporters: 2,
+            one_ways: 3,
+            terrain: 4,
+        };
+        assert_eq!(Ok(code), code.to_string().parse());
+        assert_eq!(Ok(code), code.to_string().to_lowercase().parse());
+        let code = ShareCode {
+            generator: Generator::GrowingTree {
+                newest: 0.9,
+                straight: 0.3,
+            },
+            ..code
+        };
+        assert_eq!(Ok(code), code.to_string().parse());
+
+        let code = ShareCode {
+            generator: Generator::Kruskal,
+            width: 16,
+            height: 16,
+            seed: 243_601,
+            openings: Openings::Closed,
+            items: 10,
+            doors: 0,
+            teleporters: 0,
+            one_ways: 0,
+            terrain: 0,
+        };
+        let text = code.to_string();
+        assert_eq!(20, text.len());
+        assert_eq!(Ok(code), text.replace('1', "l").parse());
+        let typo = text.replace(&text[3..4], if &text[3..4] == "X" { "Y" } else { "X" });
+        assert!(typo.parse::<ShareCode>().is_err());
+        assert!("".parse::<ShareCode>().is_err());
+        assert!("U".parse::<ShareCode>().is_err());
+        let code = ShareCode {
+            doors: MAX_DOORS + 1,
+            ..code
+        };
+        assert!(code.to_string().parse::<ShareCode>().is_err());
+    }
+}
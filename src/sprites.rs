@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use clap::ValueEnum;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthChar;
+
+/// The kinds of tiles a sprite can be drawn for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Wall,
+    Door,
+    Teleporter,
+    Sand,
+    Mud,
+    Ice,
+    Coin,
+    Star,
+    Key,
+    Stack,
+    Robot,
+    Minotaur,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 12] = [
+        Kind::Wall,
+        Kind::Door,
+        Kind::Teleporter,
+        Kind::Sand,
+        Kind::Mud,
+        Kind::Ice,
+        Kind::Coin,
+        Kind::Star,
+        Kind::Key,
+        Kind::Stack,
+        Kind::Robot,
+        Kind::Minotaur,
+    ];
+
+    /// The name of the kind in theme files.
+    pub fn name(self) -> &'static str {
+        match self {
+            Kind::Wall => "wall",
+            Kind::Door => "door",
+            Kind::Teleporter => "teleporter",
+            Kind::Sand => "sand",
+            Kind::Mud => "mud",
+            Kind::Ice => "ice",
+            Kind::Coin => "coin",
+            Kind::Star => "star",
+            Kind::Key => "key",
+            Kind::Stack => "stack",
+            Kind::Robot => "robot",
+            Kind::Minotaur => "minotaur",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Kind> {
+        Kind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+/// What a tile is drawn with instead of its usual glyph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sprite {
+    /// A character two cells wide, e.g. an emoji, covering the whole tile.
+    Wide(char),
+    /// A character for each of the two cells of the tile.
+    Pair([char; 2]),
+}
+
+impl std::str::FromStr for Sprite {
+    type Err = String;
+
+    /// One wide character, or one or two narrow ones.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chars: Vec<char> = s.chars().collect();
+        let widths: Vec<usize> = chars.iter().map(|c| c.width().unwrap_or(0)).collect();
+        match (chars.as_slice(), widths.as_slice()) {
+            (&[c], [2]) => Ok(Sprite::Wide(c)),
+            (&[c], [1]) => Ok(Sprite::Pair([c, ' '])),
+            (&[a, b], [1, 1]) => Ok(Sprite::Pair([a, b])),
+            _ => Err(format!(
+                "{:?} is neither one wide character nor one or two narrow ones",
+                s
+            )),
+        }
+    }
+}
+
+/// The available sprite sets, selected with `--sprites` or cycled at
+/// runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SpriteSet {
+    /// Plain colored tiles.
+    #[default]
+    None,
+    /// An emoji for every kind of tile. Needs a font that has them.
+    Emoji,
+    /// A pair of characters for every kind of tile.
+    Pairs,
+}
+
+impl SpriteSet {
+    /// Cycle to the next sprite set.
+    pub fn next(self) -> Self {
+        match self {
+            SpriteSet::None => SpriteSet::Emoji,
+            SpriteSet::Emoji => SpriteSet::Pairs,
+            SpriteSet::Pairs => SpriteSet::None,
+        }
+    }
+
+    pub fn sprite(self, kind: Kind) -> Option<Sprite> {
+        match self {
+            SpriteSet::None => None,
+            SpriteSet::Emoji => Some(Sprite::Wide(match kind {
+                Kind::Wall => '🧱',
+                Kind::Door => '🚪',
+                Kind::Teleporter => '🌀',
+                Kind::Sand => '🟫',
+                Kind::Mud => '🟤',
+                Kind::Ice => '🧊',
+                Kind::Coin => '🪙',
+                Kind::Star => '⭐',
+                Kind::Key => '🔑',
+                Kind::Stack => '👣',
+                Kind::Robot => '🤖',
+                Kind::Minotaur => '🐂',
+            })),
+            SpriteSet::Pairs => Some(Sprite::Pair(match kind {
+                Kind::Wall => ['▒', '▒'],
+                Kind::Door => ['[', ']'],
+                Kind::Teleporter => ['<', '>'],
+                Kind::Sand => [':', ':'],
+                Kind::Mud => ['~', '~'],
+                Kind::Ice => ['=', '='],
+                Kind::Coin => ['(', ')'],
+                Kind::Star => ['*', '*'],
+                Kind::Key => ['o', '-'],
+                Kind::Stack => ['.', '.'],
+                Kind::Robot => ['{', '}'],
+                Kind::Minotaur => ['}', '{'],
+            })),
+        }
+    }
+}
+
+impl fmt::Display for SpriteSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpriteSet::None => write!(f, "none"),
+            SpriteSet::Emoji => write!(f, "emoji"),
+            SpriteSet::Pairs => write!(f, "pairs"),
+        }
+    }
+}
+
+lazy_static! {
+    /// The sprites tiles are drawn with, global for the same reason as the
+    /// palette.
+    static ref CURRENT: RwLock<SpriteSet> = RwLock::new(SpriteSet::None);
+    /// Sprites from a theme file, drawn in place of the ones of the set.
+    static ref CUSTOM: RwLock<HashMap<Kind, Sprite>> = RwLock::new(HashMap::new());
+}
+
+/// The sprite set in use.
+pub fn current() -> SpriteSet {
+    *CURRENT.read().unwrap()
+}
+
+pub fn set(sprites: SpriteSet) {
+    *CURRENT.write().unwrap() = sprites;
+}
+
+pub fn set_custom(sprites: HashMap<Kind, Sprite>) {
+    *CUSTOM.write().unwrap() = sprites;
+}
+
+fn sprite(kind: Kind) -> Option<Sprite> {
+    let custom = CUSTOM.read().unwrap().get(&kind).copied();
+    custom.or_else(|| current().sprite(kind))
+}
+
+/// The glyph for a tile of `kind` if the sprite set in use has a pair of
+/// characters for it.
+pub fn pair(kind: Kind) -> Option<[char; 2]> {
+    match sprite(kind)? {
+        Sprite::Pair(pair) => Some(pair),
+        Sprite::Wide(_) => None,
+    }
+}
+
+/// The wide glyph for a tile of `kind` if the sprite set in use has one.
+pub fn wide(kind: Kind) -> Option<char> {
+    match sprite(kind)? {
+        Sprite::Wide(c) => Some(c),
+        Sprite::Pair(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths() {
+        for kind in Kind::ALL {
+            // a wide sprite has to fill both cells exactly, or the map shifts
+            match SpriteSet::Emoji.sprite(kind) {
+                Some(Sprite::Wide(c)) => assert_eq!(Some(2), c.width(), "{:?}", kind),
+                other => panic!("no emoji for {:?}: {:?}", kind, other),
+            }
+            match SpriteSet::Pairs.sprite(kind) {
+                Some(Sprite::Pair(pair)) => assert!(pair.iter().all(|c| c.width() == Some(1))),
+                other => panic!("no pair for {:?}: {:?}", kind, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::events::{Event, Subscriber};
+
+/// Wall-clock timer that only counts time while it is running.
+#[derive(Debug, Clone)]
+pub struct Stopwatch {
+    accumulated: Duration,
+    running_since: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub fn started() -> Self {
+        Self {
+            accumulated: Duration::ZERO,
+            running_since: Some(Instant::now()),
+        }
+    }
+
+    /// A paused stopwatch that has counted `elapsed` so far.
+    pub fn paused_at(elapsed: Duration) -> Self {
+        Self {
+            accumulated: elapsed,
+            running_since: None,
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if let Some(since) = self.running_since.take() {
+            self.accumulated += since.elapsed();
+        }
+    }
+
+    pub fn resume(&mut self) {
+        self.running_since.get_or_insert_with(Instant::now);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.accumulated + self.running_since.map_or(Duration::ZERO, |s| s.elapsed())
+    }
+}
+
+/// Counters collected during a single run.
+#[derive(Debug, Clone)]
+pub struct RunStats {
+    pub ticks: u64,
+    /// Moves onto a new tile, including moves while backtracking.
+    pub steps: u64,
+    pub turns: u64,
+    /// Ticks spent turning rather than moving.
+    pub turn_ticks: u64,
+    /// Times the robot walked into a wall and stayed put.
+    pub bumps: u64,
+    /// Steps taken back towards an earlier stack position.
+    pub backtracks: u64,
+    pub visited: usize,
+    /// Free tiles reachable from the start, or 0 if unknown (infinite mode).
+    pub reachable: usize,
+    pub items_total: u32,
+    pub items_collected: u32,
+    /// Sum of the values of all collected items.
+    pub item_score: u32,
+    pub clock: Stopwatch,
+}
+
+impl RunStats {
+    pub fn new(reachable: usize) -> Self {
+        Self {
+            ticks: 0,
+            steps: 0,
+            turns: 0,
+            turn_ticks: 0,
+            bumps: 0,
+            backtracks: 0,
+            visited: 1,
+            reachable,
+            items_total: 0,
+            items_collected: 0,
+            item_score: 0,
+            clock: Stopwatch::started(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Steps per second of (unpaused) wall-clock time.
+    pub fn steps_per_second(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs > 0.0 {
+            self.steps as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Tiles found per step, leaving out the one the robot started on. A
+    /// solver that never steps onto a tile twice gets 1.
+    pub fn coverage_efficiency(&self) -> Option<f64> {
+        (self.steps > 0).then(|| self.visited.saturating_sub(1) as f64 / self.steps as f64)
+    }
+
+    /// Fraction of reachable tiles visited so far.
+    pub fn coverage(&self) -> Option<f64> {
+        (self.reachable > 0).then(|| self.visited as f64 / self.reachable as f64)
+    }
+}
+
+/// Counts the steps, turns, visits and bumps of the robot. Helpers of a swarm
+/// and backtracking are counted where they happen.
+impl Subscriber for RunStats {
+    fn notify(&mut self, _: u64, event: &Event) -> Result<(), String> {
+        match event {
+            Event::Move { .. } => self.steps += 1,
+            Event::Turn { .. } => self.turns += 1,
+            Event::Visit { .. } => self.visited += 1,
+            Event::Bump { .. } => self.bumps += 1,
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Number of samples a `RollingAverage` averages over.
+const ROLLING_WINDOW: usize = 60;
+
+/// Average of the most recent samples of a duration.
+#[derive(Debug, Clone, Default)]
+pub struct RollingAverage {
+    samples: VecDeque<Duration>,
+    sum: Duration,
+}
+
+impl RollingAverage {
+    pub fn add(&mut self, sample: Duration) {
+        if self.samples.len() == ROLLING_WINDOW {
+            self.sum -= self.samples.pop_front().unwrap();
+        }
+        self.samples.push_back(sample);
+        self.sum += sample;
+    }
+
+    pub fn average(&self) -> Duration {
+        self.sum
+            .checked_div(self.samples.len() as u32)
+            .unwrap_or_default()
+    }
+}
+
+/// Rolling averages of how long the parts of a frame take.
+#[derive(Debug, Clone, Default)]
+pub struct FrameTimings {
+    pub draw: RollingAverage,
+    pub tick: RollingAverage,
+    pub events: RollingAverage,
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!(
+        "{}:{:02}.{:01}",
+        secs / 60,
+        secs % 60,
+        d.subsec_millis() / 100
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direction;
+
+    #[test]
+    fn test_coverage() {
+        let mut stats = RunStats::new(0);
+        assert_eq!(None, stats.coverage());
+        assert_eq!(None, stats.coverage_efficiency());
+        stats.reachable = 8;
+        stats.visited = 4;
+        stats.steps = 6;
+        assert_eq!(Some(0.5), stats.coverage());
+        assert_eq!(Some(0.5), stats.coverage_efficiency());
+    }
+
+    #[test]
+    fn test_counts_events() {
+        let mut stats = RunStats::new(10);
+        for event in [
+            Event::Move { x: 1, y: 0 },
+            Event::Visit { x: 1, y: 0 },
+            Event::Turn { dir: Direction::E },
+            Event::Move { x: 0, y: 0 },
+            Event::Bump { x: 0, y: 1 },
+            Event::Tick,
+        ] {
+            stats.notify(0, &event).unwrap();
+        }
+        assert_eq!(
+            (2, 1, 2, 1),
+            (stats.steps, stats.turns, stats.visited, stats.bumps)
+        );
+    }
+
+    #[test]
+    fn test_stopwatch_pauses() {
+        let mut clock = Stopwatch::started();
+        clock.pause();
+        let paused = clock.elapsed();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(paused, clock.elapsed());
+        clock.resume();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(clock.elapsed() >= paused + Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_rolling_average() {
+        let mut average = RollingAverage::default();
+        assert_eq!(Duration::ZERO, average.average());
+        for ms in 0..ROLLING_WINDOW as u64 {
+            average.add(Duration::from_millis(ms));
+        }
+        assert_eq!(Duration::from_micros(29_500), average.average());
+        // the oldest samples drop out of the window
+        for _ in 0..ROLLING_WINDOW {
+            average.add(Duration::from_millis(10));
+        }
+        assert_eq!(Duration::from_millis(10), average.average());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("0:00.0", format_duration(Duration::ZERO));
+        assert_eq!("1:05.2", format_duration(Duration::from_millis(65_250)));
+        assert_eq!("61:00.9", format_duration(Duration::from_millis(3_660_999)));
+    }
+}
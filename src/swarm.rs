@@ -0,0 +1,96 @@
+use std::collections::VecDeque;
+
+use crate::menu::Chassis;
+use crate::{Direction, Pos};
+
+/// Tiles this close to where one robot of the swarm is heading are left to
+/// it, so the others spread out instead of exploring the same corridor.
+const CLAIM_RADIUS: usize = 4;
+
+/// A robot of the swarm other than the main one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Helper {
+    pub pos: Pos,
+    pub dir: Direction,
+    /// Tiles to step into, ending at the tile it is heading for.
+    pub plan: VecDeque<Pos>,
+    pub chassis: Chassis,
+    /// Ticks to wait before the next step, as for the main robot.
+    pub busy: u32,
+    /// Half ticks of turning not yet waited for.
+    pub turn_debt: u32,
+}
+
+impl Helper {
+    pub fn new(pos: Pos, dir: Direction, chassis: Chassis) -> Self {
+        Self {
+            pos,
+            dir,
+            plan: VecDeque::new(),
+            chassis,
+            busy: 0,
+            turn_debt: 0,
+        }
+    }
+}
+
+/// Whether robots at `a` and `b` can talk to each other, a `radius` of 0
+/// meaning at any distance.
+pub fn in_range(a: Pos, b: Pos, radius: u16) -> bool {
+    let (dx, dy) = (a.x.abs_diff(b.x), a.y.abs_diff(b.y));
+    radius == 0 || dx * dx + dy * dy <= radius as usize * radius as usize
+}
+
+/// The robots at `positions` grouped by who can talk to whom, directly or
+/// through others in between, as bit masks of their indices.
+pub fn groups(positions: &[Pos], radius: u16) -> Vec<u8> {
+    let mut groups: Vec<u8> = Vec::new();
+    for (i, &pos) in positions.iter().enumerate() {
+        // everyone in range of the new robot ends up in one group with it
+        let mut merged = 1 << i;
+        groups.retain(|&group| {
+            let near = (0..i).any(|j| group & (1 << j) != 0 && in_range(positions[j], pos, radius));
+            if near {
+                merged |= group;
+            }
+            !near
+        });
+        groups.push(merged);
+    }
+    groups
+}
+
+/// Whether `pos` is left to a robot heading for one of `targets`.
+pub fn claimed(targets: &[Pos], pos: Pos) -> bool {
+    targets
+        .iter()
+        .any(|&target| target.distance(pos) <= CLAIM_RADIUS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claimed() {
+        let targets = [Pos::new(10, 10), Pos::new(1, 1)];
+        assert!(claimed(&targets, Pos::new(12, 8)));
+        assert!(claimed(&targets, Pos::new(1, 5)));
+        assert!(!claimed(&targets, Pos::new(13, 8)));
+        assert!(!claimed(&[], Pos::new(1, 1)));
+    }
+
+    #[test]
+    fn test_groups() {
+        // 0 and 2 only talk through 1, 3 is out of reach
+        let positions = [
+            Pos::new(0, 0),
+            Pos::new(3, 4),
+            Pos::new(6, 8),
+            Pos::new(30, 30),
+        ];
+        assert_eq!(vec![0b0111, 0b1000], groups(&positions, 5));
+        assert_eq!(vec![0b0001, 0b0010, 0b0100, 0b1000], groups(&positions, 4));
+        assert_eq!(vec![0b1111], groups(&positions, 0));
+    }
+}
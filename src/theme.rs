@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime};
+
+use clap::ValueEnum;
+use ratatui::style::Color;
+use serde::Deserialize;
+
+use crate::palette::{Palette, PaletteName};
+use crate::sprites::{Kind, Sprite};
+
+/// How often the theme file is checked for changes.
+const RELOAD_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A palette and sprites read from a theme file like
+///
+/// ```toml
+/// base = "high-contrast"
+/// glyphs = false
+///
+/// [colors]
+/// wall = "#505050"
+/// robot = [40, 220, 90]
+/// red_key = "light red"
+///
+/// [sprites]
+/// robot = "🤖"
+/// wall = "##"
+/// ```
+///
+/// Everything is optional, colors not given come from the base palette.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+    pub sprites: HashMap<Kind, Sprite>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawTheme {
+    base: Option<String>,
+    glyphs: Option<bool>,
+    #[serde(default)]
+    colors: HashMap<String, RawColor>,
+    #[serde(default)]
+    sprites: HashMap<String, String>,
+}
+
+/// A color name as ratatui knows them, `#rrggbb`, or an array of red, green
+/// and blue.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawColor {
+    Name(String),
+    Rgb([u8; 3]),
+}
+
+impl RawColor {
+    fn color(&self) -> Result<Color, String> {
+        match self {
+            RawColor::Name(name) => {
+                Color::from_str(name).map_err(|_| format!("unknown color {:?}", name))
+            }
+            &RawColor::Rgb([r, g, b]) => Ok(Color::Rgb(r, g, b)),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw: RawTheme = toml::from_str(s).map_err(|e| e.message().to_string())?;
+        let mut palette = match &raw.base {
+            Some(base) => PaletteName::from_str(base, true)
+                .map_err(|_| format!("unknown base palette {:?}", base))?
+                .palette(),
+            None => Palette::DEFAULT,
+        };
+        if let Some(glyphs) = raw.glyphs {
+            palette.glyphs = glyphs;
+        }
+        for (name, color) in &raw.colors {
+            let slot = color_slot(&mut palette, name)
+                .ok_or_else(|| format!("no tile called {:?} to color", name))?;
+            *slot = color.color()?;
+        }
+        let sprites = raw
+            .sprites
+            .iter()
+            .map(|(name, sprite)| {
+                let kind = Kind::from_name(name)
+                    .ok_or_else(|| format!("no tile called {:?} to draw a sprite for", name))?;
+                Ok((kind, sprite.parse()?))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Theme { palette, sprites })
+    }
+}
+
+/// The color of `palette` that `name` stands for in theme files.
+fn color_slot<'a>(palette: &'a mut Palette, name: &str) -> Option<&'a mut Color> {
+    Some(match name {
+        "free" => &mut palette.free,
+        "wall" => &mut palette.wall,
+        "teleporter" => &mut palette.teleporter,
+        "visited" => &mut palette.visited,
+        "stack" => &mut palette.stack,
+        "robot" => &mut palette.robot,
+        "minotaur" => &mut palette.minotaur,
+        "coin" => &mut palette.coin,
+        "star" => &mut palette.star,
+        "red_key" => &mut palette.keys[0],
+        "cyan_key" => &mut palette.keys[1],
+        "white_key" => &mut palette.keys[2],
+        "red_door" => &mut palette.doors[0],
+        "cyan_door" => &mut palette.doors[1],
+        "white_door" => &mut palette.doors[2],
+        "sand" => &mut palette.sand,
+        "mud" => &mut palette.mud,
+        "ice" => &mut palette.ice,
+        "search" => &mut palette.search,
+        "near" => &mut palette.near,
+        "far" => &mut palette.far,
+        "highlight" => &mut palette.highlight,
+        _ => return None,
+    })
+}
+
+/// A theme file, read again whenever it changes so a theme can be tweaked
+/// while the app is running.
+#[derive(Debug)]
+pub struct ThemeFile {
+    pub path: PathBuf,
+    modified: Option<SystemTime>,
+    last_check: Option<Instant>,
+}
+
+impl ThemeFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            last_check: None,
+        }
+    }
+
+    /// The theme if the file changed since the last call, or an error if it
+    /// changed but can't be used. The first call always reads it.
+    pub fn poll(&mut self) -> Option<Result<Theme, String>> {
+        let checked = match self.last_check {
+            Some(last) if last.elapsed() < RELOAD_INTERVAL => return None,
+            last => last.is_some(),
+        };
+        self.last_check = Some(Instant::now());
+        // a missing file counts as a change too, but only once
+        let modified = fs::metadata(&self.path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        if checked && modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(
+            fs::read_to_string(&self.path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| text.parse())
+                .map_err(|e| format!("{}: {}", self.path.display(), e)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let theme: Theme = r###"
+            base = "mono"
+            glyphs = false
+
+            [colors]
+            wall = "#102030"
+            robot = [1, 2, 3]
+            cyan_door = "light blue"
+
+            [sprites]
+            robot = "🤖"
+            wall = "##"
+            coin = "o"
+        "###
+        .parse()
+        .unwrap();
+        let mono = Palette::MONO;
+        assert_eq!(Color::Rgb(16, 32, 48), theme.palette.wall);
+        assert_eq!(Color::Rgb(1, 2, 3), theme.palette.robot);
+        assert_eq!(Color::LightBlue, theme.palette.doors[1]);
+        assert_eq!(mono.free, theme.palette.free);
+        assert!(!theme.palette.glyphs);
+        assert_eq!(Some(&Sprite::Wide('🤖')), theme.sprites.get(&Kind::Robot));
+        assert_eq!(
+            Some(&Sprite::Pair(['#', '#'])),
+            theme.sprites.get(&Kind::Wall)
+        );
+        assert_eq!(
+            Some(&Sprite::Pair(['o', ' '])),
+            theme.sprites.get(&Kind::Coin)
+        );
+
+        assert_eq!(Ok(Palette::DEFAULT), "".parse().map(|t: Theme| t.palette));
+        assert!("[colors]\nfloor = \"red\"".parse::<Theme>().is_err());
+        assert!("[colors]\nwall = \"reddish\"".parse::<Theme>().is_err());
+        assert!("[sprites]\nwall = \"###\"".parse::<Theme>().is_err());
+        assert!("base = \"neon\"".parse::<Theme>().is_err());
+        assert!("walls = 1".parse::<Theme>().is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Stylize,
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Widget},
+};
+
+use crate::Direction;
+
+/// Thoughts kept for the pane, the oldest are dropped.
+const CAPACITY: usize = 200;
+
+/// What a thought is about, to filter the pane by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Movement,
+    Scanning,
+    Backtracking,
+}
+
+pub const CATEGORIES: [Category; 3] = [
+    Category::Movement,
+    Category::Scanning,
+    Category::Backtracking,
+];
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Movement => "move",
+            Category::Scanning => "scan",
+            Category::Backtracking => "back",
+        }
+    }
+
+    /// The key showing or hiding the category in the pane.
+    pub fn key(self) -> char {
+        match self {
+            Category::Movement => 'M',
+            Category::Scanning => 'S',
+            Category::Backtracking => 'B',
+        }
+    }
+
+    pub fn from_key(key: char) -> Option<Self> {
+        CATEGORIES
+            .into_iter()
+            .find(|category| category.key() == key)
+    }
+}
+
+/// Which way the robot went, seen from where it was facing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Turn {
+    Front,
+    Right,
+    Left,
+    Back,
+}
+
+/// A decision of the robot, and what it was based on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Thought {
+    /// Looked around at `pos` facing `dir`, with `unexplored` tiles next to
+    /// it that it may go on to.
+    Scanned {
+        pos: (u16, u16),
+        dir: Direction,
+        unexplored: u8,
+    },
+    /// Went on to the unexplored tile at `to`.
+    Moved { turn: Turn, to: (u16, u16) },
+    /// Nothing left to explore here.
+    DeadEnd,
+    /// Went back to `to`, taken from the stack.
+    Backtracked { to: (u16, u16) },
+    /// Stays put until the minotaur is gone.
+    Waiting,
+    /// Nothing left to explore anywhere.
+    Finished,
+}
+
+impl Thought {
+    pub fn category(&self) -> Category {
+        match self {
+            Thought::Scanned { .. } => Category::Scanning,
+            Thought::Moved { .. } | Thought::Waiting | Thought::Finished => Category::Movement,
+            Thought::DeadEnd | Thought::Backtracked { .. } => Category::Backtracking,
+        }
+    }
+}
+
+impl fmt::Display for Thought {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Thought::Scanned {
+                pos,
+                dir,
+                unexplored,
+            } => write!(
+                f,
+                "at {:?} facing {:?}, {} unexplored",
+                pos, dir, unexplored
+            ),
+            Thought::Moved { turn, to } => write!(f, "move {:?} to {:?}", turn, to),
+            Thought::DeadEnd => write!(f, "dead end, backtrack"),
+            Thought::Backtracked { to } => write!(f, "back to {:?}", to),
+            Thought::Waiting => write!(f, "waiting for the minotaur to leave"),
+            Thought::Finished => write!(f, "finished"),
+        }
+    }
+}
+
+/// The latest thoughts of the robot, for a pane that shows the categories
+/// picked with their keys.
+#[derive(Debug, Clone)]
+pub struct Thoughts {
+    /// With the tick they came up in, the newest last.
+    entries: VecDeque<(u64, Thought)>,
+    hidden: Vec<Category>,
+    /// Shown thoughts skipped from the newest, 0 to follow along.
+    scroll: usize,
+}
+
+impl Default for Thoughts {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::with_capacity(CAPACITY),
+            hidden: Vec::new(),
+            scroll: 0,
+        }
+    }
+}
+
+impl Thoughts {
+    pub fn push(&mut self, tick: u64, thought: Thought) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((tick, thought));
+    }
+
+    /// Forget the thoughts that came up after `tick`, which the run was
+    /// rewound to.
+    pub fn forget_after(&mut self, tick: u64) {
+        while self.entries.back().is_some_and(|&(at, _)| at > tick) {
+            self.entries.pop_back();
+        }
+        self.scroll_by(0);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.scroll = 0;
+    }
+
+    /// Scroll back to older thoughts, or forward again for a negative
+    /// `delta`.
+    pub fn scroll_by(&mut self, delta: isize) {
+        let max = self.shown().count().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(max);
+    }
+
+    pub fn shows(&self, category: Category) -> bool {
+        !self.hidden.contains(&category)
+    }
+
+    pub fn toggle(&mut self, category: Category) {
+        if self.shows(category) {
+            self.hidden.push(category);
+        } else {
+            self.hidden.retain(|&hidden| hidden != category);
+        }
+    }
+
+    /// The thoughts of the shown categories, the newest first.
+    pub fn shown(&self) -> impl Iterator<Item = &(u64, Thought)> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|(_, thought)| self.shows(thought.category()))
+    }
+}
+
+/// The newest thoughts on top, with the category keys along the bottom,
+/// dimmed for the hidden ones.
+impl Widget for &Thoughts {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines: Vec<Line> = self
+            .shown()
+            .skip(self.scroll)
+            .take(area.height.saturating_sub(2) as usize)
+            .map(|(tick, thought)| Line::from(format!("{:>5}  {}", tick, thought)))
+            .collect();
+        let mut keys = vec![Span::raw(" ")];
+        for category in CATEGORIES {
+            let key = Span::raw(format!("{} {} ", category.key(), category.name()));
+            keys.push(if self.shows(category) {
+                key
+            } else {
+                key.dim().crossed_out()
+            });
+        }
+        let title = match self.scroll {
+            0 => " thoughts ".to_string(),
+            scroll => format!(" thoughts (+{}) ", scroll),
+        };
+        Paragraph::new(lines)
+            .block(
+                Block::bordered()
+                    .title(title)
+                    .title_bottom(Line::from(keys)),
+            )
+            .render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter() {
+        let mut thoughts = Thoughts::default();
+        for tick in 0..CAPACITY as u64 + 5 {
+            thoughts.push(tick, Thought::DeadEnd);
+        }
+        thoughts.push(300, Thought::Waiting);
+        assert_eq!(CAPACITY, thoughts.shown().count());
+        assert_eq!(Some(&(300, Thought::Waiting)), thoughts.shown().next());
+
+        thoughts.toggle(Category::from_key('B').unwrap());
+        assert_eq!(
+            vec![&(300, Thought::Waiting)],
+            thoughts.shown().collect::<Vec<_>>()
+        );
+        thoughts.toggle(Category::Backtracking);
+        assert!(thoughts.shows(Category::Backtracking));
+
+        thoughts.scroll_by(3);
+        assert_eq!(
+            Some(&(CAPACITY as u64 + 2, Thought::DeadEnd)),
+            thoughts.shown().nth(thoughts.scroll)
+        );
+        thoughts.scroll_by(-5);
+        assert_eq!(0, thoughts.scroll);
+        // rewound
+        thoughts.forget_after(CAPACITY as u64 + 2);
+        assert_eq!(
+            Some(&(CAPACITY as u64 + 2, Thought::DeadEnd)),
+            thoughts.shown().next()
+        );
+        assert_eq!(None, Category::from_key('b'));
+    }
+}
@@ -1,8 +1,15 @@
+//! Grids of tiles, and widgets drawing them with two terminal cells per
+//! tile so they come out about square.
+
+use std::fmt;
 use std::ops;
 
-use ratatui::style::Color;
+use ratatui::style::{Color, Style};
 
-#[derive(Debug)]
+use crate::pathfinding::neighbors;
+
+/// A grid of tiles, indexed by `(x, y)`.
+#[derive(Debug, Clone)]
 pub struct TileMap<T> {
     data: Vec<Vec<T>>,
     width: u16,
@@ -24,6 +31,190 @@ where
             height,
         }
     }
+
+    /// Grow or shrink the map, keeping existing tiles and filling new ones with the default.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.data.resize(height as usize, Vec::new());
+        for row in &mut self.data {
+            row.resize(width as usize, Default::default());
+        }
+        self.width = width;
+        self.height = height;
+    }
+
+    /// A `width` x `height` map with each tile taken from the tile of this
+    /// one at `source(x, y)`.
+    fn transformed(
+        &self,
+        width: u16,
+        height: u16,
+        source: impl Fn(u16, u16) -> (u16, u16),
+    ) -> Self {
+        let data = (0..height)
+            .map(|y| (0..width).map(|x| self[source(x, y)].clone()).collect())
+            .collect();
+        Self {
+            data,
+            width,
+            height,
+        }
+    }
+
+    /// The map turned a quarter clockwise.
+    pub fn rotate90(&self) -> Self {
+        let h = self.height;
+        self.transformed(self.height, self.width, |x, y| (y, h - 1 - x))
+    }
+
+    pub fn rotate180(&self) -> Self {
+        let (w, h) = (self.width, self.height);
+        self.transformed(w, h, |x, y| (w - 1 - x, h - 1 - y))
+    }
+
+    /// The map turned a quarter counterclockwise.
+    pub fn rotate270(&self) -> Self {
+        let w = self.width;
+        self.transformed(self.height, self.width, |x, y| (w - 1 - y, x))
+    }
+
+    /// The map mirrored left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let w = self.width;
+        self.transformed(w, self.height, |x, y| (w - 1 - x, y))
+    }
+
+    /// The map mirrored top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let h = self.height;
+        self.transformed(self.width, h, |x, y| (x, h - 1 - y))
+    }
+}
+
+impl<T> TileMap<T> {
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The whole map as a widget, see `TileMapWidget`.
+    pub fn widget(&self) -> TileMapWidget<'_, T> {
+        TileMapWidget::new(self.view(ratatui::prelude::Rect::new(0, 0, self.width, self.height)))
+    }
+
+    /// The tiles in `rect`, cut off where it goes past the map, without
+    /// copying them.
+    pub fn view(&self, rect: ratatui::prelude::Rect) -> TileMapView<'_, T> {
+        let offset = (rect.x.min(self.width), rect.y.min(self.height));
+        let width = self.width.saturating_sub(offset.0).min(rect.width);
+        let height = self.height.saturating_sub(offset.1).min(rect.height);
+        TileMapView {
+            map: self,
+            area: ratatui::prelude::Rect::new(offset.0, offset.1, width, height),
+        }
+    }
+
+    /// The region of tiles connected to `start` through direct neighbors for
+    /// which `predicate` holds, as a mask. Empty if it doesn't hold for `start`.
+    pub fn flood_fill(&self, start: (u16, u16), predicate: impl Fn(&T) -> bool) -> TileMap<bool> {
+        let mut region = TileMap::with_default(self.width, self.height);
+        if !predicate(&self[start]) {
+            return region;
+        }
+        let mut stack = vec![start];
+        region[start] = true;
+        while let Some(pos) = stack.pop() {
+            for next in neighbors(pos, self.width, self.height) {
+                if !region[next] && predicate(&self[next]) {
+                    region[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        region
+    }
+
+    /// Like `flood_fill`, also setting every tile of the region to `value`.
+    pub fn flood_fill_with(
+        &mut self,
+        start: (u16, u16),
+        predicate: impl Fn(&T) -> bool,
+        value: T,
+    ) -> TileMap<bool>
+    where
+        T: Clone,
+    {
+        let region = self.flood_fill(start, predicate);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if region[(x, y)] {
+                    self[(x, y)] = value.clone();
+                }
+            }
+        }
+        region
+    }
+
+    /// Tiles within `radius` of `pos` that can be seen from it, as a mask.
+    /// Opaque tiles block the view of everything behind them but are
+    /// visible themselves.
+    pub fn visible_from(
+        &self,
+        pos: (u16, u16),
+        radius: u16,
+        opaque: impl Fn(&T) -> bool,
+    ) -> TileMap<bool> {
+        let mut visible = TileMap::with_default(self.width, self.height);
+        let r = radius as i32;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if dx * dx + dy * dy > r * r {
+                    continue;
+                }
+                let (x, y) = (pos.0 as i32 + dx, pos.1 as i32 + dy);
+                if x < 0 || y < 0 || x >= self.width as i32 || y >= self.height as i32 {
+                    continue;
+                }
+                let target = (x as u16, y as u16);
+                let tiles = line(pos, target);
+                // only the tiles in between can block the view
+                visible[target] = tiles
+                    .iter()
+                    .skip(1)
+                    .take(tiles.len().saturating_sub(2))
+                    .all(|&tile| !opaque(&self[tile]));
+            }
+        }
+        visible
+    }
+}
+
+/// The tiles on the straight line from `a` to `b`, both included.
+pub fn line(a: (u16, u16), b: (u16, u16)) -> Vec<(u16, u16)> {
+    // bresenham's line algorithm, for all octants
+    let (mut x, mut y) = (a.0 as i32, a.1 as i32);
+    let (x1, y1) = (b.0 as i32, b.1 as i32);
+    let dx = (x1 - x).abs();
+    let dy = -(y1 - y).abs();
+    let sx = if x < x1 { 1 } else { -1 };
+    let sy = if y < y1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut tiles = vec![a];
+    while (x, y) != (x1, y1) {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+        tiles.push((x as u16, y as u16));
+    }
+    tiles
 }
 
 impl<T> ops::Index<(u16, u16)> for TileMap<T> {
@@ -48,28 +239,88 @@ impl<T> ops::IndexMut<(u16, u16)> for TileMap<T> {
     }
 }
 
-const ARR_RIGHT: char = '⮕';
-const ARR_DOWN: char = '⬇';
+/// Characters drawn on top of a tile's background color.
+///
+/// Tiles without a glyph leave whatever a lower layer drew in place.
+pub trait Glyph {
+    /// The characters for the two terminal cells of the tile.
+    fn glyph(&self) -> Option<[char; 2]> {
+        None
+    }
 
-impl<T> ratatui::widgets::Widget for &TileMap<T>
+    /// A character two cells wide, e.g. an emoji, drawn across the tile in
+    /// place of the glyph.
+    fn wide_glyph(&self) -> Option<char> {
+        None
+    }
+
+    fn glyph_color(&self) -> Color {
+        Color::White
+    }
+
+    /// Whether only the glyph is drawn, over the background of lower layers.
+    fn see_through(&self) -> bool {
+        false
+    }
+}
+
+/// Draw a single tile, which takes up two cells starting at `pos`.
+fn draw_tile<T>(buf: &mut ratatui::prelude::Buffer, pos: (u16, u16), tile: &T)
 where
+    T: Glyph,
     for<'a> &'a T: Into<Color>,
 {
-    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
-    where
-        Self: Sized,
-    {
-        for cy in 0..self.height {
-            for cx in 0..self.width {
-                let tile = &self[(cx, cy)];
-                // TODO maybe prettier with an if let
-                buf.cell_mut((area.x + 2 * cx, area.y + cy))
-                    .map(|cell| cell.set_bg(tile.into()));
-                buf.cell_mut((area.x + 2 * cx + 1, area.y + cy))
-                    .map(|cell| cell.set_bg(tile.into()));
+    let (glyph, wide) = (tile.glyph(), tile.wide_glyph());
+    for i in 0..2 {
+        if let Some(cell) = buf.cell_mut((pos.0 + i, pos.1)) {
+            if !tile.see_through() {
+                cell.set_bg(tile.into());
+            }
+            match (wide, glyph) {
+                // the terminal draws the wide character over the second cell
+                // too, which is left blank so nothing peeks out from under it
+                (Some(wide), _) if i == 0 => {
+                    cell.set_char(wide).set_fg(tile.glyph_color());
+                }
+                (Some(_), _) => {
+                    cell.set_char(' ');
+                }
+                (None, Some(glyph)) => {
+                    cell.set_char(glyph[i as usize]).set_fg(tile.glyph_color());
+                }
+                (None, None) => {}
             }
         }
-        let (small_x, small_y) = (area.width < 2 * self.width, area.height < self.height);
+    }
+}
+
+const ARR_RIGHT: char = '⮕';
+const ARR_DOWN: char = '⬇';
+
+impl<T> TileMap<T>
+where
+    T: Glyph,
+    for<'a> &'a T: Into<Color>,
+{
+    /// Render the map with tile `offset` in the top left corner of `area`.
+    ///
+    /// Arrows on the right and bottom edge indicate that the map continues
+    /// beyond the visible area.
+    pub fn render_window(
+        &self,
+        offset: (u16, u16),
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) {
+        let (visible_w, visible_h) = visible_size(self.width, self.height, offset, area);
+        let view = self.view(ratatui::prelude::Rect::new(
+            offset.0, offset.1, visible_w, visible_h,
+        ));
+        ratatui::widgets::Widget::render(&view, area, buf);
+        let (small_x, small_y) = (
+            offset.0 + visible_w < self.width,
+            offset.1 + visible_h < self.height,
+        );
         if small_x {
             for y in area.top()..area.bottom() {
                 buf[(area.right() - 2, y)]
@@ -87,7 +338,7 @@ where
                 buf[(x, area.bottom() - 1)]
                     .set_bg(Color::White)
                     .set_fg(Color::Black)
-                    .set_char(if (x - area.left()) % 2 == 0 {
+                    .set_char(if (x - area.left()).is_multiple_of(2) {
                         ARR_DOWN
                     } else {
                         ' '
@@ -107,9 +358,342 @@ where
     }
 }
 
+/// A borrowed window into a `TileMap`, indexed and drawn from its own top
+/// left tile, e.g. the part the camera sees.
 #[derive(Debug)]
+pub struct TileMapView<'a, T> {
+    map: &'a TileMap<T>,
+    /// The tiles of the map in the window.
+    area: ratatui::prelude::Rect,
+}
+
+impl<T> Clone for TileMapView<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TileMapView<'_, T> {}
+
+impl<'a, T> TileMapView<'a, T> {
+    pub fn width(&self) -> u16 {
+        self.area.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.area.height
+    }
+
+    /// Where the top left tile of the window is in the map.
+    pub fn offset(&self) -> (u16, u16) {
+        (self.area.x, self.area.y)
+    }
+
+    pub fn get(&self, pos: (u16, u16)) -> Option<&'a T> {
+        (pos.0 < self.area.width && pos.1 < self.area.height)
+            .then(|| &self.map[(self.area.x + pos.0, self.area.y + pos.1)])
+    }
+
+    /// A smaller window into this one, in its coordinates.
+    pub fn view(&self, rect: ratatui::prelude::Rect) -> TileMapView<'a, T> {
+        let x = rect.x.min(self.area.width);
+        let y = rect.y.min(self.area.height);
+        TileMapView {
+            map: self.map,
+            area: ratatui::prelude::Rect::new(
+                self.area.x + x,
+                self.area.y + y,
+                (self.area.width - x).min(rect.width),
+                (self.area.height - y).min(rect.height),
+            ),
+        }
+    }
+}
+
+impl<T> ops::Index<(u16, u16)> for TileMapView<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: (u16, u16)) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> ratatui::widgets::Widget for &TileMapView<'_, T>
+where
+    T: Glyph,
+    for<'a> &'a T: Into<Color>,
+{
+    /// Draw the tiles that fit into `area`, from the top left one.
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let (width, height) = visible_size(self.width(), self.height(), (0, 0), area);
+        for cy in 0..height {
+            for cx in 0..width {
+                draw_tile(buf, (area.x + 2 * cx, area.y + cy), &self[(cx, cy)]);
+            }
+        }
+    }
+}
+
+/// The largest scale of a `TileMapWidget`, whose tiles are twice as wide as
+/// they are high.
+pub const MAX_SCALE: u16 = u16::MAX / 2;
+
+type StyleFn<'a, T> = Box<dyn Fn(&T) -> Style + 'a>;
+type SymbolsFn<'a, T> = Box<dyn Fn(&T) -> Option<[char; 2]> + 'a>;
+
+/// A `TileMap`, or a view into one, as a widget for any ratatui app.
+///
+/// Each tile takes up `2 * scale` cells across and `scale` down, styled and
+/// filled with symbols by the functions it is built with:
+///
+/// ```
+/// use ratatui::{buffer::Buffer, layout::Rect, style::{Color, Style}, widgets::Widget};
+/// use ratatui_counter_app::tile::TileMap;
+///
+/// let mut map = TileMap::<bool>::with_default(10, 10);
+/// map[(4, 2)] = true;
+/// let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+/// map.widget()
+///     .viewport(Rect::new(3, 1, 4, 4))
+///     .scale(2)
+///     .style(|&wall| Style::new().bg(if wall { Color::Gray } else { Color::Black }))
+///     .symbols(|&wall| wall.then_some(['[', ']']))
+///     .render(buf.area, &mut buf);
+/// assert_eq!("[", buf[(4, 2)].symbol());
+/// ```
+pub struct TileMapWidget<'a, T> {
+    view: TileMapView<'a, T>,
+    scale: u16,
+    style: StyleFn<'a, T>,
+    symbols: Option<SymbolsFn<'a, T>>,
+}
+
+impl<'a, T> TileMapWidget<'a, T> {
+    /// The tiles of `view`, unstyled and without symbols, so they don't
+    /// change the cells they are drawn over until styled.
+    pub fn new(view: TileMapView<'a, T>) -> Self {
+        Self {
+            view,
+            scale: 1,
+            style: Box::new(|_| Style::new()),
+            symbols: None,
+        }
+    }
+
+    /// Draw only the tiles in `rect`, in the coordinates of the map drawn,
+    /// e.g. what a camera sees.
+    pub fn viewport(mut self, rect: ratatui::prelude::Rect) -> Self {
+        self.view = self.view.view(rect);
+        self
+    }
+
+    /// Draw each tile `scale` times as large, 1 at the least and at most
+    /// `MAX_SCALE`, so a tile still fits the width of an area.
+    pub fn scale(mut self, scale: u16) -> Self {
+        self.scale = scale.clamp(1, MAX_SCALE);
+        self
+    }
+
+    /// Style the cells of each tile, e.g. with its color as the background.
+    pub fn style(mut self, style: impl Fn(&T) -> Style + 'a) -> Self {
+        self.style = Box::new(style);
+        self
+    }
+
+    /// Fill the two cells of each tile with symbols, repeated over the tile
+    /// when it is scaled up. `None` keeps the symbols drawn before.
+    pub fn symbols(mut self, symbols: impl Fn(&T) -> Option<[char; 2]> + 'a) -> Self {
+        self.symbols = Some(Box::new(symbols));
+        self
+    }
+}
+
+impl<'a, T> TileMapWidget<'a, T>
+where
+    T: Glyph,
+    for<'b> &'b T: Into<Color>,
+{
+    /// Style the tiles by their color and draw their glyphs, the way the
+    /// maps of this crate draw themselves.
+    pub fn glyphs(self) -> Self {
+        self.style(|tile| {
+            let style = Style::new().fg(tile.glyph_color());
+            if tile.see_through() {
+                style
+            } else {
+                style.bg(tile.into())
+            }
+        })
+        .symbols(Glyph::glyph)
+    }
+}
+
+impl<T> fmt::Debug for TileMapWidget<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TileMapWidget")
+            .field("view", &self.view)
+            .field("scale", &self.scale)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> ratatui::widgets::Widget for TileMapWidget<'_, T> {
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer) {
+        let (w, h) = (2 * self.scale, self.scale);
+        let cols = self.view.width().min(area.width / w);
+        let rows = self.view.height().min(area.height / h);
+        for ty in 0..rows {
+            for tx in 0..cols {
+                let tile = &self.view[(tx, ty)];
+                let style = (self.style)(tile);
+                let symbols = self.symbols.as_ref().and_then(|symbols| symbols(tile));
+                for dy in 0..h {
+                    for dx in 0..w {
+                        let cell = &mut buf[(area.x + tx * w + dx, area.y + ty * h + dy)];
+                        cell.set_style(style);
+                        if let Some(symbols) = symbols {
+                            cell.set_char(symbols[dx as usize % 2]);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Number of tiles (columns, rows) of a map that fit into `area` when starting at `offset`.
+fn visible_size(
+    width: u16,
+    height: u16,
+    offset: (u16, u16),
+    area: ratatui::prelude::Rect,
+) -> (u16, u16) {
+    (
+        width.saturating_sub(offset.0).min(area.width / 2),
+        height.saturating_sub(offset.1).min(area.height),
+    )
+}
+
+impl<T> ratatui::widgets::Widget for &TileMap<T>
+where
+    T: Glyph,
+    for<'a> &'a T: Into<Color>,
+{
+    fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
+    where
+        Self: Sized,
+    {
+        self.render_window((0, 0), area, buf);
+    }
+}
+
+/// A `TileMap` with signed coordinates that can grow in every direction,
+/// e.g. for a map centered on a start position at (0, 0).
+#[derive(Debug, Clone)]
+pub struct SignedTileMap<T> {
+    map: TileMap<T>,
+    /// Coordinates of the top left tile.
+    origin: (i32, i32),
+}
+
+impl<T> SignedTileMap<T>
+where
+    T: Clone + Default,
+{
+    /// A `width` x `height` map with its top left tile at `origin`.
+    pub fn with_default(origin: (i32, i32), width: u16, height: u16) -> Self {
+        Self {
+            map: TileMap::with_default(width, height),
+            origin,
+        }
+    }
+
+    /// Grow the map until it contains `pos`, filling new tiles with the default.
+    pub fn extend_to(&mut self, pos: (i32, i32)) {
+        if self.contains(pos) {
+            return;
+        }
+        let (min, max) = self.bounds();
+        let min = (min.0.min(pos.0), min.1.min(pos.1));
+        let max = (max.0.max(pos.0 + 1), max.1.max(pos.1 + 1));
+        let mut map = TileMap::with_default((max.0 - min.0) as u16, (max.1 - min.1) as u16);
+        let (dx, dy) = (
+            (self.origin.0 - min.0) as usize,
+            (self.origin.1 - min.1) as usize,
+        );
+        for (y, row) in std::mem::take(&mut self.map.data).into_iter().enumerate() {
+            for (x, tile) in row.into_iter().enumerate() {
+                map.data[y + dy][x + dx] = tile;
+            }
+        }
+        self.map = map;
+        self.origin = min;
+    }
+}
+
+impl<T> SignedTileMap<T> {
+    /// The smallest coordinates in the map, and the ones just past the largest.
+    pub fn bounds(&self) -> ((i32, i32), (i32, i32)) {
+        let (x, y) = self.origin;
+        (
+            self.origin,
+            (x + self.map.width as i32, y + self.map.height as i32),
+        )
+    }
+
+    pub fn contains(&self, pos: (i32, i32)) -> bool {
+        self.to_local(pos).is_some()
+    }
+
+    pub fn get(&self, pos: (i32, i32)) -> Option<&T> {
+        self.to_local(pos).map(|local| &self.map[local])
+    }
+
+    /// Where `pos` is in the underlying map, e.g. to render it.
+    pub fn to_local(&self, pos: (i32, i32)) -> Option<(u16, u16)> {
+        let x = u16::try_from(pos.0 - self.origin.0).ok()?;
+        let y = u16::try_from(pos.1 - self.origin.1).ok()?;
+        (x < self.map.width && y < self.map.height).then_some((x, y))
+    }
+
+    /// The underlying map, whose (0, 0) is the top left tile.
+    pub fn local(&self) -> &TileMap<T> {
+        &self.map
+    }
+}
+
+impl<T> ops::Index<(i32, i32)> for SignedTileMap<T> {
+    type Output = T;
+
+    fn index(&self, index: (i32, i32)) -> &Self::Output {
+        self.get(index).unwrap()
+    }
+}
+
+impl<T> ops::IndexMut<(i32, i32)> for SignedTileMap<T> {
+    fn index_mut(&mut self, index: (i32, i32)) -> &mut Self::Output {
+        let local = self.to_local(index).unwrap();
+        &mut self.map[local]
+    }
+}
+
+/// A layer of tiles drawn over other maps, with `None` where the ones below
+/// show through.
+#[derive(Debug, Clone)]
 pub struct AlphaTileMap<T>(TileMap<Option<T>>);
 
+/// Which tile `AlphaTileMap::merge` keeps where both maps have one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergePolicy {
+    /// The tile of the map merged into.
+    Keep,
+    /// The tile of the map merged in.
+    Overwrite,
+}
+
 impl<T> AlphaTileMap<T>
 where
     T: Clone,
@@ -117,6 +701,78 @@ where
     pub fn empty(width: u16, height: u16) -> Self {
         Self(TileMap::with_default(width, height))
     }
+
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.0.resize(width, height);
+    }
+
+    /// A layer with the tiles of `map` for which `predicate` holds, and
+    /// nothing where it doesn't.
+    pub fn from_tilemap(map: &TileMap<T>, predicate: impl Fn(&T) -> bool) -> Self {
+        let data = map
+            .data
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| predicate(tile).then(|| tile.clone()))
+                    .collect()
+            })
+            .collect();
+        Self(TileMap {
+            data,
+            width: map.width,
+            height: map.height,
+        })
+    }
+
+    /// The layer as a plain map, with `default` where nothing is set.
+    pub fn flatten(&self, default: T) -> TileMap<T> {
+        let data = self
+            .0
+            .data
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|tile| tile.clone().unwrap_or_else(|| default.clone()))
+                    .collect()
+            })
+            .collect();
+        TileMap {
+            data,
+            width: self.0.width,
+            height: self.0.height,
+        }
+    }
+
+    /// Set the tiles `other` has, over the part of the map both cover.
+    /// Where both have a tile, `policy` picks which one stays.
+    pub fn merge(&mut self, other: &AlphaTileMap<T>, policy: MergePolicy) {
+        for (row, other_row) in self.0.data.iter_mut().zip(&other.0.data) {
+            for (tile, other_tile) in row.iter_mut().zip(other_row) {
+                match (&tile, other_tile) {
+                    (_, None) => {}
+                    (Some(_), Some(_)) if policy == MergePolicy::Keep => {}
+                    (_, Some(other_tile)) => *tile = Some(other_tile.clone()),
+                }
+            }
+        }
+    }
+}
+
+impl<T> AlphaTileMap<T> {
+    pub fn width(&self) -> u16 {
+        self.0.width
+    }
+
+    pub fn height(&self) -> u16 {
+        self.0.height
+    }
+
+    /// The whole layer as a widget. Styles that leave the colors unset and
+    /// no symbols for `None` let the cells below show through.
+    pub fn widget(&self) -> TileMapWidget<'_, Option<T>> {
+        self.0.widget()
+    }
 }
 
 impl<T> ops::Index<(u16, u16)> for AlphaTileMap<T> {
@@ -133,24 +789,216 @@ impl<T> ops::IndexMut<(u16, u16)> for AlphaTileMap<T> {
     }
 }
 
+impl<T> AlphaTileMap<T>
+where
+    T: Glyph,
+    for<'a> &'a T: Into<Color>,
+{
+    /// Render the set tiles with tile `offset` in the top left corner of `area`.
+    pub fn render_window(
+        &self,
+        offset: (u16, u16),
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+    ) {
+        let (visible_w, visible_h) = visible_size(self.0.width, self.0.height, offset, area);
+        for cy in 0..visible_h {
+            for cx in 0..visible_w {
+                if let Some(tile) = &self[(offset.0 + cx, offset.1 + cy)] {
+                    draw_tile(buf, (area.x + 2 * cx, area.y + cy), tile);
+                }
+            }
+        }
+    }
+
+    /// Like `render_window`, but drawing what `f` makes of each set tile and
+    /// its position instead, if anything.
+    pub fn render_window_with<U>(
+        &self,
+        offset: (u16, u16),
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        f: impl Fn((u16, u16), &T) -> Option<U>,
+    ) where
+        U: Glyph,
+        for<'a> &'a U: Into<Color>,
+    {
+        let (visible_w, visible_h) = visible_size(self.0.width, self.0.height, offset, area);
+        for cy in 0..visible_h {
+            for cx in 0..visible_w {
+                let pos = (offset.0 + cx, offset.1 + cy);
+                if let Some(tile) = self[pos].as_ref().and_then(|tile| f(pos, tile)) {
+                    draw_tile(buf, (area.x + 2 * cx, area.y + cy), &tile);
+                }
+            }
+        }
+    }
+}
+
 impl<T> ratatui::widgets::Widget for &AlphaTileMap<T>
 where
+    T: Glyph,
     for<'a> &'a T: Into<Color>,
 {
     fn render(self, area: ratatui::prelude::Rect, buf: &mut ratatui::prelude::Buffer)
     where
         Self: Sized,
     {
-        for cy in 0..self.0.height {
-            for cx in 0..self.0.width {
-                if let Some(tile) = &self[(cx, cy)] {
-                    // TODO maybe prettier with an if let
-                    buf.cell_mut((area.x + 2 * cx, area.y + cy))
-                        .map(|cell| cell.set_bg(tile.into()));
-                    buf.cell_mut((area.x + 2 * cx + 1, area.y + cy))
-                        .map(|cell| cell.set_bg(tile.into()));
-                }
-            }
+        self.render_window((0, 0), area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line() {
+        assert_eq!(vec![(1, 1)], line((1, 1), (1, 1)));
+        assert_eq!(vec![(0, 0), (1, 0), (2, 1), (3, 1)], line((0, 0), (3, 1)));
+        let back = line((3, 1), (0, 0));
+        assert_eq!(4, back.len());
+        assert_eq!(Some(&(0, 0)), back.last());
+    }
+
+    #[test]
+    fn test_visible_from() {
+        let mut map = TileMap::<bool>::with_default(7, 3);
+        map[(3, 1)] = true;
+        let visible = map.visible_from((1, 1), 10, |wall| *wall);
+        assert!(visible[(1, 1)]);
+        assert!(visible[(3, 1)]);
+        assert!(!visible[(5, 1)]);
+        assert!(visible[(5, 0)]);
+        assert!(!map.visible_from((1, 1), 2, |wall| *wall)[(4, 0)]);
+    }
+
+    #[test]
+    fn test_signed_tile_map() {
+        let mut map = SignedTileMap::<u8>::with_default((0, 0), 2, 2);
+        map[(1, 1)] = 1;
+        map.extend_to((-3, 1));
+        map.extend_to((0, -2));
+        assert_eq!(((-3, -2), (2, 2)), map.bounds());
+        assert_eq!(1, map[(1, 1)]);
+        map[(-3, -2)] = 2;
+        assert_eq!(Some((0, 0)), map.to_local((-3, -2)));
+        assert_eq!(2, map.local()[(0, 0)]);
+        assert_eq!(None, map.get((2, 0)));
+        assert!(!map.contains((0, 2)));
+        assert_eq!(None, map.get((-4, 0)));
+        // growing keeps the tiles where they are
+        map.extend_to((4, 3));
+        assert_eq!(((-3, -2), (5, 4)), map.bounds());
+        assert_eq!((1, 2), (map[(1, 1)], map[(-3, -2)]));
+        assert_eq!(0, map[(4, 3)]);
+    }
+
+    #[test]
+    fn test_alpha_conversions() {
+        let mut map = TileMap::<u8>::with_default(3, 2);
+        map[(0, 0)] = 1;
+        map[(2, 1)] = 2;
+        let mut layer = AlphaTileMap::from_tilemap(&map, |&tile| tile > 0);
+        assert_eq!((Some(1), None), (layer[(0, 0)], layer[(1, 0)]));
+        assert_eq!(map.data, layer.flatten(0).data);
+
+        let mut other = AlphaTileMap::empty(4, 1);
+        other[(0, 0)] = Some(7);
+        other[(1, 0)] = Some(8);
+        other[(3, 0)] = Some(9);
+        layer.merge(&other, MergePolicy::Keep);
+        assert_eq!(vec![vec![1, 8, 0], vec![0, 0, 2]], layer.flatten(0).data);
+        layer.merge(&other, MergePolicy::Overwrite);
+        assert_eq!(Some(7), layer[(0, 0)]);
+        assert_eq!(Some(2), layer[(2, 1)]);
+    }
+
+    #[test]
+    fn test_transforms() {
+        let mut map = TileMap::<u8>::with_default(3, 2);
+        map[(0, 0)] = 1;
+        map[(2, 0)] = 2;
+        let rotated = map.rotate90();
+        assert_eq!(vec![vec![0, 1], vec![0, 0], vec![0, 2]], rotated.data);
+        assert_eq!(map.data, rotated.rotate270().data);
+        assert_eq!(map.data, rotated.rotate90().rotate90().rotate90().data);
+        assert_eq!(
+            map.rotate180().data,
+            map.flip_horizontal().flip_vertical().data
+        );
+        assert_eq!(
+            vec![vec![2, 0, 1], vec![0, 0, 0]],
+            map.flip_horizontal().data
+        );
+    }
+
+    #[test]
+    fn test_view() {
+        let mut map = TileMap::<u8>::with_default(5, 4);
+        map[(3, 2)] = 1;
+        let view = map.view(ratatui::prelude::Rect::new(2, 1, 10, 2));
+        assert_eq!((3, 2), (view.width(), view.height()));
+        assert_eq!((2, 1), view.offset());
+        assert_eq!(1, view[(1, 1)]);
+        assert_eq!(None, view.get((3, 0)));
+        let inner = view.view(ratatui::prelude::Rect::new(1, 1, 1, 1));
+        assert_eq!(Some(&1), inner.get((0, 0)));
+        assert_eq!(0, map.view(ratatui::prelude::Rect::new(9, 9, 2, 2)).width());
+    }
+
+    #[test]
+    fn test_widget() {
+        let mut layer = AlphaTileMap::<u8>::empty(3, 2);
+        layer[(1, 1)] = Some(7);
+        let area = ratatui::prelude::Rect::new(0, 0, 12, 4);
+        let mut buf = ratatui::prelude::Buffer::empty(area);
+        buf[(2, 2)].set_char('x').set_bg(Color::Red);
+        ratatui::widgets::Widget::render(
+            layer
+                .widget()
+                .scale(2)
+                .style(|tile| match tile {
+                    Some(_) => Style::new().bg(Color::Blue),
+                    None => Style::new(),
+                })
+                .symbols(|tile| tile.map(|n| [char::from(b'0' + n), ' '])),
+            area,
+            &mut buf,
+        );
+        // (1, 1) covers the cells (4..8, 2..4), the others show through
+        assert_eq!(("7", Color::Blue), (buf[(4, 3)].symbol(), buf[(4, 3)].bg));
+        assert_eq!(" ", buf[(7, 2)].symbol());
+        assert_eq!(("x", Color::Red), (buf[(2, 2)].symbol(), buf[(2, 2)].bg));
+
+        // too large to fit, but no overflow
+        ratatui::widgets::Widget::render(layer.widget().scale(u16::MAX), area, &mut buf);
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        // a wall splits the map into a 2x3 and a 1x3 region
+        let mut map = TileMap::<bool>::with_default(4, 3);
+        for y in 0..3 {
+            map[(2, y)] = true;
         }
+        let region = map.flood_fill((0, 0), |wall| !wall);
+        let size = |region: &TileMap<bool>| {
+            (0..3)
+                .flat_map(|y| (0..4).map(move |x| (x, y)))
+                .filter(|&pos| region[pos])
+                .count()
+        };
+        assert_eq!(6, size(&region));
+        assert!(!region[(3, 1)]);
+        assert_eq!(3, size(&map.flood_fill((3, 2), |wall| !wall)));
+        assert_eq!(0, size(&map.flood_fill((2, 0), |wall| !wall)));
+
+        // painting the right region walls it in with the rest of the wall
+        let painted = map.flood_fill_with((3, 0), |wall| !wall, true);
+        assert_eq!(3, size(&painted));
+        assert!((0..3).all(|y| map[(3, y)]));
+        assert!(!map[(0, 0)]);
+        assert_eq!(0, size(&map.flood_fill((3, 0), |wall| !wall)));
     }
 }
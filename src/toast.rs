@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Widget},
+};
+use unicode_width::UnicodeWidthStr;
+
+use crate::events::{Event, Shared, Subscriber, TickTracker};
+
+/// How long a toast stays once it is shown.
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+/// Toasts shown at once, the others wait in the queue.
+const MAX_SHOWN: usize = 3;
+/// Tiles backtracked in a row before it gets a toast.
+const BACKTRACK_TOAST: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Toast {
+    text: String,
+    /// When it was first shown, `None` while it waits.
+    shown: Option<Instant>,
+}
+
+/// Short notifications that stack up in a corner and go away on their own.
+#[derive(Debug, Default)]
+pub struct Toasts {
+    queue: VecDeque<Toast>,
+}
+
+impl Toasts {
+    /// Queue `text`, unless the same toast is already waiting or shown.
+    pub fn push(&mut self, text: impl Into<String>) {
+        let text = text.into();
+        if self.queue.iter().any(|toast| toast.text == text) {
+            return;
+        }
+        self.queue.push_back(Toast { text, shown: None });
+    }
+
+    /// Drop the toasts that were shown long enough at `now`, and start the
+    /// clock of the ones moving up into view.
+    pub fn update(&mut self, now: Instant) {
+        self.queue.retain(|toast| {
+            toast
+                .shown
+                .is_none_or(|shown| now.duration_since(shown) < TOAST_DURATION)
+        });
+        for toast in self.queue.iter_mut().take(MAX_SHOWN) {
+            toast.shown.get_or_insert(now);
+        }
+    }
+
+    fn shown(&self) -> impl Iterator<Item = &Toast> {
+        self.queue.iter().filter(|toast| toast.shown.is_some())
+    }
+}
+
+/// The shown toasts stacked up in the bottom right corner of the area, the
+/// oldest on top.
+impl Widget for &Toasts {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut bottom = area.bottom();
+        for toast in self.shown().collect::<Vec<_>>().into_iter().rev() {
+            let w = (toast.text.width() as u16 + 4).min(area.width);
+            let h = 3;
+            if bottom < area.y + h {
+                break;
+            }
+            bottom -= h;
+            let toast_area = Rect::new(area.right() - w, bottom, w, h);
+            Clear.render(toast_area, buf);
+            Paragraph::new(Line::from(toast.text.as_str()))
+                .block(Block::bordered())
+                .centered()
+                .render(toast_area, buf);
+        }
+    }
+}
+
+/// Tells about the moments of a run worth a toast, the first time the run
+/// gets to them and not again when it is rewound and played on.
+#[derive(Debug)]
+pub struct Toaster {
+    toasts: Shared<Toasts>,
+    /// For how long the robot has been backtracking.
+    tracker: Shared<TickTracker>,
+    furthest_tick: u64,
+    /// Whether the current tick is reached for the first time.
+    fresh: bool,
+}
+
+impl Toaster {
+    pub fn new(toasts: Shared<Toasts>, tracker: Shared<TickTracker>) -> Self {
+        Self {
+            toasts,
+            tracker,
+            furthest_tick: 0,
+            fresh: true,
+        }
+    }
+}
+
+impl Subscriber for Toaster {
+    fn notify(&mut self, tick: u64, event: &Event) -> Result<(), String> {
+        match event {
+            Event::Start { .. } => {
+                self.furthest_tick = 0;
+                self.fresh = true;
+            }
+            Event::Tick => {
+                self.fresh = tick > self.furthest_tick;
+                self.furthest_tick = self.furthest_tick.max(tick);
+            }
+            _ if !self.fresh => {}
+            Event::Pop { .. } if self.tracker.borrow().backtrack_run == BACKTRACK_TOAST => {
+                self.toasts.borrow_mut().push("Backtracking…")
+            }
+            Event::Finish { caught: true } => {
+                self.toasts.borrow_mut().push("Caught by the minotaur!")
+            }
+            Event::Finish { caught: false } => self.toasts.borrow_mut().push("Goal reached!"),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update() {
+        let mut toasts = Toasts::default();
+        for text in ["a", "b", "a", "c", "d"] {
+            toasts.push(text);
+        }
+        let start = Instant::now();
+        toasts.update(start);
+        let shown = |toasts: &Toasts| toasts.shown().map(|t| t.text.clone()).collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b", "c"], shown(&toasts));
+        toasts.update(start + TOAST_DURATION / 2);
+        assert_eq!(vec!["a", "b", "c"], shown(&toasts));
+        // "d" only moves up once the others are gone, and then stays for
+        // the full duration
+        toasts.update(start + TOAST_DURATION);
+        assert_eq!(vec!["d"], shown(&toasts));
+        toasts.update(start + TOAST_DURATION * 2 - Duration::from_millis(1));
+        assert_eq!(vec!["d"], shown(&toasts));
+        toasts.update(start + TOAST_DURATION * 2);
+        assert!(toasts.queue.is_empty());
+    }
+
+    #[test]
+    fn test_toaster() {
+        let toasts = Shared::new(Toasts::default());
+        let mut toaster = Toaster::new(toasts.clone(), Shared::default());
+        let finish = Event::Finish { caught: false };
+        for tick in 1..=2 {
+            toaster.notify(tick, &Event::Tick).unwrap();
+        }
+        toaster.notify(2, &finish).unwrap();
+        assert_eq!(1, toasts.borrow().queue.len());
+        toasts.borrow_mut().queue.clear();
+        // rewound and played again
+        toaster.notify(2, &Event::Tick).unwrap();
+        toaster.notify(2, &finish).unwrap();
+        assert!(toasts.borrow().queue.is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+/// Stops, the start included, up to which `exact` plans a tour. It takes
+/// time and memory exponential in their number.
+pub const EXACT_MAX: usize = 12;
+
+/// A planned visit of several goals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tour {
+    /// The goals in the order they are visited.
+    pub stops: Vec<(u16, u16)>,
+    /// The tiles of the planned route from the start through all stops.
+    pub route: Vec<(u16, u16)>,
+    /// Cost of the order the nearest neighbor planner picked.
+    pub nearest_cost: u32,
+    /// Cost of the best order, or `None` if there were too many stops.
+    pub exact_cost: Option<u32>,
+}
+
+/// The order to visit all stops in starting from stop 0, always going on to
+/// the cheapest stop not visited yet. `cost[a][b]` is the cost of going from
+/// stop `a` to stop `b`.
+pub fn nearest_neighbor(cost: &[Vec<u32>]) -> Vec<usize> {
+    let mut order = vec![0];
+    let mut left: Vec<usize> = (1..cost.len()).collect();
+    while !left.is_empty() {
+        let last = *order.last().unwrap();
+        let i = (0..left.len())
+            .min_by_key(|&i| cost[last][left[i]])
+            .unwrap();
+        order.push(left.remove(i));
+    }
+    order
+}
+
+/// The cheapest order to visit all stops in starting from stop 0, or `None`
+/// if there are more than `EXACT_MAX` stops.
+pub fn exact(cost: &[Vec<u32>]) -> Option<Vec<usize>> {
+    let n = cost.len();
+    if n > EXACT_MAX {
+        return None;
+    }
+    if n <= 1 {
+        return Some((0..n).collect());
+    }
+    // best[set][last]: cheapest way from stop 0 through the stops in `set`,
+    // bit i standing for stop i + 1, ending at stop `last` + 1
+    let (stops, sets) = (n - 1, 1 << (n - 1));
+    let mut best = vec![vec![u32::MAX; stops]; sets];
+    let mut prev = vec![vec![usize::MAX; stops]; sets];
+    for last in 0..stops {
+        best[1 << last][last] = cost[0][last + 1];
+    }
+    for set in 1..sets {
+        for last in (0..stops).filter(|&last| set & (1 << last) != 0) {
+            if best[set][last] == u32::MAX {
+                continue;
+            }
+            for next in (0..stops).filter(|&next| set & (1 << next) == 0) {
+                let through = best[set][last].saturating_add(cost[last + 1][next + 1]);
+                let grown = set | (1 << next);
+                if through < best[grown][next] {
+                    best[grown][next] = through;
+                    prev[grown][next] = last;
+                }
+            }
+        }
+    }
+    let full = sets - 1;
+    let mut last = (0..stops).min_by_key(|&last| best[full][last])?;
+    let mut set = full;
+    let mut order = Vec::with_capacity(n);
+    while set != 0 {
+        order.push(last + 1);
+        let before = prev[set][last];
+        set &= !(1 << last);
+        last = before;
+    }
+    order.push(0);
+    order.reverse();
+    Some(order)
+}
+
+/// Cost of visiting the stops in `order`.
+pub fn cost_of(cost: &[Vec<u32>], order: &[usize]) -> u32 {
+    order
+        .windows(2)
+        .fold(0u32, |sum, leg| sum.saturating_add(cost[leg[0]][leg[1]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_planners() {
+        // stops on a line at 0, 1, -2 and 4: going to the closest first
+        // means walking back and forth
+        let at = [0i32, 1, -2, 4];
+        let cost: Vec<Vec<u32>> = at
+            .iter()
+            .map(|a| at.iter().map(|b| a.abs_diff(*b)).collect())
+            .collect();
+        let nearest = nearest_neighbor(&cost);
+        assert_eq!(vec![0, 1, 2, 3], nearest);
+        assert_eq!(1 + 3 + 6, cost_of(&cost, &nearest));
+        let best = exact(&cost).unwrap();
+        assert_eq!(vec![0, 2, 1, 3], best);
+        assert_eq!(2 + 3 + 3, cost_of(&cost, &best));
+        assert_eq!(None, exact(&vec![vec![0; EXACT_MAX + 1]; EXACT_MAX + 1]));
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::{HashMap, VecDeque};
+
+/// Trail lengths to pick from, in cells, 0 showing all the robot visited.
+pub const TRAIL_LENGTHS: [usize; 6] = [0, 25, 50, 100, 250, 500];
+
+/// Cells kept, enough for the longest trail.
+const CAPACITY: usize = 500;
+
+/// The cells the robot went through lately, in the order it last was on
+/// them, to draw a trail fading behind the robot instead of all it
+/// visited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trail {
+    /// Each cell once, the newest last.
+    cells: VecDeque<(u16, u16)>,
+}
+
+impl Trail {
+    pub fn visit(&mut self, pos: (u16, u16)) {
+        if self.cells.back() == Some(&pos) {
+            return;
+        }
+        if let Some(i) = self.cells.iter().position(|&cell| cell == pos) {
+            self.cells.remove(i);
+        }
+        if self.cells.len() == CAPACITY {
+            self.cells.pop_front();
+        }
+        self.cells.push_back(pos);
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+    }
+
+    /// The newest `length` cells, with how faded they are, from 0 for the
+    /// newest to nearly 1 for the oldest.
+    pub fn fades(&self, length: usize) -> HashMap<(u16, u16), f64> {
+        self.cells
+            .iter()
+            .rev()
+            .take(length)
+            .enumerate()
+            .map(|(age, &pos)| (pos, age as f64 / length as f64))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fades() {
+        let mut trail = Trail::default();
+        for x in 0..4 {
+            trail.visit((x, 0));
+        }
+        // back to the start, which is the newest cell again
+        trail.visit((0, 0));
+        trail.visit((0, 0));
+        let fades = trail.fades(2);
+        assert_eq!(2, fades.len());
+        assert_eq!(Some(&0.0), fades.get(&(0, 0)));
+        assert_eq!(Some(&0.5), fades.get(&(3, 0)));
+        assert_eq!(4, trail.fades(10).len());
+
+        for x in 0..CAPACITY as u16 + 1 {
+            trail.visit((x, 1));
+        }
+        assert_eq!(CAPACITY, trail.fades(CAPACITY + 1).len());
+        assert_eq!(None, trail.fades(CAPACITY).get(&(0, 1)));
+    }
+}
@@ -0,0 +1,115 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::Color,
+    widgets::{Block, Widget},
+};
+
+/// Tiles ahead the robot sees at most.
+pub const VIEW_DEPTH: usize = 6;
+
+/// One tile of the corridor ahead of the robot, and whether there are
+/// walls around it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slice {
+    pub left: bool,
+    pub right: bool,
+    /// Whether the tile after this one is a wall, which ends the corridor.
+    pub front: bool,
+}
+
+/// The corridor ahead of the robot in first person, like an old dungeon
+/// crawler: the walls are drawn in perspective, darker the farther away
+/// they are, and openings to the side show the far wall of the passage.
+#[derive(Debug, Clone)]
+pub struct FirstPerson {
+    /// From the tile of the robot on.
+    slices: Vec<Slice>,
+}
+
+impl FirstPerson {
+    pub fn new(slices: Vec<Slice>) -> Self {
+        Self { slices }
+    }
+}
+
+/// The color of a wall `depth` tiles away.
+fn shade(depth: usize) -> Color {
+    let level = 200u8.saturating_sub(30 * depth as u8);
+    Color::Rgb(level, level, level)
+}
+
+impl Widget for &FirstPerson {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered().title(" view ");
+        let inner = block.inner(area);
+        block.render(area, buf);
+        if inner.is_empty() {
+            return;
+        }
+        // the frame of the tile `depth` tiles ahead, as an inset from the
+        // sides, nearer tiles taking more of the screen
+        let levels = VIEW_DEPTH as u16 + 1;
+        let inset = |depth: usize| {
+            let depth = depth as u16;
+            (
+                inner.width / 2 * depth / levels,
+                inner.height / 2 * depth / levels,
+            )
+        };
+        let mut fill = |x: u16, top: u16, bottom: u16, color: Color| {
+            for y in top..bottom {
+                buf[(inner.x + x, inner.y + y)].set_bg(color);
+            }
+        };
+        for (depth, slice) in self.slices.iter().enumerate() {
+            let (near_x, near_y) = inset(depth);
+            let (far_x, far_y) = inset(depth + 1);
+            let (w, h) = (inner.width, inner.height);
+            for x in near_x..far_x {
+                // a wall alongside narrows from the near frame to the far one
+                let t = (x - near_x) as u32;
+                let span = (far_x - near_x).max(1) as u32;
+                let y = near_y + ((far_y - near_y) as u32 * t / span) as u16;
+                let (top, bottom) = (y, h - y);
+                let (side_top, side_bottom) = (far_y, h - far_y);
+                let (left, right) = (x, w - 1 - x);
+                match slice.left {
+                    true => fill(left, top, bottom, shade(depth)),
+                    false => fill(left, side_top, side_bottom, shade(depth + 1)),
+                }
+                match slice.right {
+                    true => fill(right, top, bottom, shade(depth)),
+                    false => fill(right, side_top, side_bottom, shade(depth + 1)),
+                }
+            }
+            if slice.front {
+                for x in far_x..w - far_x {
+                    fill(x, far_y, h - far_y, shade(depth + 1));
+                }
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dead_end() {
+        let wall = Slice {
+            left: true,
+            right: true,
+            front: true,
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 16));
+        FirstPerson::new(vec![wall]).render(buf.area, &mut buf);
+        // the near walls on both sides, the one ahead in the middle
+        assert_eq!(shade(0), buf[(1, 8)].bg);
+        assert_eq!(shade(0), buf[(28, 8)].bg);
+        assert_eq!(shade(1), buf[(15, 8)].bg);
+        assert_eq!(Color::Reset, buf[(15, 1)].bg);
+    }
+}
@@ -0,0 +1,124 @@
+use ratatui_counter_app::tile::TileMap;
+
+use crate::events::{Event, Subscriber};
+
+/// How many times the robot entered each tile, counted from its moves as
+/// they happen.
+#[derive(Debug, Clone)]
+pub struct Visits {
+    counts: TileMap<u32>,
+    /// The most entries of any tile.
+    max: u32,
+    /// Tiles entered during the tick, to take them back on a rewind.
+    entered: Vec<(u16, u16)>,
+}
+
+impl Default for Visits {
+    fn default() -> Self {
+        Self::new(1, 1)
+    }
+}
+
+impl Visits {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            counts: TileMap::with_default(width, height),
+            max: 0,
+            entered: Vec::new(),
+        }
+    }
+
+    /// Count the robot entering `pos`.
+    pub fn enter(&mut self, (x, y): (u16, u16)) {
+        // the world of an infinite run outgrows the map it started with
+        if x >= self.counts.width() || y >= self.counts.height() {
+            return;
+        }
+        self.counts[(x, y)] += 1;
+        self.max = self.max.max(self.counts[(x, y)]);
+        self.entered.push((x, y));
+    }
+
+    /// Times the robot entered `pos`.
+    pub fn count(&self, (x, y): (u16, u16)) -> u32 {
+        let inside = x < self.counts.width() && y < self.counts.height();
+        if inside {
+            self.counts[(x, y)]
+        } else {
+            0
+        }
+    }
+
+    /// How often the robot came back to `pos`, from 0 for entered once to
+    /// 255 for the tile entered most. `None` where it never went.
+    pub fn heat(&self, pos: (u16, u16)) -> Option<u8> {
+        let count = self.count(pos).checked_sub(1)?;
+        Some((count * 255 / (self.max.max(2) - 1)) as u8)
+    }
+
+    /// The tiles entered during the tick, to `undo` them later.
+    pub fn take_undo(&mut self) -> Vec<(u16, u16)> {
+        std::mem::take(&mut self.entered)
+    }
+
+    /// Take back the entries of a tick.
+    pub fn undo(&mut self, entered: Vec<(u16, u16)>) {
+        let mut lowered_max = false;
+        for pos in entered {
+            lowered_max |= self.counts[pos] == self.max;
+            self.counts[pos] -= 1;
+        }
+        if lowered_max {
+            self.max = (0..self.counts.height())
+                .flat_map(|y| (0..self.counts.width()).map(move |x| (x, y)))
+                .map(|pos| self.counts[pos])
+                .max()
+                .unwrap_or(0);
+        }
+    }
+}
+
+impl Subscriber for Visits {
+    fn notify(&mut self, _: u64, event: &Event) -> Result<(), String> {
+        match *event {
+            Event::Start { width, height, .. } => *self = Self::new(width, height),
+            Event::Tick => self.entered.clear(),
+            Event::Move { x, y } | Event::Teleport { x, y } => self.enter((x, y)),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visits() {
+        let mut visits = Visits::default();
+        let start = Event::Start {
+            seed: 0,
+            width: 5,
+            height: 3,
+        };
+        visits.notify(0, &start).unwrap();
+        visits.enter((1, 1));
+        visits.notify(1, &Event::Tick).unwrap();
+        visits.notify(1, &Event::Move { x: 2, y: 1 }).unwrap();
+        visits.notify(1, &Event::Move { x: 1, y: 1 }).unwrap();
+        assert_eq!(2, visits.count((1, 1)));
+        assert_eq!(Some(255), visits.heat((1, 1)));
+        assert_eq!(Some(0), visits.heat((2, 1)));
+        assert_eq!(None, visits.heat((3, 1)));
+        // rewound by the tick
+        let undo = visits.take_undo();
+        visits.undo(undo);
+        assert_eq!(1, visits.count((1, 1)));
+        assert_eq!(0, visits.count((2, 1)));
+        assert_eq!(1, visits.max);
+        // off the map of an infinite run
+        visits.notify(2, &Event::Teleport { x: 9, y: 9 }).unwrap();
+        assert_eq!(0, visits.count((9, 9)));
+    }
+}
@@ -0,0 +1,68 @@
+use rand::rngs::StdRng;
+use ratatui_counter_app::tile::TileMap;
+
+use crate::maze::{AldousBroder, Maze};
+use crate::BackgroundTile;
+
+/// A tick of watching the walk takes a step for each this many cells, so
+/// watching it takes about as long whatever the size of the maze.
+const CELLS_PER_STEP: usize = 10;
+
+/// The random walk of `AldousBroder` generating the maze of a run, watched
+/// as it goes. The run starts once the walk is done.
+#[derive(Debug, Clone)]
+pub struct Walk {
+    walk: AldousBroder,
+    /// The random numbers of the run, which go on from where the walk
+    /// leaves them.
+    rng: StdRng,
+}
+
+impl Walk {
+    /// Start the walk on an `nx` x `ny` maze, carving the cell it starts on
+    /// into `tiles`, which are walls otherwise.
+    pub fn new(nx: usize, ny: usize, mut rng: StdRng, tiles: &mut TileMap<BackgroundTile>) -> Self {
+        let walk = AldousBroder::new(nx, ny, &mut rng);
+        let pos = walk.pos();
+        tiles[(pos.0 as u16, pos.1 as u16)] = BackgroundTile::Free;
+        Self { walk, rng }
+    }
+
+    /// Where the walk is.
+    pub fn pos(&self) -> (u16, u16) {
+        let (x, y) = self.walk.pos();
+        (x as u16, y as u16)
+    }
+
+    /// Cells carved so far, and cells there are.
+    pub fn progress(&self) -> (usize, usize) {
+        self.walk.progress()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.walk.is_done()
+    }
+
+    /// Go on by a tick's worth of steps, carving the cells entered for the
+    /// first time and the passages into them into `tiles`.
+    pub fn advance(&mut self, tiles: &mut TileMap<BackgroundTile>) {
+        let steps = self.progress().1.div_ceil(CELLS_PER_STEP);
+        for _ in 0..steps {
+            if self.is_done() {
+                return;
+            }
+            for (x, y) in self.walk.step(&mut self.rng).into_iter().flatten() {
+                tiles[(x as u16, y as u16)] = BackgroundTile::Free;
+            }
+        }
+    }
+
+    /// Walk the rest of the way at once, returning the maze and the random
+    /// numbers to go on with.
+    pub fn finish(mut self) -> (Maze, StdRng) {
+        while !self.walk.is_done() {
+            self.walk.step(&mut self.rng);
+        }
+        (self.walk.into_maze(), self.rng)
+    }
+}
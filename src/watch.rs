@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// A condition on the run that pauses the simulation once it starts to hold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Watch {
+    /// The robot's stack holds more entries than this.
+    StackDepth(usize),
+    /// The robot steps onto a tile it has been on before.
+    Revisit,
+    /// At least this percentage of the reachable tiles was visited.
+    Coverage(f64),
+}
+
+/// Arguments of the `watch` command.
+pub const WATCH_NAMES: [&str; 4] = ["clear", "coverage", "revisit", "stack"];
+
+/// What watches look at, taken at the end of a tick.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Probe {
+    pub stack_depth: usize,
+    /// Whether the robot stepped onto a visited tile during the tick.
+    pub revisited: bool,
+    /// Visited fraction of the reachable tiles, if they are known.
+    pub coverage: Option<f64>,
+}
+
+impl Watch {
+    pub fn holds(&self, probe: &Probe) -> bool {
+        match *self {
+            Watch::StackDepth(depth) => probe.stack_depth > depth,
+            Watch::Revisit => probe.revisited,
+            Watch::Coverage(percent) => probe
+                .coverage
+                .is_some_and(|coverage| coverage * 100.0 >= percent),
+        }
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Watch::StackDepth(depth) => write!(f, "stack depth > {}", depth),
+            Watch::Revisit => write!(f, "revisit"),
+            Watch::Coverage(percent) => write!(f, "coverage >= {}%", percent),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_holds() {
+        let probe = Probe {
+            stack_depth: 5,
+            revisited: false,
+            coverage: Some(0.5),
+        };
+        assert!(Watch::StackDepth(4).holds(&probe));
+        assert!(!Watch::StackDepth(5).holds(&probe));
+        assert!(!Watch::Revisit.holds(&probe));
+        assert!(Watch::Coverage(50.0).holds(&probe));
+        assert!(!Watch::Coverage(50.5).holds(&probe));
+        assert!(!Watch::Coverage(0.0).holds(&Probe::default()));
+    }
+}
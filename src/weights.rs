@@ -0,0 +1,130 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// How the robot picks between equally good tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TieBreak {
+    Random,
+    /// The first in the order front, right, left, back.
+    Fixed,
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TieBreak::Random => write!(f, "random"),
+            TieBreak::Fixed => write!(f, "fixed"),
+        }
+    }
+}
+
+/// A weight that can be selected in the weights pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Heuristic,
+    Revisit,
+    TieBreak,
+}
+
+impl Field {
+    pub const ALL: [Field; 3] = [Field::Heuristic, Field::Revisit, Field::TieBreak];
+
+    pub fn next(self) -> Self {
+        match self {
+            Field::Heuristic => Field::Revisit,
+            Field::Revisit => Field::TieBreak,
+            Field::TieBreak => Field::Heuristic,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Field::Heuristic => "heuristic",
+            Field::Revisit => "revisit",
+            Field::TieBreak => "ties",
+        }
+    }
+}
+
+/// Amount a weight changes by per key press.
+const STEP: f64 = 0.5;
+
+/// The largest a weight can be.
+const MAX_WEIGHT: f64 = 10.0;
+
+/// How the robot rates the unexplored tiles next to it. It moves to the one
+/// with the lowest score; with all weights at zero, that is the cheapest
+/// ground.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Weights {
+    /// Multiplier of the distance to the goal.
+    pub heuristic: f64,
+    /// Penalty per explored tile next to the candidate, steering the robot
+    /// away from ground it has covered already.
+    pub revisit: f64,
+    pub tie_break: TieBreak,
+}
+
+impl Default for Weights {
+    fn default() -> Self {
+        Self {
+            heuristic: 0.0,
+            revisit: 0.0,
+            tie_break: TieBreak::Random,
+        }
+    }
+}
+
+impl Weights {
+    /// Score of a tile costing `cost` to enter, `goal_distance` away from
+    /// the goal and next to `explored` explored tiles.
+    pub fn score(&self, cost: u32, goal_distance: Option<usize>, explored: usize) -> f64 {
+        cost as f64
+            + self.heuristic * goal_distance.unwrap_or(0) as f64
+            + self.revisit * explored as f64
+    }
+
+    /// Raise `field` if `up`, or lower it.
+    pub fn adjust(&mut self, field: Field, up: bool) {
+        let step = if up { STEP } else { -STEP };
+        match field {
+            Field::Heuristic => self.heuristic = (self.heuristic + step).clamp(0.0, MAX_WEIGHT),
+            Field::Revisit => self.revisit = (self.revisit + step).clamp(0.0, MAX_WEIGHT),
+            Field::TieBreak => {
+                self.tie_break = match self.tie_break {
+                    TieBreak::Random => TieBreak::Fixed,
+                    TieBreak::Fixed => TieBreak::Random,
+                }
+            }
+        }
+    }
+
+    /// The value of `field` as shown in the pane.
+    pub fn value(&self, field: Field) -> String {
+        match field {
+            Field::Heuristic => format!("{:.1}", self.heuristic),
+            Field::Revisit => format!("{:.1}", self.revisit),
+            Field::TieBreak => self.tie_break.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust() {
+        let mut weights = Weights::default();
+        weights.adjust(Field::Heuristic, false);
+        assert_eq!(0.0, weights.heuristic);
+        weights.adjust(Field::Heuristic, true);
+        weights.adjust(Field::Revisit, true);
+        weights.adjust(Field::Revisit, true);
+        assert_eq!(3.0 + 0.5 * 4.0 + 1.0 * 2.0, weights.score(3, Some(4), 2));
+        assert_eq!(3.0, Weights::default().score(3, Some(4), 2));
+        weights.adjust(Field::TieBreak, true);
+        assert_eq!(TieBreak::Fixed, weights.tie_break);
+    }
+}